@@ -0,0 +1,127 @@
+// Particle counts for live effects over the last few seconds, for the graph in the Live section.
+// Hanabi doesn't read particle counts back from the GPU, so the number alive is estimated from the
+// spawn counts and the mean lifetime, capped at the capacity.
+
+use std::collections::VecDeque;
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_hanabi::prelude::*;
+
+use crate::{reffect::REffect, LiveEffect};
+
+/// Seconds of history shown in the graph.
+pub const HISTORY_SECONDS: f32 = 10.0;
+
+/// Samples kept per effect: ten seconds at 120 frames per second.
+pub const HISTORY_LEN: usize = 1200;
+
+pub struct AlivePlugin;
+
+impl Plugin for AlivePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AliveHistory>().add_system(record_alive);
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct AliveSample {
+    /// Elapsed time in seconds.
+    pub time: f32,
+    /// Particles spawned this frame.
+    pub spawned: u32,
+    /// Estimated particles alive. None if the effect has no lifetime.
+    pub alive: Option<f32>,
+}
+
+/// A ring buffer of samples for each live effect.
+#[derive(Resource, Default)]
+pub struct AliveHistory(pub(crate) HashMap<Entity, VecDeque<AliveSample>>);
+
+impl AliveHistory {
+    pub fn get(&self, entity: Entity) -> Option<&VecDeque<AliveSample>> {
+        self.0.get(&entity)
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        self.0.remove(&entity);
+    }
+}
+
+/// Particles spawned within `lifetime` seconds of `time`, plus `spawned`, capped at `capacity`.
+pub fn estimate_alive(
+    samples: &VecDeque<AliveSample>,
+    time: f32,
+    spawned: u32,
+    lifetime: f32,
+    capacity: u32,
+) -> f32 {
+    let recent: u32 = samples
+        .iter()
+        .rev()
+        .take_while(|s| time - s.time < lifetime)
+        .map(|s| s.spawned)
+        .sum();
+    ((recent + spawned) as f32).min(capacity as f32)
+}
+
+fn record_alive(
+    time: Res<Time>,
+    reffects: Res<Assets<REffect>>,
+    mut history: ResMut<AliveHistory>,
+    live_effects: Query<(Entity, &LiveEffect, Option<&EffectSpawner>)>,
+    mut removed: RemovedComponents<LiveEffect>,
+) {
+    for entity in removed.iter() {
+        history.0.remove(&entity);
+    }
+
+    let now = time.elapsed_seconds();
+    for (entity, live_effect, spawner) in live_effects.iter() {
+        let samples = history.0.entry(entity).or_default();
+        let spawned = spawner.map_or(0, |s| s.spawn_count());
+        let alive = reffects.get(&live_effect.0).and_then(|re| {
+            let lifetime = re.mean_remaining_lifetime()?;
+            Some(estimate_alive(samples, now, spawned, lifetime, re.capacity))
+        });
+
+        if samples.len() == HISTORY_LEN {
+            samples.pop_front();
+        }
+        samples.push_back(AliveSample {
+            time: now,
+            spawned,
+            alive,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(spawned: &[u32]) -> VecDeque<AliveSample> {
+        spawned
+            .iter()
+            .enumerate()
+            .map(|(i, &spawned)| AliveSample {
+                time: i as f32,
+                spawned,
+                alive: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn estimate_within_lifetime() {
+        // Spawned at 0, 1, 2 and 3 seconds; only the last two are within 2 seconds of 3.5.
+        let s = samples(&[10, 10, 5, 5]);
+        assert_eq!(estimate_alive(&s, 3.5, 0, 2.0, 100), 10.0);
+        assert_eq!(estimate_alive(&s, 3.5, 1, 2.0, 100), 11.0);
+    }
+
+    #[test]
+    fn estimate_capped() {
+        let s = samples(&[50, 50, 50]);
+        assert_eq!(estimate_alive(&s, 2.0, 50, 10.0, 64), 64.0);
+    }
+}
@@ -1,17 +1,50 @@
-use std::{borrow::Cow, path::*};
+use std::{borrow::Cow, collections::BTreeMap, ops::Range, path::*};
 
 use ::serde::de::DeserializeSeed;
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use bevy::{
-    asset::{Asset, AssetLoader, AssetPath, LoadContext, LoadedAsset},
+    asset::{Asset, AssetLoader, AssetPath, HandleId, LoadContext, LoadState, LoadedAsset},
     prelude::*,
-    reflect::{serde::UntypedReflectDeserializer, TypeRegistryArc},
+    reflect::{
+        serde::{ReflectSerializer, UntypedReflectDeserializer},
+        TypeInfo, TypeRegistry, TypeRegistryArc, TypeUuid, Typed,
+    },
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
     utils::BoxedFuture,
 };
-use bevy_hanabi::EffectAsset;
+use bevy_hanabi::{EffectAsset, ParticleEffect};
 use relative_path::*;
 
-use crate::{gradient::*, reffect::*, LiveEffect};
+use crate::{browser::*, gradient::*, reffect::*, toast::Toasts, LiveEffect};
+
+/// Errors from reading, writing and validating asset files. These are converted to toasts in the
+/// UI, see [AssetOpError::toast].
+#[derive(Debug, thiserror::Error)]
+pub enum AssetOpError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("failed to serialize: {0}")]
+    Serialize(#[from] ron::Error),
+    #[error("failed to deserialize: {0}")]
+    Deserialize(String),
+    #[error("{0}")]
+    InvalidPath(String),
+    #[error("path already exists: {}", .0.display())]
+    PathConflict(PathBuf),
+    #[error("cancelled")]
+    Cancelled,
+}
+
+impl AssetOpError {
+    /// Show the error as a toast, prefixed with what was being done. Cancelling isn't an error, so
+    /// nothing is shown.
+    pub fn toast(&self, what: impl std::fmt::Display, toasts: &Toasts) {
+        match self {
+            AssetOpError::Cancelled => info!("{}: {}", what, self),
+            _ => toasts.error(format!("{}: {}", what, self)),
+        }
+    }
+}
 
 // This is basically a dupe of SceneLoader.
 pub struct HanLoader {
@@ -34,38 +67,17 @@ impl AssetLoader for HanLoader {
         load_context: &'a mut LoadContext,
     ) -> BoxedFuture<'a, Result<()>> {
         Box::pin(async move {
-            // This is way easier, but requires deriving Deserialize directly.
-            //let re: REffect = ron::de::from_bytes(bytes)?;
-
-            let mut deserializer = ron::de::Deserializer::from_bytes(bytes)?;
-            let type_registry = self.type_registry.read();
-            let rde = UntypedReflectDeserializer::new(&type_registry);
-            let re = rde.deserialize(&mut deserializer).map_err(|e| {
-                let span_error = deserializer.span_error(e);
-                anyhow!(
-                    "{} at {}:{}",
-                    span_error.code,
-                    load_context.path().display(),
-                    span_error.position,
-                )
-            })?;
-
             let mut reff =
-                <REffect as FromReflect>::take_from_reflect(re).expect("reflect to reffect");
+                deserialize_effect(bytes, &self.type_registry.read(), load_context.path())?;
 
             // Load the particle texture, if set.
-            let loaded_asset = match reff.render_particle_texture {
-                ParticleTexture::Path(path) => {
-                    let rel_path = RelativePath::from_path(&path)?;
-                    // This looks silly, but it just converts the platform-independent relative path
-                    // into a native one.
-                    let path = rel_path.to_path("");
-                    let asset_path = AssetPath::new_ref(&path, None);
+            let loaded_asset = match resolve_texture(&mut reff, load_context.path()) {
+                Some(asset_path) => {
                     let handle = load_context.get_handle(asset_path.clone());
                     reff.render_particle_texture = ParticleTexture::Texture(handle);
                     LoadedAsset::new(reff).with_dependency(asset_path)
                 }
-                _ => LoadedAsset::new(reff),
+                None => LoadedAsset::new(reff),
             };
 
             load_context.set_default_asset(loaded_asset);
@@ -80,73 +92,317 @@ impl AssetLoader for HanLoader {
     }
 }
 
-// Does it make sense to merge this with the loader?
-// TODO: add a preview, e.g. thumbnail for images
-#[derive(Resource)]
-pub struct AssetPaths<T: Asset> {
-    pub root_path: PathBuf,
-    pub extension: &'static str,
-    pub paths: Vec<(PathBuf, Option<Handle<T>>, bool)>,
-}
-
-impl<T: Asset> AssetPaths<T> {
-    pub fn new(extension: &'static str) -> Self {
-        // TODO read asset dir
-        let root_path = PathBuf::from("assets").canonicalize().unwrap();
-
-        // TODO read from asset io instead of glob - similarly, can we read all known assets by
-        // extension?
-        let pat = format!("{}/**/*.{}", root_path.to_str().unwrap(), extension);
-        let paths = glob::glob(&pat)
-            .map_err(|e| error!("failed to find asset paths: {:?}", e))
-            .map(|paths| {
-                paths
-                    .map(|path| {
-                        path.map_err(|e| error!("error: {:?}", e)).and_then(|path| {
-                            // We want the paths stored relative to assets, not the root.
-                            path.strip_prefix(&root_path)
-                                .map(|path| path.to_path_buf())
-                                .map_err(|e| error!("error: {:?}", e))
-                        })
-                    })
-                    // Filter out errors.
-                    .flatten()
-                    .map(|path| (path, None, true))
-                    .collect()
-            })
-            .unwrap_or_default();
+/// Deserialize an effect via reflection. The path is only used for errors.
+pub fn deserialize_effect(
+    bytes: &[u8],
+    type_registry: &TypeRegistry,
+    path: &Path,
+) -> Result<REffect, AssetOpError> {
+    // This is way easier, but requires deriving Deserialize directly.
+    //let re: REffect = ron::de::from_bytes(bytes)?;
 
-        Self {
-            root_path,
-            extension,
-            paths,
+    // Set aside fields this build doesn't know so they can be written back.
+    let ron = std::str::from_utf8(bytes)
+        .map_err(|e| AssetOpError::Deserialize(format!("{} in {}", e, path.display())))?;
+    let (ron, passthrough) = split_unknown_fields(ron, |name| match REffect::type_info() {
+        TypeInfo::Struct(info) => info.field(name).is_some(),
+        _ => true,
+    });
+    for (name, _) in passthrough.iter() {
+        warn!("{}: keeping unknown field {}", path.display(), name);
+    }
+
+    let mut deserializer = ron::de::Deserializer::from_str(&ron)
+        .map_err(|e| AssetOpError::Deserialize(format!("{} in {}", e, path.display())))?;
+    let rde = UntypedReflectDeserializer::new(type_registry);
+    let re = rde.deserialize(&mut deserializer).map_err(|e| {
+        let span_error = deserializer.span_error(e);
+        AssetOpError::Deserialize(format!(
+            "{} at {}:{}",
+            span_error.code,
+            path.display(),
+            span_error.position,
+        ))
+    })?;
+
+    let mut re = <REffect as FromReflect>::take_from_reflect(re)
+        .map_err(|_| AssetOpError::Deserialize(format!("not an effect: {}", path.display())))?;
+    re.passthrough = passthrough;
+    re.migrate();
+    for warning in re.fix_up() {
+        warn!("{}: {}", path.display(), warning);
+    }
+    Ok(re)
+}
+
+pub fn serialize_effect(
+    effect: &REffect,
+    type_registry: &TypeRegistry,
+) -> Result<String, AssetOpError> {
+    let rs = ReflectSerializer::new(effect, type_registry);
+    let ron = ron::ser::to_string_pretty(&rs, ron::ser::PrettyConfig::new())?;
+    Ok(insert_fields(&ron, &effect.passthrough))
+}
+
+// Byte ranges of the top-level fields of the effect struct, the `(name: value, ...)` inside the
+// type map. Each range includes the whitespace before the field and its trailing comma. Also
+// returns the offset of the struct's closing paren. None if the text isn't shaped like an effect.
+fn struct_fields(ron: &str) -> Option<(Vec<Range<usize>>, usize)> {
+    let bytes = ron.as_bytes();
+    let mut depth = 0;
+    let mut fields = Vec::new();
+    let mut start = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            // Strings and chars.
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i + 1 < bytes.len() && !(bytes[i] == b'*' && bytes[i + 1] == b'/') {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'(' | b'[' | b'{' => {
+                depth += 1;
+                if depth == 2 {
+                    start = Some(i + 1);
+                }
+            }
+            b')' | b']' | b'}' => {
+                if depth == 2 {
+                    let start = start?;
+                    if !ron[start..i].trim().is_empty() {
+                        fields.push(start..i);
+                    }
+                    return Some((fields, i));
+                }
+                depth -= 1;
+            }
+            b',' if depth == 2 => {
+                fields.push(start?..i + 1);
+                start = Some(i + 1);
+            }
+            _ => (),
         }
+        i += 1;
     }
+    None
+}
 
-    // Iterate all paths with handles. Is this needed?
-    pub fn iter(&self) -> impl Iterator<Item = (&Path, &Handle<T>)> {
-        self.paths
-            .iter()
-            .filter_map(|(p, h, ..)| h.as_ref().map(|h| (p.as_path(), h)))
+/// Remove top-level effect fields that aren't `known` from serialized effect text, returning the
+/// remaining text and the removed fields as (name, value). Fields with anything unexpected before
+/// the name (e.g. a comment) are kept.
+pub fn split_unknown_fields(
+    ron: &str,
+    known: impl Fn(&str) -> bool,
+) -> (Cow<str>, Vec<(String, String)>) {
+    let Some((fields, _)) = struct_fields(ron) else {
+        return (ron.into(), Vec::new());
+    };
+
+    let mut kept = String::with_capacity(ron.len());
+    let mut unknown = Vec::new();
+    let mut last = 0;
+    for range in fields {
+        let text = ron[range.clone()].trim().trim_end_matches(',');
+        let Some((name, value)) = text.split_once(':') else {
+            continue;
+        };
+        let name = name.trim();
+        let ident = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !ident || known(name) {
+            continue;
+        }
+        unknown.push((name.to_string(), value.trim().to_string()));
+        kept.push_str(&ron[last..range.start]);
+        last = range.end;
+    }
+
+    if unknown.is_empty() {
+        return (ron.into(), unknown);
     }
+    kept.push_str(&ron[last..]);
+    (kept.into(), unknown)
+}
+
+/// Add fields to the end of the effect struct in serialized effect text. See
+/// [split_unknown_fields].
+pub fn insert_fields(ron: &str, fields: &[(String, String)]) -> String {
+    let Some((_, close)) = struct_fields(ron).filter(|_| !fields.is_empty()) else {
+        return ron.to_string();
+    };
+
+    let mut out = ron[..close].trim_end().to_string();
+    if !out.ends_with(',') && !out.ends_with('(') {
+        out.push(',');
+    }
+    for (name, value) in fields {
+        out.push_str(&format!("\n        {}: {},", name, value));
+    }
+    out.push_str("\n    ");
+    out.push_str(&ron[close..]);
+    out
+}
+
+/// Split an asset path string into the path and label, e.g. "scene.gltf#Texture0".
+pub fn split_label(path: &str) -> (&str, Option<&str>) {
+    match path.split_once('#') {
+        Some((path, label)) => (path, Some(label)),
+        None => (path, None),
+    }
+}
+
+/// The asset path for a texture path stored in an effect, which may have a label for sub-assets.
+/// Like [validate_path], the path must be relative and stay inside the assets root, so a hand-edited
+/// file can't point the asset server somewhere unexpected.
+pub fn texture_asset_path(path: &str) -> Result<AssetPath<'static>, AssetOpError> {
+    let (path, label) = split_label(path);
+    let slashed = slash_path(path);
+    let invalid = |why: &str| AssetOpError::InvalidPath(format!("{}: {}", why, path));
+
+    // Drive letters aren't absolute to RelativePath.
+    let drive = slashed.len() > 1 && slashed.as_bytes()[1] == b':';
+    if slashed.starts_with('/') || drive || Path::new(path).is_absolute() {
+        return Err(invalid("path not relative"));
+    }
+    let rel_path = RelativePath::new(&slashed).normalize();
+    if rel_path.as_str().is_empty() || rel_path.file_name().is_none() {
+        return Err(invalid("no file name"));
+    }
+    if rel_path.components().next() == Some(relative_path::Component::ParentDir) {
+        return Err(invalid("path outside assets root"));
+    }
+
+    // This converts the platform-independent relative path into a native one.
+    Ok(AssetPath::new(
+        rel_path.to_path(""),
+        label.map(String::from),
+    ))
+}
+
+// Swap the texture path of a loaded effect for the asset path to load. Invalid paths are kept as
+// missing textures, with a warning.
+fn resolve_texture(re: &mut REffect, effect_path: &Path) -> Option<AssetPath<'static>> {
+    let ParticleTexture::Path(path) = &re.render_particle_texture else {
+        return None;
+    };
+    match texture_asset_path(path) {
+        Ok(asset_path) => Some(asset_path),
+        Err(e) => {
+            warn!("{}: texture not loaded: {}", effect_path.display(), e);
+            re.render_particle_texture = ParticleTexture::Missing(path.clone());
+            None
+        }
+    }
+}
+
+/// Platform-independent relative path with the label (if any), for serialization.
+pub fn asset_path_string(asset_path: &AssetPath) -> Result<String, AssetOpError> {
+    let rel_path = RelativePathBuf::from_path(asset_path.path()).map_err(|e| {
+        AssetOpError::InvalidPath(format!("{}: {}", e, asset_path.path().display()))
+    })?;
+    let rel_path = slash_path(rel_path.as_str());
+    Ok(match asset_path.label() {
+        Some(label) => format!("{}#{}", rel_path, label),
+        None => rel_path,
+    })
+}
+
+// RelativePathBuf only converts the platform's separators, so backslashes typed on other platforms
+// are kept. Effects are always saved with forward slashes.
+fn slash_path(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/// Which effects use which textures.
+#[derive(Default)]
+pub struct Dependencies {
+    /// Texture (asset path string) to effect paths.
+    pub textures: BTreeMap<String, Vec<PathBuf>>,
+    /// Image paths not referenced by any effect.
+    pub unused: Vec<PathBuf>,
+}
+
+impl Dependencies {
+    pub fn new(
+        effect_paths: &AssetBrowser<REffect>,
+        image_paths: &AssetBrowser<Image>,
+        reffects: &Assets<REffect>,
+        asset_server: &AssetServer,
+    ) -> Self {
+        let mut textures: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+
+        for AssetEntry { path, handle, .. } in effect_paths.entries.iter() {
+            // Use the loaded effect if there is one, otherwise peek at the file.
+            let texture = match handle.as_ref().and_then(|h| reffects.get(h)) {
+                Some(re) => match &re.render_particle_texture {
+                    ParticleTexture::None | ParticleTexture::Solid(_) => None,
+                    ParticleTexture::Path(p) | ParticleTexture::Missing(p) => Some(p.clone()),
+                    ParticleTexture::Texture(h) => asset_server
+                        .get_handle_path(h.id())
+                        .and_then(|p| asset_path_string(&p).ok()),
+                },
+                None => peek_texture_path(&effect_paths.root_path.join(path)),
+            };
+
+            if let Some(texture) = texture {
+                textures.entry(texture).or_default().push(path.clone());
+            }
+        }
+
+        let unused = image_paths
+            .entries
+            .iter()
+            .map(|entry| &entry.path)
+            .filter(|path| {
+                RelativePathBuf::from_path(path)
+                    .map(|p| !textures.contains_key(p.as_str()))
+                    .unwrap_or(true)
+            })
+            .cloned()
+            .collect();
 
-    // This is just to get around multiple borrows. Needs revision.
-    pub fn iter_mut(
-        &mut self,
-    ) -> impl Iterator<Item = (&Path, &mut PathBuf, &mut Option<Handle<T>>, &mut bool)> {
-        self.paths
-            .iter_mut()
-            .map(|(p, h, saved)| (self.root_path.as_ref(), p, h, saved))
+        Self { textures, unused }
     }
 }
 
+// Where the serialized texture path is in an effect file, if it has one.
+fn texture_path_range(ron: &str) -> Option<Range<usize>> {
+    const PREFIX: &str = "render_particle_texture: Path(\"";
+
+    let start = ron.find(PREFIX)? + PREFIX.len();
+    let len = ron[start..].find('"')?;
+    Some(start..start + len)
+}
+
+/// Read the serialized texture path from an effect file without deserializing the whole thing.
+pub fn peek_texture_path(path: &Path) -> Option<String> {
+    let ron = std::fs::read_to_string(path).ok()?;
+    texture_path_range(&ron).map(|range| ron[range].to_string())
+}
+
 // Make sure multiple assets don't point to the same path?
 pub fn validate_path<'a>(
     path: &'a str,
     ext: &str,
     root_path: &Path,
     //asset_server: &AssetServer,
-) -> Result<Cow<'a, Path>> {
+) -> Result<Cow<'a, Path>, AssetOpError> {
     use path_absolutize::path_dedot::*;
 
     // No wasm/android:
@@ -160,9 +416,12 @@ pub fn validate_path<'a>(
 
     // Check empty path/file name.
     if path.as_os_str().len() == 0 {
-        return Err(anyhow!("empty path"));
+        return Err(AssetOpError::InvalidPath("empty path".into()));
     } else if path.file_name().is_none() {
-        return Err(anyhow!("no file name: {}", path.display()));
+        return Err(AssetOpError::InvalidPath(format!(
+            "no file name: {}",
+            path.display()
+        )));
     }
 
     // Contain the path to assets. This can make relative paths absolute by using "..".
@@ -172,7 +431,10 @@ pub fn validate_path<'a>(
     // Make sure it's relative to assets. This should never be true after stripping the prefix but
     // who knows.
     if path.is_absolute() {
-        return Err(anyhow!("path not relative: {}", path.display()));
+        return Err(AssetOpError::InvalidPath(format!(
+            "path not relative: {}",
+            path.display()
+        )));
     }
 
     // Ensure extension.
@@ -184,9 +446,18 @@ pub fn validate_path<'a>(
 // AFAIK, there is no way to (without unsafe) easily replace the reference inside a Cow with a
 // shorter reference even if they both point to the same memory. So we copy the stripped path into a
 // new PathBuf.
-fn strip_prefix<'a>(path: Cow<'a, Path>, prefix: &Path) -> Result<Cow<'a, Path>> {
+fn strip_prefix<'a>(path: Cow<'a, Path>, prefix: &Path) -> Result<Cow<'a, Path>, AssetOpError> {
     Ok(if path.is_absolute() {
-        Cow::Owned(path.strip_prefix(prefix)?.into())
+        Cow::Owned(
+            path.strip_prefix(prefix)
+                .map_err(|_| {
+                    AssetOpError::InvalidPath(format!(
+                        "path outside assets root: {}",
+                        path.display()
+                    ))
+                })?
+                .into(),
+        )
     } else {
         path
     })
@@ -202,7 +473,7 @@ pub fn with_extension<'a>(path: Cow<'a, Path>, extension: &str) -> Cow<'a, Path>
 }
 
 // Make unique path for new assets.
-pub fn unique_path<'a>(path_buf: &'a PathBuf, ext: &str) -> Result<Cow<'a, Path>> {
+pub fn unique_path<'a>(path_buf: &'a PathBuf, ext: &str) -> Result<Cow<'a, Path>, AssetOpError> {
     //use path_absolutize::*;
 
     if !path_buf.symlink_metadata().is_ok() {
@@ -211,78 +482,460 @@ pub fn unique_path<'a>(path_buf: &'a PathBuf, ext: &str) -> Result<Cow<'a, Path>
         let file_prefix = path_buf
             .with_extension("") // this clones
             .file_name()
-            .ok_or_else(|| anyhow!("no file name: {}", path_buf.display()))?
+            .ok_or_else(|| {
+                AssetOpError::InvalidPath(format!("no file name: {}", path_buf.display()))
+            })?
             .to_string_lossy()
             .to_string();
 
-        let mut path_buf = path_buf.clone();
+        let mut unique = path_buf.clone();
         for i in 1..=64 {
-            path_buf.set_file_name(format!("{}{}.{}", file_prefix, i, ext));
-            if !path_buf.symlink_metadata().is_ok() {
-                return Ok(Cow::from(path_buf));
+            unique.set_file_name(format!("{}{}.{}", file_prefix, i, ext));
+            if !unique.symlink_metadata().is_ok() {
+                return Ok(Cow::from(unique));
             }
         }
 
-        Err(anyhow!(
-            "failed to make unique path: {}",
-            path_buf.display()
-        ))
+        Err(AssetOpError::PathConflict(path_buf.clone()))
     }
 }
 
-pub fn save_effect(
-    mut effect: REffect,
-    // Root and relative path to asset.
-    (root_path, path): (&Path, &Path),
-    type_registry: AppTypeRegistry,
+/// A file name for an effect name: lowercase, with whitespace as underscores and anything else
+/// that isn't alphanumeric, '-' or '_' dropped.
+pub fn file_slug(name: &str) -> String {
+    name.trim()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_whitespace() => Some('_'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Rename a file under the assets root, making the new name unique. The file is only moved if it
+/// exists, so this works for effects that were never saved. Both paths are relative to the root.
+/// Returns the new path.
+pub fn rename_file(root_path: &Path, from: &Path, to: &Path) -> Result<PathBuf, AssetOpError> {
+    let ext = to
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    let dest = root_path.join(to);
+    let dest = unique_path(&dest, ext)?.into_owned();
+
+    let src = root_path.join(from);
+    if src.exists() {
+        std::fs::rename(&src, &dest)?;
+        info!("renamed {} to {}", src.display(), dest.display());
+    }
+
+    Ok(strip_prefix(dest.into(), root_path)?.into_owned())
+}
+
+/// How the texture of an effect imported from a library was brought along. Paths are as stored in
+/// the effect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextureImport {
+    /// Copied to the same path under the assets root.
+    Copied(String),
+    /// The same file is already at the path.
+    Existing(String),
+    /// A different file has the path, so it was copied under a new name and the effect points
+    /// there.
+    Renamed { from: String, to: String },
+    /// Not found in the library (or the path leaves it). The path is kept.
+    Missing(String),
+}
+
+/// Copy an effect from a library, another assets root, to the same path under the assets root,
+/// made unique. Its texture is copied along with it; see [TextureImport]. Paths are relative to
+/// their roots. Returns the effect's new path.
+pub fn import_from_library(
+    library_root: &Path,
+    effect: &Path,
+    root_path: &Path,
+) -> Result<(PathBuf, Option<TextureImport>), AssetOpError> {
+    if effect.is_absolute() || effect.components().any(|c| c == Component::ParentDir) {
+        return Err(AssetOpError::InvalidPath(format!(
+            "effect outside library: {}",
+            effect.display()
+        )));
+    }
+    let mut ron = std::fs::read_to_string(library_root.join(effect))?;
+
+    let texture = match texture_path_range(&ron) {
+        Some(range) => {
+            let texture = import_texture(library_root, &ron[range.clone()], root_path)?;
+            if let TextureImport::Renamed { to, .. } = &texture {
+                ron.replace_range(range, to);
+            }
+            Some(texture)
+        }
+        None => None,
+    };
+
+    let dest = root_path.join(effect);
+    if let Some(dir) = dest.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let dest = unique_path(&dest, "han")?.into_owned();
+    std::fs::write(&dest, ron)?;
+    info!(
+        "imported {} to {}",
+        library_root.join(effect).display(),
+        dest.display()
+    );
+
+    Ok((strip_prefix(dest.into(), root_path)?.into_owned(), texture))
+}
+
+// Copy a library effect's texture to the same path under the assets root, unless the same file is
+// there already. Another file there gets a unique name instead.
+fn import_texture(
+    library_root: &Path,
+    path: &str,
+    root_path: &Path,
+) -> Result<TextureImport, AssetOpError> {
+    let Ok(asset_path) = texture_asset_path(path) else {
+        return Ok(TextureImport::Missing(path.to_string()));
+    };
+    let src = library_root.join(asset_path.path());
+    if !src.is_file() {
+        return Ok(TextureImport::Missing(path.to_string()));
+    }
+
+    let dest = root_path.join(asset_path.path());
+    if !dest.exists() {
+        if let Some(dir) = dest.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::copy(&src, &dest)?;
+        return Ok(TextureImport::Copied(path.to_string()));
+    }
+    if std::fs::read(&src)? == std::fs::read(&dest)? {
+        return Ok(TextureImport::Existing(path.to_string()));
+    }
+
+    let ext = dest
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    let dest = unique_path(&dest, ext)?.into_owned();
+    std::fs::copy(&src, &dest)?;
+    let rel_path = strip_prefix(dest.into(), root_path)?.into_owned();
+    let to = asset_path_string(&AssetPath::new(
+        rel_path,
+        asset_path.label().map(String::from),
+    ))?;
+    Ok(TextureImport::Renamed {
+        from: path.to_string(),
+        to,
+    })
+}
+
+/// Load an effect from a library for previewing, with its texture loaded from the library too.
+/// The effect isn't under the assets root, so it's read directly rather than by the asset server.
+pub fn load_library_effect(
+    library_root: &Path,
+    effect: &Path,
+    type_registry: &TypeRegistry,
     asset_server: &AssetServer,
-) -> Result<()> {
-    use bevy::{reflect::serde::ReflectSerializer, tasks::IoTaskPool};
-    use std::{fs::File, io::Write};
+) -> Result<REffect, AssetOpError> {
+    let file = library_root.join(effect);
+    let bytes = std::fs::read(&file)?;
+    let mut re = deserialize_effect(&bytes, type_registry, &file)?;
+    if let Some(asset_path) = resolve_texture(&mut re, &file) {
+        // Joining an absolute path replaces the asset server's root.
+        let asset_path = AssetPath::new(
+            library_root.join(asset_path.path()),
+            asset_path.label().map(String::from),
+        );
+        re.render_particle_texture = ParticleTexture::Texture(asset_server.load(asset_path));
+    }
+    Ok(re)
+}
+
+/// Copy a file into a folder under the assets root, making the name unique. The folder is created
+/// if needed. Returns the path relative to the root.
+pub fn import_file(file: &Path, root_path: &Path, folder: &Path) -> Result<PathBuf, AssetOpError> {
+    let file_name = file
+        .file_name()
+        .ok_or_else(|| AssetOpError::InvalidPath(format!("no file name: {}", file.display())))?;
+    let ext = file
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+
+    // Joining an absolute path replaces the root.
+    if folder.is_absolute() || folder.components().any(|c| c == Component::ParentDir) {
+        return Err(AssetOpError::InvalidPath(format!(
+            "folder outside assets root: {}",
+            folder.display()
+        )));
+    }
 
-    // Convert texture to asset path:
+    let dir = root_path.join(folder);
+    std::fs::create_dir_all(&dir)?;
+    let dest = dir.join(file_name);
+    let dest = unique_path(&dest, ext)?.into_owned();
+    std::fs::copy(file, &dest)?;
+    info!("imported {} to {}", file.display(), dest.display());
+
+    Ok(strip_prefix(dest.into(), root_path)?.into_owned())
+}
+
+/// Convert the texture back to a path for serializing.
+pub fn prepare_save(effect: &mut REffect, asset_server: &AssetServer) -> Result<(), AssetOpError> {
     match &mut effect.render_particle_texture {
         ParticleTexture::Texture(handle) => {
             if let Some(path) = asset_server.get_handle_path(handle.id()) {
-                // Write platform-independent relative path.
-                let rel_path = RelativePathBuf::from_path(path.path())?;
-                effect.render_particle_texture = ParticleTexture::Path(rel_path.into_string());
+                effect.render_particle_texture = ParticleTexture::Path(asset_path_string(&path)?);
             }
         }
+        // Write back the original path, not the placeholder.
+        ParticleTexture::Missing(path) => {
+            effect.render_particle_texture = ParticleTexture::Path(slash_path(path));
+        }
+        ParticleTexture::Path(path) => *path = slash_path(path),
         _ => (),
     }
+    Ok(())
+}
 
-    // Clone to move.
-    let effect_path = root_path.join(path);
+pub fn save_effect(
+    mut effect: REffect,
+    // Root and relative path to asset.
+    (root_path, path): (&Path, &Path),
+    // Backups kept, see [crate::backup].
+    backups: usize,
+    type_registry: AppTypeRegistry,
+    asset_server: &AssetServer,
+    toasts: &Toasts,
+) -> Result<(), AssetOpError> {
+    prepare_save(&mut effect, asset_server)?;
 
-    IoTaskPool::get()
-        .spawn(async move {
-            let ron = {
-                let type_registry = type_registry.read();
-                let rs = ReflectSerializer::new(&effect, &type_registry);
-                ron::ser::to_string_pretty(&rs, ron::ser::PrettyConfig::new())
-                    .map_err(|e| error!("failed to serialize: {:?}", e))
-            };
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use bevy::tasks::IoTaskPool;
+        use std::{fs::File, io::Write};
+
+        // Clone to move.
+        let effect_path = root_path.join(path);
+        let journal_file = crate::recovery::journal_file(root_path, path);
+        let (root_path, path) = (root_path.to_path_buf(), path.to_path_buf());
+        let toasts = toasts.clone();
 
-            // Should this handle creation of directories or just error?
-            ron.and_then(|ron| {
-                File::create(&effect_path)
-                    .and_then(|mut file| file.write(ron.as_bytes()))
-                    .map_err(|e| error!("{}", e))
-                    .map(|bytes| info!("saved effect ({} bytes): {:?}", bytes, effect_path))
+        IoTaskPool::get()
+            .spawn(async move {
+                // Should this handle creation of directories or just error?
+                let write = || -> Result<(String, usize), AssetOpError> {
+                    let ron = serialize_effect(&effect, &type_registry.read())?;
+                    let bytes = File::create(&effect_path)?.write(ron.as_bytes())?;
+                    Ok((ron, bytes))
+                };
+
+                // The task is detached, so report here.
+                match write() {
+                    Ok((ron, bytes)) => {
+                        toasts.info(format!("saved {} ({} bytes)", effect_path.display(), bytes));
+                        // Saved changes don't need recovering.
+                        if let Some(file) = journal_file {
+                            _ = std::fs::remove_file(file);
+                        }
+                        if backups > 0 {
+                            let time = std::time::SystemTime::now();
+                            if let Err(e) =
+                                crate::backup::write_backup(&root_path, &path, &ron, backups, time)
+                            {
+                                warn!("failed to back up {}: {}", path.display(), e);
+                            }
+                        }
+                    }
+                    Err(e) => e.toast(format!("failed to save {}", effect_path.display()), &toasts),
+                }
             })
+            .detach();
+    }
+
+    // There is no file system, so download the file instead.
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = (root_path, backups);
+        let ron = serialize_effect(&effect, &type_registry.read())?;
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        crate::web::download(&file_name, &ron).map_err(|e| {
+            AssetOpError::Io(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("failed to download: {:?}", e),
+            ))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Write a file on the IO task pool, reporting the result like [save_effect].
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write_file(file: PathBuf, contents: String, toasts: &Toasts) {
+    use bevy::tasks::IoTaskPool;
+
+    let toasts = toasts.clone();
+    IoTaskPool::get()
+        .spawn(async move {
+            match std::fs::write(&file, contents.as_bytes()) {
+                Ok(()) => toasts.info(format!(
+                    "saved {} ({} bytes)",
+                    file.display(),
+                    contents.len()
+                )),
+                Err(e) => AssetOpError::from(e)
+                    .toast(format!("failed to save {}", file.display()), &toasts),
+            }
         })
         .detach();
+}
 
-    Ok(())
+/// Rendered in place of textures that failed to load.
+pub const PLACEHOLDER_TEXTURE: HandleUntyped =
+    HandleUntyped::weak_from_u64(Image::TYPE_UUID, 0x6d1f_0c3b_a2e4_5f17);
+
+// Magenta and black checkerboard.
+pub fn setup_placeholder_texture(mut images: ResMut<Assets<Image>>) {
+    const SIZE: u32 = 16;
+
+    let data = (0..SIZE * SIZE)
+        .flat_map(|i| {
+            let (x, y) = (i % SIZE, i / SIZE);
+            if (x / 4 + y / 4) % 2 == 0 {
+                [255, 0, 255, 255]
+            } else {
+                [0, 0, 0, 255]
+            }
+        })
+        .collect();
+
+    let image = Image::new(
+        Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+
+    images.set_untracked(PLACEHOLDER_TEXTURE, image);
+}
+
+/// The texture for a [ParticleTexture::Solid] color. The handle is derived from the color, so each
+/// color has one image however often the effect is regenerated. See [update_solid_textures].
+pub fn solid_texture(color: Vec4) -> Handle<Image> {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    ("solid", color.to_array().map(f32::to_bits)).hash(&mut hasher);
+    Handle::weak(HandleId::new(Image::TYPE_UUID, hasher.finish()))
+}
+
+/// A 1×1 image of a (linear, unmultiplied) color.
+pub fn solid_image(color: Vec4) -> Image {
+    let color = Color::rgba_linear(color.x, color.y, color.z, color.w);
+    Image::new(
+        Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        color.as_rgba_u8().to_vec(),
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+/// Generate images for the solid colors in use, and remove those no longer used so dragging a
+/// color doesn't leave an image behind for every step. Runs after the UI so the images exist
+/// before live effects are extracted for rendering.
+pub fn update_solid_textures(
+    reffects: Res<Assets<REffect>>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated: Local<Vec<HandleId>>,
+) {
+    let mut used = Vec::new();
+    for (_, re) in reffects.iter() {
+        if let ParticleTexture::Solid(color) = re.render_particle_texture {
+            let handle = solid_texture(color);
+            if !images.contains(&handle) {
+                images.set_untracked(handle.clone(), solid_image(color));
+            }
+            used.push(handle.id());
+        }
+    }
+
+    for id in generated.iter().filter(|id| !used.contains(id)) {
+        images.remove(Handle::<Image>::weak(*id));
+    }
+    *generated = used;
 }
 
-pub fn spawn_circle(
-    mut commands: Commands,
+/// The loader can't tell if a texture dependency fails to load, so we poll the load state and
+/// swap in [ParticleTexture::Missing]. Live effects are updated in place.
+pub fn check_missing_textures(
     asset_server: Res<AssetServer>,
-    mut effects: ResMut<Assets<EffectAsset>>,
+    toasts: Res<Toasts>,
     mut reffects: ResMut<Assets<REffect>>,
+    mut effects: ResMut<Assets<EffectAsset>>,
+    live_effects: Query<(&ParticleEffect, &LiveEffect)>,
 ) {
+    let missing: Vec<_> = reffects
+        .iter()
+        .filter_map(|(id, re)| match &re.render_particle_texture {
+            ParticleTexture::Texture(handle)
+                if asset_server.get_load_state(handle.id()) == LoadState::Failed =>
+            {
+                Some((id, handle.id()))
+            }
+            _ => None,
+        })
+        .collect();
+
+    for (id, texture_id) in missing {
+        let path = asset_server
+            .get_handle_path(texture_id)
+            .and_then(|path| asset_path_string(&path).ok())
+            .unwrap_or_default();
+        toasts.warn(format!("missing texture: {}", path));
+
+        let Some(re) = reffects.get_mut(&Handle::weak(id)) else {
+            continue;
+        };
+        re.render_particle_texture = ParticleTexture::Missing(path);
+
+        for (effect, _) in live_effects.iter().filter(|(_, live)| live.0.id() == id) {
+            if let Some(effect) = effects.get_mut(&effect.handle) {
+                *effect = re.to_effect_asset(&asset_server);
+            }
+        }
+    }
+}
+
+/// Add a circle of particles in memory, without a path. For testing effects that aren't in the
+/// browser until they're adopted (see [AssetBrowser::adopt]).
+pub fn spawn_circle(asset_server: &AssetServer, reffects: &mut Assets<REffect>) -> Handle<REffect> {
+    reffects.add(circle_effect(asset_server.load(EXAMPLE_TEXTURE).into()))
+}
+
+/// Texture of the circle effect, if the assets have it.
+pub const EXAMPLE_TEXTURE: &str = "plus.png";
+
+/// A burst of particles rising from a circle and fading out. The default effect, and the example
+/// offered in empty projects.
+pub fn circle_effect(texture: ParticleTexture) -> REffect {
     use bevy_hanabi::*;
 
     let mut gradient = bevy_hanabi::Gradient::new();
@@ -290,8 +943,7 @@ pub fn spawn_circle(
     gradient.add_key(0.5, Vec4::splat(1.0));
     gradient.add_key(1.0, Vec4::new(1.0, 1.0, 1.0, 0.0));
 
-    // spawn default effect
-    let effect = REffect {
+    REffect {
         name: "default".to_owned(),
         capacity: 32768,
         spawner: Spawner::once(32.0.into(), true),
@@ -309,14 +961,660 @@ pub fn spawn_circle(
         init_lifetime: Some(InitLifetimeModifier {
             lifetime: 5_f32.into(),
         }),
-        render_particle_texture: asset_server.load("plus.png").into(),
+        render_particle_texture: texture,
         render_color_over_lifetime: Some(ColorGradient::default()),
         ..default()
-    };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A canonical root, since validation strips it from absolute paths.
+    fn root() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        (dir, root)
+    }
+
+    fn valid(path: &str, root: &Path) -> PathBuf {
+        validate_path(path, "han", root).unwrap().into_owned()
+    }
+
+    fn invalid(path: &str, root: &Path) -> bool {
+        matches!(
+            validate_path(path, "han", root),
+            Err(AssetOpError::InvalidPath(_))
+        )
+    }
+
+    #[test]
+    fn validate_empty() {
+        let (_dir, root) = root();
+        assert!(invalid("", &root));
+        assert!(invalid(".", &root));
+        assert!(invalid("..", &root));
+    }
+
+    #[test]
+    fn validate_dots() {
+        let (_dir, root) = root();
+        assert_eq!(valid("./effects/../smoke", &root), Path::new("smoke.han"));
+        assert_eq!(
+            valid("effects/./fire/../smoke.han", &root),
+            Path::new("effects/smoke.han")
+        );
+        // Escaping the root.
+        assert!(invalid("../smoke.han", &root));
+        assert!(invalid("./../../smoke.han", &root));
+    }
+
+    #[test]
+    fn validate_absolute() {
+        let (_dir, root) = root();
+        let inside = root.join("effects").join("smoke.han");
+        assert_eq!(
+            valid(&inside.to_string_lossy(), &root),
+            Path::new("effects/smoke.han")
+        );
+
+        let outside = root.parent().unwrap().join("smoke.han");
+        assert!(invalid(&outside.to_string_lossy(), &root));
+    }
+
+    #[test]
+    fn validate_extension() {
+        let (_dir, root) = root();
+        assert!(matches!(
+            validate_path("smoke.han", "han", &root),
+            Ok(Cow::Borrowed(_))
+        ));
+        assert_eq!(valid("smoke", &root), Path::new("smoke.han"));
+        assert_eq!(valid("smoke.ron", &root), Path::new("smoke.han"));
+        assert_eq!(valid("smoke.tar.gz", &root), Path::new("smoke.tar.han"));
+        assert_eq!(valid("smoke.HAN", &root), Path::new("smoke.han"));
+    }
+
+    #[test]
+    fn validate_unicode() {
+        let (_dir, root) = root();
+        assert_eq!(valid("煙/火花", &root), Path::new("煙/火花.han"));
+        assert_eq!(valid("fumée.han", &root), Path::new("fumée.han"));
+    }
+
+    #[test]
+    fn extension() {
+        let path = with_extension(Cow::Borrowed(Path::new("smoke.han")), "han");
+        assert!(matches!(path, Cow::Borrowed(_)));
+
+        let path = with_extension(Cow::Borrowed(Path::new("smoke.png")), "han");
+        assert_eq!(path, Path::new("smoke.han"));
+    }
+
+    #[test]
+    fn strip() {
+        let (_dir, root) = root();
+        let path = strip_prefix(Cow::Owned(root.join("a/b.han")), &root).unwrap();
+        assert_eq!(path, Path::new("a/b.han"));
+
+        // Relative paths are left alone.
+        let path = strip_prefix(Cow::Borrowed(Path::new("a/b.han")), &root).unwrap();
+        assert!(matches!(path, Cow::Borrowed(_)));
+
+        assert!(matches!(
+            strip_prefix(Cow::Borrowed(Path::new("/elsewhere/b.han")), &root),
+            Err(AssetOpError::InvalidPath(_))
+        ));
+    }
+
+    #[test]
+    fn unique() {
+        let (_dir, root) = root();
+        let path = root.join("smoke.han");
+        assert!(matches!(unique_path(&path, "han"), Ok(Cow::Borrowed(_))));
+
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(
+            unique_path(&path, "han").unwrap(),
+            root.join("smoke1.han").as_path()
+        );
+
+        for i in 1..=64 {
+            std::fs::write(root.join(format!("smoke{}.han", i)), "").unwrap();
+        }
+        assert!(matches!(
+            unique_path(&path, "han"),
+            Err(AssetOpError::PathConflict(p)) if p == path
+        ));
+    }
 
-    // Save both asset handles.
-    commands.spawn((
-        ParticleEffectBundle::new(effects.add(effect.to_effect_asset(&asset_server))),
-        LiveEffect(reffects.add(effect)),
-    ));
+    #[test]
+    fn unique_unicode() {
+        let (_dir, root) = root();
+        let path = root.join("火花.han");
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(
+            unique_path(&path, "han").unwrap(),
+            root.join("火花1.han").as_path()
+        );
+    }
+
+    #[test]
+    fn import() {
+        let (_dir, root) = root();
+        let (_src_dir, src) = root();
+        let file = src.join("smoke.png");
+        std::fs::write(&file, "").unwrap();
+
+        let none = Path::new("");
+        assert_eq!(
+            import_file(&file, &root, none).unwrap(),
+            Path::new("smoke.png")
+        );
+        assert_eq!(
+            import_file(&file, &root, none).unwrap(),
+            Path::new("smoke1.png")
+        );
+        assert!(root.join("smoke1.png").exists());
+
+        // The folder is created.
+        let textures = Path::new("textures");
+        assert_eq!(
+            import_file(&file, &root, textures).unwrap(),
+            Path::new("textures/smoke.png")
+        );
+
+        assert!(matches!(
+            import_file(&file, &root, Path::new("../textures")),
+            Err(AssetOpError::InvalidPath(_))
+        ));
+    }
+
+    #[test]
+    fn slug() {
+        assert_eq!(file_slug("smoke_heavy"), "smoke_heavy");
+        assert_eq!(file_slug(" Heavy Smoke! "), "heavy_smoke");
+        assert_eq!(file_slug("火花-2"), "火花-2");
+        assert_eq!(file_slug("?"), "");
+    }
+
+    #[test]
+    fn rename() {
+        let (_dir, root) = root();
+        std::fs::write(root.join("smoke.han"), "").unwrap();
+        std::fs::write(root.join("fire.han"), "").unwrap();
+
+        let to = rename_file(&root, Path::new("smoke.han"), Path::new("fire.han")).unwrap();
+        assert_eq!(to, Path::new("fire1.han"));
+        assert!(!root.join("smoke.han").exists());
+        assert!(root.join("fire1.han").exists());
+
+        // Never saved, so there's nothing to move.
+        let to = rename_file(&root, Path::new("new.han"), Path::new("newer.han")).unwrap();
+        assert_eq!(to, Path::new("newer.han"));
+        assert!(!root.join("newer.han").exists());
+    }
+
+    // Texture paths are saved with forward slashes and load as asset paths on any platform.
+    #[test]
+    fn texture_path_round_trip() {
+        for (path, expected) in [
+            ("textures/smoke.png", "textures/smoke.png"),
+            ("textures\\smoke.png", "textures/smoke.png"),
+            ("textures\\fx\\火花.png", "textures/fx/火花.png"),
+            ("scenes\\fx.gltf#Texture0", "scenes/fx.gltf#Texture0"),
+        ] {
+            let saved = asset_path_string(&AssetPath::from(path)).unwrap();
+            assert_eq!(saved, expected);
+
+            // Loading splits the label back off.
+            let loaded = AssetPath::from(saved.as_str());
+            assert_eq!(asset_path_string(&loaded).unwrap(), expected);
+            assert_eq!(loaded.label(), split_label(expected).1);
+        }
+    }
+
+    const EFFECT_RON: &str = r#"{
+    "han_ed::reffect::REffect": (
+        name: "sparks, (big)",
+        capacity: 32,
+        update_force_field: [
+            (position: (0.0, 1.0, 0.0), max_radius: 2.0),
+        ],
+        render_orientation: Billboard,
+    ),
+}"#;
+
+    #[test]
+    fn unknown_fields() {
+        let known = |name: &str| name != "update_force_field";
+        let (ron, unknown) = split_unknown_fields(EFFECT_RON, known);
+        assert_eq!(unknown.len(), 1);
+        assert_eq!(unknown[0].0, "update_force_field");
+        assert!(!ron.contains("update_force_field"));
+        assert!(ron.contains("name: \"sparks, (big)\""));
+        assert!(ron.contains("render_orientation: Billboard"));
+
+        // Written back, the same field is split off again.
+        let saved = insert_fields(&ron, &unknown);
+        assert!(saved.contains("render_orientation: Billboard,\n        update_force_field: ["));
+        let (_, again) = split_unknown_fields(&saved, known);
+        assert_eq!(again, unknown);
+    }
+
+    #[test]
+    fn unknown_fields_none() {
+        let (ron, unknown) = split_unknown_fields(EFFECT_RON, |_| true);
+        assert_eq!(ron, EFFECT_RON);
+        assert!(unknown.is_empty());
+        assert_eq!(insert_fields(EFFECT_RON, &[]), EFFECT_RON);
+    }
+
+    // HanabiPlugin registers hanabi's types, but it needs a renderer, so they're listed here.
+    fn type_registry() -> AppTypeRegistry {
+        use bevy::core::TypeRegistrationPlugin;
+        use bevy_hanabi::prelude::*;
+
+        let mut app = App::new();
+        app.add_plugin(TypeRegistrationPlugin)
+            .add_plugin(ReflectTypesPlugin)
+            .register_type::<REffect>()
+            .register_type::<Spawner>()
+            .register_type::<Value<f32>>()
+            .register_type::<Value<Vec2>>()
+            .register_type::<Value<Vec4>>()
+            .register_type::<SimulationSpace>()
+            .register_type::<SimulationCondition>()
+            .register_type::<ShapeDimension>()
+            .register_type::<InitPositionCircleModifier>()
+            .register_type::<InitPositionSphereModifier>()
+            .register_type::<InitPositionCone3dModifier>()
+            .register_type::<InitVelocityCircleModifier>()
+            .register_type::<InitVelocitySphereModifier>()
+            .register_type::<InitVelocityTangentModifier>()
+            .register_type::<DimValue>()
+            .register_type::<InitSizeModifier>()
+            .register_type::<Option<InitSizeModifier>>()
+            .register_type::<InitAgeModifier>()
+            .register_type::<Option<InitAgeModifier>>()
+            .register_type::<InitLifetimeModifier>()
+            .register_type::<Option<InitLifetimeModifier>>()
+            .register_type::<graph::Value>()
+            .register_type::<ValueOrProperty>()
+            .register_type::<AccelModifier>()
+            .register_type::<RadialAccelModifier>()
+            .register_type::<TangentAccelModifier>()
+            .register_type::<ForceFieldSource>()
+            .register_type::<Vec<ForceFieldSource>>()
+            .register_type::<LinearDragModifier>()
+            .register_type::<Option<LinearDragModifier>>()
+            .register_type::<AabbKillModifier>()
+            .register_type::<Option<AabbKillModifier>>()
+            .register_type::<SetColorModifier>()
+            .register_type::<Option<SetColorModifier>>()
+            .register_type::<SetSizeModifier>()
+            .register_type::<Option<SetSizeModifier>>();
+        app.world.resource::<AppTypeRegistry>().clone()
+    }
+
+    // Every optional modifier set, with uniform values and a texture path.
+    fn full_effect() -> REffect {
+        use bevy_hanabi::prelude::*;
+
+        REffect {
+            name: "full".to_string(),
+            capacity: 256,
+            spawner: Spawner::new(
+                Value::Uniform((4.0, 8.0)),
+                Value::Uniform((0.1, 0.2)),
+                0.5.into(),
+            ),
+            start_delay: 0.25,
+            bounds: Some(EffectBounds {
+                center: Vec3::Y,
+                half_extents: Vec3::new(2.0, 4.0, 2.0),
+            }),
+            target: crate::profile::TargetProfile::WebGpu,
+            properties: vec![EffectProperty {
+                name: "wind".to_string(),
+                default_value: AttributeValue::Float3(Vec3::X),
+            }],
+            init_position: InitPosition::Sphere(InitPositionSphereModifier {
+                center: Vec3::Y,
+                radius: 2.0,
+                dimension: ShapeDimension::Surface,
+            }),
+            init_velocity: Some(InitVelocity::Cone(InitVelocityTangentModifier {
+                origin: Vec3::ZERO,
+                axis: Vec3::Y,
+                speed: Value::Uniform((1.0, 3.0)),
+            })),
+            init_size: Some(InitSizeModifier {
+                size: DimValue::D2(Vec2::new(0.5, 1.0).into()),
+            }),
+            init_age: Some(InitAgeModifier {
+                age: Value::Uniform((0.0, 0.5)),
+            }),
+            init_lifetime: Some(InitLifetimeModifier {
+                lifetime: Value::Uniform((1.0, 2.0)),
+            }),
+            init_attributes: vec![InitAttribute::new(Attribute::AGE)],
+            update_accels: vec![
+                UpdateAccel::Linear(AccelModifier::constant(Vec3::new(0.0, -9.8, 0.0))),
+                UpdateAccel::Radial(RadialAccelModifier::constant(Vec3::ZERO, 2.0)),
+                UpdateAccel::Tangent(TangentAccelModifier::constant(Vec3::ZERO, Vec3::Y, 1.0)),
+            ],
+            #[cfg(feature = "force-field")]
+            update_force_field: vec![ForceFieldSource {
+                position: Vec3::Y,
+                max_radius: 4.0,
+                ..default()
+            }],
+            update_linear_drag: Some(LinearDragModifier { drag: 0.5 }),
+            #[cfg(feature = "aabb-kill")]
+            update_aabb_kill: Some(AabbKillModifier {
+                center: Vec3::ZERO,
+                half_size: Vec3::splat(10.0),
+                kill_inside: false,
+            }),
+            render_particle_texture: ParticleTexture::Path("textures/fx/spark.png".to_string()),
+            render_set_color: Some(SetColorModifier {
+                color: Vec4::ONE.into(),
+            }),
+            render_color_over_lifetime: Some(
+                ColorGradient::from_ron(
+                    "(keys: [(0.0, (1.0, 0.5, 0.0, 1.0)), (0.5, (1.0, 0.0, 0.0, 1.0)), (1.0, (0.0, 0.0, 0.0, 0.0))])",
+                )
+                .unwrap(),
+            ),
+            render_set_size: Some(SetSizeModifier {
+                size: DimValue::D1(Value::Uniform((0.1, 0.2))),
+            }),
+            render_size_over_lifetime: Some(
+                SizeGradient::from_ron("(keys: [(0.0, (0.1, 0.1)), (1.0, (1.0, 2.0))])").unwrap(),
+            ),
+            render_orientation: Orientation::AlongVelocity,
+            render_order: vec![RenderModifierKind::SetSize, RenderModifierKind::SetColor],
+            ..default()
+        }
+    }
+
+    // Compare field by field via reflection, falling back to the debug output for values that
+    // don't support comparison.
+    fn assert_fields_eq(a: &REffect, b: &REffect) {
+        use bevy::reflect::Struct;
+
+        for i in 0..a.field_len() {
+            let (x, y) = (a.field_at(i).unwrap(), b.field_at(i).unwrap());
+            let eq = x
+                .reflect_partial_eq(y)
+                .unwrap_or_else(|| format!("{:?}", x) == format!("{:?}", y));
+            assert!(eq, "{}: {:?} != {:?}", a.name_at(i).unwrap(), x, y);
+        }
+    }
+
+    // Files from builds without force fields or kill boxes load in builds with them.
+    #[test]
+    fn optional_modifiers_missing() {
+        let type_registry = type_registry();
+        let type_registry = type_registry.read();
+        let ron = serialize_effect(&full_effect(), &type_registry).unwrap();
+        let (ron, removed) = split_unknown_fields(&ron, |name| {
+            !matches!(name, "update_force_field" | "update_aabb_kill")
+        });
+        assert_eq!(
+            removed.len(),
+            cfg!(feature = "force-field") as usize + cfg!(feature = "aabb-kill") as usize
+        );
+
+        let re = deserialize_effect(ron.as_bytes(), &type_registry, Path::new("test.han")).unwrap();
+        assert_eq!(re.name, full_effect().name);
+        #[cfg(feature = "force-field")]
+        assert!(re.update_force_field.is_empty());
+        #[cfg(feature = "aabb-kill")]
+        assert!(re.update_aabb_kill.is_none());
+    }
+
+    // Solid colors are saved as the color, with no texture path.
+    #[test]
+    fn solid_texture_round_trip() {
+        let type_registry = type_registry();
+        let type_registry = type_registry.read();
+        let color = Vec4::new(1.0, 0.5, 0.0, 0.8);
+        let re = REffect {
+            render_particle_texture: ParticleTexture::Solid(color),
+            ..default()
+        };
+
+        let ron = serialize_effect(&re, &type_registry).unwrap();
+        assert!(ron.contains("Solid"), "{}", ron);
+        let loaded =
+            deserialize_effect(ron.as_bytes(), &type_registry, Path::new("test.han")).unwrap();
+        assert_eq!(
+            loaded.render_particle_texture,
+            ParticleTexture::Solid(color)
+        );
+    }
+
+    #[test]
+    fn solid_texture_per_color() {
+        let red = Vec4::new(1.0, 0.0, 0.0, 1.0);
+        assert_eq!(solid_texture(red), solid_texture(red));
+        assert_ne!(solid_texture(red), solid_texture(Vec4::ONE));
+        assert_ne!(solid_texture(red).id(), PLACEHOLDER_TEXTURE.id());
+
+        let image = solid_image(red);
+        assert_eq!(image.data, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn texture_paths() {
+        let valid = |path: &str| texture_asset_path(path).ok();
+        assert_eq!(
+            valid("textures/spark.png"),
+            Some(AssetPath::new("textures/spark.png".into(), None))
+        );
+        assert_eq!(
+            valid("models/fx.gltf#Texture0"),
+            Some(AssetPath::new(
+                "models/fx.gltf".into(),
+                Some("Texture0".into())
+            ))
+        );
+        // Dots that stay inside the root are fine.
+        assert_eq!(
+            valid("textures/../spark.png"),
+            Some(AssetPath::new("spark.png".into(), None))
+        );
+    }
+
+    // Labeled textures, e.g. an image in a glTF file, keep their label through save and load.
+    #[test]
+    fn labeled_texture_round_trip() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default());
+        let asset_server = app.world.resource::<AssetServer>();
+        let type_registry = type_registry();
+        let type_registry = type_registry.read();
+
+        let mut re = REffect {
+            render_particle_texture: ParticleTexture::Path("models\\fx.gltf#Texture0".to_string()),
+            ..default()
+        };
+        prepare_save(&mut re, asset_server).unwrap();
+        let ron = serialize_effect(&re, &type_registry).unwrap();
+        assert!(ron.contains("models/fx.gltf#Texture0"), "{}", ron);
+
+        let mut loaded =
+            deserialize_effect(ron.as_bytes(), &type_registry, Path::new("test.han")).unwrap();
+        let asset_path = resolve_texture(&mut loaded, Path::new("test.han")).unwrap();
+        assert_eq!(asset_path.path(), Path::new("models/fx.gltf"));
+        assert_eq!(asset_path.label(), Some("Texture0"));
+        assert_eq!(
+            asset_path_string(&asset_path).unwrap(),
+            "models/fx.gltf#Texture0"
+        );
+    }
+
+    // Hand-edited files pointing outside the root are kept as missing textures.
+    #[test]
+    fn escaping_texture_paths() {
+        let type_registry = type_registry();
+        let type_registry = type_registry.read();
+        for path in [
+            "../../../tmp/foo.png",
+            "textures/../../foo.png",
+            "..\\..\\foo.png",
+            "/etc/passwd",
+            "C:/Windows/foo.png",
+            "",
+            "textures/..",
+        ] {
+            let re = REffect {
+                render_particle_texture: ParticleTexture::Path(path.to_string()),
+                ..default()
+            };
+            let ron = serialize_effect(&re, &type_registry).unwrap();
+            let mut loaded =
+                deserialize_effect(ron.as_bytes(), &type_registry, Path::new("test.han")).unwrap();
+
+            assert_eq!(resolve_texture(&mut loaded, Path::new("test.han")), None);
+            assert_eq!(
+                loaded.render_particle_texture,
+                ParticleTexture::Missing(path.to_string()),
+            );
+        }
+    }
+
+    #[test]
+    fn effect_round_trip() {
+        let type_registry = type_registry();
+        let type_registry = type_registry.read();
+        let with_passthrough = REffect {
+            passthrough: vec![("update_gizmo".to_string(), "(size: 2.0)".to_string())],
+            ..default()
+        };
+
+        for re in [REffect::default(), full_effect(), with_passthrough] {
+            let ron = serialize_effect(&re, &type_registry).unwrap();
+            let loaded = deserialize_effect(ron.as_bytes(), &type_registry, Path::new("test.han"))
+                .unwrap_or_else(|e| panic!("{}\n{}", e, ron));
+            assert_fields_eq(&re, &loaded);
+            assert_eq!(re.passthrough, loaded.passthrough);
+        }
+    }
+
+    #[test]
+    fn orientation_round_trip() {
+        let type_registry = type_registry();
+        let type_registry = type_registry.read();
+        for orientation in Orientation::ALL {
+            let re = REffect {
+                render_orientation: orientation,
+                ..default()
+            };
+            let ron = serialize_effect(&re, &type_registry).unwrap();
+            assert!(!ron.contains("render_billboard"), "{}", ron);
+            let loaded =
+                deserialize_effect(ron.as_bytes(), &type_registry, Path::new("test.han")).unwrap();
+            assert_eq!(loaded.render_orientation, orientation);
+        }
+
+        // Files from before the orientation have the old pair, and orient along velocity won.
+        let old = r#"{
+    "han_ed::reffect::REffect": (
+        name: "old",
+        capacity: 32,
+        render_billboard: true,
+        render_orient_along_velocity: Some(()),
+        render_order: [OrientAlongVelocity, SetColor, Billboard],
+    ),
+}"#;
+        let loaded =
+            deserialize_effect(old.as_bytes(), &type_registry, Path::new("old.han")).unwrap();
+        assert_eq!(loaded.render_orientation, Orientation::AlongVelocity);
+        assert_eq!(
+            loaded.render_order,
+            [
+                RenderModifierKind::Orientation,
+                RenderModifierKind::SetColor
+            ]
+        );
+        let ron = serialize_effect(&loaded, &type_registry).unwrap();
+        assert!(ron.contains("render_orientation: AlongVelocity"), "{}", ron);
+        assert!(!ron.contains("render_orient_along_velocity"), "{}", ron);
+    }
+
+    #[test]
+    fn library_import() {
+        let (_dir, root) = root();
+        let (_library_dir, library) = root();
+        let effect = |texture: &str| {
+            format!(
+                "(name: \"spark\", render_particle_texture: Path(\"{}\"), capacity: 32)",
+                texture
+            )
+        };
+        std::fs::create_dir_all(library.join("fx/textures")).unwrap();
+        std::fs::write(library.join("fx/spark.han"), effect("fx/textures/dot.png")).unwrap();
+        std::fs::write(library.join("fx/textures/dot.png"), "dot").unwrap();
+
+        // Copied alongside, at the same paths.
+        let (path, texture) =
+            import_from_library(&library, Path::new("fx/spark.han"), &root).unwrap();
+        assert_eq!(path, Path::new("fx/spark.han"));
+        assert_eq!(
+            texture,
+            Some(TextureImport::Copied("fx/textures/dot.png".to_string()))
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("fx/textures/dot.png")).unwrap(),
+            "dot"
+        );
+
+        // Again: the effect is made unique and the texture is reused.
+        let (path, texture) =
+            import_from_library(&library, Path::new("fx/spark.han"), &root).unwrap();
+        assert_eq!(path, Path::new("fx/spark1.han"));
+        assert_eq!(
+            texture,
+            Some(TextureImport::Existing("fx/textures/dot.png".to_string()))
+        );
+
+        // A different texture at the path: copied under a new name, and the effect rewritten.
+        std::fs::write(root.join("fx/textures/dot.png"), "other").unwrap();
+        let (path, texture) =
+            import_from_library(&library, Path::new("fx/spark.han"), &root).unwrap();
+        assert_eq!(
+            texture,
+            Some(TextureImport::Renamed {
+                from: "fx/textures/dot.png".to_string(),
+                to: "fx/textures/dot1.png".to_string(),
+            })
+        );
+        assert_eq!(
+            peek_texture_path(&root.join(path)).as_deref(),
+            Some("fx/textures/dot1.png")
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("fx/textures/dot1.png")).unwrap(),
+            "dot"
+        );
+
+        // Missing or outside the library: the path is kept.
+        std::fs::write(library.join("lost.han"), effect("../dot.png")).unwrap();
+        let (path, texture) = import_from_library(&library, Path::new("lost.han"), &root).unwrap();
+        assert_eq!(
+            texture,
+            Some(TextureImport::Missing("../dot.png".to_string()))
+        );
+        assert_eq!(
+            peek_texture_path(&root.join(path)).as_deref(),
+            Some("../dot.png")
+        );
+
+        assert!(import_from_library(&library, Path::new("../spark.han"), &root).is_err());
+    }
 }
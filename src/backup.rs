@@ -0,0 +1,243 @@
+// Backups of saved effects. Each save also writes a timestamped copy under `.han-ed/backups` in the
+// assets root, in a folder named for the effect's path, and the oldest copies past the configured
+// count are removed. The folder is hidden, so the browser doesn't list backups as effects. Backups
+// are restored into the loaded effect as unsaved changes; the effect file is only written on save.
+
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    asset::AssetOpError,
+    browser::AssetBrowser,
+    change::ChangeFlags,
+    recovery::restore,
+    reffect::REffect,
+    toast::Toasts,
+    ui::actions::{Action, Actions},
+};
+
+/// Backups folder, relative to the assets root.
+pub const BACKUP_DIR: &str = ".han-ed/backups";
+
+/// Memory id for the history dialog state while it's open.
+pub const HISTORY: &str = "history";
+
+pub struct BackupPlugin;
+
+impl Plugin for BackupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(ui_history);
+    }
+}
+
+/// A backup of an effect.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Backup {
+    pub file: PathBuf,
+    pub time: SystemTime,
+}
+
+/// The folder with the backups of an effect (relative to the root).
+pub fn backup_dir(root_path: &Path, path: &Path) -> PathBuf {
+    root_path.join(BACKUP_DIR).join(path)
+}
+
+/// Write a backup of an effect, then remove all but the newest `keep`. Backups are named for the
+/// time in milliseconds. Returns the new backup.
+pub fn write_backup(
+    root_path: &Path,
+    path: &Path,
+    contents: &str,
+    keep: usize,
+    time: SystemTime,
+) -> Result<PathBuf, AssetOpError> {
+    let dir = backup_dir(root_path, path);
+    std::fs::create_dir_all(&dir)?;
+
+    let mut millis = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    // Saved twice in the same millisecond.
+    let file = loop {
+        let file = dir.join(format!("{}.han", millis));
+        if !file.exists() {
+            break file;
+        }
+        millis += 1;
+    };
+    std::fs::write(&file, contents)?;
+
+    for backup in backups(root_path, path).into_iter().skip(keep) {
+        std::fs::remove_file(backup.file)?;
+    }
+    Ok(file)
+}
+
+/// Backups of an effect, newest first.
+pub fn backups(root_path: &Path, path: &Path) -> Vec<Backup> {
+    let Ok(entries) = std::fs::read_dir(backup_dir(root_path, path)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file = entry.path();
+            let millis = file.file_stem()?.to_str()?.parse::<u64>().ok()?;
+            Some(Backup {
+                file,
+                time: UNIX_EPOCH + Duration::from_millis(millis),
+            })
+        })
+        .collect();
+    backups.sort_by(|a, b| b.time.cmp(&a.time));
+    backups
+}
+
+// How long ago, roughly.
+fn age(time: SystemTime) -> String {
+    let secs = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or_default()
+        .as_secs();
+    match secs {
+        s if s < 60 => format!("{}s ago", s),
+        s if s < 60 * 60 => format!("{}m ago", s / 60),
+        s if s < 60 * 60 * 24 => format!("{}h ago", s / (60 * 60)),
+        s => format!("{}d ago", s / (60 * 60 * 24)),
+    }
+}
+
+#[derive(Clone)]
+struct HistoryState {
+    handle: Handle<REffect>,
+    path: PathBuf,
+    backups: Vec<Backup>,
+}
+
+/// Open the history dialog for an effect. The backups are listed once, on opening.
+pub fn open_history(handle: &Handle<REffect>, root_path: &Path, path: &Path, ctx: &egui::Context) {
+    let state = HistoryState {
+        handle: handle.clone(),
+        path: path.to_path_buf(),
+        backups: backups(root_path, path),
+    };
+    ctx.memory_mut(|memory| memory.data.insert_temp(egui::Id::new(HISTORY), state));
+}
+
+// List the backups of an effect, with a button to restore each. Restoring replaces the loaded effect
+// and marks it dirty.
+#[allow(clippy::too_many_arguments)]
+fn ui_history(
+    mut contexts: EguiContexts,
+    mut browser: ResMut<AssetBrowser<REffect>>,
+    mut reffects: ResMut<Assets<REffect>>,
+    asset_server: Res<AssetServer>,
+    type_registry: Res<AppTypeRegistry>,
+    toasts: Res<Toasts>,
+    mut actions: ResMut<Actions>,
+) {
+    let ctx = contexts.ctx_mut();
+    let id = egui::Id::new(HISTORY);
+    let Some(state) = ctx.memory_mut(|memory| memory.data.get_temp::<HistoryState>(id)) else {
+        return;
+    };
+
+    let mut open = true;
+    let mut close = false;
+    egui::Window::new(format!("History of {}", state.path.display()))
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            if state.backups.is_empty() {
+                ui.label("No backups yet. One is kept each time the effect is saved.");
+                return;
+            }
+            egui::Grid::new("history").show(ui, |ui| {
+                for backup in state.backups.iter() {
+                    ui.label(age(backup.time))
+                        .on_hover_text(backup.file.display().to_string());
+                    if ui.button("Restore").clicked() {
+                        match restore(&backup.file, &state.path, &asset_server, &type_registry) {
+                            Ok(restored) => {
+                                if let Some(re) = reffects.get_mut(&state.handle) {
+                                    *re = restored;
+                                    browser.mark_dirty(&state.path);
+                                    actions.push(Action::Regenerate(
+                                        state.handle.clone(),
+                                        ChangeFlags::all(),
+                                    ));
+                                    toasts.info(format!(
+                                        "restored {} from {}",
+                                        state.path.display(),
+                                        age(backup.time)
+                                    ));
+                                }
+                            }
+                            Err(e) => e.toast(
+                                format!("failed to restore {}", backup.file.display()),
+                                &toasts,
+                            ),
+                        }
+                        close = true;
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+
+    if close || !open {
+        ctx.memory_mut(|memory| memory.data.remove::<HistoryState>(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation() {
+        let root = tempfile::tempdir().unwrap();
+        let path = Path::new("fx/spark.han");
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        for i in 0..7 {
+            let time = start + Duration::from_secs(i);
+            write_backup(root.path(), path, &i.to_string(), 5, time).unwrap();
+        }
+
+        // The newest five, newest first.
+        let backups = backups(root.path(), path);
+        assert_eq!(backups.len(), 5);
+        assert_eq!(backups[0].time, start + Duration::from_secs(6));
+        assert_eq!(backups[4].time, start + Duration::from_secs(2));
+        assert_eq!(std::fs::read_to_string(&backups[0].file).unwrap(), "6");
+        assert!(backups[0]
+            .file
+            .starts_with(root.path().join(".han-ed/backups/fx/spark.han")));
+    }
+
+    #[test]
+    fn same_time() {
+        let root = tempfile::tempdir().unwrap();
+        let path = Path::new("spark.han");
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        let a = write_backup(root.path(), path, "a", 5, time).unwrap();
+        let b = write_backup(root.path(), path, "b", 5, time).unwrap();
+        assert_ne!(a, b);
+        assert_eq!(backups(root.path(), path).len(), 2);
+
+        // Fewer kept prunes the rest on the next save.
+        write_backup(root.path(), path, "c", 1, time).unwrap();
+        let backups = backups(root.path(), path);
+        assert_eq!(backups.len(), 1);
+        assert_eq!(std::fs::read_to_string(&backups[0].file).unwrap(), "c");
+    }
+}
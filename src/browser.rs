@@ -0,0 +1,912 @@
+use std::{path::*, time::SystemTime};
+
+use bevy::{
+    asset::{Asset, HandleId, LoadState},
+    prelude::*,
+    tasks::{futures_lite::future, IoTaskPool, Task},
+    utils::{HashMap, HashSet},
+};
+use bevy_egui::egui;
+
+use crate::{complete::PathCompletions, config::EditorConfig, toast::Toasts};
+
+/// Always ignored, in addition to hidden files and the configured patterns.
+pub const DEFAULT_IGNORE: &[&str] = &["*.meta"];
+
+/// How often loaded entries are checked on disk, in seconds.
+const REFRESH_INTERVAL: f32 = 2.0;
+
+/// Size and modified time of a file.
+#[derive(Clone, Copy, Debug)]
+pub struct FileMeta {
+    pub size: u64,
+    pub mtime: Option<SystemTime>,
+}
+
+impl FileMeta {
+    pub fn read(path: &Path) -> std::io::Result<Self> {
+        let m = std::fs::metadata(path)?;
+        Ok(Self {
+            size: m.len(),
+            mtime: m.modified().ok(),
+        })
+    }
+}
+
+/// What we know about an entry's file on disk.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum FileState {
+    /// Never saved, e.g. new or opened from outside the file system.
+    #[default]
+    None,
+    Exists(FileMeta),
+    /// It existed, but was deleted (externally).
+    Missing,
+}
+
+/// Combined file and edit state, for display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileStatus {
+    Clean,
+    Dirty,
+    NeverSaved,
+    Missing,
+}
+
+impl FileStatus {
+    /// Whether the file is on disk.
+    pub fn exists(&self) -> bool {
+        matches!(self, FileStatus::Clean | FileStatus::Dirty)
+    }
+
+    pub fn glyph(&self) -> &'static str {
+        match self {
+            FileStatus::Clean => "✔",
+            FileStatus::Dirty => "✏",
+            FileStatus::NeverSaved => "🆕",
+            FileStatus::Missing => "⚠",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            FileStatus::Clean => "saved",
+            FileStatus::Dirty => "unsaved changes",
+            FileStatus::NeverSaved => "never saved",
+            FileStatus::Missing => "file missing on disk",
+        }
+    }
+}
+
+/// An asset file known to the browser.
+pub struct AssetEntry<T: Asset> {
+    /// Relative to the assets root.
+    pub path: PathBuf,
+    pub handle: Option<Handle<T>>,
+    pub load_state: LoadState,
+    /// Changed since it was loaded or last saved.
+    pub dirty: bool,
+    pub file: FileState,
+    /// Selected for batch editing.
+    pub selected: bool,
+    /// The path for display in lists, so it isn't formatted every frame. Updated on rescan.
+    pub label: String,
+}
+
+impl<T: Asset> AssetEntry<T> {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            label: path.display().to_string(),
+            path,
+            handle: None,
+            load_state: LoadState::NotLoaded,
+            dirty: false,
+            file: FileState::None,
+            selected: false,
+        }
+    }
+
+    pub fn status(&self) -> FileStatus {
+        match (self.file, self.dirty) {
+            (FileState::Missing, _) => FileStatus::Missing,
+            (FileState::None, _) => FileStatus::NeverSaved,
+            (FileState::Exists(_), true) => FileStatus::Dirty,
+            (FileState::Exists(_), false) => FileStatus::Clean,
+        }
+    }
+
+    /// The folder the file is in, relative to the root. Empty for files in the root.
+    pub fn folder(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new(""))
+    }
+
+    /// Re-read the file metadata. A file that was known to exist and is now gone is missing.
+    pub fn refresh(&mut self, root_path: &Path) {
+        if self.path.as_os_str().is_empty() {
+            return;
+        }
+        match FileMeta::read(&root_path.join(&self.path)) {
+            Ok(meta) => self.file = FileState::Exists(meta),
+            Err(_) => {
+                if let FileState::Exists(_) = self.file {
+                    self.file = FileState::Missing;
+                }
+            }
+        }
+    }
+}
+
+// TODO: add a preview, e.g. thumbnail for images
+#[derive(Resource)]
+pub struct AssetBrowser<T: Asset> {
+    pub root_path: PathBuf,
+    pub extension: &'static str,
+    pub entries: Vec<AssetEntry<T>>,
+    /// Filter entries by path in the browser widget.
+    pub filter: String,
+    /// Paths (relative to the root) matching these are skipped when scanning.
+    pub ignore: Vec<glob::Pattern>,
+    /// The files aren't under the asset server's root (e.g. a library), so entries aren't loaded
+    /// from the list. Every entry is passed to the row in [AssetBrowser::show].
+    pub external: bool,
+    /// Known paths for the path fields, updated as entries are found.
+    pub completions: PathCompletions,
+    /// Last entry ctrl-clicked, for extending the selection with shift-click.
+    select_anchor: Option<PathBuf>,
+    scan: Option<Task<Vec<(PathBuf, Option<FileMeta>)>>>,
+}
+
+impl<T: Asset> AssetBrowser<T> {
+    pub fn new(root_path: impl Into<PathBuf>, extension: &'static str) -> Self {
+        let root_path: PathBuf = root_path.into();
+        let root_path = root_path.canonicalize().unwrap_or_else(|e| {
+            error!("failed to find assets root: {:?}", e);
+            root_path
+        });
+
+        let mut browser = Self {
+            root_path,
+            extension,
+            entries: Vec::new(),
+            filter: String::new(),
+            ignore: ignore_patterns([]),
+            external: false,
+            completions: PathCompletions::default(),
+            select_anchor: None,
+            scan: None,
+        };
+        // There's no file system on the web. Entries are added as files are opened.
+        #[cfg(not(target_arch = "wasm32"))]
+        browser.rescan();
+        browser
+    }
+
+    /// Resolve the root path again, e.g. after it was created, and rescan.
+    pub fn resolve_root(&mut self) {
+        if let Ok(root_path) = self.root_path.canonicalize() {
+            self.root_path = root_path;
+        }
+        self.rescan();
+    }
+
+    /// Start scanning for new files under the root path in the background. Existing entries are
+    /// kept. Results are merged in [update_asset_browser].
+    pub fn rescan(&mut self) {
+        let root_path = self.root_path.clone();
+        let extension = self.extension;
+        let ignore = self.ignore.clone();
+
+        // TODO read from asset io instead of walking the file system - similarly, can we read all
+        // known assets by extension?
+        self.scan = Some(IoTaskPool::get().spawn(async move {
+            let mut paths = Vec::new();
+            scan(&root_path, &root_path, extension, &ignore, &mut paths);
+            paths
+                .into_iter()
+                .map(|path| {
+                    let meta = FileMeta::read(&root_path.join(&path)).ok();
+                    (path, meta)
+                })
+                .collect()
+        }));
+    }
+
+    /// Skip paths matching the patterns, in addition to [DEFAULT_IGNORE], and rescan if they
+    /// changed. Entries that aren't loaded and are now ignored are dropped.
+    pub fn set_ignore<'a>(&mut self, patterns: impl IntoIterator<Item = &'a str>) {
+        let ignore = ignore_patterns(patterns);
+        if ignore == self.ignore {
+            return;
+        }
+        self.ignore = ignore;
+        let ignore = &self.ignore;
+        self.entries
+            .retain(|e| e.handle.is_some() || !ignore.iter().any(|p| p.matches_path(&e.path)));
+        #[cfg(not(target_arch = "wasm32"))]
+        self.rescan();
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.scan.is_some()
+    }
+
+    /// Merge the scan results if it's done. See [update_asset_browser].
+    pub fn poll_scan(&mut self) {
+        let Some(paths) = self
+            .scan
+            .as_mut()
+            .and_then(|task| future::block_on(future::poll_once(task)))
+        else {
+            return;
+        };
+        self.scan = None;
+
+        // Known files that weren't found are missing, unless they're ignored now. There can be
+        // tens of thousands of files, so look them up rather than searching.
+        let found: HashSet<&Path> = paths.iter().map(|(path, _)| path.as_path()).collect();
+        let ignore = &self.ignore;
+        for entry in self.entries.iter_mut() {
+            if let FileState::Exists(_) = entry.file {
+                if !found.contains(entry.path.as_path())
+                    && !ignore.iter().any(|p| p.matches_path(&entry.path))
+                {
+                    entry.file = FileState::Missing;
+                }
+            }
+        }
+
+        let index: HashMap<PathBuf, usize> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.path.clone(), i))
+            .collect();
+        for (path, meta) in paths {
+            let file = meta.map_or(FileState::None, FileState::Exists);
+            match index.get(&path) {
+                Some(&i) => self.entries[i].file = file,
+                None => {
+                    let mut entry = AssetEntry::new(path);
+                    entry.file = file;
+                    self.entries.push(entry);
+                }
+            }
+        }
+
+        // Paths may have been edited since.
+        for entry in self.entries.iter_mut() {
+            entry.label = entry.path.display().to_string();
+        }
+
+        self.entries.sort_by(|a, b| a.path.cmp(&b.path));
+        self.update_completions();
+    }
+
+    fn update_completions(&self) {
+        self.completions.set_files(
+            &self.root_path,
+            self.entries.iter().map(|e| e.path.as_path()),
+        );
+    }
+
+    pub fn entry(&self, path: &Path) -> Option<&AssetEntry<T>> {
+        self.entries.iter().find(|e| e.path == path)
+    }
+
+    pub fn entry_mut(&mut self, path: &Path) -> Option<&mut AssetEntry<T>> {
+        self.entries.iter_mut().find(|e| e.path == path)
+    }
+
+    /// Add a path and handle, or update the handle if the path is already known.
+    pub fn insert(&mut self, path: PathBuf, handle: Handle<T>) {
+        match self.entry_mut(&path) {
+            Some(entry) => entry.handle = Some(handle),
+            None => {
+                let mut entry = AssetEntry::new(path);
+                entry.handle = Some(handle);
+                self.entries.push(entry);
+                self.update_completions();
+            }
+        }
+    }
+
+    /// Add an entry with no path for an asset that isn't known, e.g. one added in memory by another
+    /// system. It's listed apart from the files until given a path. Returns whether it was added.
+    pub fn adopt(&mut self, handle: Handle<T>) -> bool {
+        if self
+            .entries
+            .iter()
+            .any(|e| e.handle.as_ref() == Some(&handle))
+        {
+            return false;
+        }
+        let mut entry = AssetEntry::new(PathBuf::new());
+        entry.handle = Some(handle);
+        entry.dirty = true;
+        // Sorted first.
+        self.entries.insert(0, entry);
+        true
+    }
+
+    /// A path for a new asset named `stem` that's neither known nor on disk, e.g. "spark.han",
+    /// then "spark1.han".
+    pub fn new_path(&self, stem: &str) -> PathBuf {
+        (0..)
+            .map(|i| match i {
+                0 => PathBuf::from(format!("{}.{}", stem, self.extension)),
+                i => PathBuf::from(format!("{}{}.{}", stem, i, self.extension)),
+            })
+            .find(|path| self.entry(path).is_none() && !self.root_path.join(path).exists())
+            .unwrap()
+    }
+
+    /// Load the asset at the path if it's known and not loaded already.
+    pub fn load(&mut self, path: &Path, asset_server: &AssetServer) {
+        if let Some(entry) = self.entry_mut(path) {
+            if entry.handle.is_none() {
+                entry.handle = Some(asset_server.load(entry.path.as_path()));
+            }
+        }
+    }
+
+    pub fn mark_dirty(&mut self, path: &Path) {
+        if let Some(entry) = self.entry_mut(path) {
+            entry.dirty = true;
+        }
+    }
+
+    /// Iterate all paths with handles.
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &Handle<T>)> {
+        self.entries
+            .iter()
+            .filter_map(|e| e.handle.as_ref().map(|h| (e.path.as_path(), h)))
+    }
+
+    /// Loaded entries that are selected.
+    pub fn selected(&self) -> impl Iterator<Item = &AssetEntry<T>> {
+        self.entries
+            .iter()
+            .filter(|e| e.selected && e.handle.is_some())
+    }
+
+    pub fn clear_selection(&mut self) {
+        for entry in self.entries.iter_mut() {
+            entry.selected = false;
+        }
+        self.select_anchor = None;
+    }
+
+    // Ctrl-click toggles an entry, shift-click selects the (visible) range from the last
+    // ctrl-clicked entry.
+    fn select(&mut self, visible: &[usize], n: usize, response: &egui::Response) {
+        if !response.clicked() {
+            return;
+        }
+
+        let modifiers = response.ctx.input(|input| input.modifiers);
+        if modifiers.shift {
+            let anchor = self
+                .select_anchor
+                .as_ref()
+                .and_then(|anchor| {
+                    visible
+                        .iter()
+                        .position(|i| &self.entries[*i].path == anchor)
+                })
+                .unwrap_or(n);
+            for i in &visible[anchor.min(n)..=anchor.max(n)] {
+                self.entries[*i].selected = true;
+            }
+        } else if modifiers.command {
+            let entry = &mut self.entries[visible[n]];
+            entry.selected = !entry.selected;
+            self.select_anchor = Some(entry.path.clone());
+        }
+    }
+
+    /// Show the (filtered) list of entries, grouped by folder. Unloaded entries get a load button
+    /// and failed ones an error. Loaded entries are shown with `row`, which is passed the root
+    /// path. The response returned from `row` is used for selection. Each folder other than the
+    /// root gets a collapsing header with an unsaved indicator, and `folder` adds to the header,
+    /// e.g. buttons for the whole folder.
+    pub fn show(
+        &mut self,
+        asset_server: &AssetServer,
+        ui: &mut egui::Ui,
+        mut folder: impl FnMut(&Path, &[&AssetEntry<T>], &mut egui::Ui),
+        mut row: impl FnMut(&Path, &mut AssetEntry<T>, &mut egui::Ui) -> Option<egui::Response>,
+    ) {
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.text_edit_singleline(&mut self.filter);
+            if self.scan.is_some() {
+                ui.spinner();
+                ui.label("scanning…");
+            }
+        });
+
+        let filter = self.filter.to_lowercase();
+
+        // Visible entries by folder, keeping the sorted order within each. Entries without a path
+        // are always shown, in their own group.
+        let mut unsaved = Vec::new();
+        let mut folders: Vec<(PathBuf, Vec<usize>)> = Vec::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            if entry.path.as_os_str().is_empty() {
+                unsaved.push(i);
+                continue;
+            }
+            if !filter.is_empty()
+                && !entry
+                    .path
+                    .to_string_lossy()
+                    .to_lowercase()
+                    .contains(&filter)
+            {
+                continue;
+            }
+            match folders
+                .iter_mut()
+                .find(|(f, _)| f.as_path() == entry.folder())
+            {
+                Some((_, indices)) => indices.push(i),
+                None => folders.push((entry.folder().to_path_buf(), vec![i])),
+            }
+        }
+
+        // In display order, for range selection.
+        let visible: Vec<usize> = unsaved
+            .iter()
+            .copied()
+            .chain(folders.iter().flat_map(|(_, v)| v.iter().copied()))
+            .collect();
+
+        let Self {
+            root_path,
+            entries,
+            external,
+            ..
+        } = self;
+        // Collapsed groups aren't drawn, so rows are found by entry rather than counted.
+        let positions: HashMap<usize, usize> =
+            visible.iter().enumerate().map(|(n, i)| (*i, n)).collect();
+        let mut responses = Vec::new();
+        let mut rows = |indices: &[usize], entries: &mut Vec<AssetEntry<T>>, ui: &mut egui::Ui| {
+            for i in indices {
+                let entry = &mut entries[*i];
+                let response = match *external {
+                    true => row(root_path, entry, ui),
+                    false => show_entry(entry, root_path, asset_server, ui, &mut row),
+                };
+                if let Some(response) = response {
+                    responses.push((positions[i], response));
+                }
+            }
+        };
+
+        if !unsaved.is_empty() {
+            egui::CollapsingHeader::new(format!("Unsaved / in-memory ({})", unsaved.len()))
+                .id_source("unsaved")
+                .default_open(true)
+                .show(ui, |ui| rows(&unsaved, entries, ui))
+                .header_response
+                .on_hover_text("Added without a file. Set a path to save them.");
+        }
+
+        for (path, indices) in folders.iter() {
+            if path.as_os_str().is_empty() {
+                rows(indices, entries, ui);
+                continue;
+            }
+
+            let id = ui.make_persistent_id(("folder", path));
+            egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, true)
+                .show_header(ui, |ui| {
+                    let group: Vec<_> = indices.iter().map(|i| &entries[*i]).collect();
+                    ui.label(format!("📁 {}", path.display()));
+                    let unsaved = group
+                        .iter()
+                        .filter(|e| {
+                            matches!(e.status(), FileStatus::Dirty | FileStatus::NeverSaved)
+                        })
+                        .count();
+                    if unsaved > 0 {
+                        ui.label(FileStatus::Dirty.glyph())
+                            .on_hover_text(format!("{} with unsaved changes", unsaved));
+                    }
+                    folder(path, &group, ui);
+                })
+                .body(|ui| rows(indices, entries, ui));
+        }
+
+        for (n, response) in responses {
+            self.select(&visible, n, &response);
+        }
+    }
+}
+
+// One entry in the list. See [AssetBrowser::show].
+fn show_entry<T: Asset>(
+    entry: &mut AssetEntry<T>,
+    root_path: &Path,
+    asset_server: &AssetServer,
+    ui: &mut egui::Ui,
+    row: &mut impl FnMut(&Path, &mut AssetEntry<T>, &mut egui::Ui) -> Option<egui::Response>,
+) -> Option<egui::Response> {
+    match (entry.handle.is_some(), entry.load_state) {
+        (false, _) => {
+            ui.horizontal(|ui| {
+                ui.label(entry.path.to_string_lossy());
+                if ui.button("Load").clicked() {
+                    entry.handle = Some(asset_server.load(entry.path.as_path()));
+                }
+            });
+            None
+        }
+        (true, LoadState::Failed) => {
+            ui.horizontal(|ui| {
+                ui.label(entry.path.to_string_lossy());
+                ui.colored_label(ui.visuals().error_fg_color, "failed to load");
+                if ui.button("Retry").clicked() {
+                    entry.handle = Some(asset_server.load(entry.path.as_path()));
+                    entry.load_state = LoadState::Loading;
+                }
+            });
+            None
+        }
+        (true, LoadState::Loaded) => row(root_path, entry, ui),
+        _ => {
+            ui.horizontal(|ui| {
+                ui.label(entry.path.to_string_lossy());
+                ui.spinner(); // loading still
+            });
+            None
+        }
+    }
+}
+
+/// Apply the configured ignore patterns when they change.
+pub fn apply_ignore<T: Asset>(config: Res<EditorConfig>, mut browser: ResMut<AssetBrowser<T>>) {
+    if config.is_changed() {
+        browser.set_ignore(config.ignore.iter().map(String::as_str));
+    }
+}
+
+/// Merge scan results and track the load state of each entry. Loaded entries are periodically
+/// checked on disk.
+pub fn update_asset_browser<T: Asset>(
+    asset_server: Res<AssetServer>,
+    toasts: Res<Toasts>,
+    time: Res<Time>,
+    mut since_refresh: Local<f32>,
+    mut browser: ResMut<AssetBrowser<T>>,
+) {
+    browser.poll_scan();
+
+    *since_refresh += time.delta_seconds();
+    let refresh = !cfg!(target_arch = "wasm32") && *since_refresh > REFRESH_INTERVAL;
+    if refresh {
+        *since_refresh = 0.0;
+    }
+
+    let browser = &mut *browser;
+    for entry in browser.entries.iter_mut() {
+        if refresh && entry.handle.is_some() {
+            entry.refresh(&browser.root_path);
+        }
+
+        if let Some(handle) = entry.handle.as_ref() {
+            let load_state = match handle.id() {
+                // Added directly to assets, not loaded from a file.
+                HandleId::Id(..) => LoadState::Loaded,
+                id => asset_server.get_load_state(id),
+            };
+
+            if load_state == LoadState::Failed && entry.load_state != LoadState::Failed {
+                toasts.error(format!("failed to load {}", entry.path.display()));
+            }
+            entry.load_state = load_state;
+        }
+    }
+}
+
+// The default patterns and the given ones. Invalid patterns are skipped with a warning.
+fn ignore_patterns<'a>(patterns: impl IntoIterator<Item = &'a str>) -> Vec<glob::Pattern> {
+    DEFAULT_IGNORE
+        .iter()
+        .copied()
+        .chain(patterns)
+        .filter_map(|p| match glob::Pattern::new(p) {
+            Ok(pattern) => Some(pattern),
+            Err(e) => {
+                warn!("invalid ignore pattern {}: {}", p, e);
+                None
+            }
+        })
+        .collect()
+}
+
+// Recursively find files with the extension, skipping hidden and ignored paths. Paths are relative
+// to the root.
+fn scan(root: &Path, dir: &Path, ext: &str, ignore: &[glob::Pattern], paths: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            error!("failed to read {}: {:?}", dir.display(), e);
+            return;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(rel_path) = path.strip_prefix(root) else {
+            continue;
+        };
+
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if hidden || ignore.iter().any(|p| p.matches_path(rel_path)) {
+            continue;
+        }
+
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => scan(root, &path, ext, ignore, paths),
+            Ok(_) if path.extension().is_some_and(|e| e == ext) => {
+                paths.push(rel_path.to_path_buf())
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::tasks::TaskPool;
+
+    use super::*;
+
+    fn wait_scan(browser: &mut AssetBrowser<Image>) {
+        while browser.is_scanning() {
+            std::thread::yield_now();
+            browser.poll_scan();
+        }
+    }
+
+    // A browser over the folder, with the scan done.
+    fn scanned(dir: &Path) -> AssetBrowser<Image> {
+        IoTaskPool::init(TaskPool::default);
+        let mut browser = AssetBrowser::<Image>::new(dir, "png");
+        wait_scan(&mut browser);
+        browser
+    }
+
+    fn handle() -> Handle<Image> {
+        Handle::weak(HandleId::random::<Image>())
+    }
+
+    #[test]
+    fn insert_and_find() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut browser = scanned(dir.path());
+        let (a, b) = (handle(), handle());
+
+        browser.insert("a.png".into(), a.clone());
+        let entry = browser.entry(Path::new("a.png")).unwrap();
+        assert_eq!(entry.handle.as_ref(), Some(&a));
+        assert_eq!(entry.status(), FileStatus::NeverSaved);
+        assert!(browser.entry(Path::new("b.png")).is_none());
+
+        // A known path gets the new handle.
+        browser.insert("a.png".into(), b.clone());
+        assert_eq!(browser.entries.len(), 1);
+        assert_eq!(browser.entry(Path::new("a.png")).unwrap().handle, Some(b));
+
+        browser.entry_mut(Path::new("a.png")).unwrap().selected = true;
+        assert_eq!(browser.selected().count(), 1);
+        assert!(browser.entry_mut(Path::new("b.png")).is_none());
+    }
+
+    #[test]
+    fn mark_dirty() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.png"), "").unwrap();
+        let mut browser = scanned(dir.path());
+        let path = Path::new("a.png");
+        assert_eq!(browser.entry(path).unwrap().status(), FileStatus::Clean);
+
+        browser.mark_dirty(path);
+        assert_eq!(browser.entry(path).unwrap().status(), FileStatus::Dirty);
+
+        // Unknown paths aren't added.
+        browser.mark_dirty(Path::new("b.png"));
+        assert_eq!(browser.entries.len(), 1);
+    }
+
+    // New paths are neither known nor on disk.
+    #[test]
+    fn new_path_unique() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut browser = scanned(dir.path());
+        assert_eq!(browser.new_path("spark"), Path::new("spark.png"));
+
+        std::fs::write(dir.path().join("spark.png"), "").unwrap();
+        browser.insert("spark1.png".into(), handle());
+        assert_eq!(browser.new_path("spark"), Path::new("spark2.png"));
+        assert_eq!(browser.new_path("smoke"), Path::new("smoke.png"));
+    }
+
+    // Files that existed and are gone are missing, whether found by a refresh or a rescan.
+    #[test]
+    fn exists_then_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        for file in ["a.png", "b.png"] {
+            std::fs::write(dir.path().join(file), "").unwrap();
+        }
+        let mut browser = scanned(dir.path());
+        let root_path = browser.root_path.clone();
+        let (a, b) = (Path::new("a.png"), Path::new("b.png"));
+        assert!(matches!(
+            browser.entry(a).unwrap().file,
+            FileState::Exists(_)
+        ));
+
+        std::fs::remove_file(dir.path().join(a)).unwrap();
+        browser.entry_mut(a).unwrap().refresh(&root_path);
+        assert_eq!(browser.entry(a).unwrap().status(), FileStatus::Missing);
+
+        // And back when it's recreated.
+        std::fs::write(dir.path().join(a), "").unwrap();
+        browser.entry_mut(a).unwrap().refresh(&root_path);
+        assert_eq!(browser.entry(a).unwrap().status(), FileStatus::Clean);
+
+        std::fs::remove_file(dir.path().join(b)).unwrap();
+        browser.rescan();
+        wait_scan(&mut browser);
+        assert_eq!(browser.entry(b).unwrap().status(), FileStatus::Missing);
+        assert_eq!(browser.entry(a).unwrap().status(), FileStatus::Clean);
+
+        // Never saved entries stay that way.
+        browser.insert("c.png".into(), handle());
+        browser
+            .entry_mut(Path::new("c.png"))
+            .unwrap()
+            .refresh(&root_path);
+        assert_eq!(
+            browser.entry(Path::new("c.png")).unwrap().status(),
+            FileStatus::NeverSaved
+        );
+    }
+
+    #[test]
+    fn configured_ignore() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("tmp")).unwrap();
+        for file in ["a.png", "tmp/b.png", "tmp/c.png"] {
+            std::fs::write(dir.path().join(file), "").unwrap();
+        }
+        let mut browser = scanned(dir.path());
+        browser.insert("tmp/c.png".into(), handle());
+        assert_eq!(browser.entries.len(), 3);
+
+        browser.set_ignore(["tmp/*", "[invalid"]);
+        wait_scan(&mut browser);
+        // Loaded entries are kept, and aren't missing.
+        let paths: Vec<_> = browser.entries.iter().map(|e| e.path.as_path()).collect();
+        assert_eq!(paths, [Path::new("a.png"), Path::new("tmp/c.png")]);
+        assert_eq!(
+            browser.entry(Path::new("tmp/c.png")).unwrap().status(),
+            FileStatus::Clean
+        );
+
+        // The same patterns don't rescan.
+        browser.set_ignore(["tmp/*"]);
+        assert!(!browser.is_scanning());
+
+        browser.set_ignore([]);
+        wait_scan(&mut browser);
+        assert_eq!(browser.entries.len(), 3);
+    }
+
+    // Ctrl-click an entry after a collapsed folder.
+    #[test]
+    fn select_after_collapsed() {
+        let dir = tempfile::tempdir().unwrap();
+        for folder in ["a", "b"] {
+            std::fs::create_dir(dir.path().join(folder)).unwrap();
+            std::fs::write(dir.path().join(folder).join("1.png"), "").unwrap();
+        }
+        let mut browser = scanned(dir.path());
+        browser.external = true;
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default());
+        let asset_server = app.world.resource::<AssetServer>();
+
+        let ctx = egui::Context::default();
+        let mut target = egui::Rect::NOTHING;
+        let mut frame = |events: Vec<egui::Event>, target: &mut egui::Rect| {
+            let input = egui::RawInput {
+                screen_rect: Some(egui::Rect::from_min_size(
+                    egui::Pos2::ZERO,
+                    egui::vec2(800.0, 600.0),
+                )),
+                modifiers: egui::Modifiers {
+                    ctrl: true,
+                    command: true,
+                    ..default()
+                },
+                events,
+                ..default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    let id = ui.make_persistent_id(("folder", Path::new("a")));
+                    let mut state =
+                        egui::collapsing_header::CollapsingState::load_with_default_open(
+                            ctx, id, true,
+                        );
+                    state.set_open(false);
+                    state.store(ctx);
+
+                    browser.show(
+                        asset_server,
+                        ui,
+                        |_, _, _| (),
+                        |_, entry, ui| {
+                            let response = ui.add(
+                                egui::Label::new(entry.label.clone()).sense(egui::Sense::click()),
+                            );
+                            if entry.path == Path::new("b/1.png") {
+                                *target = response.rect;
+                            }
+                            Some(response)
+                        },
+                    );
+                });
+            });
+        };
+
+        frame(Vec::new(), &mut target);
+        let pos = target.center();
+        let button = |pressed| egui::Event::PointerButton {
+            pos,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: egui::Modifiers {
+                ctrl: true,
+                command: true,
+                ..default()
+            },
+        };
+        frame(
+            vec![egui::Event::PointerMoved(pos), button(true)],
+            &mut target,
+        );
+        frame(vec![button(false)], &mut target);
+
+        assert!(browser.entry(Path::new("b/1.png")).unwrap().selected);
+        assert!(!browser.entry(Path::new("a/1.png")).unwrap().selected);
+    }
+
+    #[test]
+    fn adopt_once() {
+        IoTaskPool::init(TaskPool::default);
+        let dir = tempfile::tempdir().unwrap();
+        let mut browser = AssetBrowser::<Image>::new(dir.path(), "png");
+        browser.insert("a.png".into(), Handle::weak(HandleId::random::<Image>()));
+
+        let handle = Handle::weak(HandleId::random::<Image>());
+        assert!(browser.adopt(handle.clone()));
+        assert!(!browser.adopt(handle.clone()));
+
+        let entry = &browser.entries[0];
+        assert_eq!(entry.handle.as_ref(), Some(&handle));
+        assert_eq!(entry.status(), FileStatus::NeverSaved);
+        assert_eq!(browser.entries.len(), 2);
+
+        // The root isn't mistaken for its file.
+        let mut entry = AssetEntry::<Image>::new(PathBuf::new());
+        entry.refresh(dir.path());
+        assert_eq!(entry.status(), FileStatus::NeverSaved);
+    }
+}
@@ -0,0 +1,215 @@
+// Orbit camera with view presets. Drag with the right mouse button to orbit, the middle button to
+// pan, and scroll to zoom. Presets and input both set the target orbit, which the camera eases
+// toward.
+
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::{
+    input::mouse::{MouseMotion, MouseWheel},
+    prelude::*,
+    render::camera::ScalingMode,
+};
+use bevy_egui::EguiContexts;
+
+/// Radians per pixel dragged.
+const ORBIT_SPEED: f32 = 0.005;
+/// Fraction of the distance per scroll line.
+const ZOOM_SPEED: f32 = 0.1;
+/// How quickly the camera catches up to the target (per second).
+const EASE: f32 = 10.0;
+/// Just short of straight up or down, where the up vector is undefined.
+const MAX_PITCH: f32 = FRAC_PI_2 - 0.001;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(orbit_input)
+            .add_system(update_camera.after(orbit_input));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Orbit {
+    pub focus: Vec3,
+    /// Around +Y, zero looking down -Z.
+    pub yaw: f32,
+    /// Positive is above the focus.
+    pub pitch: f32,
+    pub distance: f32,
+}
+
+impl Orbit {
+    pub fn looking_at(eye: Vec3, focus: Vec3) -> Self {
+        let offset = eye - focus;
+        let distance = offset.length();
+        Self {
+            focus,
+            yaw: offset.x.atan2(offset.z),
+            pitch: (offset.y / distance).asin(),
+            distance,
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        let rotation = Quat::from_euler(EulerRot::YXZ, self.yaw, -self.pitch, 0.0);
+        Transform {
+            translation: self.focus + rotation * Vec3::Z * self.distance,
+            rotation,
+            ..default()
+        }
+    }
+
+    // Yaw takes the short way around.
+    fn lerp(&self, to: &Self, t: f32) -> Self {
+        let yaw = (to.yaw - self.yaw + PI).rem_euclid(2.0 * PI) - PI;
+        Self {
+            focus: self.focus.lerp(to.focus, t),
+            yaw: self.yaw + yaw * t,
+            pitch: self.pitch + (to.pitch - self.pitch) * t,
+            distance: self.distance + (to.distance - self.distance) * t,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViewPreset {
+    Front,
+    Top,
+    Side,
+    Perspective,
+}
+
+impl ViewPreset {
+    pub const ALL: [Self; 4] = [Self::Front, Self::Top, Self::Side, Self::Perspective];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Front => "Front",
+            Self::Top => "Top",
+            Self::Side => "Side",
+            Self::Perspective => "Perspective",
+        }
+    }
+
+    /// Keeps the focus and distance.
+    pub fn orbit(&self, orbit: &Orbit) -> Orbit {
+        let (yaw, pitch) = match self {
+            Self::Front => (0.0, 0.0),
+            Self::Top => (0.0, MAX_PITCH),
+            Self::Side => (FRAC_PI_2, 0.0),
+            Self::Perspective => {
+                let default = Orbit::default();
+                (default.yaw, default.pitch)
+            }
+        };
+        Orbit {
+            yaw,
+            pitch,
+            ..*orbit
+        }
+    }
+}
+
+/// The initial view.
+impl Default for Orbit {
+    fn default() -> Self {
+        Self::looking_at(Vec3::new(3.0, 3.0, 5.0), Vec3::new(0.0, 1.0, 0.0))
+    }
+}
+
+#[derive(Component)]
+pub struct OrbitCamera {
+    pub current: Orbit,
+    pub target: Orbit,
+    pub orthographic: bool,
+    /// Vertical field of view in radians. Also used to frame the orthographic view.
+    pub fov: f32,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self {
+            current: default(),
+            target: default(),
+            orthographic: false,
+            fov: PerspectiveProjection::default().fov,
+        }
+    }
+}
+
+impl OrbitCamera {
+    pub fn preset(&mut self, preset: ViewPreset) {
+        self.target = preset.orbit(&self.target);
+    }
+}
+
+fn orbit_input(
+    mut contexts: EguiContexts,
+    buttons: Res<Input<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut wheel: EventReader<MouseWheel>,
+    mut cameras: Query<&mut OrbitCamera>,
+) {
+    let delta: Vec2 = motion.iter().map(|e| e.delta).sum();
+    let scroll: f32 = wheel.iter().map(|e| e.y.signum()).sum();
+
+    // Don't steal input from the UI.
+    if contexts.ctx_mut().wants_pointer_input() || contexts.ctx_mut().is_pointer_over_area() {
+        return;
+    }
+
+    for mut camera in cameras.iter_mut() {
+        let target = &mut camera.target;
+        if buttons.pressed(MouseButton::Right) {
+            target.yaw -= delta.x * ORBIT_SPEED;
+            target.pitch = (target.pitch + delta.y * ORBIT_SPEED).clamp(-MAX_PITCH, MAX_PITCH);
+        } else if buttons.pressed(MouseButton::Middle) {
+            let rotation = target.transform().rotation;
+            let scale = target.distance * ORBIT_SPEED * 0.2;
+            target.focus += rotation * Vec3::new(-delta.x, delta.y, 0.0) * scale;
+        }
+        if scroll != 0.0 {
+            target.distance = (target.distance * (1.0 - scroll * ZOOM_SPEED)).max(0.1);
+        }
+    }
+}
+
+fn update_camera(
+    time: Res<Time>,
+    mut cameras: Query<(&mut OrbitCamera, &mut Transform, &mut Projection)>,
+) {
+    let t = 1.0 - (-EASE * time.delta_seconds()).exp();
+    for (mut camera, mut transform, mut projection) in cameras.iter_mut() {
+        let camera = &mut *camera;
+        camera.current = camera.current.lerp(&camera.target, t);
+        *transform = camera.current.transform();
+
+        // Swap projections, keeping the framing at the focus.
+        let height = 2.0 * camera.current.distance * (camera.fov / 2.0).tan();
+        match (camera.orthographic, &mut *projection) {
+            (true, Projection::Orthographic(ortho)) => {
+                ortho.scaling_mode = ScalingMode::FixedVertical(height)
+            }
+            (true, _) => {
+                *projection = Projection::Orthographic(OrthographicProjection {
+                    scaling_mode: ScalingMode::FixedVertical(height),
+                    // Don't clip anything between the camera and the focus.
+                    near: -1000.0,
+                    ..default()
+                })
+            }
+            (false, Projection::Perspective(perspective)) => {
+                if perspective.fov != camera.fov {
+                    perspective.fov = camera.fov;
+                }
+            }
+            (false, _) => {
+                *projection = Projection::Perspective(PerspectiveProjection {
+                    fov: camera.fov,
+                    ..default()
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,198 @@
+// Capture log records from hanabi and the renderer so problems show up in the editor instead of
+// scrolling past in the terminal. On native this replaces bevy's LogPlugin with the same terminal
+// output plus a layer that forwards the records we care about.
+
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    log::Level,
+    prelude::*,
+    utils::tracing::field::{Field, Visit},
+};
+
+use crate::toast::Toasts;
+
+/// Targets that report shader generation and compilation errors.
+pub const SHADER_TARGETS: &[&str] = &["naga", "wgpu", "wgpu_core", "bevy_render::render_resource"];
+
+/// Other targets captured for the log section.
+pub const CAPTURE_TARGETS: &[&str] = &["bevy_hanabi"];
+
+/// Maximum length of a shader error toast. The full message is still logged.
+const TOAST_LENGTH: usize = 500;
+
+/// Number of records kept for the log section.
+pub const LOG_CAPACITY: usize = 200;
+
+pub struct CapturePlugin {
+    /// Same as [LogPlugin](bevy::log::LogPlugin), for the terminal.
+    pub level: Level,
+    pub filter: String,
+}
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        let capture = LogCapture::default();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        install(&capture, self.level, &self.filter);
+
+        app.insert_resource(capture)
+            .add_system(forward_shader_errors);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Record {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    /// The effect most recently regenerated when this was recorded.
+    pub effect: Option<String>,
+}
+
+impl Record {
+    pub fn is_shader(&self) -> bool {
+        SHADER_TARGETS.iter().any(|t| self.target.starts_with(t))
+    }
+}
+
+#[derive(Default)]
+struct Captured {
+    pending: Vec<Record>,
+    /// The last [LOG_CAPACITY] records, oldest first.
+    history: VecDeque<Record>,
+    regenerated: Option<String>,
+}
+
+/// Records captured from the log. Cloning shares them, like [Toasts].
+#[derive(Resource, Clone, Default)]
+pub struct LogCapture(Arc<Mutex<Captured>>);
+
+impl LogCapture {
+    /// Note the effect being regenerated. Shaders are compiled later in the render world, so
+    /// errors are associated with whichever effect was regenerated last.
+    pub fn regenerated(&self, name: &str) {
+        self.0.lock().unwrap().regenerated = Some(name.to_string());
+    }
+
+    fn push(&self, level: Level, target: &str, message: String) {
+        let mut captured = self.0.lock().unwrap();
+        let record = Record {
+            level,
+            target: target.to_string(),
+            message,
+            effect: captured.regenerated.clone(),
+        };
+
+        if captured.history.len() == LOG_CAPACITY {
+            captured.history.pop_front();
+        }
+        captured.history.push_back(record.clone());
+        captured.pending.push(record);
+    }
+
+    /// Copy of the kept records, oldest first.
+    pub fn history(&self) -> Vec<Record> {
+        self.0.lock().unwrap().history.iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.0.lock().unwrap().history.clear();
+    }
+
+    /// Take the records captured since the last call.
+    pub fn drain(&self) -> Vec<Record> {
+        std::mem::take(&mut self.0.lock().unwrap().pending)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn install(capture: &LogCapture, level: Level, filter: &str) {
+    use bevy::utils::tracing::subscriber;
+    use tracing_subscriber::{filter::Targets, prelude::*, EnvFilter, Registry};
+
+    // Records from the log crate (wgpu, naga).
+    if let Err(e) = tracing_log::LogTracer::init() {
+        eprintln!("failed to set up log tracer: {:?}", e);
+    }
+
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(format!("{},{}", level, filter)))
+        .unwrap_or_else(|_| EnvFilter::new(level.to_string()));
+
+    // Capture warnings regardless of the terminal filter.
+    let targets = SHADER_TARGETS
+        .iter()
+        .chain(CAPTURE_TARGETS)
+        .fold(Targets::new(), |targets, target| {
+            targets.with_target(*target, Level::WARN)
+        });
+
+    let subscriber = Registry::default()
+        .with(tracing_subscriber::fmt::layer().with_filter(filter))
+        .with(CaptureLayer(capture.clone()).with_filter(targets));
+
+    if let Err(e) = subscriber::set_global_default(subscriber) {
+        eprintln!("failed to set up logging: {:?}", e);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+struct CaptureLayer(LogCapture);
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<S: bevy::utils::tracing::Subscriber> tracing_subscriber::Layer<S> for CaptureLayer {
+    fn on_event(
+        &self,
+        event: &bevy::utils::tracing::Event<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let mut message = Message::default();
+        event.record(&mut message);
+        let metadata = event.metadata();
+        self.0.push(*metadata.level(), metadata.target(), message.0);
+    }
+}
+
+// The message followed by any other fields.
+#[derive(Default)]
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{:?}", value));
+        } else if !field.name().starts_with("log.") {
+            _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+// Shader errors usually mean the effect silently doesn't render, so they get a toast.
+fn forward_shader_errors(capture: Res<LogCapture>, toasts: Res<Toasts>) {
+    for record in capture.drain() {
+        if record.level != Level::ERROR || !record.is_shader() {
+            continue;
+        }
+
+        let mut message = record.message;
+        if message.len() > TOAST_LENGTH {
+            let mut end = TOAST_LENGTH;
+            while !message.is_char_boundary(end) {
+                end -= 1;
+            }
+            message.truncate(end);
+            message.push('…');
+        }
+
+        toasts.error(match record.effect {
+            Some(effect) => format!("{}: shader error: {}", effect, message),
+            None => format!("shader error: {}", message),
+        });
+    }
+}
@@ -1,5 +1,13 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use bevy::{asset::HandleId, prelude::*, reflect::ReflectRef, utils::HashMap};
 use bevy_egui::egui;
 
+use crate::reffect::REffect;
+
 pub trait Merge {
     fn merge(self) -> Change;
 }
@@ -44,6 +52,173 @@ impl Change {
             Change::Response(r) => r.changed(),
         }
     }
+
+    /// Tag the change with a category. Returns empty flags if nothing changed.
+    pub fn tag(&self, flags: ChangeFlags) -> ChangeFlags {
+        if self.changed() {
+            flags
+        } else {
+            ChangeFlags::empty()
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// Coarse categories of changes to an effect, collected per frame. These determine how much
+    /// of a live effect needs to be regenerated.
+    #[derive(Default)]
+    pub struct ChangeFlags: u16 {
+        /// Name and other metadata that doesn't affect the effect asset.
+        const META = 1 << 0;
+        const CAPACITY = 1 << 1;
+        const SPAWNER = 1 << 2;
+        const SIMULATION = 1 << 3;
+        const INIT = 1 << 4;
+        const UPDATE = 1 << 5;
+        const RENDER = 1 << 6;
+        /// The particle texture only.
+        const TEXTURE = 1 << 7;
+        const PROPERTIES = 1 << 8;
+        /// The culling bounds, set on the live entity.
+        const BOUNDS = 1 << 9;
+    }
+}
+
+/// How a live effect is regenerated after a change. See [ChangeFlags::regeneration].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Regeneration {
+    /// Only the live entity is updated (its name or bounds). The effect asset is unchanged.
+    Entity,
+    /// The effect asset is replaced under the same handle. Hanabi recompiles the effect, but the
+    /// entity, its spawner state and the particles alive are kept, so continuous effects don't
+    /// hiccup.
+    InPlace,
+    /// The live effect is despawned and spawned again in the same place. The spawner starts over
+    /// and the particles alive are cleared.
+    Respawn,
+}
+
+impl ChangeFlags {
+    /// Changes that only update the live entity.
+    pub const ENTITY: Self = Self::META.union(Self::BOUNDS);
+
+    /// Changes that only affect how particles are drawn, applied in place.
+    pub const IN_PLACE: Self = Self::ENTITY.union(Self::RENDER).union(Self::TEXTURE);
+
+    /// What the changes take, by category:
+    ///
+    /// | Category                     | Regeneration |
+    /// |------------------------------|--------------|
+    /// | META, BOUNDS                 | Entity       |
+    /// | RENDER, TEXTURE              | InPlace      |
+    /// | SPAWNER                      | Respawn: the spawner is copied from the asset when spawned |
+    /// | CAPACITY                     | Respawn: the particle buffers are sized when spawned |
+    /// | SIMULATION, INIT, UPDATE     | Respawn: particles alive were spawned and moved under the old settings |
+    /// | PROPERTIES                   | Respawn: the property layout changes |
+    ///
+    /// A mix takes the most of any of them. No flags means nothing is known about what changed,
+    /// so the effect is respawned.
+    pub fn regeneration(&self) -> Regeneration {
+        if self.is_empty() || !Self::IN_PLACE.contains(*self) {
+            Regeneration::Respawn
+        } else if Self::ENTITY.contains(*self) {
+            Regeneration::Entity
+        } else {
+            Regeneration::InPlace
+        }
+    }
+}
+
+/// Hash of each effect's content when loaded, saved or last changed in the editor. Widgets can
+/// report changes when nothing changed (a combo box reopened, a drag back to the same value), so
+/// edits are only kept if the content hash differs. See [content_hash].
+#[derive(Resource, Default)]
+pub struct ContentHashes(HashMap<HandleId, u64>);
+
+impl ContentHashes {
+    /// Record the effect's content, e.g. when loaded or saved.
+    pub fn insert(&mut self, handle: &Handle<REffect>, re: &REffect) {
+        self.0.insert(handle.id(), content_hash(re));
+    }
+
+    /// Whether the effect's content differs from what was last recorded, recording it if so.
+    /// Effects with nothing recorded count as changed.
+    pub fn changed(&mut self, handle: &Handle<REffect>, re: &REffect) -> bool {
+        let hash = content_hash(re);
+        self.0.insert(handle.id(), hash) != Some(hash)
+    }
+}
+
+/// Record loaded effects, and forget removed ones.
+pub fn record_loaded(
+    mut events: EventReader<AssetEvent<REffect>>,
+    reffects: Res<Assets<REffect>>,
+    mut hashes: ResMut<ContentHashes>,
+) {
+    for event in events.iter() {
+        match event {
+            AssetEvent::Created { handle } => {
+                if let Some(re) = reffects.get(handle) {
+                    hashes.insert(handle, re);
+                }
+            }
+            AssetEvent::Removed { handle } => {
+                hashes.0.remove(&handle.id());
+            }
+            // The editor modifies effects every frame it shows them.
+            AssetEvent::Modified { .. } => (),
+        }
+    }
+}
+
+/// Hash a value's content via reflection. Floats hash by their bits, with every NaN alike so a NaN
+/// doesn't look like a change each frame. Opaque values hash by their debug output.
+pub fn content_hash(value: &dyn Reflect) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hash_reflect(value, &mut hasher);
+    hasher.finish()
+}
+
+fn hash_reflect(value: &dyn Reflect, hasher: &mut DefaultHasher) {
+    if let Some(v) = value.downcast_ref::<f32>() {
+        let v = if v.is_nan() { f32::NAN } else { *v };
+        v.to_bits().hash(hasher);
+        return;
+    }
+
+    match value.reflect_ref() {
+        ReflectRef::Struct(s) => {
+            for (i, field) in s.iter_fields().enumerate() {
+                s.name_at(i).hash(hasher);
+                hash_reflect(field, hasher);
+            }
+        }
+        ReflectRef::TupleStruct(s) => s.iter_fields().for_each(|f| hash_reflect(f, hasher)),
+        ReflectRef::Tuple(t) => t.iter_fields().for_each(|f| hash_reflect(f, hasher)),
+        ReflectRef::List(l) => {
+            l.len().hash(hasher);
+            l.iter().for_each(|v| hash_reflect(v, hasher));
+        }
+        ReflectRef::Array(a) => a.iter().for_each(|v| hash_reflect(v, hasher)),
+        ReflectRef::Map(m) => {
+            for (k, v) in m.iter() {
+                hash_reflect(k, hasher);
+                hash_reflect(v, hasher);
+            }
+        }
+        ReflectRef::Enum(e) => {
+            e.variant_name().hash(hasher);
+            for i in 0..e.field_len() {
+                if let Some(field) = e.field_at(i) {
+                    hash_reflect(field, hasher);
+                }
+            }
+        }
+        ReflectRef::Value(v) => match v.reflect_hash() {
+            Some(h) => h.hash(hasher),
+            None => format!("{:?}", v).hash(hasher),
+        },
+    }
 }
 
 impl std::ops::BitOr for Change {
@@ -106,3 +281,148 @@ impl From<egui::Response> for Change {
         Change::Response(r)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_hanabi::prelude::*;
+
+    use super::*;
+    use crate::reffect::InitVelocity;
+
+    fn effect() -> REffect {
+        REffect {
+            name: "spark".to_string(),
+            capacity: 64,
+            spawner: Spawner::rate(30.0.into()),
+            init_velocity: Some(InitVelocity::default()),
+            init_lifetime: Some(InitLifetimeModifier {
+                lifetime: Value::Uniform((1.0, 2.0)),
+            }),
+            ..default()
+        }
+    }
+
+    // Tags collect into one set of flags, and an unchanged editor adds none.
+    #[test]
+    fn flag_merging() {
+        let name = Change::from(true).tag(ChangeFlags::META);
+        let spawner = (Change::from(false) | Change::from(true)).tag(ChangeFlags::SPAWNER);
+        let render = (Change::from(false) | Change::from(())).tag(ChangeFlags::RENDER);
+        assert!(render.is_empty());
+        assert_eq!(
+            name | spawner | render,
+            ChangeFlags::META | ChangeFlags::SPAWNER
+        );
+        assert_eq!(ChangeFlags::empty() | name, name);
+
+        let mut flags = ChangeFlags::empty();
+        flags |= name;
+        flags |= name;
+        assert_eq!(flags, ChangeFlags::META);
+    }
+
+    // Merging with a response keeps a change from either side.
+    #[test]
+    fn change_merging() {
+        let ctx = egui::Context::default();
+        let _ = ctx.run(default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let r = ui.label("unchanged");
+                assert!(!(Change::from(false) | r.clone()).changed());
+                assert!((Change::from(true) | r.clone()).changed());
+                assert!((Change::from(r.clone()) | Change::from(true)).changed());
+                assert!(!(Change::from(r.clone()) | Change::from(r)).changed());
+            });
+        });
+    }
+
+    #[test]
+    fn regeneration_table() {
+        use Regeneration::*;
+        let table = [
+            (ChangeFlags::META, Entity),
+            (ChangeFlags::BOUNDS, Entity),
+            (ChangeFlags::META | ChangeFlags::BOUNDS, Entity),
+            (ChangeFlags::RENDER, InPlace),
+            (ChangeFlags::TEXTURE, InPlace),
+            (ChangeFlags::RENDER | ChangeFlags::META, InPlace),
+            (ChangeFlags::TEXTURE | ChangeFlags::BOUNDS, InPlace),
+            (ChangeFlags::SPAWNER, Respawn),
+            (ChangeFlags::CAPACITY, Respawn),
+            (ChangeFlags::SIMULATION, Respawn),
+            (ChangeFlags::INIT, Respawn),
+            (ChangeFlags::UPDATE, Respawn),
+            (ChangeFlags::PROPERTIES, Respawn),
+            (ChangeFlags::RENDER | ChangeFlags::INIT, Respawn),
+            (ChangeFlags::all(), Respawn),
+            (ChangeFlags::empty(), Respawn),
+        ];
+        for (changes, regeneration) in table {
+            assert_eq!(changes.regeneration(), regeneration, "{:?}", changes);
+        }
+    }
+
+    #[test]
+    fn same_content_same_hash() {
+        let re = effect();
+        assert_eq!(content_hash(&re), content_hash(&re.clone()));
+        assert_eq!(
+            content_hash(&REffect::default()),
+            content_hash(&REffect::default())
+        );
+    }
+
+    #[test]
+    fn changes_change_hash() {
+        let re = effect();
+        let hash = content_hash(&re);
+
+        let changes: [fn(&mut REffect); 4] = [
+            |re| re.capacity += 1,
+            |re| re.name.push('!'),
+            |re| re.init_lifetime = None,
+            |re| {
+                re.init_lifetime = Some(InitLifetimeModifier {
+                    lifetime: Value::Uniform((1.0, 2.5)),
+                })
+            },
+        ];
+        for change in changes {
+            let mut changed = re.clone();
+            change(&mut changed);
+            assert_ne!(content_hash(&changed), hash);
+        }
+    }
+
+    #[test]
+    fn nan_hashes_alike() {
+        let nan = |v: f32| REffect {
+            start_delay: v,
+            ..default()
+        };
+        assert_eq!(content_hash(&nan(f32::NAN)), content_hash(&nan(-f32::NAN)));
+        assert_eq!(
+            content_hash(&nan(f32::NAN)),
+            content_hash(&nan(f32::from_bits(f32::NAN.to_bits() | 1)))
+        );
+        assert_ne!(content_hash(&nan(f32::NAN)), content_hash(&nan(0.0)));
+    }
+
+    #[test]
+    fn changed_only_when_content_differs() {
+        let mut hashes = ContentHashes::default();
+        let handle = Handle::weak(HandleId::random::<REffect>());
+        let mut re = effect();
+
+        // Nothing recorded yet.
+        assert!(hashes.changed(&handle, &re));
+        assert!(!hashes.changed(&handle, &re));
+
+        re.capacity = 128;
+        assert!(hashes.changed(&handle, &re));
+        assert!(!hashes.changed(&handle, &re));
+
+        hashes.insert(&handle, &effect());
+        assert!(hashes.changed(&handle, &re));
+    }
+}
@@ -0,0 +1,204 @@
+// Completion for the path fields. Typing part of a path offers the folders and files in the folder
+// typed so far, e.g. `fx/fi` offers `fx/fire/` and `fx/fizz.han`. Candidates come from the
+// browser's entries, plus folders read from disk the first time their parent is typed into. The
+// read happens on the IO task pool; until it's done only the folders of known files are offered, so
+// the UI never waits on the file system.
+
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    tasks::{futures_lite::future, IoTaskPool, Task},
+    utils::HashMap,
+};
+
+/// Candidates offered at most.
+pub const MAX_CANDIDATES: usize = 12;
+
+/// Known files and folders under a root, shared with the path fields. Cloning shares them.
+#[derive(Clone, Default)]
+pub struct PathCompletions(Arc<Mutex<Known>>);
+
+#[derive(Default)]
+struct Known {
+    root: PathBuf,
+    /// Relative to the root, with slashes.
+    files: Vec<String>,
+    /// Subfolder names read from disk, by folder typed (with its trailing slash).
+    folders: HashMap<String, Vec<String>>,
+    reading: HashMap<String, Task<Vec<String>>>,
+}
+
+impl PathCompletions {
+    /// Replace the known files, e.g. after a scan. Folders are read again when next typed into.
+    pub fn set_files<'a>(&self, root: &Path, files: impl IntoIterator<Item = &'a Path>) {
+        let mut known = self.0.lock().unwrap();
+        known.root = root.to_path_buf();
+        known.files = files
+            .into_iter()
+            .filter(|path| !path.as_os_str().is_empty())
+            .map(slashed)
+            .collect();
+        known.folders.clear();
+        known.reading.clear();
+    }
+
+    /// Completions of the typed path, see [candidates]. Starts reading the folder typed so far if
+    /// it hasn't been.
+    pub fn complete(&self, typed: &str) -> Vec<String> {
+        let (folder, _) = split_typed(typed);
+        let mut known = self.0.lock().unwrap();
+        let known = &mut *known;
+
+        match known.reading.get_mut(folder) {
+            Some(task) => {
+                if let Some(folders) = future::block_on(future::poll_once(task)) {
+                    known.reading.remove(folder);
+                    known.folders.insert(folder.to_string(), folders);
+                }
+            }
+            // Nothing outside the root.
+            None if !known.folders.contains_key(folder)
+                && !folder.split('/').any(|c| c == "..") =>
+            {
+                let dir = known.root.join(folder);
+                let task = IoTaskPool::get().spawn(async move { read_folders(&dir) });
+                known.reading.insert(folder.to_string(), task);
+            }
+            None => (),
+        }
+
+        let folders = known
+            .folders
+            .get(folder)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        candidates(typed, known.files.iter().map(String::as_str), folders)
+    }
+}
+
+/// The path with slashes, whatever the platform.
+fn slashed(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The folder typed so far, with its trailing slash, and the partial name after it.
+pub fn split_typed(typed: &str) -> (&str, &str) {
+    match typed.rfind('/') {
+        Some(i) => typed.split_at(i + 1),
+        None => ("", typed),
+    }
+}
+
+/// Completions of `typed` from known files (relative to the root, with slashes) and the subfolders
+/// of the folder typed so far. Folders end with a slash and come first. Names match by prefix,
+/// ignoring case, and the typed path itself is left out.
+pub fn candidates<'a>(
+    typed: &str,
+    files: impl IntoIterator<Item = &'a str>,
+    folders: &[String],
+) -> Vec<String> {
+    let (folder, partial) = split_typed(typed);
+    let partial = partial.to_lowercase();
+    let matches = |name: &str| name.to_lowercase().starts_with(&partial);
+
+    let mut found_folders: BTreeSet<&str> = folders.iter().map(String::as_str).collect();
+    let mut found_files = BTreeSet::new();
+    for file in files {
+        let Some(rest) = file.strip_prefix(folder) else {
+            continue;
+        };
+        match rest.split_once('/') {
+            Some((name, _)) => found_folders.insert(name),
+            None => found_files.insert(rest),
+        };
+    }
+
+    let sorted = |names: BTreeSet<&str>| {
+        let mut names: Vec<&str> = names.into_iter().filter(|n| matches(n)).collect();
+        names.sort_by_key(|n| n.to_lowercase());
+        names
+    };
+    sorted(found_folders)
+        .into_iter()
+        .map(|name| format!("{}{}/", folder, name))
+        .chain(
+            sorted(found_files)
+                .into_iter()
+                .map(|name| format!("{}{}", folder, name)),
+        )
+        .filter(|c| c != typed)
+        .take(MAX_CANDIDATES)
+        .collect()
+}
+
+// Names of the folders in a folder, skipping hidden ones.
+fn read_folders(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut folders: Vec<String> = entries
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    folders.sort();
+    folders
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILES: &[&str] = &[
+        "fx/fire/big.han",
+        "fx/fizz.han",
+        "fx/Flame.han",
+        "smoke.han",
+    ];
+
+    fn complete(typed: &str, folders: &[&str]) -> Vec<String> {
+        let folders: Vec<String> = folders.iter().map(|f| f.to_string()).collect();
+        candidates(typed, FILES.iter().copied(), &folders)
+    }
+
+    #[test]
+    fn split() {
+        assert_eq!(split_typed("fx/fi"), ("fx/", "fi"));
+        assert_eq!(split_typed("fx/"), ("fx/", ""));
+        assert_eq!(split_typed("smo"), ("", "smo"));
+    }
+
+    #[test]
+    fn folders_then_files() {
+        assert_eq!(complete("fx/fi", &[]), ["fx/fire/", "fx/fizz.han"]);
+        assert_eq!(complete("", &[]), ["fx/", "smoke.han"]);
+        assert_eq!(complete("fx/fire/", &[]), ["fx/fire/big.han"]);
+        // Folders read from disk join those of known files. Case is ignored.
+        assert_eq!(
+            complete("fx/F", &["fire", "fog"]),
+            ["fx/fire/", "fx/fog/", "fx/fizz.han", "fx/Flame.han"]
+        );
+        // Nothing more to complete.
+        assert!(complete("fx/fizz.han", &[]).is_empty());
+        assert!(complete("sparks/", &[]).is_empty());
+    }
+
+    #[test]
+    fn read_folders_only() {
+        let dir = tempfile::tempdir().unwrap();
+        for folder in ["b", "a", ".hidden"] {
+            std::fs::create_dir(dir.path().join(folder)).unwrap();
+        }
+        std::fs::write(dir.path().join("c.han"), "").unwrap();
+        assert_eq!(read_folders(dir.path()), ["a", "b"]);
+        assert!(read_folders(&dir.path().join("missing")).is_empty());
+    }
+}
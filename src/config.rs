@@ -0,0 +1,349 @@
+// Editor settings that persist between sessions, kept in the platform config directory (native
+// only). Unlike [PreviewSettings](crate::preview::PreviewSettings) these outlive the session. The
+// file is rewritten shortly after the settings stop changing, so dragging a value doesn't write
+// every frame. Missing fields take their defaults, so older files keep loading.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{
+    prelude::*,
+    winit::{UpdateMode, WinitSettings},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{asset::asset_path_string, ui::paths::PathDisplay};
+
+/// Seconds after the last change before the config is written.
+pub const SAVE_DELAY: f32 = 1.0;
+
+/// How many recently used textures are kept.
+pub const RECENT_TEXTURES: usize = 8;
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EditorConfig::load())
+            .add_system(apply_background);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_system(save_config);
+    }
+}
+
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EditorConfig {
+    pub background: BackgroundSettings,
+    pub backups: BackupSettings,
+    pub palette: Palette,
+    /// Asset paths of the textures most recently set on an effect, most recent first.
+    pub recent_textures: Vec<String>,
+    /// Notes pinned to effects in the viewport, by effect asset path. Kept here rather than in the
+    /// effect files.
+    pub notes: BTreeMap<String, Vec<Note>>,
+    /// Defaults for newly enabled modifiers, serialized like an effect. See
+    /// [ModifierDefaults](crate::ui::defaults::ModifierDefaults).
+    pub modifier_defaults: Option<String>,
+    /// What new effects start from, serialized like an effect. See
+    /// [NewEffectTemplate](crate::ui::defaults::NewEffectTemplate).
+    pub new_effect: Option<String>,
+    /// Record edits to a log file. See [session](crate::session).
+    pub session_log: bool,
+    /// How effect and texture paths are shown.
+    pub path_display: PathDisplay,
+    /// Effects locked against edits, by asset path.
+    pub locked: BTreeSet<String>,
+    /// A folder of effects outside the assets root to preview and import from, e.g. a shared
+    /// library. See [library](crate::ui::library).
+    pub library: Option<PathBuf>,
+    /// Glob patterns for paths (relative to the assets root) the browsers skip, in addition to
+    /// [DEFAULT_IGNORE](crate::browser::DEFAULT_IGNORE) and hidden files.
+    pub ignore: Vec<String>,
+}
+
+/// A short text shown next to a live effect.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Note {
+    /// Relative to the effect.
+    pub offset: Vec3,
+    pub text: String,
+}
+
+/// Updates while the window is unfocused or minimized.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackgroundSettings {
+    /// Keep simulating at a reduced rate, rather than waiting for input.
+    pub simulate: bool,
+    /// Updates per second while simulating in the background.
+    pub rate: f32,
+}
+
+impl Default for BackgroundSettings {
+    fn default() -> Self {
+        Self {
+            simulate: true,
+            rate: 20.0,
+        }
+    }
+}
+
+/// Copies kept of saved effects. See [backup](crate::backup).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    /// Backups kept per effect. None are written if zero.
+    pub keep: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self { keep: 5 }
+    }
+}
+
+/// Colors for the size curves and viewport overlays, as unmultiplied sRGBA.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Palette {
+    pub curve_x: [u8; 4],
+    pub curve_y: [u8; 4],
+    pub emitter: [u8; 4],
+    pub kill_box: [u8; 4],
+    pub bounds: [u8; 4],
+    pub selection: [u8; 4],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            curve_x: [255, 0, 0, 255],
+            curve_y: [0, 255, 0, 255],
+            emitter: [255, 255, 0, 160],
+            kill_box: [255, 96, 64, 160],
+            bounds: [96, 160, 96, 160],
+            selection: [192, 222, 255, 255],
+        }
+    }
+}
+
+impl Palette {
+    /// Colors told apart with the common forms of color blindness, from the Okabe-Ito palette.
+    pub fn colorblind() -> Self {
+        Self {
+            curve_x: [230, 159, 0, 255],
+            curve_y: [86, 180, 233, 255],
+            emitter: [240, 228, 66, 160],
+            kill_box: [213, 94, 0, 160],
+            bounds: [0, 158, 115, 160],
+            selection: [0, 114, 178, 255],
+        }
+    }
+}
+
+impl BackgroundSettings {
+    /// How winit updates the app while unfocused.
+    pub fn unfocused_mode(&self) -> UpdateMode {
+        match self.simulate {
+            true => UpdateMode::Reactive {
+                max_wait: Duration::from_secs_f32(1.0 / self.rate.max(1.0)),
+            },
+            // Effectively paused until there's input.
+            false => UpdateMode::ReactiveLowPower {
+                max_wait: Duration::from_secs(60),
+            },
+        }
+    }
+}
+
+impl EditorConfig {
+    /// The config file. None without a config directory.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("han-ed").join("config.ron"))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn path() -> Option<PathBuf> {
+        None
+    }
+
+    /// Move a texture to the front of the recently used textures.
+    pub fn use_texture(&mut self, path: String) {
+        self.recent_textures.retain(|p| *p != path);
+        self.recent_textures.insert(0, path);
+        self.recent_textures.truncate(RECENT_TEXTURES);
+    }
+
+    /// Whether the effect at the path is locked against edits.
+    pub fn is_locked(&self, path: &Path) -> bool {
+        asset_path_string(&path.into()).map_or(false, |key| self.locked.contains(&key))
+    }
+
+    pub fn set_locked(&mut self, path: &Path, locked: bool) {
+        let Ok(key) = asset_path_string(&path.into()) else {
+            return;
+        };
+        match locked {
+            true => self.locked.insert(key),
+            false => self.locked.remove(&key),
+        };
+    }
+
+    /// Read the config file, or the defaults if there isn't one or it can't be read.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(s) => ron::from_str(&s).unwrap_or_else(|e| {
+                warn!("failed to read {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+}
+
+fn apply_background(config: Res<EditorConfig>, winit: Option<ResMut<WinitSettings>>) {
+    if !config.is_changed() {
+        return;
+    }
+    if let Some(mut winit) = winit {
+        winit.unfocused_mode = config.background.unfocused_mode();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_config(time: Res<Time>, config: Res<EditorConfig>, mut changed_at: Local<Option<f32>>) {
+    let now = time.elapsed_seconds();
+    if config.is_changed() && !config.is_added() {
+        *changed_at = Some(now);
+    }
+    if !changed_at.map_or(false, |t| now - t >= SAVE_DELAY) {
+        return;
+    }
+    *changed_at = None;
+
+    let Some(path) = EditorConfig::path() else {
+        return;
+    };
+    let write = || -> anyhow::Result<()> {
+        let ron = ron::ser::to_string_pretty(&*config, ron::ser::PrettyConfig::default())?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, ron)?;
+        Ok(())
+    };
+    if let Err(e) = write() {
+        warn!("failed to write {}: {}", path.display(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_default() {
+        let config: EditorConfig = ron::from_str("(background: (rate: 5.0))").unwrap();
+        assert!(config.background.simulate);
+        assert_eq!(config.background.rate, 5.0);
+        assert_eq!(ron::from_str::<EditorConfig>("()").unwrap(), default());
+    }
+
+    #[test]
+    fn locked() {
+        let mut config = EditorConfig::default();
+        let path = Path::new("fx/ember.han");
+        assert!(!config.is_locked(path));
+        config.set_locked(path, true);
+        assert!(config.is_locked(path));
+        assert!(!config.is_locked(Path::new("fx/spark.han")));
+
+        let config: EditorConfig = ron::from_str(&ron::to_string(&config).unwrap()).unwrap();
+        assert!(config.is_locked(path));
+    }
+
+    #[test]
+    fn recent_textures() {
+        let mut config = EditorConfig::default();
+        for i in 0..RECENT_TEXTURES + 2 {
+            config.use_texture(format!("{}.png", i));
+        }
+        assert_eq!(config.recent_textures.len(), RECENT_TEXTURES);
+        assert_eq!(
+            config.recent_textures[0],
+            format!("{}.png", RECENT_TEXTURES + 1)
+        );
+
+        // Using one again moves it to the front without duplicating it.
+        config.use_texture("5.png".to_string());
+        assert_eq!(config.recent_textures[0], "5.png");
+        assert_eq!(config.recent_textures.len(), RECENT_TEXTURES);
+        assert_eq!(
+            config
+                .recent_textures
+                .iter()
+                .filter(|p| *p == "5.png")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn notes_round_trip() {
+        let mut config = EditorConfig::default();
+        config.notes.insert(
+            "fx/spark.han".to_string(),
+            vec![Note {
+                offset: Vec3::new(0.0, 2.0, 0.0),
+                text: "trail too thin here".to_string(),
+            }],
+        );
+        let ron = ron::to_string(&config).unwrap();
+        assert_eq!(ron::from_str::<EditorConfig>(&ron).unwrap(), config);
+    }
+
+    #[test]
+    fn palette_presets_distinct() {
+        for palette in [Palette::default(), Palette::colorblind()] {
+            let colors = [
+                palette.curve_x,
+                palette.curve_y,
+                palette.emitter,
+                palette.kill_box,
+                palette.bounds,
+                palette.selection,
+            ];
+            for (i, a) in colors.iter().enumerate() {
+                assert!(colors[i + 1..].iter().all(|b| a != b), "{:?}", palette);
+            }
+        }
+    }
+
+    #[test]
+    fn background_rate() {
+        let mut background = BackgroundSettings {
+            simulate: true,
+            rate: 4.0,
+        };
+        assert!(matches!(
+            background.unfocused_mode(),
+            UpdateMode::Reactive { max_wait } if max_wait == Duration::from_millis(250)
+        ));
+
+        background.simulate = false;
+        assert!(matches!(
+            background.unfocused_mode(),
+            UpdateMode::ReactiveLowPower { .. }
+        ));
+    }
+}
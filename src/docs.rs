@@ -0,0 +1,299 @@
+// Hover documentation for modifiers and fields, distilled from the bevy_hanabi docs. Keys are the
+// labels used in the UI.
+
+use bevy_egui::egui;
+
+pub static DOCS: &[(&str, &str)] = &[
+    // Effect.
+    (
+        "Capacity",
+        "Maximum number of particles alive at once. GPU buffers are allocated up front for this many particles, so don't make it much larger than needed. It's limited to what fits in the adapter's largest storage buffer, which depends on the attributes the modifiers use; the estimate next to it doesn't count padding.",
+    ),
+    (
+        "Simulation Space",
+        "Global: particles are simulated in world space and stay behind when the emitter moves. Local: particles are simulated relative to the emitter's transform and move with it.",
+    ),
+    (
+        "Simulation Condition",
+        "WhenVisible: only simulate while the effect is visible. Always: simulate even when culled.",
+    ),
+    (
+        "Target",
+        "The kind of platform the effect is made for. The effect is checked against what that platform can be relied on to provide, with warnings below.",
+    ),
+    (
+        "Behavior",
+        "What the spawner does, in words. Spawners that don't start active wait for the game to activate them; \"Activate now\" does that for the live effect.",
+    ),
+    (
+        "Paths",
+        "How effect and texture paths are shown: relative to the assets root, the file name only, or with the folders between the first and the file name left out. Right-click an effect header or texture to copy its path.",
+    ),
+    (
+        "Lock",
+        "Lock the effect against edits, e.g. to keep a reference effect as it is. Locked effects can still be shown and reset, but not saved, restored, batch edited or have textures replaced. Locks are kept in the editor settings.",
+    ),
+    (
+        "Scatter test",
+        "Spawn copies of the effect at random positions in a box, a burst every few seconds, to check it reads well wherever it appears. Each copy plays the effect once and is removed when done. Global-space modifiers stay where they are. Unchecking removes all copies.",
+    ),
+    (
+        "Mute",
+        "Leave modifiers out of the live effect to see what each one contributes. Click to mute, right-click to solo (mute the rest of the section). Only the preview changes: the effect isn't edited or marked unsaved, and mutes are forgotten on exit.",
+    ),
+    (
+        "Timeline",
+        "Two periods of the spawner. The shaded part of each period is when particles spawn, with the number spawned each cycle. Bursts (no spawn time) are a line. Updated as the spawner changes.",
+    ),
+    (
+        "Session log",
+        "Append every edit to session.log in the cache directory, with the new values of what changed, to help reproduce crashes. The previous session's log is kept as session.1.log. Copy the last edits to paste into a bug report.",
+    ),
+    (
+        "Bounds",
+        "Cull the live effect when this box (relative to the effect) is out of view. Without bounds the effect is never culled. Too small and the effect disappears while particles are still on screen.",
+    ),
+    (
+        "Ignore",
+        "Glob patterns, separated by commas, for files the effect and texture lists skip, e.g. build/** or *_old.han. Paths are relative to the assets root. Hidden files and *.meta are always skipped.",
+    ),
+    (
+        "Simulate in background",
+        "Keep updating at a reduced rate while the window is unfocused, so effects are current when you come back. Effects with the WhenVisible simulation condition still pause while the window is minimized, since nothing is visible; use Always for those. Off, the editor waits for input and uses no power in the background.",
+    ),
+    (
+        "Properties",
+        "Named values that modifiers can read. They can be changed on live effects without regenerating, from the Live section.",
+    ),
+    // Spawner.
+    (
+        "Spawner",
+        "Controls how many particles are spawned and when.",
+    ),
+    (
+        "Particles",
+        "Number of particles spawned each period, spread evenly over the spawn time.",
+    ),
+    (
+        "Spawn Time",
+        "Duration in seconds over which the particles of one period are spawned. Zero spawns them all at once (a burst).",
+    ),
+    (
+        "Period",
+        "Time in seconds between the start of each spawn. Right-click to make it infinite (spawn once).",
+    ),
+    (
+        "Starts Active",
+        "Whether the spawner is active when the effect is spawned. An inactive spawner spawns nothing until activated.",
+    ),
+    (
+        "Starts Immediately",
+        "Whether the first spawn happens right away or only after the first period has elapsed.",
+    ),
+    (
+        "Start Delay",
+        "Seconds after the effect is spawned before the spawner starts. Hanabi has no delay, so the spawner starts inactive and the editor activates it; games need to do the same.",
+    ),
+    (
+        "Duration",
+        "Total visual duration of a spawn-once effect: the spawn time plus the longest particle lifetime (less the initial age).",
+    ),
+    (
+        "Steady State",
+        "Estimated number of particles alive once a repeating spawner has settled: particles per second times the average lifetime. Useful for sizing the capacity.",
+    ),
+    // Init.
+    (
+        "Initial Modifiers",
+        "Modifiers that set particle attributes once, when the particle is spawned.",
+    ),
+    (
+        "Position",
+        "Initial position. Circle: on a circle (or disc) of radius around center, perpendicular to axis. Sphere: on (or in) a sphere of radius. Cone: in a truncated cone with its base at the origin, extending along Y by height, with base_radius at the bottom and top_radius at the top.",
+    ),
+    (
+        "Velocity",
+        "Initial velocity. Circle: radially outward from center in the plane perpendicular to axis. Sphere: radially outward from center. Tangent: tangent to a circle around axis through origin.",
+    ),
+    (
+        "Size",
+        "Initial particle size, either uniform (1D) or width/height (2D).",
+    ),
+    (
+        "Age",
+        "Initial age in seconds. A non-zero age makes particles start partway through their lifetime.",
+    ),
+    (
+        "Lifetime",
+        "Lifetime in seconds. Particles are killed once their age exceeds it.",
+    ),
+    (
+        "Lifetime warning",
+        "Effects require a lifetime unless provided via InitAttributeModifier.",
+    ),
+    (
+        "Attributes",
+        "Set any particle attribute to a constant when the particle is spawned (InitAttributeModifier). Setting lifetime here replaces the Lifetime modifier.",
+    ),
+    // Update.
+    (
+        "Update Modifiers",
+        "Modifiers that change particle attributes every frame.",
+    ),
+    (
+        "Acceleration",
+        "Linear: constant acceleration in units/s², e.g. gravity. Radial: acceleration away from (positive) or toward (negative) the origin. Tangent: acceleration tangent to a circle around axis through origin. Multiple accelerations add up.",
+    ),
+    ("Accel.", "Magnitude of the acceleration in units/s²."),
+    (
+        "Origin",
+        "Origin of the acceleration, in simulation space.",
+    ),
+    ("Axis", "Rotation axis for the tangent direction."),
+    (
+        "Force Field",
+        "Up to 16 point sources attracting or repelling particles. Mass is the strength (negative repels), and the force only applies between min_radius and max_radius. The force falls off with distance raised to force_exponent.",
+    ),
+    (
+        "Linear Drag",
+        "Drag proportional to velocity. The coefficient is in 1/s: each second the velocity is reduced by drag × velocity. Larger values stop particles faster.",
+    ),
+    (
+        "AABB Kill",
+        "Kills particles inside (or outside) an axis-aligned box given by center and half_size. The box is in simulation space: with Local simulation space it moves with the emitter, with Global it's fixed in world space.",
+    ),
+    // Render.
+    (
+        "Render Modifiers",
+        "Modifiers that affect how particles are drawn.",
+    ),
+    (
+        "Particle Texture",
+        "Image used for each particle quad, multiplied by the particle color. Use a texture with alpha or particles will render as squares.",
+    ),
+    (
+        "Texture Path",
+        "Path of the texture relative to the assets root, with #label for a labeled sub-asset, e.g. an image in a glTF file. Tab completes folders and files.",
+    ),
+    (
+        "Import Texture",
+        "Copy images from outside the assets root into a folder under it, so they can be loaded. The first image picked is set as the particle texture.",
+    ),
+    ("Set Color", "Constant particle color."),
+    (
+        "Color Over Lifetime",
+        "Particle color as a gradient over the normalized age (0 at spawn, 1 at death). Drag keys to move them, right-click to remove them.",
+    ),
+    ("Set Size", "Constant particle size."),
+    (
+        "Size Over Lifetime",
+        "Particle size as a gradient over the normalized age. Red is width, green is height.",
+    ),
+    (
+        "Orientation",
+        "How particle quads are oriented. Billboard keeps them parallel to the screen, Face Camera turns each toward the camera, and Along Velocity stretches them along their motion, e.g. for sparks or rain. With none they face along the effect's Z axis.",
+    ),
+    (
+        "Order",
+        "Order the render modifiers are applied in. Later modifiers override earlier ones, e.g. Set Color after Color Over Lifetime wins.",
+    ),
+    (
+        "Global",
+        "Particles stay where they were spawned; moving the emitter leaves a trail behind.",
+    ),
+    (
+        "Local",
+        "Particles move, rotate and scale with the emitter, as if attached to it.",
+    ),
+    (
+        "Space conflict",
+        "In global space these stay at the world origin rather than following the emitter. Use local space, or move them to where the effect is placed.",
+    ),
+    (
+        "Face Camera fallback",
+        "This version of hanabi has no face camera mode, so the particles are billboarded instead. Saved effects keep Face Camera.",
+    ),
+];
+
+/// Look up the documentation for a label.
+pub fn get(key: &str) -> Option<&'static str> {
+    DOCS.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+pub trait Doc {
+    /// Add hover documentation for the label, if any, and if tooltips are enabled.
+    fn doc(self, key: &str) -> Self;
+}
+
+impl Doc for egui::Response {
+    fn doc(self, key: &str) -> Self {
+        match get(key) {
+            Some(text) if self.ctx.style().explanation_tooltips => self.on_hover_text(text),
+            _ => self,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The sources of the custom UIs, for the labels they document.
+    const SOURCES: &[&str] = &[
+        include_str!("scatter.rs"),
+        include_str!("session.rs"),
+        include_str!("ui/defaults.rs"),
+        include_str!("ui/effects.rs"),
+        include_str!("ui/global.rs"),
+        include_str!("ui/modifiers/init.rs"),
+        include_str!("ui/modifiers/mod.rs"),
+        include_str!("ui/modifiers/render.rs"),
+        include_str!("ui/modifiers/update.rs"),
+        include_str!("ui/tools.rs"),
+    ];
+
+    // Calls that take a label and look up its documentation.
+    const CALLS: &[&str] = &[
+        "row!(",
+        "header!(ui,",
+        "ui_option(",
+        "ui_option_with(",
+        "ui_option_reflect(",
+        "ui_checkbox(",
+        "ui_reflect(",
+        ".doc(",
+    ];
+
+    // The string literal labels passed to the calls above.
+    fn labels(source: &str) -> Vec<&str> {
+        let mut labels = Vec::new();
+        for call in CALLS {
+            for (i, _) in source.match_indices(call) {
+                let rest = source[i + call.len()..].trim_start();
+                if let Some(rest) = rest.strip_prefix('"') {
+                    labels.extend(rest.split('"').next());
+                }
+            }
+        }
+        labels
+    }
+
+    #[test]
+    fn fields_documented() {
+        let labels: Vec<_> = SOURCES.iter().flat_map(|s| labels(s)).collect();
+        assert!(labels.contains(&"Capacity"));
+        assert!(labels.contains(&"Texture Path"));
+        for label in labels {
+            assert!(get(label).is_some(), "{} has no docs", label);
+        }
+    }
+
+    #[test]
+    fn labels_found() {
+        let source = r#"row!("A", ui, |ui| ui.label("B")) | ui_option(
+            "C",
+            x,
+        ) | ui_option(label, x)"#;
+        assert_eq!(labels(source), ["A", "C"]);
+    }
+}
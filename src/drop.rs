@@ -0,0 +1,164 @@
+// Drag-and-drop files onto the window.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use bevy::{asset::Asset, prelude::*, window::FileDragAndDrop};
+use bevy_egui::{egui, EguiContexts};
+use bevy_hanabi::prelude::*;
+
+use crate::{
+    asset::{asset_path_string, import_file},
+    browser::*,
+    config::EditorConfig,
+    reffect::*,
+    toast::Toasts,
+    LiveEffect,
+};
+
+pub struct DropPlugin;
+
+impl Plugin for DropPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(file_drop);
+    }
+}
+
+/// A dropped texture, waiting for confirmation before it's set on an effect.
+struct TextureOffer {
+    /// The effect to set the texture on.
+    effect: Handle<REffect>,
+    texture: Handle<Image>,
+    path: PathBuf,
+}
+
+// Dropped effects are loaded and dropped images are added to the image browser. If exactly one
+// effect is live, offer to set the dropped image as its texture.
+fn file_drop(
+    mut events: EventReader<FileDragAndDrop>,
+    mut contexts: EguiContexts,
+    mut offer: Local<Option<TextureOffer>>,
+    asset_server: Res<AssetServer>,
+    toasts: Res<Toasts>,
+    mut reffect_paths: ResMut<AssetBrowser<REffect>>,
+    mut image_paths: ResMut<AssetBrowser<Image>>,
+    mut reffects: ResMut<Assets<REffect>>,
+    mut effects: ResMut<Assets<EffectAsset>>,
+    live_effects: Query<(&ParticleEffect, &LiveEffect)>,
+    mut config: ResMut<EditorConfig>,
+) {
+    let (mut effect_count, mut image_count, mut failed) = (0, 0, 0);
+
+    // Process in the order they were dropped.
+    for event in events.iter() {
+        let FileDragAndDrop::DroppedFile { path_buf, .. } = event else {
+            continue;
+        };
+
+        let ext = path_buf.extension().and_then(|ext| ext.to_str());
+        let result = if ext == Some(reffect_paths.extension) {
+            drop_file(path_buf, &mut reffect_paths, &asset_server).map(|_| effect_count += 1)
+        } else if ext == Some(image_paths.extension) {
+            drop_file(path_buf, &mut image_paths, &asset_server).map(|(path, texture)| {
+                image_count += 1;
+
+                // Only offer when it's unambiguous which effect it's for.
+                let mut live = live_effects.iter();
+                *offer = match (live.next(), live.next()) {
+                    (Some((_, live_effect)), None) => Some(TextureOffer {
+                        effect: live_effect.0.clone(),
+                        texture,
+                        path,
+                    }),
+                    _ => None,
+                };
+            })
+        } else {
+            Err(anyhow!("unsupported file type"))
+        };
+
+        if let Err(e) = result {
+            toasts.error(format!("failed to open {}: {:?}", path_buf.display(), e));
+            failed += 1;
+        }
+    }
+
+    if effect_count + image_count + failed > 0 {
+        toasts.info(format!(
+            "dropped {} effect(s), {} image(s), {} failed",
+            effect_count, image_count, failed
+        ));
+    }
+
+    let Some(TextureOffer {
+        effect,
+        texture,
+        path,
+    }) = offer.as_ref()
+    else {
+        return;
+    };
+
+    let (mut set, mut close) = (false, false);
+    egui::Window::new("Set texture")
+        .collapsible(false)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            let name = reffects.get(effect).map(|re| re.name.as_str());
+            ui.label(format!(
+                "Set {} as the particle texture of {}?",
+                path.display(),
+                name.unwrap_or("the effect")
+            ));
+            ui.horizontal(|ui| {
+                set = ui.button("Set").clicked();
+                close = ui.button("Cancel").clicked();
+            });
+        });
+
+    if set {
+        if let Some(re) = reffects.get_mut(effect) {
+            re.render_particle_texture = ParticleTexture::Texture(texture.clone());
+            if let Ok(path) = asset_path_string(&path.as_path().into()) {
+                config.use_texture(path);
+            }
+
+            // The texture can be swapped in place.
+            for (particle_effect, _) in live_effects.iter().filter(|(_, live)| &live.0 == effect) {
+                if let Some(effect_asset) = effects.get_mut(&particle_effect.handle) {
+                    *effect_asset = re.to_effect_asset(&asset_server);
+                }
+            }
+
+            if let Some(entry) = reffect_paths
+                .entries
+                .iter_mut()
+                .find(|e| e.handle.as_ref() == Some(effect))
+            {
+                entry.dirty = true;
+            }
+        }
+    }
+
+    if set || close {
+        *offer = None;
+    }
+}
+
+// Copy the file under the assets root if it's outside, then add it to the browser and load it.
+// Returns the path relative to the root.
+fn drop_file<T: Asset>(
+    file: &Path,
+    browser: &mut AssetBrowser<T>,
+    asset_server: &AssetServer,
+) -> Result<(PathBuf, Handle<T>)> {
+    let file = file.canonicalize()?;
+    let path = match file.strip_prefix(&browser.root_path) {
+        Ok(path) => path.to_path_buf(),
+        Err(_) => import_file(&file, &browser.root_path, Path::new(""))?,
+    };
+
+    let handle = asset_server.load(path.as_path());
+    browser.insert(path.clone(), handle.clone());
+    Ok((path, handle))
+}
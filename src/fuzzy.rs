@@ -0,0 +1,101 @@
+// Fuzzy matching for the command palette. A candidate matches if it contains the pattern's
+// characters in order, ignoring case and whitespace in the pattern. Of the ways to match, the best
+// scoring counts: characters that follow the previous match or start a word (or the candidate)
+// score extra, and each character skipped between matches costs a little.
+
+/// Score of each matched character.
+const MATCH: i32 = 1;
+/// Bonus for a match right after the previous one.
+const CONSECUTIVE: i32 = 8;
+/// Bonus for a match at the start of a word.
+const WORD_START: i32 = 6;
+/// Bonus for a match at the start of the candidate.
+const FIRST: i32 = 4;
+/// Cost of each character skipped between two matches.
+const GAP: i32 = 1;
+
+fn eq(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+// Whether the character at `i` starts a word: the first, after a non-alphanumeric character, or
+// an uppercase letter after a lowercase one.
+fn word_start(chars: &[char], i: usize) -> bool {
+    match i.checked_sub(1).map(|p| chars[p]) {
+        None => true,
+        Some(prev) => !prev.is_alphanumeric() || (prev.is_lowercase() && chars[i].is_uppercase()),
+    }
+}
+
+/// The best score of `candidate` for `pattern`, or `None` if it doesn't match. An empty pattern
+/// matches everything with a score of zero.
+pub fn score(pattern: &str, candidate: &str) -> Option<i32> {
+    let pattern: Vec<char> = pattern.chars().filter(|c| !c.is_whitespace()).collect();
+    if pattern.is_empty() {
+        return Some(0);
+    }
+    let chars: Vec<char> = candidate.chars().collect();
+
+    let bonus = |j: usize| {
+        MATCH + if word_start(&chars, j) { WORD_START } else { 0 } + if j == 0 { FIRST } else { 0 }
+    };
+
+    // The best score with the current pattern character matched at each position.
+    let mut best: Vec<Option<i32>> = chars
+        .iter()
+        .enumerate()
+        .map(|(j, c)| eq(pattern[0], *c).then(|| bonus(j)))
+        .collect();
+
+    for p in pattern[1..].iter() {
+        let mut next = vec![None; chars.len()];
+        for (j, c) in chars.iter().enumerate() {
+            if !eq(*p, *c) {
+                continue;
+            }
+            next[j] = (0..j)
+                .filter_map(|k| {
+                    best[k].map(|s| match j - k - 1 {
+                        0 => s + CONSECUTIVE,
+                        gap => s - gap as i32 * GAP,
+                    })
+                })
+                .max()
+                .map(|s| s + bonus(j));
+        }
+        best = next;
+    }
+
+    best.into_iter().flatten().max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subsequence() {
+        assert!(score("sva", "Save All").is_some());
+        assert!(score("SAVE", "save all").is_some());
+        assert!(score("save all", "Save All").is_some());
+        assert_eq!(score("las", "Save All"), None);
+        assert_eq!(score("x", ""), None);
+        assert_eq!(score("", "Quit"), Some(0));
+    }
+
+    #[test]
+    fn ranking() {
+        // Consecutive beats scattered.
+        assert!(score("sav", "Save All") > score("sav", "Show vortex.han (as saved)"));
+        // Word starts beat the middle of words.
+        assert!(score("sa", "Save All") > score("sa", "Disable"));
+        assert!(score("ef", "Export Scene (effects)") > score("ef", "Reference"));
+        // Camel case starts words.
+        assert!(score("es", "ExportScene") > score("es", "Expressed"));
+        // The best of several matches counts, not the first found.
+        assert_eq!(
+            score("sa", "Show Save All"),
+            Some(MATCH + WORD_START + CONSECUTIVE + MATCH)
+        );
+    }
+}
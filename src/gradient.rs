@@ -1,19 +1,158 @@
-use std::cmp::Ordering;
+// Gradient editors for the color and size over lifetime modifiers.
+//
+// Keys are (position, value) pairs with positions from 0 to 1, sampled like
+// [bevy_hanabi::Gradient]: the value is constant before the first key and after the last, and
+// interpolated linearly (in linear color space) in between. Several keys at the same position make
+// a hard step: the gradient arrives at the first of them and leaves from the last. The previews are
+// drawn from the same model, see [sample].
+//
+// Colors (here and in Set Color) are stored as hanabi uses them: linear RGB with unmultiplied
+// alpha. egui's color types are converted at the boundary with [rgba], [hsva] and [from_hsva]
+// only, so a color picked in the editor is the color hanabi draws. Earlier versions premultiplied
+// picked colors. Files aren't migrated, since hanabi has always read them as unmultiplied and they
+// draw the same as before.
+
+use std::{
+    cmp::Ordering,
+    ops::{Add, Mul, Sub},
+};
 
 use bevy::{
-    prelude::{Vec2, Vec4},
-    reflect::{FromReflect, Reflect},
+    log::warn,
+    prelude::{Image, Vec2, Vec4},
+    reflect::{FromReflect, Reflect, ReflectRef, Struct},
+    render::render_resource::TextureFormat,
 };
 use bevy_egui::egui::{self, epaint::Hsva, widgets::color_picker::*, *};
 use bevy_hanabi::{ColorOverLifetimeModifier, SizeOverLifetimeModifier};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::change::Change;
+use crate::{
+    asset::AssetOpError,
+    change::Change,
+    ui::{color32, palette},
+};
 
-#[derive(Clone, Reflect, FromReflect)]
+// FromReflect is implemented below to repair the keys.
+#[derive(Clone, Reflect)]
 pub struct ColorGradient {
     keys: Vec<(f32, Vec4)>,
 }
 
+/// Pixels sampled by [ColorGradient::from_image].
+pub const IMAGE_SAMPLES: usize = 65;
+/// Most keys kept by [ColorGradient::from_image].
+pub const IMAGE_MAX_KEYS: usize = 8;
+/// Largest difference in any channel for a key to be dropped as redundant.
+pub const SIMPLIFY_TOLERANCE: f32 = 0.01;
+
+impl ColorGradient {
+    /// Sample evenly spaced pixels from the middle row of an image, e.g. a 256×1 ramp, and keep
+    /// only the keys needed to reproduce it. See [simplify].
+    pub fn from_image(image: &Image, samples: usize, max_keys: usize) -> Result<Self, String> {
+        let size = image.texture_descriptor.size;
+        let (width, height) = (size.width as usize, size.height as usize);
+        if width == 0 || height == 0 {
+            return Err("empty image".into());
+        }
+
+        let format = image.texture_descriptor.format;
+        let pixel = |x: usize| -> Result<Vec4, String> {
+            let i = (height / 2 * width + x) * 4;
+            match format {
+                TextureFormat::Rgba8UnormSrgb | TextureFormat::Bgra8UnormSrgb => {
+                    let p = &image.data[i..i + 4];
+                    let c = Vec4::new(
+                        ecolor::linear_f32_from_gamma_u8(p[0]),
+                        ecolor::linear_f32_from_gamma_u8(p[1]),
+                        ecolor::linear_f32_from_gamma_u8(p[2]),
+                        ecolor::linear_f32_from_linear_u8(p[3]),
+                    );
+                    Ok(match format {
+                        TextureFormat::Bgra8UnormSrgb => Vec4::new(c.z, c.y, c.x, c.w),
+                        _ => c,
+                    })
+                }
+                TextureFormat::Rgba8Unorm => {
+                    let p = &image.data[i..i + 4];
+                    Ok(Vec4::new(p[0] as f32, p[1] as f32, p[2] as f32, p[3] as f32) / 255.0)
+                }
+                _ => Err(format!(
+                    "unsupported format {:?}, use an 8-bit RGBA image",
+                    format
+                )),
+            }
+        };
+
+        let samples = samples.clamp(2, width.max(2));
+        let mut keys = (0..samples)
+            .map(|j| {
+                let t = j as f32 / (samples - 1) as f32;
+                let x = (t * (width - 1) as f32).round() as usize;
+                Ok((t, pixel(x)?))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        simplify(&mut keys, max_keys, SIMPLIFY_TOLERANCE);
+
+        Ok(Self { keys })
+    }
+}
+
+/// Drop keys that are within `tolerance` of the linear interpolation of their neighbors, least
+/// significant first, then keep dropping until there are at most `max_keys` (no less than two).
+/// The first and last keys are kept.
+pub fn simplify(keys: &mut Vec<(f32, Vec4)>, max_keys: usize, tolerance: f32) {
+    let max_keys = max_keys.max(2);
+    while keys.len() > 2 {
+        let (i, error) = (1..keys.len() - 1)
+            .map(|i| (i, interpolation_error(keys, i)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .expect("interior key");
+        if error > tolerance && keys.len() <= max_keys {
+            break;
+        }
+        keys.remove(i);
+    }
+}
+
+// How far a key is from the line between its neighbors, in the worst channel.
+fn interpolation_error(keys: &[(f32, Vec4)], i: usize) -> f32 {
+    let ((k0, v0), (k, v), (k1, v1)) = (keys[i - 1], keys[i], keys[i + 1]);
+    let t = if k1 > k0 { (k - k0) / (k1 - k0) } else { 0.0 };
+    (v0.lerp(v1, t) - v).abs().max_element()
+}
+
+impl ColorGradient {
+    /// The color at `t`, as rendered by hanabi. See [sample].
+    pub fn sample(&self, t: f32) -> Vec4 {
+        sample(&self.keys, t)
+    }
+
+    /// See [fix_up_keys].
+    pub fn fix_up(&mut self) -> Option<String> {
+        fix_up_keys(&mut self.keys, || Self::default().keys)
+    }
+
+    /// Multiply all colors by the tint.
+    pub fn tint(&mut self, tint: Vec4) {
+        for (_, color) in self.keys.iter_mut() {
+            *color *= tint;
+        }
+    }
+}
+
+impl FromReflect for ColorGradient {
+    fn from_reflect(reflect: &dyn Reflect) -> Option<Self> {
+        let mut g = Self {
+            keys: keys_from_reflect(reflect)?,
+        };
+        if let Some(warning) = g.fix_up() {
+            warn!("color gradient: {}", warning);
+        }
+        Some(g)
+    }
+}
+
 impl Default for ColorGradient {
     fn default() -> Self {
         Self {
@@ -22,11 +161,76 @@ impl Default for ColorGradient {
     }
 }
 
-#[derive(Clone, Reflect, FromReflect)]
+#[derive(Clone, Reflect)]
 pub struct SizeGradient {
     keys: Vec<(f32, Vec2)>,
 }
 
+impl SizeGradient {
+    /// Multiply all sizes by the factor.
+    pub fn scale(&mut self, factor: f32) {
+        for (_, size) in self.keys.iter_mut() {
+            *size *= factor;
+        }
+    }
+
+    pub fn keys(&self) -> &[(f32, Vec2)] {
+        &self.keys
+    }
+
+    /// See [fix_up_keys].
+    pub fn fix_up(&mut self) -> Option<String> {
+        fix_up_keys(&mut self.keys, || Self::default().keys)
+    }
+}
+
+impl FromReflect for SizeGradient {
+    fn from_reflect(reflect: &dyn Reflect) -> Option<Self> {
+        let mut g = Self {
+            keys: keys_from_reflect(reflect)?,
+        };
+        if let Some(warning) = g.fix_up() {
+            warn!("size gradient: {}", warning);
+        }
+        Some(g)
+    }
+}
+
+fn keys_from_reflect<V: FromReflect>(reflect: &dyn Reflect) -> Option<Vec<(f32, V)>> {
+    match reflect.reflect_ref() {
+        ReflectRef::Struct(s) => FromReflect::from_reflect(s.field("keys")?),
+        _ => None,
+    }
+}
+
+/// Repair a (hand-edited) key list: clamp positions to 0..=1, sort, and replace an empty list
+/// with the defaults. Returns what was wrong, if anything.
+pub fn fix_up_keys<V>(
+    keys: &mut Vec<(f32, V)>,
+    default: impl FnOnce() -> Vec<(f32, V)>,
+) -> Option<String> {
+    let mut problems = Vec::new();
+
+    if keys.is_empty() {
+        *keys = default();
+        problems.push("no keys, using the default");
+    }
+
+    if keys.iter().any(|(k, _)| !(0.0..=1.0).contains(k)) {
+        for (k, _) in keys.iter_mut() {
+            *k = if k.is_nan() { 0.0 } else { k.clamp(0.0, 1.0) };
+        }
+        problems.push("positions out of range, clamped");
+    }
+
+    if keys.windows(2).any(|w| w[0].0 > w[1].0) {
+        keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+        problems.push("keys out of order, sorted");
+    }
+
+    (!problems.is_empty()).then(|| problems.join("; "))
+}
+
 impl Default for SizeGradient {
     fn default() -> Self {
         Self {
@@ -35,37 +239,233 @@ impl Default for SizeGradient {
     }
 }
 
-trait IntoColor {
+// Gradient files
+//
+// A single gradient can be exported to (and imported from) a RON file so gradients can be shared
+// between effects. The format is stable, so it can also be generated by other tools:
+//
+// ```ron
+// (
+//     keys: [
+//         (0.0, (1.0, 0.5, 0.0, 1.0)),
+//         (1.0, (0.2, 0.2, 0.2, 0.0)),
+//     ],
+//     interpolation: Linear,
+// )
+// ```
+//
+// Each key is a position from 0 to 1 and a value: linear RGBA for color gradients and (x, y) for
+// size gradients. Keys may be in any order; they're sorted on import. `interpolation` may be
+// omitted. Hanabi only interpolates linearly, so `Linear` is the only mode for now.
+
+/// The contents of a gradient file. `V` is `[f32; 4]` for colors and `[f32; 2]` for sizes.
+#[derive(Serialize, Deserialize)]
+pub struct GradientFile<V> {
+    pub keys: Vec<(f32, V)>,
+    #[serde(default)]
+    pub interpolation: Interpolation,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub enum Interpolation {
+    #[default]
+    Linear,
+}
+
+/// Check that there is at least one key and all positions are within 0 to 1, then sort by
+/// position.
+pub fn validate_keys<V>(keys: &mut [(f32, V)]) -> Result<(), String> {
+    if keys.is_empty() {
+        return Err("no keys".into());
+    }
+    if let Some((k, _)) = keys.iter().find(|(k, _)| !(0.0..=1.0).contains(k)) {
+        return Err(format!("key position {} is not between 0 and 1", k));
+    }
+    keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+    Ok(())
+}
+
+fn write_keys<V: Serialize>(keys: Vec<(f32, V)>) -> Result<String, AssetOpError> {
+    let file = GradientFile {
+        keys,
+        interpolation: Interpolation::Linear,
+    };
+    Ok(ron::ser::to_string_pretty(
+        &file,
+        ron::ser::PrettyConfig::default(),
+    )?)
+}
+
+fn read_keys<V: DeserializeOwned>(ron: &str) -> Result<Vec<(f32, V)>, AssetOpError> {
+    let mut file: GradientFile<V> =
+        ron::from_str(ron).map_err(|e| AssetOpError::Deserialize(e.to_string()))?;
+    validate_keys(&mut file.keys).map_err(AssetOpError::Deserialize)?;
+    Ok(file.keys)
+}
+
+/// Gradient key values: how they're drawn and edited in the key popup.
+trait KeyValue {
     fn into_color(&self) -> Color32;
+    /// Returns true if changed.
+    fn edit(&mut self, ui: &mut Ui) -> bool;
 }
 
-impl IntoColor for Vec4 {
+impl KeyValue for Vec4 {
     fn into_color(&self) -> Color32 {
         rgba(self).into()
     }
+
+    fn edit(&mut self, ui: &mut Ui) -> bool {
+        let mut hsva = hsva(self);
+        let changed = color_picker_hsva_2d(ui, &mut hsva, Alpha::OnlyBlend);
+        if changed {
+            *self = from_hsva(hsva);
+        }
+        changed
+    }
 }
 
-impl IntoColor for Vec2 {
+impl KeyValue for Vec2 {
     fn into_color(&self) -> Color32 {
         Color32::GRAY
     }
+
+    fn edit(&mut self, ui: &mut Ui) -> bool {
+        ui.horizontal(|ui| size_drag_values(self, ui))
+            .inner
+            .changed()
+    }
+}
+
+// The popup for a key is open while the key is selected. The id is the same for the bar and the
+// values row, given the gradient's ui.
+fn key_popup_id(gradient_id: Id, i: usize) -> Id {
+    gradient_id.with(("key", i))
+}
+
+fn is_selected(ui: &Ui, gradient_id: Id, i: usize) -> bool {
+    ui.memory(|memory| memory.is_popup_open(key_popup_id(gradient_id, i)))
+}
+
+// Outline the widget for the selected key in the values row.
+fn highlight(response: &Response, ui: &Ui) {
+    let stroke = Stroke::new(
+        ui.visuals().selection.stroke.width,
+        color32(palette(ui.ctx()).selection),
+    );
+    ui.painter()
+        .rect_stroke(response.rect.expand(2.0), 2.0, stroke);
+}
+
+/// Mirror key positions around 0.5 and reverse their order.
+pub fn reverse_keys<V>(keys: &mut [(f32, V)]) {
+    keys.reverse();
+    for (k, _) in keys.iter_mut() {
+        *k = 1.0 - *k;
+    }
 }
 
-fn initial_value<T>(keys: &Vec<(f32, T)>) -> Option<&T> {
-    if keys[0].0 > 0.0 {
-        Some(&keys[0].1)
-    } else if let Some((_k, v)) = keys.iter().take_while(|k| k.0 == 0.0).last() {
-        Some(v)
-    } else {
-        None
+/// Space keys evenly from 0 to 1, keeping their order and values. A single key is left alone.
+pub fn distribute_keys<V>(keys: &mut [(f32, V)]) {
+    if keys.len() < 2 {
+        return;
+    }
+    let n = (keys.len() - 1) as f32;
+    for (i, (k, _)) in keys.iter_mut().enumerate() {
+        *k = i as f32 / n;
+    }
+}
+
+/// Map the first and last key positions to 0 and 1, scaling the others to match. Keys that are
+/// all at the same position are left alone.
+pub fn normalize_keys<V>(keys: &mut [(f32, V)]) {
+    let (Some(min), Some(max)) = (keys.first().map(|k| k.0), keys.last().map(|k| k.0)) else {
+        return;
+    };
+    if max <= min {
+        return;
+    }
+    for (k, _) in keys.iter_mut() {
+        *k = (*k - min) / (max - min);
+    }
+}
+
+/// Sample keys at `t` the same way as [bevy_hanabi::Gradient::sample]. Keys are sorted by
+/// position first, keeping the order of keys at the same position. Before the first key and after
+/// the last the value is constant. Exactly at a position shared by several keys the first of them
+/// is used, and just past it the last, so duplicate keys make a hard step.
+pub fn sample<T>(keys: &[(f32, T)], t: f32) -> T
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    let mut keys = keys.to_vec();
+    keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let (Some(first), Some(last)) = (keys.first(), keys.last()) else {
+        return T::default();
+    };
+    if t <= first.0 {
+        return first.1;
+    }
+    if t >= last.0 {
+        return last.1;
+    }
+
+    // The first key past t. There is always one before it since t is past the first key.
+    let i = keys.partition_point(|k| k.0 <= t);
+    if keys[i - 1].0 == t {
+        let first = keys.partition_point(|k| k.0 < t);
+        return keys[first].1;
+    }
+    let ((k0, v0), (k1, v1)) = (keys[i - 1], keys[i]);
+    v0 + (v1 - v0) * ((t - k0) / (k1 - k0))
+}
+
+/// Segments per key interval in the color preview. Hanabi interpolates in linear space but the
+/// mesh interpolates vertex colors in gamma space, so intervals are split to stay close.
+const PREVIEW_SUBDIVISIONS: usize = 8;
+
+/// The stops drawn by the previews from 0 to 1, with linear interpolation between them. Keys
+/// are sorted like [sample] and the ends are padded with the first and last values. Duplicate
+/// keys make zero-width segments, i.e. hard steps.
+fn preview_stops<T>(keys: &[(f32, T)], subdivisions: usize) -> Vec<(f32, T)>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>,
+{
+    let mut keys = keys.to_vec();
+    keys.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let (Some(&(_, first)), Some(&(_, last))) = (keys.first(), keys.last()) else {
+        return Vec::new();
+    };
+    let mut stops = vec![(0.0, first)];
+    for (k, v) in keys {
+        let (k0, v0) = stops[stops.len() - 1];
+        if k > k0 {
+            for i in 1..subdivisions {
+                let s = i as f32 / subdivisions as f32;
+                stops.push((k0 + (k - k0) * s, v0 + (v - v0) * s));
+            }
+        }
+        stops.push((k, v));
     }
+    stops.push((1.0, last));
+    stops
 }
 
-/// Add draggable keys.
-fn show_keys(keys: &mut Vec<(f32, impl IntoColor)>, rect: Rect, ui: &mut Ui) -> bool {
+/// Add draggable keys. Dragging horizontally moves the key. Vertical drags (in points, up is
+/// positive) are passed to `drag_value`. Clicking a key selects it and opens a popup to edit the
+/// value.
+fn show_keys<T: KeyValue>(
+    keys: &mut Vec<(f32, T)>,
+    rect: Rect,
+    ui: &mut Ui,
+    mut drag_value: impl FnMut(&mut T, f32),
+) -> bool {
     let mut sort = false;
     let mut changed = false;
     let count = keys.len();
+    let gradient_id = ui.id();
 
     // The scope is to paper over the layered space allocations. Following widgets will get
     // placed after the last (inset) allocation without it.
@@ -81,18 +481,49 @@ fn show_keys(keys: &mut Vec<(f32, impl IntoColor)>, rect: Rect, ui: &mut Ui) ->
                 ),
                 Sense::click_and_drag(),
             );
-            let visuals = ui.style().interact(&re);
+            let popup_id = key_popup_id(gradient_id, i);
+            let selected = is_selected(ui, gradient_id, i);
+            let stroke = if selected {
+                ui.visuals().selection.stroke
+            } else {
+                ui.style().interact(&re).fg_stroke
+            };
             ui.painter().add(epaint::CircleShape {
                 center: re.rect.center(),
                 radius: re.rect.size().x / 2.0,
                 fill,
-                stroke: visuals.fg_stroke,
+                stroke,
             });
 
+            if re.clicked() {
+                ui.memory_mut(|memory| memory.toggle_popup(popup_id));
+            }
+
+            // Like the color picker's popup, this stays open while it's being used.
+            if ui.memory(|memory| memory.is_popup_open(popup_id)) {
+                let area = Area::new(popup_id)
+                    .order(Order::Foreground)
+                    .fixed_pos(re.rect.left_bottom())
+                    .show(ui.ctx(), |ui| {
+                        Frame::popup(ui.style()).show(ui, |ui| value.edit(ui)).inner
+                    });
+                changed |= area.inner;
+
+                if !re.clicked()
+                    && (ui.input(|i| i.key_pressed(Key::Escape))
+                        || area.response.clicked_elsewhere())
+                {
+                    ui.memory_mut(|memory| memory.close_popup());
+                }
+            }
+
             // You need at least one key.
             if count > 1 && re.clicked_by(PointerButton::Secondary) {
                 // Delete the key.
                 keys.remove(i);
+                if selected {
+                    ui.memory_mut(|memory| memory.close_popup());
+                }
                 changed = true;
                 break;
             }
@@ -104,6 +535,10 @@ fn show_keys(keys: &mut Vec<(f32, impl IntoColor)>, rect: Rect, ui: &mut Ui) ->
                     let x = (p - rect.min).x / rect.width();
                     *key = x.clamp(0.0, 1.0);
                 }
+                let dy = -re.drag_delta().y;
+                if dy != 0.0 {
+                    drag_value(value, dy);
+                }
             } else if re.drag_released() {
                 // Don't sort until the drag is released otherwise it starts
                 // flickering. Probably because the ids get swapped?
@@ -122,16 +557,79 @@ pub trait Gradient {
     type Value;
 
     fn show(&mut self, ui: &mut Ui) -> Change {
-        self.show_gradient(ui) | self.show_values(ui)
+        self.show_gradient(ui) | self.show_values(ui) | self.show_key_ops(ui)
     }
 
     fn show_gradient(&mut self, ui: &mut Ui) -> Change;
     fn show_values(&mut self, ui: &mut Ui) -> Change;
+
+    fn keys_mut(&mut self) -> &mut Vec<(f32, Self::Value)>;
+
+    /// A menu of operations on all the keys.
+    fn show_key_ops(&mut self, ui: &mut Ui) -> Change {
+        let ops: [(&str, &str, fn(&mut [(f32, Self::Value)])); 3] = [
+            (
+                "Reverse",
+                "Mirror the keys around the middle, e.g. a fade in becomes a fade out.",
+                reverse_keys,
+            ),
+            (
+                "Distribute evenly",
+                "Space the keys evenly from 0 to 1, keeping their values.",
+                distribute_keys,
+            ),
+            (
+                "Normalize",
+                "Stretch the keys so the first is at 0 and the last is at 1.",
+                normalize_keys,
+            ),
+        ];
+
+        ui.menu_button("⋯", |ui| {
+            let mut changed = false;
+            for (label, hover, op) in ops {
+                if ui.button(label).on_hover_text(hover).clicked() {
+                    op(self.keys_mut());
+                    // The popups are by index.
+                    ui.memory_mut(|memory| memory.close_popup());
+                    ui.close_menu();
+                    changed = true;
+                }
+            }
+            changed
+        })
+        .inner
+        .unwrap_or_default()
+        .into()
+    }
+
+    /// Serialize to the gradient file format (see [GradientFile]).
+    fn to_ron(&self) -> Result<String, AssetOpError>;
+
+    /// Deserialize from the gradient file format. The keys are validated and sorted.
+    fn from_ron(ron: &str) -> Result<Self, AssetOpError>
+    where
+        Self: Sized;
 }
 
 impl Gradient for ColorGradient {
     type Value = Vec4;
 
+    fn keys_mut(&mut self) -> &mut Vec<(f32, Self::Value)> {
+        &mut self.keys
+    }
+
+    fn to_ron(&self) -> Result<String, AssetOpError> {
+        write_keys(self.keys.iter().map(|(k, v)| (*k, v.to_array())).collect())
+    }
+
+    fn from_ron(ron: &str) -> Result<Self, AssetOpError> {
+        let keys = read_keys::<[f32; 4]>(ron)?;
+        Ok(Self {
+            keys: keys.into_iter().map(|(k, v)| (k, Vec4::from(v))).collect(),
+        })
+    }
+
     fn show_gradient(&mut self, ui: &mut Ui) -> Change {
         let desired_size = vec2(ui.spacing().slider_width, ui.spacing().interact_size.y);
         let (rect, mut response) = ui.allocate_at_least(desired_size, Sense::hover());
@@ -139,39 +637,45 @@ impl Gradient for ColorGradient {
         if ui.is_rect_visible(rect) {
             let w = rect.width();
 
-            let keys = &mut self.keys;
-            assert!(keys.len() > 0);
-
-            // The starting color is the first key (if non-zero) or the last zero-value key.
-            let color = initial_value(keys).map(rgba).unwrap_or_default();
-            let mut mesh = start_strip(rect, color.into());
-
-            let mut last_k = 0.0;
-            for (key, color) in keys.iter_mut().skip_while(|(k, _)| *k == 0.0) {
-                add_segment(
-                    &mut mesh,
-                    (key.min(1.0) - last_k) * w,
-                    Some(rgba(color).into()),
-                );
-                last_k = *key;
-            }
-            if last_k < 1.0 {
-                add_segment(&mut mesh, (1.0 - last_k) * w, None);
+            let stops = preview_stops(&self.keys, PREVIEW_SUBDIVISIONS);
+            if let Some((_, color)) = stops.first() {
+                let mut mesh = start_strip(rect, rgba(color).into());
+                for pair in stops.windows(2) {
+                    let ((k0, _), (k1, color)) = (pair[0], pair[1]);
+                    add_segment(&mut mesh, (k1 - k0) * w, rgba(&color).into());
+                }
+                ui.painter().add(Shape::mesh(mesh));
             }
 
-            ui.painter().add(Shape::mesh(mesh));
-
             let visuals = ui.style().interact(&response);
             ui.painter().rect_stroke(rect, 0.0, visuals.bg_stroke);
 
             // if ui.scope(|ui| self.show_keys(ui)).inner {
             //     response.mark_changed();
             // }
-            if show_keys(&mut self.keys, rect, ui) {
+            if show_keys(&mut self.keys, rect, ui, |_, _| ()) {
                 response.mark_changed();
             }
         }
-        response.into()
+
+        // Show the color at the hovered position, as it's rendered on particles.
+        match response.hover_pos() {
+            Some(pos) => {
+                let t = ((pos.x - rect.min.x) / rect.width()).clamp(0.0, 1.0);
+                let color = self.sample(t);
+                response.on_hover_ui_at_pointer(|ui| {
+                    ui.horizontal(|ui| {
+                        _ = show_color(ui, rgba(&color), vec2(16.0, 16.0));
+                        ui.monospace(format!(
+                            "{:.3}  ({:.3}, {:.3}, {:.3}, {:.3})",
+                            t, color.x, color.y, color.z, color.w
+                        ));
+                    });
+                })
+            }
+            None => response,
+        }
+        .into()
     }
 
     // The color picker from egui is natively HSVA. So there's a lot of unnecessary conversion and
@@ -179,88 +683,219 @@ impl Gradient for ColorGradient {
     // which I presume has something to do with the conversion to HSVA. Which is why egui caches them?
     // We may have to write our own color picker just for RGBA.
     fn show_values(&mut self, ui: &mut Ui) -> Change {
-        let keys = &mut self.keys;
+        let gradient_id = ui.id();
+        let list_id = gradient_id.with("list");
+        let mut list = ui
+            .memory_mut(|memory| memory.data.get_temp::<bool>(list_id))
+            .unwrap_or_default();
 
-        let mut changed = false;
+        let toggle = ui
+            .selectable_label(list, "☰")
+            .on_hover_text("Edit the keys as a list, one row per key.");
+        if toggle.clicked() {
+            list = !list;
+            ui.memory_mut(|memory| memory.data.insert_temp(list_id, list));
+        }
 
-        let mut response = ui
-            .horizontal(|ui| {
-                // Make the buttons smaller.
-                ui.spacing_mut().interact_size = egui::Vec2::splat(12.0);
+        match list {
+            true => {
+                ui.vertical(|ui| show_key_list(&mut self.keys, gradient_id, ui))
+                    .inner
+            }
+            false => show_swatches(&mut self.keys, gradient_id, ui),
+        }
+    }
+}
 
-                for (_key, color) in keys.iter_mut() {
-                    let mut hsva = hsva(color);
-                    if color_edit_button_hsva(ui, &mut hsva, Alpha::OnlyBlend).changed() {
-                        *color = Vec4::from_slice(&hsva.to_rgba_premultiplied());
-                        // TODO only set changed when the popup is closed
-                        changed = true;
-                    }
-                }
+// A swatch per key in a row. These get tiny with many keys; see [show_key_list].
+fn show_swatches(keys: &mut Vec<(f32, Vec4)>, gradient_id: Id, ui: &mut Ui) -> Change {
+    let mut changed = false;
+
+    let mut response = ui
+        .horizontal(|ui| {
+            // Make the buttons smaller.
+            ui.spacing_mut().interact_size = egui::Vec2::splat(12.0);
 
-                if ui.small_button("+").clicked() {
-                    keys.push((1.0, Vec4::ZERO));
+            for (i, (_key, color)) in keys.iter_mut().enumerate() {
+                let mut hsva = hsva(color);
+                let response = color_edit_button_hsva(ui, &mut hsva, Alpha::OnlyBlend);
+                if response.changed() {
+                    *color = from_hsva(hsva);
+                    // TODO only set changed when the popup is closed
                     changed = true;
                 }
-            })
-            .response;
+                if is_selected(ui, gradient_id, i) {
+                    highlight(&response, ui);
+                }
+            }
 
-        if changed {
-            response.mark_changed();
-        }
+            if ui.small_button("+").clicked() {
+                keys.push((1.0, Vec4::ZERO));
+                changed = true;
+            }
+        })
+        .response;
 
-        response.into()
+    if changed {
+        response.mark_changed();
     }
+
+    response.into()
+}
+
+// A row per key with its position, color, hex code and a delete button. Clicking the index selects
+// the key, as on the bar. Keys are sorted once a position is done being edited.
+fn show_key_list(keys: &mut Vec<(f32, Vec4)>, gradient_id: Id, ui: &mut Ui) -> Change {
+    let (mut changed, mut sort) = (false, false);
+    let mut delete = None;
+    let count = keys.len();
+
+    ScrollArea::vertical()
+        .id_source(gradient_id.with("list_scroll"))
+        .max_height(160.0)
+        .show(ui, |ui| {
+            Grid::new(gradient_id.with("list_grid"))
+                .striped(true)
+                .show(ui, |ui| {
+                    for (i, (key, color)) in keys.iter_mut().enumerate() {
+                        let selected = is_selected(ui, gradient_id, i);
+                        if ui.selectable_label(selected, i.to_string()).clicked() {
+                            let popup_id = key_popup_id(gradient_id, i);
+                            ui.memory_mut(|memory| memory.toggle_popup(popup_id));
+                        }
+
+                        let response = ui.add(
+                            DragValue::new(key)
+                                .clamp_range(0.0..=1.0)
+                                .speed(0.01)
+                                .max_decimals(3),
+                        );
+                        changed |= response.changed();
+                        sort |= response.drag_released() || response.lost_focus();
+
+                        let mut hsva = hsva(color);
+                        if color_edit_button_hsva(ui, &mut hsva, Alpha::OnlyBlend).changed() {
+                            *color = from_hsva(hsva);
+                            changed = true;
+                        }
+
+                        // Keep the text while it's being typed; it's applied when done.
+                        let hex_id = gradient_id.with(("hex", i));
+                        let mut hex = ui
+                            .memory_mut(|memory| memory.data.get_temp::<String>(hex_id))
+                            .unwrap_or_else(|| to_hex(color));
+                        let response = ui.add(
+                            TextEdit::singleline(&mut hex)
+                                .desired_width(72.0)
+                                .font(TextStyle::Monospace),
+                        );
+                        if response.lost_focus() {
+                            if let Some(c) = from_hex(&hex) {
+                                changed |= c != *color;
+                                *color = c;
+                            }
+                            ui.memory_mut(|memory| memory.data.remove::<String>(hex_id));
+                        } else if response.has_focus() {
+                            ui.memory_mut(|memory| memory.data.insert_temp(hex_id, hex));
+                        }
+
+                        // You need at least one key.
+                        if ui
+                            .add_enabled(count > 1, Button::new("🗙").small())
+                            .clicked()
+                        {
+                            delete = Some(i);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+    if ui.small_button("+").clicked() {
+        keys.push((1.0, Vec4::ZERO));
+        changed = true;
+    }
+
+    if let Some(i) = delete {
+        keys.remove(i);
+        changed = true;
+    }
+    if sort {
+        keys.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+    }
+    // The popups are by index.
+    if delete.is_some() || sort {
+        ui.memory_mut(|memory| memory.close_popup());
+    }
+
+    changed.into()
+}
+
+/// A color (linear, unmultiplied) as an sRGB hex code with alpha, e.g. `#ff8000ff`.
+pub fn to_hex(c: &Vec4) -> String {
+    let [r, g, b, a] = rgba(c).to_srgba_unmultiplied();
+    format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+}
+
+/// Parse an sRGB hex code, with or without the `#` and alpha. The inverse of [to_hex].
+pub fn from_hex(hex: &str) -> Option<Vec4> {
+    let hex = hex.trim().trim_start_matches('#');
+    if !matches!(hex.len(), 6 | 8) {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    let a = match hex.len() {
+        8 => byte(6)?,
+        _ => 255,
+    };
+    Some(Vec4::new(
+        ecolor::linear_f32_from_gamma_u8(byte(0)?),
+        ecolor::linear_f32_from_gamma_u8(byte(2)?),
+        ecolor::linear_f32_from_gamma_u8(byte(4)?),
+        ecolor::linear_f32_from_linear_u8(a),
+    ))
 }
 
 impl Gradient for SizeGradient {
     type Value = Vec2;
 
-    fn show_gradient(&mut self, ui: &mut Ui) -> Change {
-        assert!(self.keys.len() > 0);
+    fn keys_mut(&mut self) -> &mut Vec<(f32, Self::Value)> {
+        &mut self.keys
+    }
+
+    fn to_ron(&self) -> Result<String, AssetOpError> {
+        write_keys(self.keys.iter().map(|(k, v)| (*k, v.to_array())).collect())
+    }
+
+    fn from_ron(ron: &str) -> Result<Self, AssetOpError> {
+        let keys = read_keys::<[f32; 2]>(ron)?;
+        Ok(Self {
+            keys: keys.into_iter().map(|(k, v)| (k, Vec2::from(v))).collect(),
+        })
+    }
 
+    fn show_gradient(&mut self, ui: &mut Ui) -> Change {
         let desired_size = vec2(ui.spacing().slider_width, ui.spacing().interact_size.y);
         let (rect, mut response) = ui.allocate_at_least(desired_size, Sense::hover());
         let visuals = ui.style().interact(&response);
 
         if ui.is_rect_visible(rect) {
-            let w = rect.width();
-
-            let stroke_x = Stroke::new(visuals.fg_stroke.width, Color32::RED);
-            let stroke_y = Stroke::new(visuals.fg_stroke.width, Color32::GREEN);
-
-            let mut max = Vec2::ZERO;
-
-            let initial =
-                initial_value(&self.keys).map(|v| (pos2(rect.min.x, v.x), pos2(rect.min.x, v.y)));
-
-            // Add a final key if the last one is < 1.0.
-            let last = self
-                .keys
-                .last()
-                .filter(|(k, _)| *k < 1.0)
-                .map(|(_, v)| (pos2(rect.max.x, v.x), pos2(rect.max.x, v.y)));
-
-            let (mut line_x, mut line_y): (Vec<_>, Vec<_>) = initial
-                .into_iter()
-                .chain(self.keys.iter().map(|(k, v)| {
-                    max = max.max(*v);
-                    let x = rect.min.x + k * w;
-                    (pos2(x, v.x), pos2(x, v.y))
-                }))
-                .chain(last.into_iter())
-                .unzip();
-
-            // Scale to fit vertically and offset from rect.
-            let max = rect.height() / max.x.max(max.y);
-            line_x.iter_mut().for_each(|p| p.y = rect.max.y - p.y * max);
-            line_y.iter_mut().for_each(|p| p.y = rect.max.y - p.y * max);
+            let palette = palette(ui.ctx());
+            let stroke_x = Stroke::new(visuals.fg_stroke.width, color32(palette.curve_x));
+            let stroke_y = Stroke::new(visuals.fg_stroke.width, color32(palette.curve_y));
 
+            let (scale, line_x, line_y) = size_lines(&self.keys, rect);
             ui.painter().add(Shape::line(line_x, stroke_x));
             ui.painter().add(Shape::line(line_y, stroke_y));
 
             ui.painter().rect_stroke(rect, 0.0, visuals.bg_stroke);
 
-            if show_keys(&mut self.keys, rect, ui) {
+            // Dragging a key up or down changes both components by the same amount, at the display
+            // scale. The lines follow on the next frame.
+            let drag_size = |size: &mut Vec2, dy: f32| {
+                *size = (*size + Vec2::splat(dy / scale)).max(Vec2::ZERO);
+            };
+            if show_keys(&mut self.keys, rect, ui, drag_size) {
                 response.mark_changed();
             }
         }
@@ -268,24 +903,20 @@ impl Gradient for SizeGradient {
     }
 
     fn show_values(&mut self, ui: &mut Ui) -> Change {
+        let gradient_id = ui.id();
         ui.horizontal(|ui| {
             ui.spacing_mut().interact_size = egui::Vec2::splat(4.0);
 
             let mut response = self
                 .keys
                 .iter_mut()
-                .map(|(_key, value)| {
-                    ui.add(
-                        egui::DragValue::new(&mut value[0])
-                            .prefix("x: ")
-                            .speed(0.01)
-                            .clamp_range(0.0..=f32::MAX),
-                    ) | ui.add(
-                        egui::DragValue::new(&mut value[1])
-                            .prefix("y: ")
-                            .speed(0.01)
-                            .clamp_range(0.0..=f32::MAX),
-                    )
+                .enumerate()
+                .map(|(i, (_key, value))| {
+                    let response = size_drag_values(value, ui);
+                    if is_selected(ui, gradient_id, i) {
+                        highlight(&response, ui);
+                    }
+                    response
                 })
                 .reduce(|a, b| a | b)
                 .expect("at least one key");
@@ -300,6 +931,43 @@ impl Gradient for SizeGradient {
     }
 }
 
+/// The x and y lines of the size preview and the vertical scale, fit so the largest size reaches
+/// the top of the rect. All-zero sizes make a flat line along the bottom, and negative sizes are
+/// clamped to it. A single key makes a flat line across the full width.
+fn size_lines(keys: &[(f32, Vec2)], rect: Rect) -> (f32, Vec<Pos2>, Vec<Pos2>) {
+    let max = keys
+        .iter()
+        .fold(0.0f32, |max, (_, v)| max.max(v.max_element()));
+    // Avoid dividing by zero.
+    let scale = rect.height() / max.max(0.01);
+
+    let point = |k: f32, v: f32| {
+        pos2(
+            lerp(rect.x_range(), k),
+            (rect.max.y - v * scale).clamp(rect.min.y, rect.max.y),
+        )
+    };
+    let (line_x, line_y) = preview_stops(keys, 1)
+        .into_iter()
+        .map(|(k, v)| (point(k, v.x), point(k, v.y)))
+        .unzip();
+    (scale, line_x, line_y)
+}
+
+fn size_drag_values(value: &mut Vec2, ui: &mut Ui) -> Response {
+    ui.add(
+        egui::DragValue::new(&mut value[0])
+            .prefix("x: ")
+            .speed(0.01)
+            .clamp_range(0.0..=f32::MAX),
+    ) | ui.add(
+        egui::DragValue::new(&mut value[1])
+            .prefix("y: ")
+            .speed(0.01)
+            .clamp_range(0.0..=f32::MAX),
+    )
+}
+
 impl From<ColorGradient> for ColorOverLifetimeModifier {
     fn from(g: ColorGradient) -> Self {
         let mut gradient = bevy_hanabi::Gradient::new();
@@ -322,13 +990,20 @@ impl From<SizeGradient> for SizeOverLifetimeModifier {
     }
 }
 
-// This is still the fastest way to Color32?
+/// A stored color (linear, unmultiplied) for drawing. egui's [Rgba] is premultiplied. This is
+/// still the fastest way to Color32?
 pub fn rgba(c: &Vec4) -> Rgba {
-    Rgba::from_rgba_premultiplied(c[0], c[1], c[2], c[3])
+    Rgba::from_rgba_unmultiplied(c[0], c[1], c[2], c[3])
 }
 
+/// A stored color (linear, unmultiplied) for the color picker.
 pub fn hsva(c: &Vec4) -> Hsva {
-    Hsva::from_rgba_premultiplied(c[0], c[1], c[2], c[3])
+    Hsva::from_rgba_unmultiplied(c[0], c[1], c[2], c[3])
+}
+
+/// A color from the color picker, to store. The inverse of [hsva].
+pub fn from_hsva(hsva: Hsva) -> Vec4 {
+    Vec4::from_slice(&hsva.to_rgba_unmultiplied())
 }
 
 // Start a strip with two vertices.
@@ -340,15 +1015,12 @@ fn start_strip(rect: Rect, color: Color32) -> Mesh {
 }
 
 // Add two vertices and fill with two triangles.
-fn add_segment(mesh: &mut Mesh, width: f32, color: Option<Color32>) {
+fn add_segment(mesh: &mut Mesh, width: f32, color: Color32) {
     let v1 = (mesh.vertices.len() - 1) as u32;
     let v2 = v1 - 1;
     let p1 = mesh.vertices[v1 as usize].pos;
     let p2 = mesh.vertices[v2 as usize].pos;
 
-    // Use the last color if no color is provided.
-    let color = color.unwrap_or_else(|| mesh.vertices[v1 as usize].color);
-
     mesh.colored_vertex(p2 + vec2(width, 0.0), color);
     mesh.colored_vertex(p1 + vec2(width, 0.0), color);
 
@@ -357,3 +1029,290 @@ fn add_segment(mesh: &mut Mesh, width: f32, color: Option<Color32>) {
     mesh.add_triangle(v2, v1 + 1, v1);
     mesh.add_triangle(v1, v1 + 1, v1 + 2);
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::render_resource::{Extent3d, TextureDimension};
+
+    use super::*;
+
+    fn ramp(n: usize, f: impl Fn(f32) -> Vec4) -> Vec<(f32, Vec4)> {
+        (0..n)
+            .map(|i| {
+                let t = i as f32 / (n - 1) as f32;
+                (t, f(t))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn simplify_linear() {
+        let mut keys = ramp(64, |t| Vec4::new(t, 0.0, 1.0 - t, 1.0));
+        simplify(&mut keys, 8, SIMPLIFY_TOLERANCE);
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0], (0.0, Vec4::new(0.0, 0.0, 1.0, 1.0)));
+        assert_eq!(keys[1], (1.0, Vec4::new(1.0, 0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn simplify_corner() {
+        // Black to red to white.
+        let mut keys = ramp(65, |t| {
+            if t <= 0.5 {
+                Vec4::new(t * 2.0, 0.0, 0.0, 1.0)
+            } else {
+                Vec4::new(1.0, t * 2.0 - 1.0, t * 2.0 - 1.0, 1.0)
+            }
+        });
+        simplify(&mut keys, 8, SIMPLIFY_TOLERANCE);
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[1], (0.5, Vec4::new(1.0, 0.0, 0.0, 1.0)));
+    }
+
+    #[test]
+    fn simplify_max_keys() {
+        let mut keys = ramp(64, |t| Vec4::splat((t * 20.0).sin()));
+        simplify(&mut keys, 5, SIMPLIFY_TOLERANCE);
+        assert_eq!(keys.len(), 5);
+        assert_eq!(keys[0].0, 0.0);
+        assert_eq!(keys[4].0, 1.0);
+    }
+
+    fn image(pixels: &[[u8; 4]], format: TextureFormat) -> Image {
+        Image::new(
+            Extent3d {
+                width: pixels.len() as u32,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            pixels.concat(),
+            format,
+        )
+    }
+
+    #[test]
+    fn from_image() {
+        let pixels: Vec<_> = (0..=255u8).map(|v| [v, 0, 255 - v, 255]).collect();
+        let g = ColorGradient::from_image(
+            &image(&pixels, TextureFormat::Rgba8Unorm),
+            IMAGE_SAMPLES,
+            IMAGE_MAX_KEYS,
+        )
+        .unwrap();
+        assert_eq!(g.keys.len(), 2);
+        assert_eq!(g.keys[1].1, Vec4::new(1.0, 0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn from_image_unsupported() {
+        let pixels = [[0, 0, 0, 0]; 4];
+        assert!(ColorGradient::from_image(
+            &image(&pixels, TextureFormat::R32Float),
+            IMAGE_SAMPLES,
+            IMAGE_MAX_KEYS
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn gradient_file_round_trip() {
+        let g = ColorGradient {
+            keys: vec![(0.0, Vec4::new(1.0, 0.5, 0.0, 1.0)), (1.0, Vec4::ZERO)],
+        };
+        let g2 = ColorGradient::from_ron(&g.to_ron().unwrap()).unwrap();
+        assert_eq!(g.keys, g2.keys);
+    }
+
+    #[test]
+    fn gradient_file_sorted() {
+        let g = SizeGradient::from_ron("(keys: [(1.0, (2.0, 2.0)), (0.0, (1.0, 1.0))])").unwrap();
+        assert_eq!(g.keys, vec![(0.0, Vec2::ONE), (1.0, Vec2::splat(2.0))]);
+    }
+
+    #[test]
+    fn gradient_file_invalid() {
+        assert!(SizeGradient::from_ron("(keys: [])").is_err());
+        assert!(SizeGradient::from_ron("(keys: [(1.5, (1.0, 1.0))])").is_err());
+        assert!(ColorGradient::from_ron("(keys: [(0.0, (1.0, 1.0))])").is_err());
+    }
+
+    fn keys(positions: &[f32]) -> Vec<(f32, usize)> {
+        positions.iter().copied().zip(0..).collect()
+    }
+
+    #[test]
+    fn reverse() {
+        let mut k = keys(&[0.0, 0.25, 1.0]);
+        reverse_keys(&mut k);
+        assert_eq!(k, vec![(0.0, 2), (0.75, 1), (1.0, 0)]);
+    }
+
+    #[test]
+    fn distribute() {
+        let mut k = keys(&[0.1, 0.2, 0.3, 0.9, 1.0]);
+        distribute_keys(&mut k);
+        assert_eq!(k, vec![(0.0, 0), (0.25, 1), (0.5, 2), (0.75, 3), (1.0, 4)]);
+
+        let mut k = keys(&[0.3]);
+        distribute_keys(&mut k);
+        assert_eq!(k, keys(&[0.3]));
+    }
+
+    #[test]
+    fn normalize() {
+        let mut k = keys(&[0.25, 0.5, 0.75]);
+        normalize_keys(&mut k);
+        assert_eq!(k, keys(&[0.0, 0.5, 1.0]));
+
+        let mut k = keys(&[0.5, 0.5]);
+        normalize_keys(&mut k);
+        assert_eq!(k, keys(&[0.5, 0.5]));
+    }
+
+    // Key lists for comparing against hanabi, including duplicate positions for hard steps.
+    fn sample_corpus() -> Vec<Vec<(f32, Vec4)>> {
+        let (a, b, c) = (Vec4::ONE, Vec4::new(1.0, 0.0, 0.0, 1.0), Vec4::ZERO);
+        vec![
+            vec![(0.5, a)],
+            vec![(0.0, a), (1.0, c)],
+            vec![(0.25, a), (0.75, b)],
+            vec![(0.0, a), (0.0, b), (1.0, c)],
+            vec![(0.0, a), (0.5, b), (0.5, c), (1.0, a)],
+            vec![(0.0, a), (1.0, b), (1.0, c)],
+            vec![(0.6, c), (0.2, a), (0.4, b)],
+        ]
+    }
+
+    #[test]
+    fn sample_matches_hanabi() {
+        for keys in sample_corpus() {
+            let g = ColorGradient { keys };
+            let hanabi = ColorOverLifetimeModifier::from(g.clone()).gradient;
+            for i in 0..=100 {
+                let t = i as f32 / 100.0;
+                assert_eq!(g.sample(t), hanabi.sample(t), "{:?} at {}", g.keys, t);
+            }
+        }
+    }
+
+    #[test]
+    fn fix_up() {
+        let mut keys = vec![(1.5, 0), (f32::NAN, 1), (0.5, 2)];
+        assert!(fix_up_keys(&mut keys, Vec::new).is_some());
+        assert_eq!(keys, vec![(0.0, 1), (0.5, 2), (1.0, 0)]);
+
+        let mut keys = vec![];
+        assert!(fix_up_keys(&mut keys, || vec![(0.5, 0)]).is_some());
+        assert_eq!(keys, vec![(0.5, 0)]);
+
+        let mut keys = vec![(0.0, 0), (1.0, 1)];
+        assert!(fix_up_keys(&mut keys, Vec::new).is_none());
+    }
+
+    #[test]
+    fn from_reflect_fixes_up() {
+        use bevy::reflect::DynamicStruct;
+
+        let mut s = DynamicStruct::default();
+        s.insert("keys", vec![(1.5f32, Vec4::ONE), (0.25f32, Vec4::ZERO)]);
+        let g = ColorGradient::from_reflect(&s).unwrap();
+        assert_eq!(g.keys, vec![(0.25, Vec4::ZERO), (1.0, Vec4::ONE)]);
+
+        let mut s = DynamicStruct::default();
+        s.insert("keys", Vec::<(f32, Vec2)>::new());
+        let g = SizeGradient::from_reflect(&s).unwrap();
+        assert_eq!(g.keys, SizeGradient::default().keys);
+    }
+
+    // Evaluate the preview stops between stop positions.
+    fn eval_stops(stops: &[(f32, Vec4)], t: f32) -> Vec4 {
+        let i = stops.partition_point(|s| s.0 <= t);
+        let ((k0, v0), (k1, v1)) = (stops[i - 1], stops[i]);
+        v0.lerp(v1, (t - k0) / (k1 - k0))
+    }
+
+    #[test]
+    fn preview_matches_hanabi() {
+        for keys in sample_corpus() {
+            let stops = preview_stops(&keys, PREVIEW_SUBDIVISIONS);
+            let hanabi = ColorOverLifetimeModifier::from(ColorGradient { keys: keys.clone() });
+            for i in 0..200 {
+                // Between samples, so never exactly at a key.
+                let t = (i as f32 + 0.5) / 200.0;
+                let d = eval_stops(&stops, t) - hanabi.gradient.sample(t);
+                assert!(d.abs().max_element() < 1e-5, "{:?} at {}", keys, t);
+            }
+        }
+    }
+
+    #[test]
+    fn size_lines_zero() {
+        let rect = Rect::from_min_size(Pos2::ZERO, vec2(100.0, 10.0));
+        let (scale, line_x, line_y) = size_lines(&[(0.0, Vec2::ZERO), (1.0, Vec2::ZERO)], rect);
+        assert!(scale.is_finite());
+        assert!(line_x.iter().chain(&line_y).all(|p| p.y == rect.max.y));
+    }
+
+    #[test]
+    fn size_lines_single_key() {
+        let rect = Rect::from_min_size(Pos2::ZERO, vec2(100.0, 10.0));
+        let (_, line_x, _) = size_lines(&[(0.5, Vec2::ONE)], rect);
+        assert_eq!(line_x.first().map(|p| p.x), Some(rect.min.x));
+        assert_eq!(line_x.last().map(|p| p.x), Some(rect.max.x));
+        assert!(line_x.iter().all(|p| p.y == rect.min.y));
+    }
+
+    #[test]
+    fn size_lines_negative() {
+        let rect = Rect::from_min_size(Pos2::ZERO, vec2(100.0, 10.0));
+        let (scale, line_x, line_y) = size_lines(&[(0.0, Vec2::splat(-1.0))], rect);
+        assert!(scale.is_finite());
+        assert!(line_x.iter().chain(&line_y).all(|p| rect.contains(*p)));
+    }
+
+    #[test]
+    fn color_reference_values() {
+        // Premultiplied for drawing only: linear 0.5 is sRGB 188 and 0.25 is 137.
+        let c = Color32::from(rgba(&Vec4::new(1.0, 0.5, 0.0, 0.5)));
+        assert_eq!(c, Color32::from_rgba_premultiplied(188, 137, 0, 128));
+
+        // sRGB 128 (mid gray) is linear 0.2159.
+        let c = Color32::from(rgba(&Vec4::new(0.2159, 0.2159, 0.2159, 1.0)));
+        assert_eq!(c, Color32::from_gray(128));
+
+        // The picker sees the unmultiplied color.
+        let h = hsva(&Vec4::new(1.0, 0.0, 0.0, 0.25));
+        assert_eq!((h.v, h.a), (1.0, 0.25));
+    }
+
+    #[test]
+    fn color_round_trip() {
+        for c in [
+            Vec4::new(1.0, 0.0, 0.0, 1.0),
+            Vec4::new(0.2, 0.4, 0.8, 0.5),
+            Vec4::new(0.9, 0.9, 0.1, 0.1),
+            Vec4::new(0.0, 0.0, 0.0, 0.0),
+            Vec4::new(0.5, 0.25, 0.125, 0.75),
+        ] {
+            let back = from_hsva(hsva(&c));
+            assert!(
+                (back - c).abs().max_element() < 1e-5,
+                "{} became {}",
+                c,
+                back
+            );
+        }
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        for hex in ["#ff8000ff", "#00000000", "#80808080", "#1a2b3cff"] {
+            assert_eq!(to_hex(&from_hex(hex).unwrap()), hex);
+        }
+        // Alpha and the # are optional.
+        assert_eq!(from_hex("ff0000"), Some(Vec4::new(1.0, 0.0, 0.0, 1.0)));
+        assert_eq!(from_hex("#ff00"), None);
+        assert_eq!(from_hex("#gg0000"), None);
+    }
+}
@@ -0,0 +1,132 @@
+// Importing textures from outside the assets root, which the asset server can't reach. Files are
+// copied into a folder under the root in a task, then loaded and added to the image browser.
+
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    prelude::*,
+    tasks::{futures_lite::future, IoTaskPool, Task},
+};
+
+use crate::{
+    asset::{asset_path_string, import_file, AssetOpError},
+    browser::AssetBrowser,
+    change::ChangeFlags,
+    config::EditorConfig,
+    reffect::{ParticleTexture, REffect},
+    toast::Toasts,
+    ui::actions::{Action, Actions},
+};
+
+pub struct ImportPlugin;
+
+impl Plugin for ImportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TextureImports>()
+            .add_system(finish_imports);
+    }
+}
+
+struct Import {
+    file: PathBuf,
+    /// The effect to set the texture on, if any.
+    effect: Option<Handle<REffect>>,
+    task: Task<Result<PathBuf, AssetOpError>>,
+}
+
+#[derive(Resource)]
+pub struct TextureImports {
+    /// Folder under the assets root that textures are copied to.
+    pub folder: PathBuf,
+    imports: Vec<Import>,
+}
+
+impl Default for TextureImports {
+    fn default() -> Self {
+        Self {
+            folder: PathBuf::from("textures"),
+            imports: Vec::new(),
+        }
+    }
+}
+
+impl TextureImports {
+    /// Pick image files to import. If an effect is given, the first image is set as its texture.
+    pub fn pick(&mut self, image_paths: &AssetBrowser<Image>, effect: Option<Handle<REffect>>) {
+        let ext = image_paths.extension;
+        let Some(files) = rfd::FileDialog::new().add_filter(ext, &[ext]).pick_files() else {
+            return;
+        };
+
+        let mut effect = effect;
+        for file in files {
+            self.import(file, &image_paths.root_path, effect.take());
+        }
+    }
+
+    /// Start copying a file into the import folder.
+    pub fn import(&mut self, file: PathBuf, root_path: &Path, effect: Option<Handle<REffect>>) {
+        let (root_path, folder) = (root_path.to_path_buf(), self.folder.clone());
+        let task = IoTaskPool::get().spawn({
+            let file = file.clone();
+            async move { import_file(&file, &root_path, &folder) }
+        });
+        self.imports.push(Import { file, effect, task });
+    }
+
+    pub fn is_importing(&self) -> bool {
+        !self.imports.is_empty()
+    }
+}
+
+// Load finished imports and set them on their effects.
+fn finish_imports(
+    mut imports: ResMut<TextureImports>,
+    asset_server: Res<AssetServer>,
+    toasts: Res<Toasts>,
+    mut image_paths: ResMut<AssetBrowser<Image>>,
+    mut reffect_paths: ResMut<AssetBrowser<REffect>>,
+    mut reffects: ResMut<Assets<REffect>>,
+    mut actions: ResMut<Actions>,
+    mut config: ResMut<EditorConfig>,
+) {
+    if !imports.is_importing() {
+        return;
+    }
+
+    imports.imports.retain_mut(|import| {
+        let Some(result) = future::block_on(future::poll_once(&mut import.task)) else {
+            return true;
+        };
+
+        match result {
+            Ok(path) => {
+                toasts.info(format!("imported {}", path.display()));
+                let texture: Handle<Image> = asset_server.load(path.as_path());
+                image_paths.insert(path, texture.clone());
+
+                if let Some(effect) = import.effect.as_ref() {
+                    if let Some(re) = reffects.get_mut(effect) {
+                        re.render_particle_texture = ParticleTexture::Texture(texture);
+                        if let Ok(path) = asset_path_string(&path.as_path().into()) {
+                            config.use_texture(path);
+                        }
+                        if let Some(entry) = reffect_paths
+                            .entries
+                            .iter_mut()
+                            .find(|e| e.handle.as_ref() == Some(effect))
+                        {
+                            entry.dirty = true;
+                        }
+                        actions.push(Action::Regenerate(effect.clone(), ChangeFlags::TEXTURE));
+                    }
+                }
+            }
+            Err(e) => e.toast(
+                format!("failed to import {}", import.file.display()),
+                &toasts,
+            ),
+        }
+        false
+    });
+}
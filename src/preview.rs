@@ -0,0 +1,269 @@
+// Editor-side preview settings. None of this is saved in effects.
+
+use bevy::{asset::HandleId, prelude::*, utils::HashMap};
+use bevy_hanabi::{prelude::*, Random};
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+
+use crate::{
+    reffect::{ModifierField, REffect},
+    LiveEffect,
+};
+
+pub struct PreviewPlugin;
+
+impl Plugin for PreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PreviewSettings>()
+            .init_resource::<PreviewMutes>()
+            .add_system(reseed)
+            .add_system(delay_start)
+            .add_system(start_delayed.after(delay_start));
+    }
+}
+
+#[derive(Resource)]
+pub struct PreviewSettings {
+    /// Reseed the spawner random number generator whenever an effect is (re)spawned or reset, so
+    /// previews are repeatable.
+    pub deterministic: bool,
+    pub seed: u64,
+    /// Where newly shown effects are spawned.
+    pub placement: Placement,
+    /// Distance between grid cells for [Placement::Grid].
+    pub grid_spacing: f32,
+    /// Delay each shown effect by a random fraction of its period, so copies of a repeating effect
+    /// don't pulse in sync.
+    pub phase_offset: bool,
+    /// Outline the emitter shape, kill box and bounds of live effects.
+    pub show_gizmos: bool,
+    /// Draw an axis triad at the origin of each live effect.
+    pub show_axes: bool,
+}
+
+impl Default for PreviewSettings {
+    fn default() -> Self {
+        Self {
+            deterministic: false,
+            seed: 0x5eed,
+            placement: Placement::Origin,
+            grid_spacing: 4.0,
+            phase_offset: false,
+            show_gizmos: true,
+            show_axes: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Placement {
+    #[default]
+    Origin,
+    /// The next free cell of a grid on the ground plane.
+    Grid,
+    /// Where the cursor ray hits the ground plane.
+    Cursor,
+}
+
+impl Placement {
+    pub const ALL: [Placement; 3] = [Placement::Origin, Placement::Grid, Placement::Cursor];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Placement::Origin => "Origin",
+            Placement::Grid => "Grid",
+            Placement::Cursor => "Cursor",
+        }
+    }
+}
+
+/// Cells per grid row.
+const GRID_COLUMNS: usize = 5;
+
+/// The grid cell a live effect was placed in. The cell is free again once the effect is despawned.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GridCell(pub usize);
+
+impl GridCell {
+    /// The lowest cell not taken.
+    pub fn free(taken: &[usize]) -> Self {
+        Self((0..).find(|cell| !taken.contains(cell)).unwrap())
+    }
+
+    /// Rows extend away from the default view, centered on the origin.
+    pub fn position(&self, spacing: f32) -> Vec3 {
+        let (col, row) = (self.0 % GRID_COLUMNS, self.0 / GRID_COLUMNS);
+        let col = col as f32 - (GRID_COLUMNS / 2) as f32;
+        Vec3::new(col * spacing, 0.0, -(row as f32) * spacing)
+    }
+}
+
+/// Position of the `i`th of `n` effects in a row along X, centered on the origin.
+pub fn row_position(i: usize, n: usize, spacing: f32) -> Vec3 {
+    let offset = i as f32 - n.saturating_sub(1) as f32 / 2.0;
+    Vec3::new(offset * spacing, 0.0, 0.0)
+}
+
+/// Where a ray hits the ground plane (y = 0), if it does.
+pub fn ground_hit(ray: Ray) -> Option<Vec3> {
+    if ray.direction.y.abs() < f32::EPSILON {
+        return None;
+    }
+    let t = -ray.origin.y / ray.direction.y;
+    (t >= 0.0).then(|| ray.origin + ray.direction * t)
+}
+
+impl PreviewSettings {
+    /// Reseed now, if deterministic.
+    pub fn reseed(&self, commands: &mut Commands) {
+        if self.deterministic {
+            commands.insert_resource(Random(Pcg32::seed_from_u64(self.seed)));
+        }
+    }
+
+    /// Whether a live effect's spawner starts inactive and is activated later, by [delay_start].
+    /// Set on the effect asset when spawning.
+    pub fn starts_delayed(&self, re: &REffect) -> bool {
+        re.spawner.starts_active
+            && (re.start_delay > 0.0 || (self.phase_offset && re.period().is_some()))
+    }
+}
+
+/// Modifiers left out of live effects, by effect, to hear what each one contributes (like muting
+/// tracks). Only the generated effect assets change; the effects aren't touched or marked unsaved.
+/// Not saved.
+#[derive(Resource, Default, Debug)]
+pub struct PreviewMutes(HashMap<HandleId, Vec<&'static str>>);
+
+impl PreviewMutes {
+    /// The muted fields of the effect, as [ModifierField::field].
+    pub fn muted(&self, handle: &Handle<REffect>) -> &[&'static str] {
+        self.0
+            .get(&handle.id())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn is_muted(&self, handle: &Handle<REffect>, field: &str) -> bool {
+        self.muted(handle).contains(&field)
+    }
+
+    pub fn toggle(&mut self, handle: &Handle<REffect>, field: &'static str) {
+        let muted = self.0.entry(handle.id()).or_default();
+        match muted.iter().position(|f| *f == field) {
+            Some(i) => {
+                muted.remove(i);
+            }
+            None => muted.push(field),
+        }
+        if muted.is_empty() {
+            self.0.remove(&handle.id());
+        }
+    }
+
+    /// Mute the others and unmute the field.
+    pub fn solo(
+        &mut self,
+        handle: &Handle<REffect>,
+        field: &'static str,
+        others: &[ModifierField],
+    ) {
+        let muted = self.0.entry(handle.id()).or_default();
+        muted.retain(|f| *f != field);
+        for other in others.iter().filter(|f| f.field != field) {
+            if !muted.contains(&other.field) {
+                muted.push(other.field);
+            }
+        }
+    }
+
+    pub fn clear(&mut self, handle: &Handle<REffect>) {
+        self.0.remove(&handle.id());
+    }
+}
+
+/// Time left before a live effect's spawner is activated.
+#[derive(Component)]
+pub struct StartDelay(Timer);
+
+// Delay new live effects that start inactive but were authored to start active. The delay is the
+// effect's start delay plus the random phase offset, if any.
+fn delay_start(
+    mut commands: Commands,
+    settings: Res<PreviewSettings>,
+    mut random: ResMut<Random>,
+    reffects: Res<Assets<REffect>>,
+    effects: Res<Assets<EffectAsset>>,
+    added: Query<(Entity, &LiveEffect, &ParticleEffect), Added<LiveEffect>>,
+) {
+    for (entity, live_effect, effect) in added.iter() {
+        let (Some(re), Some(asset)) = (reffects.get(&live_effect.0), effects.get(&effect.handle))
+        else {
+            continue;
+        };
+        if asset.spawner.starts_active || !re.spawner.starts_active {
+            continue;
+        }
+
+        let phase = match (settings.phase_offset, re.period()) {
+            (true, Some(period)) => random.0.gen::<f32>() * period,
+            _ => 0.0,
+        };
+        let delay = re.start_delay.max(0.0) + phase;
+        commands
+            .entity(entity)
+            .insert(StartDelay(Timer::from_seconds(delay, TimerMode::Once)));
+    }
+}
+
+fn start_delayed(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut delayed: Query<(Entity, &mut StartDelay, &mut EffectSpawner)>,
+) {
+    for (entity, mut delay, mut spawner) in delayed.iter_mut() {
+        if delay.0.tick(time.delta()).finished() {
+            spawner.set_active(true);
+            commands.entity(entity).remove::<StartDelay>();
+        }
+    }
+}
+
+// Reseed when live effects are spawned or the settings change.
+fn reseed(
+    mut commands: Commands,
+    settings: Res<PreviewSettings>,
+    added: Query<(), Added<LiveEffect>>,
+) {
+    if settings.is_changed() || !added.is_empty() {
+        settings.reseed(&mut commands);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reffect::modifier_fields;
+
+    #[test]
+    fn mutes() {
+        let handle = Handle::<REffect>::weak(HandleId::random::<REffect>());
+        let mut mutes = PreviewMutes::default();
+        mutes.toggle(&handle, "init_velocity");
+        assert!(mutes.is_muted(&handle, "init_velocity"));
+        mutes.toggle(&handle, "init_velocity");
+        assert!(mutes.muted(&handle).is_empty());
+
+        let fields: Vec<_> = modifier_fields()
+            .into_iter()
+            .filter(|f| f.field.starts_with("init_"))
+            .collect();
+        mutes.toggle(&handle, "init_size");
+        mutes.solo(&handle, "init_size", &fields);
+        assert!(!mutes.is_muted(&handle, "init_size"));
+        assert_eq!(mutes.muted(&handle).len(), fields.len() - 1);
+
+        mutes.clear(&handle);
+        assert!(mutes.muted(&handle).is_empty());
+    }
+}
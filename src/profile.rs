@@ -0,0 +1,334 @@
+// Target profiles: what the platforms an effect is made for can do, checked against the effect. The
+// editor runs with whatever the local adapter provides (and forces VERTEX_WRITABLE_STORAGE on), so
+// an effect can preview fine and still break on the target. The limits here are conservative
+// guesses at what each kind of platform guarantees, not what any one device reports. The local
+// adapter's own limit on capacity is in [DeviceLimits].
+
+use bevy::{
+    prelude::*,
+    render::{render_resource::TextureFormat, renderer::RenderDevice},
+};
+use bevy_hanabi::prelude::*;
+
+use crate::reffect::REffect;
+
+/// What an effect is made to run on. Editor metadata; it doesn't change the effect.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect, FromReflect)]
+pub enum TargetProfile {
+    #[default]
+    Desktop,
+    WebGpu,
+    WebGl,
+}
+
+impl TargetProfile {
+    pub const ALL: [Self; 3] = [Self::Desktop, Self::WebGpu, Self::WebGl];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Desktop => "Desktop",
+            Self::WebGpu => "WebGPU",
+            Self::WebGl => "WebGL",
+        }
+    }
+
+    pub fn limits(&self) -> ProfileLimits {
+        match self {
+            Self::Desktop => ProfileLimits {
+                compute: true,
+                vertex_writable_storage: true,
+                max_capacity: 1 << 20,
+                max_texture_size: 16384,
+                compressed: &[Compression::Bc],
+            },
+            Self::WebGpu => ProfileLimits {
+                compute: true,
+                vertex_writable_storage: false,
+                max_capacity: 1 << 18,
+                max_texture_size: 8192,
+                compressed: &[],
+            },
+            Self::WebGl => ProfileLimits {
+                compute: false,
+                vertex_writable_storage: false,
+                max_capacity: 1 << 16,
+                max_texture_size: 2048,
+                compressed: &[],
+            },
+        }
+    }
+}
+
+/// Families of block-compressed texture formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Bc,
+    Etc2,
+    Astc,
+}
+
+impl Compression {
+    pub fn of(format: TextureFormat) -> Option<Self> {
+        use TextureFormat::*;
+        match format {
+            Bc1RgbaUnorm | Bc1RgbaUnormSrgb | Bc2RgbaUnorm | Bc2RgbaUnormSrgb | Bc3RgbaUnorm
+            | Bc3RgbaUnormSrgb | Bc4RUnorm | Bc4RSnorm | Bc5RgUnorm | Bc5RgSnorm
+            | Bc6hRgbUfloat | Bc6hRgbSfloat | Bc7RgbaUnorm | Bc7RgbaUnormSrgb => Some(Self::Bc),
+            Etc2Rgb8Unorm | Etc2Rgb8UnormSrgb | Etc2Rgb8A1Unorm | Etc2Rgb8A1UnormSrgb
+            | Etc2Rgba8Unorm | Etc2Rgba8UnormSrgb | EacR11Unorm | EacR11Snorm | EacRg11Unorm
+            | EacRg11Snorm => Some(Self::Etc2),
+            Astc { .. } => Some(Self::Astc),
+            _ => None,
+        }
+    }
+}
+
+/// What a profile can be relied on to provide.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProfileLimits {
+    /// Compute shaders, which simulate the particles.
+    pub compute: bool,
+    /// Storage buffers written in the vertex stage, which hanabi renders from.
+    pub vertex_writable_storage: bool,
+    pub max_capacity: u32,
+    /// Largest width or height of a 2d texture.
+    pub max_texture_size: u32,
+    /// Compressed texture formats supported everywhere.
+    pub compressed: &'static [Compression],
+}
+
+/// Size and format of the particle texture, if it's loaded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureFacts {
+    pub size: UVec2,
+    pub format: TextureFormat,
+}
+
+/// Ways the effect exceeds the profile.
+pub fn check(re: &REffect, texture: Option<TextureFacts>, profile: TargetProfile) -> Vec<String> {
+    let limits = profile.limits();
+    let name = profile.label();
+    let mut warnings = Vec::new();
+
+    if !limits.compute {
+        warnings.push(format!(
+            "{} has no compute shaders to simulate particles",
+            name
+        ));
+    }
+    if !limits.vertex_writable_storage {
+        warnings.push(format!("{} lacks VERTEX_WRITABLE_STORAGE", name));
+    }
+    if re.capacity > limits.max_capacity {
+        warnings.push(format!(
+            "capacity {} over the {} limit of {}",
+            re.capacity, name, limits.max_capacity
+        ));
+    }
+    if let Some(TextureFacts { size, format }) = texture {
+        if size.max_element() > limits.max_texture_size {
+            warnings.push(format!(
+                "texture {}×{} over the {} limit of {}",
+                size.x, size.y, name, limits.max_texture_size
+            ));
+        }
+        if let Some(compression) = Compression::of(format) {
+            if !limits.compressed.contains(&compression) {
+                warnings.push(format!(
+                    "texture format {:?} isn't supported everywhere on {}",
+                    format, name
+                ));
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Limits of the local adapter, queried at startup. Missing without a renderer.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceLimits {
+    /// Largest storage buffer binding in bytes, which bounds the particle buffer.
+    pub max_storage_buffer: u64,
+}
+
+impl DeviceLimits {
+    /// The most particles of the effect that fit in the particle buffer.
+    pub fn max_capacity(&self, re: &REffect) -> u32 {
+        max_capacity(self.max_storage_buffer, particle_bytes(re))
+    }
+
+    /// An error if the effect's particles don't fit, since hanabi fails to allocate the buffer
+    /// (or hangs) rather than saying so.
+    pub fn check_capacity(&self, re: &REffect) -> Result<(), String> {
+        let max = self.max_capacity(re);
+        match re.capacity > max {
+            true => Err(format!(
+                "capacity {} of {} over this adapter's limit of {} ({} bytes per particle)",
+                re.capacity,
+                re.name,
+                max,
+                particle_bytes(re)
+            )),
+            false => Ok(()),
+        }
+    }
+}
+
+pub fn setup_device_limits(mut commands: Commands, device: Option<Res<RenderDevice>>) {
+    if let Some(device) = device {
+        commands.insert_resource(DeviceLimits {
+            max_storage_buffer: device.limits().max_storage_buffer_binding_size as u64,
+        });
+    }
+}
+
+/// Particle attributes the effect's modifiers use, in the order first used. Hanabi stores these
+/// for each particle.
+pub fn particle_attributes(re: &REffect) -> Vec<Attribute> {
+    let mut attributes: Vec<Attribute> = Vec::new();
+    for (_, modifier) in re.modifiers() {
+        for attribute in modifier.attributes() {
+            if !attributes.iter().any(|a| a.name() == attribute.name()) {
+                attributes.push(*attribute);
+            }
+        }
+    }
+    attributes
+}
+
+/// Bytes per particle, not counting padding between attributes.
+pub fn particle_bytes(re: &REffect) -> u64 {
+    particle_attributes(re)
+        .iter()
+        .map(|a| a.size() as u64)
+        .sum()
+}
+
+/// The most particles of the size that fit in the buffer, at least one.
+pub fn max_capacity(max_buffer: u64, particle_bytes: u64) -> u32 {
+    (max_buffer / particle_bytes.max(1)).clamp(1, u32::MAX as u64) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn effect(capacity: u32) -> REffect {
+        REffect {
+            capacity,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn desktop_passes() {
+        let texture = TextureFacts {
+            size: UVec2::splat(256),
+            format: TextureFormat::Bc7RgbaUnormSrgb,
+        };
+        assert!(check(&effect(4096), Some(texture), TargetProfile::Desktop).is_empty());
+    }
+
+    #[test]
+    fn capacity() {
+        let limit = TargetProfile::WebGpu.limits().max_capacity;
+        let warnings = |capacity| check(&effect(capacity), None, TargetProfile::WebGpu);
+        let over = |w: &Vec<String>| w.iter().any(|w| w.starts_with("capacity"));
+        assert!(!over(&warnings(limit)));
+        assert!(over(&warnings(limit + 1)));
+    }
+
+    #[test]
+    fn textures() {
+        let texture = |size, format| {
+            check(
+                &effect(64),
+                Some(TextureFacts {
+                    size: UVec2::splat(size),
+                    format,
+                }),
+                TargetProfile::WebGl,
+            )
+        };
+        let about_texture = |w: Vec<String>| w.iter().filter(|w| w.starts_with("texture")).count();
+        assert_eq!(
+            about_texture(texture(256, TextureFormat::Rgba8UnormSrgb)),
+            0
+        );
+        assert_eq!(
+            about_texture(texture(4096, TextureFormat::Rgba8UnormSrgb)),
+            1
+        );
+        assert_eq!(
+            about_texture(texture(4096, TextureFormat::Bc3RgbaUnormSrgb)),
+            2
+        );
+    }
+
+    #[test]
+    fn web_features() {
+        let warnings = check(&effect(64), None, TargetProfile::WebGl);
+        assert_eq!(warnings.len(), 2);
+        let warnings = check(&effect(64), None, TargetProfile::WebGpu);
+        assert_eq!(warnings.len(), 1);
+    }
+
+    #[test]
+    fn compression() {
+        assert_eq!(
+            Compression::of(TextureFormat::Bc1RgbaUnorm),
+            Some(Compression::Bc)
+        );
+        assert_eq!(
+            Compression::of(TextureFormat::Etc2Rgba8Unorm),
+            Some(Compression::Etc2)
+        );
+        assert_eq!(Compression::of(TextureFormat::Rgba8Unorm), None);
+    }
+
+    #[test]
+    fn attributes_from_modifiers() {
+        let names = |re: &REffect| -> Vec<&str> {
+            particle_attributes(re).iter().map(|a| a.name()).collect()
+        };
+        let mut re = effect(64);
+        assert_eq!(names(&re), [Attribute::POSITION.name()]);
+        assert_eq!(particle_bytes(&re), 12);
+
+        re.init_velocity = Some(default());
+        re.init_lifetime = Some(InitLifetimeModifier {
+            lifetime: 1.0.into(),
+        });
+        re.render_color_over_lifetime = Some(default());
+        assert_eq!(
+            names(&re),
+            [
+                Attribute::POSITION.name(),
+                Attribute::VELOCITY.name(),
+                Attribute::LIFETIME.name(),
+                Attribute::AGE.name(),
+            ]
+        );
+        assert_eq!(particle_bytes(&re), 12 + 12 + 4 + 4);
+
+        // Used twice, counted once.
+        re.update_linear_drag = Some(LinearDragModifier { drag: 1.0 });
+        assert_eq!(particle_bytes(&re), 32);
+    }
+
+    #[test]
+    fn device_capacity() {
+        assert_eq!(max_capacity(1024, 32), 32);
+        assert_eq!(max_capacity(1024, 0), 1024);
+        assert_eq!(max_capacity(16, 32), 1);
+        assert_eq!(max_capacity(u64::MAX, 4), u32::MAX);
+
+        let limits = DeviceLimits {
+            max_storage_buffer: 12 * 1000,
+        };
+        assert_eq!(limits.max_capacity(&effect(64)), 1000);
+        assert!(limits.check_capacity(&effect(1000)).is_ok());
+        assert!(limits.check_capacity(&effect(1001)).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+// Esc and quitting. Esc cancels whatever is in progress (text edits, popups, dialogs) and only asks
+// to quit when there's nothing else to cancel. Ctrl+Q quits, confirming if there are unsaved
+// changes.
+
+use bevy::{app::AppExit, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    browser::{AssetBrowser, FileStatus},
+    reffect::REffect,
+};
+
+/// Memory id for the quit confirmation.
+pub const QUIT: &str = "quit";
+
+pub struct QuitPlugin;
+
+impl Plugin for QuitPlugin {
+    fn build(&self, app: &mut App) {
+        // Before the UI so text edits still have focus when we check.
+        app.add_system(handle_keys.before(crate::ui::han_ed_ui))
+            .add_system(confirm_quit.after(crate::ui::han_ed_ui));
+    }
+}
+
+/// Ask to quit. The confirmation is shown next frame.
+pub fn request(ctx: &egui::Context) {
+    ctx.memory_mut(|memory| memory.data.insert_temp(egui::Id::new(QUIT), true));
+}
+
+fn is_requested(ctx: &egui::Context) -> bool {
+    ctx.memory_mut(|memory| memory.data.get_temp::<bool>(egui::Id::new(QUIT)))
+        .unwrap_or_default()
+}
+
+fn cancel(ctx: &egui::Context) {
+    ctx.memory_mut(|memory| memory.data.remove::<bool>(egui::Id::new(QUIT)));
+}
+
+// Effects with changes that would be lost.
+fn unsaved(browser: &AssetBrowser<REffect>) -> Vec<String> {
+    browser
+        .entries
+        .iter()
+        .filter(|e| e.dirty || e.status() == FileStatus::Missing)
+        .map(|e| e.path.display().to_string())
+        .collect()
+}
+
+fn handle_keys(
+    mut contexts: EguiContexts,
+    keys: Res<Input<KeyCode>>,
+    browser: Res<AssetBrowser<REffect>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let ctx = contexts.ctx_mut();
+
+    if keys.just_pressed(KeyCode::Escape) {
+        if ctx.wants_keyboard_input() {
+            // The text edit drops focus on its own (see edit_path for restoring paths).
+        } else if ctx.memory(|memory| memory.any_popup_open()) {
+            ctx.memory_mut(|memory| memory.close_popup());
+        } else if is_requested(ctx) {
+            cancel(ctx);
+        } else if cfg!(not(target_arch = "wasm32")) {
+            request(ctx);
+        }
+    }
+
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if ctrl && keys.just_pressed(KeyCode::Q) {
+        if unsaved(&browser).is_empty() {
+            exit.send(AppExit);
+        } else {
+            request(ctx);
+        }
+    }
+}
+
+fn confirm_quit(
+    mut contexts: EguiContexts,
+    browser: Res<AssetBrowser<REffect>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let ctx = contexts.ctx_mut();
+    if !is_requested(ctx) {
+        return;
+    }
+
+    let unsaved = unsaved(&browser);
+    egui::Window::new("Quit")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            if unsaved.is_empty() {
+                ui.label("Quit han-ed?");
+            } else {
+                ui.label(format!(
+                    "{} effect(s) have unsaved changes that will be lost:",
+                    unsaved.len()
+                ));
+                for path in unsaved.iter() {
+                    ui.label(format!("• {}", path));
+                }
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Quit").clicked() {
+                    exit.send(AppExit);
+                }
+                if ui.button("Cancel").clicked() {
+                    cancel(ui.ctx());
+                }
+            });
+        });
+}
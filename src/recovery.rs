@@ -0,0 +1,315 @@
+// Crash recovery. Dirty effects are written to a journal in the platform cache directory every few
+// seconds, keyed by their path under the assets root. On startup, journal files newer than their
+// effects are offered for restoring. Journal files are removed when the effect is saved or the
+// changes are discarded. Writing happens on the IO task pool and failures are only logged, so an
+// unwritable cache directory never gets in the way of editing.
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{futures_lite::future, IoTaskPool, Task},
+    utils::HashMap,
+};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    asset::{deserialize_effect, prepare_save, serialize_effect, texture_asset_path, AssetOpError},
+    browser::AssetBrowser,
+    reffect::{ParticleTexture, REffect},
+    toast::Toasts,
+};
+
+/// Seconds between journal writes. Effects are only rewritten if they changed.
+pub const JOURNAL_INTERVAL: f32 = 2.0;
+
+pub struct RecoveryPlugin;
+
+impl Plugin for RecoveryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Journal>()
+            .add_system(write_journal)
+            .add_system(ui_recovery);
+    }
+}
+
+#[derive(Resource)]
+pub struct Journal {
+    /// None without a cache directory.
+    dir: Option<PathBuf>,
+    since_write: f32,
+    /// Hash of what was last written for each effect, so unchanged effects aren't rewritten.
+    written: HashMap<PathBuf, u64>,
+    task: Option<Task<()>>,
+    /// Effects with journal files newer than the effect, found on startup.
+    pub offers: Vec<PathBuf>,
+}
+
+impl FromWorld for Journal {
+    fn from_world(world: &mut World) -> Self {
+        let root_path = world.resource::<AssetBrowser<REffect>>().root_path.clone();
+        let dir = dirs::cache_dir().map(|cache| journal_dir(&cache, &root_path));
+        let offers = dir
+            .as_deref()
+            .map(|dir| find_offers(dir, &root_path))
+            .unwrap_or_default();
+        if !offers.is_empty() {
+            info!("{} effects have unsaved changes to recover", offers.len());
+        }
+        Self {
+            dir,
+            since_write: 0.0,
+            written: HashMap::default(),
+            task: None,
+            offers,
+        }
+    }
+}
+
+/// The journal for an assets root: a folder under the cache directory named for the root.
+pub fn journal_dir(cache_dir: &Path, root_path: &Path) -> PathBuf {
+    let name: String = root_path
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    cache_dir.join("han-ed").join("recovery").join(name)
+}
+
+/// The journal file for an effect. None without a cache directory.
+pub fn journal_file(root_path: &Path, path: &Path) -> Option<PathBuf> {
+    Some(journal_dir(&dirs::cache_dir()?, root_path).join(path))
+}
+
+/// Effect paths (relative to the root) with journal files newer than the effect, or whose effect is
+/// gone. Older journal files are stale and removed.
+pub fn find_offers(dir: &Path, root_path: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    walk(dir, &mut files);
+
+    let mut offers = Vec::new();
+    for file in files {
+        let Ok(path) = file.strip_prefix(dir) else {
+            continue;
+        };
+        let modified = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+        match (modified(&file), modified(&root_path.join(path))) {
+            (Some(journal), Some(effect)) if journal <= effect => {
+                _ = std::fs::remove_file(&file);
+            }
+            _ => offers.push(path.to_path_buf()),
+        }
+    }
+    offers.sort();
+    offers
+}
+
+fn walk(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            walk(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+// Write changed dirty effects to the journal. A write still in progress skips this round rather
+// than waiting.
+fn write_journal(
+    time: Res<Time>,
+    browser: Res<AssetBrowser<REffect>>,
+    reffects: Res<Assets<REffect>>,
+    asset_server: Res<AssetServer>,
+    type_registry: Res<AppTypeRegistry>,
+    mut journal: ResMut<Journal>,
+) {
+    journal.since_write += time.delta_seconds();
+    if journal.since_write < JOURNAL_INTERVAL {
+        return;
+    }
+    if let Some(task) = journal.task.as_mut() {
+        if future::block_on(future::poll_once(task)).is_none() {
+            return;
+        }
+        journal.task = None;
+    }
+    journal.since_write = 0.0;
+
+    let Some(dir) = journal.dir.clone() else {
+        return;
+    };
+
+    // Forget saved effects, so they're written again once changed.
+    journal
+        .written
+        .retain(|path, _| browser.entry(path).map_or(false, |e| e.dirty));
+
+    let mut writes = Vec::new();
+    for entry in browser.entries.iter().filter(|e| e.dirty) {
+        let Some(re) = entry.handle.as_ref().and_then(|h| reffects.get(h)) else {
+            continue;
+        };
+        let mut re = re.clone();
+        let ron = prepare_save(&mut re, &asset_server)
+            .and_then(|_| serialize_effect(&re, &type_registry.read()));
+        let ron = match ron {
+            Ok(ron) => ron,
+            Err(e) => {
+                warn!("failed to journal {}: {}", entry.path.display(), e);
+                continue;
+            }
+        };
+
+        let mut hasher = DefaultHasher::new();
+        ron.hash(&mut hasher);
+        let hash = hasher.finish();
+        if journal.written.get(&entry.path) != Some(&hash) {
+            journal.written.insert(entry.path.clone(), hash);
+            writes.push((dir.join(&entry.path), ron));
+        }
+    }
+
+    if writes.is_empty() {
+        return;
+    }
+    journal.task = Some(IoTaskPool::get().spawn(async move {
+        for (file, ron) in writes {
+            let write = || -> std::io::Result<()> {
+                if let Some(parent) = file.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&file, ron)
+            };
+            if let Err(e) = write() {
+                warn!("failed to write {}: {}", file.display(), e);
+            }
+        }
+    }));
+}
+
+/// Read a journal file (or backup) as an unsaved effect, loading its texture.
+pub fn restore(
+    file: &Path,
+    path: &Path,
+    asset_server: &AssetServer,
+    type_registry: &AppTypeRegistry,
+) -> Result<REffect, AssetOpError> {
+    let bytes = std::fs::read(file)?;
+    let mut re = deserialize_effect(&bytes, &type_registry.read(), path)?;
+    if let ParticleTexture::Path(texture) = &re.render_particle_texture {
+        re.render_particle_texture = match texture_asset_path(texture) {
+            Ok(asset_path) => ParticleTexture::Texture(asset_server.load(asset_path)),
+            Err(_) => ParticleTexture::Missing(texture.clone()),
+        };
+    }
+    Ok(re)
+}
+
+// Ask about each effect with changes to recover. Restored effects replace the browser's entry and
+// are marked dirty, so the changes still need saving.
+#[allow(clippy::too_many_arguments)]
+fn ui_recovery(
+    mut contexts: EguiContexts,
+    mut journal: ResMut<Journal>,
+    mut browser: ResMut<AssetBrowser<REffect>>,
+    mut reffects: ResMut<Assets<REffect>>,
+    asset_server: Res<AssetServer>,
+    type_registry: Res<AppTypeRegistry>,
+    toasts: Res<Toasts>,
+) {
+    if journal.offers.is_empty() {
+        return;
+    }
+    let Some(dir) = journal.dir.clone() else {
+        return;
+    };
+
+    let mut answered = Vec::new();
+    egui::Window::new("Restore unsaved changes?")
+        .collapsible(false)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label("The editor closed before these effects were saved.");
+            egui::Grid::new("recovery").show(ui, |ui| {
+                for path in journal.offers.iter() {
+                    let file = dir.join(path);
+                    ui.label(path.display().to_string());
+                    if ui.button("Restore").clicked() {
+                        match restore(&file, path, &asset_server, &type_registry) {
+                            Ok(re) => {
+                                let handle = reffects.add(re);
+                                browser.insert(path.clone(), handle);
+                                browser.mark_dirty(path);
+                                toasts.info(format!("restored {}", path.display()));
+                            }
+                            Err(e) => {
+                                e.toast(format!("failed to restore {}", path.display()), &toasts)
+                            }
+                        }
+                        answered.push(path.clone());
+                    }
+                    if ui.button("Discard").clicked() {
+                        _ = std::fs::remove_file(&file);
+                        answered.push(path.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+
+    journal.offers.retain(|path| !answered.contains(path));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+
+    #[test]
+    fn journal_keyed_by_root() {
+        let cache = Path::new("cache");
+        let a = journal_dir(cache, Path::new("/games/a/assets"));
+        let b = journal_dir(cache, Path::new("/games/b/assets"));
+        assert_ne!(a, b);
+        assert!(a.starts_with("cache/han-ed/recovery"));
+        assert_eq!(a.components().count(), 4);
+    }
+
+    #[test]
+    fn offers_newer_only() {
+        let root = tempfile::tempdir().unwrap();
+        let journal = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        let touch = |dir: &Path, path: &str, age: u64| {
+            let file = dir.join(path);
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::File::create(&file)
+                .unwrap()
+                .set_modified(now - Duration::from_secs(age))
+                .unwrap();
+        };
+
+        // Edited after the last save.
+        touch(root.path(), "fx/newer.han", 60);
+        touch(journal.path(), "fx/newer.han", 10);
+        // Saved after the last edit.
+        touch(root.path(), "older.han", 10);
+        touch(journal.path(), "older.han", 60);
+        // Never saved.
+        touch(journal.path(), "new.han", 10);
+
+        let offers = find_offers(journal.path(), root.path());
+        assert_eq!(offers, [PathBuf::from("fx/newer.han"), "new.han".into()]);
+        assert!(!journal.path().join("older.han").exists());
+    }
+}
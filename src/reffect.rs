@@ -1,8 +1,59 @@
 use bevy::{prelude::*, reflect::TypeUuid};
 //use bevy::reflect::*;
-use crate::gradient::{ColorGradient, SizeGradient};
+use crate::{
+    asset::{solid_texture, PLACEHOLDER_TEXTURE},
+    gradient::{ColorGradient, SizeGradient},
+    profile::TargetProfile,
+};
 use bevy_hanabi::prelude::*;
 
+mod optional;
+pub use optional::*;
+
+/// Registers the types in [REffect] that hanabi doesn't, for (de)serializing effects.
+pub struct ReflectTypesPlugin;
+
+impl Plugin for ReflectTypesPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<InitPosition>()
+            .register_type::<InitVelocity>()
+            .register_type::<Option<InitVelocity>>()
+            .register_type::<UpdateAccel>()
+            .register_type::<ColorGradient>()
+            .register_type::<Option<ColorGradient>>()
+            .register_type::<Vec<(f32, Vec4)>>()
+            .register_type::<(f32, Vec4)>()
+            .register_type::<SizeGradient>()
+            .register_type::<Option<SizeGradient>>()
+            .register_type::<Vec<(f32, Vec2)>>()
+            .register_type::<(f32, Vec2)>()
+            .register_type::<ParticleTexture>()
+            .register_type::<InitAttribute>()
+            .register_type::<AttributeValue>()
+            .register_type::<Vec<InitAttribute>>()
+            .register_type::<EffectProperty>()
+            .register_type::<Vec<EffectProperty>>()
+            .register_type::<Option<UpdateAccel>>()
+            .register_type::<Vec<UpdateAccel>>()
+            .register_type::<RenderModifierKind>()
+            .register_type::<Vec<RenderModifierKind>>()
+            .register_type::<Orientation>()
+            .register_type::<OldOrientAlongVelocity>()
+            .register_type::<Option<OldOrientAlongVelocity>>()
+            .register_type::<EffectBounds>()
+            .register_type::<Option<EffectBounds>>()
+            .register_type::<TargetProfile>();
+    }
+}
+
+/// Headroom over the steady state count in [REffect::suggested_capacity], since spawn counts and
+/// lifetimes vary around the mean.
+pub const CAPACITY_HEADROOM: f32 = 1.25;
+
+/// Most spawn windows in a span. Shorter periods are less than a pixel apart on a timeline, so
+/// they're treated as spawning continuously. See [SpawnCycle::windows].
+pub const MAX_SPAWN_WINDOWS: usize = 256;
+
 // This is all to get around the fact that EffectAsset cannot be serialized.
 #[derive(Default, Clone, TypeUuid, Reflect, FromReflect)]
 #[uuid = "2933798f-a750-44c4-b7f9-0b7055368944"]
@@ -10,11 +61,25 @@ pub struct REffect {
     pub name: String,
     pub capacity: u32,
     pub spawner: Spawner,
+    /// Seconds before the spawner starts. Hanabi has no delay, so the spawner starts inactive and
+    /// the editor activates it later. See [crate::preview].
+    #[reflect(default)]
+    pub start_delay: f32,
     pub z_layer_2d: f32,
     pub simulation_space: SimulationSpace,
     pub simulation_condition: SimulationCondition,
+    /// Bounds of the live effect for culling, relative to the effect. Without them the effect is
+    /// never culled. See [REffect::computed_bounds].
+    #[reflect(default)]
+    pub bounds: Option<EffectBounds>,
+    /// What the effect is checked against. See [crate::profile].
+    #[reflect(default)]
+    pub target: TargetProfile,
 
-    // skip properties for now...
+    /// Properties declared with their default values. These can be changed at runtime without
+    /// regenerating the effect.
+    #[reflect(default)]
+    pub properties: Vec<EffectProperty>,
     // skip motion_integration
 
     // InitModifier(s)
@@ -25,12 +90,21 @@ pub struct REffect {
     pub init_age: Option<InitAgeModifier>,
     // So this is required unless lifetime is a property? Or InitAttributeModifier.
     pub init_lifetime: Option<InitLifetimeModifier>,
-    //pub init_attributes: Vec<InitAttributeModifier>,
+    #[reflect(default)]
+    pub init_attributes: Vec<InitAttribute>,
 
     // UpdateModifiers(s)
+    /// Old single acceleration, only read from old files. See [REffect::migrate].
+    #[reflect(default, skip_serializing)]
     pub update_accel: Option<UpdateAccel>,
+    #[reflect(default)]
+    pub update_accels: Vec<UpdateAccel>,
+    #[cfg(feature = "force-field")]
+    #[reflect(default)]
     pub update_force_field: Vec<ForceFieldSource>,
     pub update_linear_drag: Option<LinearDragModifier>,
+    #[cfg(feature = "aabb-kill")]
+    #[reflect(default)]
     pub update_aabb_kill: Option<AabbKillModifier>,
 
     // RenderModifier(s)
@@ -39,10 +113,121 @@ pub struct REffect {
     pub render_color_over_lifetime: Option<ColorGradient>,
     pub render_set_size: Option<SetSizeModifier>,
     pub render_size_over_lifetime: Option<SizeGradient>,
+    #[reflect(default)]
+    pub render_orientation: Orientation,
+    /// Old orientation fields, only read from old files. See [REffect::migrate].
+    #[reflect(default, skip_serializing)]
     pub render_billboard: bool,
-    pub render_orient_along_velocity: Option<OrientAlongVelocityModifier>,
+    #[reflect(default, skip_serializing)]
+    pub render_orient_along_velocity: Option<OldOrientAlongVelocity>,
+    /// Order render modifiers are applied in. Missing kinds follow in the default order, so this
+    /// is empty until something is reordered.
+    #[reflect(default)]
+    pub render_order: Vec<RenderModifierKind>,
+
+    /// Fields this build doesn't know, e.g. optional modifiers that are compiled out, as (name,
+    /// RON value). They're written back as-is when saving.
+    #[reflect(ignore)]
+    pub passthrough: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect, FromReflect)]
+pub enum RenderModifierKind {
+    ParticleTexture,
+    SetColor,
+    ColorOverLifetime,
+    SetSize,
+    SizeOverLifetime,
+    Orientation,
+    /// Old kinds, only read from old files. See [REffect::migrate].
+    Billboard,
+    OrientAlongVelocity,
+}
+
+impl RenderModifierKind {
+    /// The order render modifiers were always applied in.
+    pub const DEFAULT_ORDER: [Self; 6] = [
+        Self::ParticleTexture,
+        Self::SetColor,
+        Self::ColorOverLifetime,
+        Self::SetSize,
+        Self::SizeOverLifetime,
+        Self::Orientation,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::ParticleTexture => "Particle Texture",
+            Self::SetColor => "Set Color",
+            Self::ColorOverLifetime => "Color Over Lifetime",
+            Self::SetSize => "Set Size",
+            Self::SizeOverLifetime => "Size Over Lifetime",
+            Self::Orientation => "Orientation",
+            Self::Billboard => "Billboard",
+            Self::OrientAlongVelocity => "Orient Along Velocity",
+        }
+    }
+}
+
+/// How particle quads are oriented. Hanabi 0.6 has a modifier for each but [Self::FaceCamera],
+/// which falls back to a billboard. With the `orient-modifier` feature they're the modes of
+/// hanabi's `OrientModifier`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect, FromReflect)]
+pub enum Orientation {
+    /// Quads keep facing along the effect's Z axis.
+    #[default]
+    None,
+    /// Quads are parallel to the camera's depth plane.
+    Billboard,
+    /// Quads face the camera's position, turning toward it away from the center of the view.
+    FaceCamera,
+    /// Quads are stretched along the particle's velocity, e.g. for sparks or rain.
+    AlongVelocity,
+}
+
+impl Orientation {
+    pub const ALL: [Self; 4] = [
+        Self::None,
+        Self::Billboard,
+        Self::FaceCamera,
+        Self::AlongVelocity,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::None => "None",
+            Self::Billboard => "Billboard",
+            Self::FaceCamera => "Face Camera",
+            Self::AlongVelocity => "Along Velocity",
+        }
+    }
+
+    #[cfg(feature = "orient-modifier")]
+    fn modifiers(&self) -> Vec<Box<dyn Modifier>> {
+        let mode = match self {
+            Self::None => None,
+            Self::Billboard => Some(OrientMode::ParallelCameraDepthPlane),
+            Self::FaceCamera => Some(OrientMode::FaceCameraPosition),
+            Self::AlongVelocity => Some(OrientMode::AlongVelocity),
+        };
+        boxed(mode.map(|mode| OrientModifier { mode }).as_ref())
+    }
+
+    #[cfg(not(feature = "orient-modifier"))]
+    fn modifiers(&self) -> Vec<Box<dyn Modifier>> {
+        match self {
+            Self::None => Vec::new(),
+            Self::Billboard | Self::FaceCamera => boxed(Some(&BillboardModifier)),
+            Self::AlongVelocity => boxed(Some(&OrientAlongVelocityModifier::default())),
+        }
+    }
 }
 
+/// Stands in for hanabi's `OrientAlongVelocityModifier` (which has no fields) when reading old
+/// files, so they load whichever orient API is compiled in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Reflect, FromReflect)]
+pub struct OldOrientAlongVelocity;
+
 #[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect)]
 pub enum InitPosition {
     Circle(InitPositionCircleModifier),
@@ -77,6 +262,179 @@ impl Default for InitVelocity {
     }
 }
 
+/// Mirror of [InitAttributeModifier], which doesn't deserialize via reflection. The attribute is
+/// stored by name.
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect)]
+pub struct InitAttribute {
+    pub attribute: String,
+    pub value: AttributeValue,
+}
+
+impl InitAttribute {
+    /// Attribute with its default value.
+    pub fn new(attribute: Attribute) -> Self {
+        Self {
+            attribute: attribute.name().to_string(),
+            value: AttributeValue::from(attribute.default_value()),
+        }
+    }
+}
+
+impl Default for InitAttribute {
+    fn default() -> Self {
+        Self::new(Attribute::AGE)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Reflect, FromReflect)]
+pub struct EffectProperty {
+    pub name: String,
+    pub default_value: AttributeValue,
+}
+
+/// Constant value for attributes and properties.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect)]
+pub enum AttributeValue {
+    Float(f32),
+    Float2(Vec2),
+    Float3(Vec3),
+    Float4(Vec4),
+    Uint(u32),
+}
+
+impl AttributeValue {
+    /// Zero value of each type, for adding properties.
+    pub const TYPES: [Self; 5] = [
+        Self::Float(0.0),
+        Self::Float2(Vec2::ZERO),
+        Self::Float3(Vec3::ZERO),
+        Self::Float4(Vec4::ZERO),
+        Self::Uint(0),
+    ];
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Float(_) => "f32",
+            Self::Float2(_) => "vec2",
+            Self::Float3(_) => "vec3",
+            Self::Float4(_) => "vec4",
+            Self::Uint(_) => "u32",
+        }
+    }
+}
+
+impl From<graph::Value> for AttributeValue {
+    fn from(value: graph::Value) -> Self {
+        match value {
+            graph::Value::Float2(v) => Self::Float2(v),
+            graph::Value::Float3(v) => Self::Float3(v),
+            graph::Value::Float4(v) => Self::Float4(v),
+            graph::Value::Uint(v) => Self::Uint(v),
+            graph::Value::Float(v) => Self::Float(v),
+            _ => Self::Float(0.0),
+        }
+    }
+}
+
+impl From<AttributeValue> for graph::Value {
+    fn from(value: AttributeValue) -> Self {
+        match value {
+            AttributeValue::Float(v) => graph::Value::Float(v),
+            AttributeValue::Float2(v) => graph::Value::Float2(v),
+            AttributeValue::Float3(v) => graph::Value::Float3(v),
+            AttributeValue::Float4(v) => graph::Value::Float4(v),
+            AttributeValue::Uint(v) => graph::Value::Uint(v),
+        }
+    }
+}
+
+/// One cycle of a spawner: particles spawn over the first `spawn_time` seconds of each period.
+/// Uniform values are taken at their mean, except the spawn time, which is the longest.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpawnCycle {
+    pub spawn_time: f32,
+    /// None if the spawner only spawns once.
+    pub period: Option<f32>,
+    /// Particles spawned per cycle.
+    pub count: f32,
+}
+
+impl SpawnCycle {
+    pub fn of(spawner: &Spawner) -> Self {
+        Self {
+            spawn_time: value_max(&spawner.spawn_time).max(0.0),
+            period: value_min(&spawner.period)
+                .is_finite()
+                .then(|| value_mean(&spawner.period).max(0.0)),
+            count: value_mean(&spawner.num_particles),
+        }
+    }
+
+    /// The spans spawning within the first `span` seconds, as (start, end). A span with no
+    /// length is a burst. Spawning stops at the end of each period, even if the spawn time is
+    /// longer. A zero period, or one too short to tell apart (see [MAX_SPAWN_WINDOWS]), spawns
+    /// continuously.
+    pub fn windows(&self, span: f32) -> Vec<(f32, f32)> {
+        match self.period {
+            None => vec![(0.0, self.spawn_time.min(span))],
+            Some(period) if period <= 0.0 || span / period > MAX_SPAWN_WINDOWS as f32 => {
+                vec![(0.0, span)]
+            }
+            Some(period) => (0..)
+                .map(|i| i as f32 * period)
+                .take_while(|start| *start < span)
+                .map(|start| (start, (start + self.spawn_time.min(period)).min(span)))
+                .collect(),
+        }
+    }
+}
+
+/// An axis-aligned box, relative to the effect's transform.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, FromReflect)]
+pub struct EffectBounds {
+    pub center: Vec3,
+    pub half_extents: Vec3,
+}
+
+impl Default for EffectBounds {
+    fn default() -> Self {
+        Self {
+            center: Vec3::ZERO,
+            half_extents: Vec3::ONE,
+        }
+    }
+}
+
+impl EffectBounds {
+    pub fn from_min_max(min: Vec3, max: Vec3) -> Self {
+        Self {
+            center: (min + max) / 2.0,
+            half_extents: (max - min) / 2.0,
+        }
+    }
+
+    pub fn min(&self) -> Vec3 {
+        self.center - self.half_extents
+    }
+
+    pub fn max(&self) -> Vec3 {
+        self.center + self.half_extents
+    }
+
+    /// The eight corners, as signs of the half extents in x, y, z order: corner `i` has bit 0 set
+    /// for +x, bit 1 for +y and bit 2 for +z.
+    pub fn corners(&self) -> [Vec3; 8] {
+        std::array::from_fn(|i| {
+            let sign = Vec3::new(
+                if i & 1 == 0 { -1.0 } else { 1.0 },
+                if i & 2 == 0 { -1.0 } else { 1.0 },
+                if i & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            self.center + self.half_extents * sign
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Reflect, FromReflect)]
 pub enum UpdateAccel {
     Linear(AccelModifier),
@@ -92,9 +450,16 @@ pub enum UpdateAccel {
 pub enum ParticleTexture {
     #[default]
     None,
-    // RelativePathBuf does not impl Reflect.
+    // RelativePathBuf does not impl Reflect. This is the serialized asset path, with the label
+    // for sub-assets, e.g. "scene.gltf#Texture0".
     Path(String),
     Texture(Handle<Image>),
+    /// The texture failed to load. This keeps the original path for saving while the placeholder
+    /// texture is rendered.
+    Missing(String),
+    /// A solid color (linear, unmultiplied), rendered with a generated 1×1 texture. See
+    /// [solid_texture](crate::asset::solid_texture).
+    Solid(Vec4),
 }
 
 impl From<Handle<Image>> for ParticleTexture {
@@ -116,6 +481,7 @@ impl ParticleTexture {
                 None
             }
             ParticleTexture::Texture(handle) => Some(handle),
+            ParticleTexture::Missing(_) | ParticleTexture::Solid(_) => None,
         }
     }
 }
@@ -127,104 +493,1360 @@ impl Default for UpdateAccel {
 }
 
 impl REffect {
-    // We need to asset server to load the texture.
-    pub fn to_effect_asset(&self, _asset_server: &AssetServer) -> EffectAsset {
-        let mut effect = EffectAsset {
-            name: self.name.clone(),
-            capacity: self.capacity,
-            spawner: self.spawner,
-            z_layer_2d: self.z_layer_2d,
-            modifiers: vec![match self.init_position {
-                InitPosition::Circle(m) => m.boxed_clone(),
-                InitPosition::Sphere(m) => m.boxed_clone(),
-                InitPosition::Cone(m) => m.boxed_clone(),
-            }],
-            simulation_space: self.simulation_space,
-            simulation_condition: self.simulation_condition,
+    /// Bring effects deserialized from older files up to date. Called by the loader.
+    pub fn migrate(&mut self) {
+        // A single optional acceleration became a list.
+        if let Some(accel) = self.update_accel.take() {
+            self.update_accels.insert(0, accel);
+        }
 
-            ..default()
+        // Billboard and orient along velocity became one orientation. Orient along velocity won
+        // if both were set. The kind it replaces takes its place in the render order.
+        let old = if self.render_orient_along_velocity.take().is_some() {
+            Some(RenderModifierKind::OrientAlongVelocity)
+        } else if std::mem::take(&mut self.render_billboard) {
+            Some(RenderModifierKind::Billboard)
+        } else {
+            None
         };
-
-        if let Some(m) = self.init_velocity.as_ref() {
-            match m {
-                InitVelocity::Circle(m) => effect = effect.init(m.clone()),
-                InitVelocity::Sphere(m) => effect = effect.init(m.clone()),
-                InitVelocity::Cone(m) => effect = effect.init(m.clone()),
+        if let Some(kind) = old {
+            if self.render_orientation == Orientation::None {
+                self.render_orientation = match kind {
+                    RenderModifierKind::Billboard => Orientation::Billboard,
+                    _ => Orientation::AlongVelocity,
+                };
+            }
+        }
+        let mut order = Vec::new();
+        for kind in self.render_order.drain(..) {
+            let kind = match kind {
+                RenderModifierKind::Billboard | RenderModifierKind::OrientAlongVelocity
+                    if old == Some(kind) =>
+                {
+                    RenderModifierKind::Orientation
+                }
+                RenderModifierKind::Billboard | RenderModifierKind::OrientAlongVelocity => continue,
+                kind => kind,
             };
+            if !order.contains(&kind) {
+                order.push(kind);
+            }
+        }
+        self.render_order = order;
+    }
+
+    /// Repair invalid values from hand-edited files. Gradients are also repaired when constructed
+    /// via reflection. Returns a warning for each repair.
+    pub fn fix_up(&mut self) -> Vec<String> {
+        let color = self
+            .render_color_over_lifetime
+            .as_mut()
+            .and_then(|g| g.fix_up())
+            .map(|w| format!("Color Over Lifetime: {}", w));
+        let size = self
+            .render_size_over_lifetime
+            .as_mut()
+            .and_then(|g| g.fix_up())
+            .map(|w| format!("Size Over Lifetime: {}", w));
+        color.into_iter().chain(size).collect()
+    }
+
+    /// Number of enabled init modifiers. Position is always set.
+    pub fn init_count(&self) -> usize {
+        1 + [
+            self.init_velocity.is_some(),
+            self.init_size.is_some(),
+            self.init_age.is_some(),
+            self.init_lifetime.is_some(),
+        ]
+        .into_iter()
+        .filter(|b| *b)
+        .count()
+            + self.init_attributes.len()
+    }
+
+    /// Lifetime is set, either by the lifetime modifier or an attribute.
+    pub fn has_lifetime(&self) -> bool {
+        self.init_lifetime.is_some()
+            || self
+                .init_attributes
+                .iter()
+                .any(|a| a.attribute == Attribute::LIFETIME.name())
+    }
+
+    /// Number of enabled update modifiers.
+    pub fn update_count(&self) -> usize {
+        #[allow(unused_mut)]
+        let mut count = self.update_accels.len() + usize::from(self.update_linear_drag.is_some());
+        #[cfg(feature = "force-field")]
+        {
+            count += ForceField::count(self);
+        }
+        #[cfg(feature = "aabb-kill")]
+        {
+            count += AabbKill::count(self);
         }
+        count
+    }
 
-        if let Some(m) = self.init_size.as_ref() {
-            if matches!(m.size, DimValue::D2(_)) {
-                effect = effect.init(InitAttributeModifier {
-                    attribute: Attribute::SIZE2,
-                    value: ValueOrProperty::Value(Vec2::new(1.0, 1.0).into()),
-                });
+    /// Number of enabled render modifiers.
+    pub fn render_count(&self) -> usize {
+        RenderModifierKind::DEFAULT_ORDER
+            .into_iter()
+            .filter(|kind| self.has_render(*kind))
+            .count()
+    }
+
+    /// Whether the render modifier is set.
+    pub fn has_render(&self, kind: RenderModifierKind) -> bool {
+        match kind {
+            RenderModifierKind::ParticleTexture => {
+                self.render_particle_texture != ParticleTexture::None
             }
-            effect = effect.init(m.clone());
+            RenderModifierKind::SetColor => self.render_set_color.is_some(),
+            RenderModifierKind::ColorOverLifetime => self.render_color_over_lifetime.is_some(),
+            RenderModifierKind::SetSize => self.render_set_size.is_some(),
+            RenderModifierKind::SizeOverLifetime => self.render_size_over_lifetime.is_some(),
+            RenderModifierKind::Orientation => self.render_orientation != Orientation::None,
+            // Migrated on load.
+            RenderModifierKind::Billboard | RenderModifierKind::OrientAlongVelocity => false,
         }
+    }
 
-        if let Some(m) = self.init_age.as_ref() {
-            effect = effect.init(m.clone());
+    /// The full render modifier order: the stored order followed by any missing kinds in the
+    /// default order.
+    pub fn render_order(&self) -> Vec<RenderModifierKind> {
+        let mut order = self.render_order.clone();
+        order.dedup();
+        for kind in RenderModifierKind::DEFAULT_ORDER {
+            if !order.contains(&kind) {
+                order.push(kind);
+            }
         }
+        order
+    }
 
-        if let Some(m) = self.init_lifetime.as_ref() {
-            effect = effect.init(m.clone());
+    /// Longest time a particle is alive, after the initial age. None without a lifetime.
+    pub fn max_remaining_lifetime(&self) -> Option<f32> {
+        let lifetime = match self.init_lifetime.as_ref() {
+            Some(m) => value_max(&m.lifetime),
+            None => self.init_attributes.iter().find_map(|a| match a.value {
+                AttributeValue::Float(v) if a.attribute == Attribute::LIFETIME.name() => Some(v),
+                _ => None,
+            })?,
+        };
+        // Particles can start partway through their lifetime.
+        let age = self
+            .init_age
+            .as_ref()
+            .map(|m| value_min(&m.age))
+            .unwrap_or_default();
+        Some((lifetime - age).max(0.0))
+    }
+
+    /// Average time a particle is alive, after the initial age.
+    pub fn mean_remaining_lifetime(&self) -> Option<f32> {
+        let lifetime = match self.init_lifetime.as_ref() {
+            Some(m) => value_mean(&m.lifetime),
+            None => self.max_remaining_lifetime()?,
+        };
+        let age = self
+            .init_age
+            .as_ref()
+            .map(|m| value_mean(&m.age))
+            .unwrap_or_default();
+        Some((lifetime - age).max(0.0))
+    }
+
+    /// Whether the spawner only spawns once (the period is infinite).
+    pub fn is_once(&self) -> bool {
+        !value_min(&self.spawner.period).is_finite()
+    }
+
+    /// Total visual duration of a spawn-once effect: the start delay and spawn time plus the longest
+    /// lifetime.
+    /// None for repeating spawners or without a lifetime.
+    pub fn duration(&self) -> Option<f32> {
+        let cycle = SpawnCycle::of(&self.spawner);
+        if cycle.period.is_some() {
+            return None;
         }
+        Some(self.start_delay + cycle.spawn_time + self.max_remaining_lifetime()?)
+    }
 
-        if let Some(m) = self.update_accel.as_ref() {
-            match m {
-                UpdateAccel::Linear(m) => effect = effect.update(m.clone()),
-                UpdateAccel::Radial(m) => effect = effect.update(m.clone()),
-                UpdateAccel::Tangent(m) => effect = effect.update(m.clone()),
-            };
+    /// Average period of a repeating spawner. None for spawn-once effects.
+    pub fn period(&self) -> Option<f32> {
+        let period = value_mean(&self.spawner.period);
+        (!self.is_once() && period > 0.0).then_some(period)
+    }
+
+    /// Estimated number of particles alive once a repeating spawner has settled: the spawn rate
+    /// times the average lifetime. None for spawn-once effects or without a lifetime.
+    pub fn steady_state_count(&self) -> Option<f32> {
+        let period = value_mean(&self.spawner.period);
+        if self.is_once() || period <= 0.0 {
+            return None;
         }
+        let rate = value_mean(&self.spawner.num_particles) / period;
+        Some(rate * self.mean_remaining_lifetime()?)
+    }
+
+    /// A capacity that fits the particles alive at once: the largest burst for spawn-once
+    /// effects, or the [steady state](REffect::steady_state_count) plus headroom for repeating
+    /// ones. None for repeating spawners without a lifetime.
+    pub fn suggested_capacity(&self) -> Option<u32> {
+        let count = if self.is_once() {
+            value_max(&self.spawner.num_particles)
+        } else {
+            self.steady_state_count()? * CAPACITY_HEADROOM
+        };
+        Some((count.ceil() as u32).max(1))
+    }
+
+    /// Fields changed by [REffect::scale].
+    pub const SCALED_FIELDS: &'static [&'static str] = &[
+        "Position: center, radius, base/top radius, height",
+        "Velocity: center/origin, speed",
+        "Size",
+        "Acceleration: linear accel, radial/tangent origin and accel",
+        "Force Field: position, min/max radius",
+        "AABB Kill: center, half size",
+        "Set Size",
+        "Size Over Lifetime",
+    ];
 
-        if !self.update_force_field.is_empty() {
-            effect = effect.update(ForceFieldModifier::new(
-                self.update_force_field.iter().cloned(),
-            ));
+    /// Scale all spatial values by the factor, e.g. to make an effect authored in meters work at
+    /// a different scale. Velocities and accelerations scale linearly. Times, colors and drag are
+    /// unchanged.
+    pub fn scale(&mut self, factor: f32) {
+        match &mut self.init_position {
+            InitPosition::Circle(m) => {
+                m.center *= factor;
+                m.radius *= factor;
+            }
+            InitPosition::Sphere(m) => {
+                m.center *= factor;
+                m.radius *= factor;
+            }
+            InitPosition::Cone(m) => {
+                m.base_radius *= factor;
+                m.top_radius *= factor;
+                m.height *= factor;
+            }
         }
 
-        if let Some(m) = self.update_linear_drag.as_ref() {
-            effect = effect.update(m.clone());
+        match &mut self.init_velocity {
+            Some(InitVelocity::Circle(m)) => {
+                m.center *= factor;
+                scale_value(&mut m.speed, factor);
+            }
+            Some(InitVelocity::Sphere(m)) => {
+                m.center *= factor;
+                scale_value(&mut m.speed, factor);
+            }
+            Some(InitVelocity::Cone(m)) => {
+                m.origin *= factor;
+                scale_value(&mut m.speed, factor);
+            }
+            None => (),
         }
 
-        if let Some(m) = self.update_aabb_kill.as_ref() {
-            effect = effect.update(m.clone());
+        if let Some(m) = self.init_size.as_mut() {
+            scale_dim_value(&mut m.size, factor);
         }
 
-        match self.render_particle_texture {
-            ParticleTexture::Path(ref path) => {
-                // This should never happen since the texture is loaded when the asset is loaded.
-                error!("particle texture not loaded: {}", path)
+        for accel in self.update_accels.iter_mut() {
+            match accel {
+                UpdateAccel::Linear(m) => scale_value_or_property(&mut m.accel, factor),
+                UpdateAccel::Radial(m) => {
+                    m.origin *= factor;
+                    scale_value_or_property(&mut m.accel, factor);
+                }
+                UpdateAccel::Tangent(m) => {
+                    m.origin *= factor;
+                    scale_value_or_property(&mut m.accel, factor);
+                }
             }
-            ParticleTexture::Texture(ref handle) => {
-                effect = effect.render(ParticleTextureModifier {
-                    texture: handle.clone(),
-                });
+        }
+
+        #[cfg(feature = "force-field")]
+        ForceField::scale(self, factor);
+        #[cfg(feature = "aabb-kill")]
+        AabbKill::scale(self, factor);
+
+        if let Some(m) = self.render_set_size.as_mut() {
+            scale_dim_value(&mut m.size, factor);
+        }
+
+        if let Some(g) = self.render_size_over_lifetime.as_mut() {
+            g.scale(factor);
+        }
+    }
+
+    /// Fields changed by [REffect::reorient].
+    pub const REORIENTED_FIELDS: &'static [&'static str] = &[
+        "Position: circle center and axis, sphere center",
+        "Velocity: center/origin, axis",
+        "Acceleration: linear accel, radial origin, tangent origin and axis",
+        "Force Field: position",
+        "AABB Kill: center",
+    ];
+
+    /// Rotate all directional fields by the rotation, e.g. to make an effect authored along +Z
+    /// point along +Y. The cone has no axis and AABB extents can't be rotated, so those are
+    /// unchanged. Orient along velocity follows the velocity.
+    pub fn reorient(&mut self, rotation: Quat) {
+        match &mut self.init_position {
+            InitPosition::Circle(m) => {
+                m.center = rotation * m.center;
+                m.axis = rotation * m.axis;
             }
-            _ => (),
+            InitPosition::Sphere(m) => m.center = rotation * m.center,
+            InitPosition::Cone(_) => (),
         }
 
-        if let Some(m) = self.render_set_color.as_ref() {
-            effect = effect.render(m.clone());
+        match &mut self.init_velocity {
+            Some(InitVelocity::Circle(m)) => {
+                m.center = rotation * m.center;
+                m.axis = rotation * m.axis;
+            }
+            Some(InitVelocity::Sphere(m)) => m.center = rotation * m.center,
+            Some(InitVelocity::Cone(m)) => {
+                m.origin = rotation * m.origin;
+                m.axis = rotation * m.axis;
+            }
+            None => (),
         }
-        if let Some(m) = self.render_color_over_lifetime.as_ref() {
-            effect = effect.render(ColorOverLifetimeModifier::from(m.clone()))
+
+        for accel in self.update_accels.iter_mut() {
+            match accel {
+                UpdateAccel::Linear(m) => {
+                    if let ValueOrProperty::Value(graph::Value::Float3(v)) = &mut m.accel {
+                        *v = rotation * *v;
+                    }
+                }
+                UpdateAccel::Radial(m) => m.origin = rotation * m.origin,
+                UpdateAccel::Tangent(m) => {
+                    m.origin = rotation * m.origin;
+                    m.axis = rotation * m.axis;
+                }
+            }
         }
-        if let Some(m) = self.render_set_size.as_ref() {
-            effect = effect.render(m.clone());
+
+        #[cfg(feature = "force-field")]
+        ForceField::reorient(self, rotation);
+        #[cfg(feature = "aabb-kill")]
+        AabbKill::reorient(self, rotation);
+    }
+
+    /// Multiply the colors by the tint, per channel: the set color and the color over lifetime.
+    /// Particles of effects with neither are drawn white, so those get a set color of the tint.
+    /// Textures and attributes are unchanged.
+    pub fn tint(&mut self, tint: Vec4) {
+        match self.render_set_color.as_mut() {
+            Some(m) => match &mut m.color {
+                Value::Single(c) => *c *= tint,
+                Value::Uniform((a, b)) => {
+                    *a *= tint;
+                    *b *= tint;
+                }
+            },
+            None if self.render_color_over_lifetime.is_none() => {
+                self.render_set_color = Some(SetColorModifier { color: tint.into() })
+            }
+            None => (),
         }
-        if let Some(m) = self.render_size_over_lifetime.as_ref() {
-            effect = effect.render(SizeOverLifetimeModifier::from(m.clone()));
+
+        if let Some(g) = self.render_color_over_lifetime.as_mut() {
+            g.tint(tint);
         }
-        if self.render_billboard {
-            effect = effect.render(BillboardModifier);
+    }
+
+    /// A copy with the colors multiplied by the tint. See [REffect::tint].
+    pub fn tinted(&self, tint: Vec4) -> Self {
+        let mut re = self.clone();
+        re.tint(tint);
+        re
+    }
+
+    /// Conservative bounds from the modifiers: the emitter shape, grown by how far a particle can
+    /// travel in its longest lifetime at the top speed and acceleration, plus the particle size.
+    /// Drag, force fields and properties are ignored. None without a lifetime.
+    pub fn computed_bounds(&self) -> Option<EffectBounds> {
+        let lifetime = self.max_remaining_lifetime()?;
+
+        let (min, max) = match &self.init_position {
+            InitPosition::Circle(m) => (
+                m.center - Vec3::splat(m.radius),
+                m.center + Vec3::splat(m.radius),
+            ),
+            InitPosition::Sphere(m) => (
+                m.center - Vec3::splat(m.radius),
+                m.center + Vec3::splat(m.radius),
+            ),
+            // The base is at the origin and the cone extends up the Y axis.
+            InitPosition::Cone(m) => {
+                let r = m.base_radius.max(m.top_radius);
+                (Vec3::new(-r, 0.0, -r), Vec3::new(r, m.height, r))
+            }
+        };
+
+        let speed = match &self.init_velocity {
+            Some(InitVelocity::Circle(m)) => value_max(&m.speed),
+            Some(InitVelocity::Sphere(m)) => value_max(&m.speed),
+            Some(InitVelocity::Cone(m)) => value_max(&m.speed),
+            None => 0.0,
+        };
+        let accel: f32 = self
+            .update_accels
+            .iter()
+            .map(|accel| match accel {
+                UpdateAccel::Linear(m) => value_or_property_magnitude(&m.accel),
+                UpdateAccel::Radial(m) => value_or_property_magnitude(&m.accel),
+                UpdateAccel::Tangent(m) => value_or_property_magnitude(&m.accel),
+            })
+            .sum();
+        let size = self
+            .init_size
+            .as_ref()
+            .map(|m| dim_value_max(&m.size))
+            .into_iter()
+            .chain(
+                self.render_set_size
+                    .as_ref()
+                    .map(|m| dim_value_max(&m.size)),
+            )
+            .fold(0.0, f32::max);
+
+        let reach = speed.abs() * lifetime + 0.5 * accel * lifetime * lifetime + size / 2.0;
+        Some(EffectBounds::from_min_max(
+            min - Vec3::splat(reach),
+            max + Vec3::splat(reach),
+        ))
+    }
+
+    /// Modifiers that look authored around the emitter but stay put in world space when the
+    /// simulation space is global, by label.
+    pub fn space_conflicts(&self) -> Vec<&'static str> {
+        #[allow(unused_mut)]
+        let mut conflicts = Vec::new();
+        if self.simulation_space != SimulationSpace::Global {
+            return conflicts;
         }
-        if let Some(m) = self.render_orient_along_velocity.as_ref() {
-            effect = effect.render(m.clone());
+
+        // Centered on the origin, so presumably on the emitter.
+        #[cfg(feature = "aabb-kill")]
+        if self
+            .update_aabb_kill
+            .as_ref()
+            .map_or(false, |m| m.center == Vec3::ZERO)
+        {
+            conflicts.push("AABB Kill");
         }
 
+        conflicts
+    }
+
+    // We need to asset server to load the texture.
+    pub fn to_effect_asset(&self, asset_server: &AssetServer) -> EffectAsset {
+        self.to_effect_asset_muted(asset_server, &[])
+    }
+
+    /// The effect asset without the modifiers of the muted fields. See
+    /// [PreviewMutes](crate::preview::PreviewMutes).
+    pub fn to_effect_asset_muted(
+        &self,
+        _asset_server: &AssetServer,
+        muted: &[&str],
+    ) -> EffectAsset {
+        let mut effect = EffectAsset {
+            name: self.name.clone(),
+            capacity: self.capacity,
+            spawner: Spawner {
+                starts_active: self.spawner.starts_active && self.start_delay <= 0.0,
+                ..self.spawner
+            },
+            z_layer_2d: self.z_layer_2d,
+            simulation_space: self.simulation_space,
+            simulation_condition: self.simulation_condition,
+
+            ..default()
+        };
+
+        for p in self.properties.iter() {
+            effect = effect.with_property(&p.name, p.default_value.into());
+        }
+
+        effect.modifiers = self
+            .modifiers()
+            .into_iter()
+            .filter(|(f, _)| !muted.contains(&f.field))
+            .map(|(_, m)| m)
+            .collect();
         effect
     }
+
+    /// All modifiers in the order they're applied, with the field each came from. See
+    /// [modifier_fields].
+    pub fn modifiers(&self) -> Vec<(ModifierField, Box<dyn Modifier>)> {
+        let fields = modifier_fields();
+        let render = self.render_order().into_iter().filter_map(|kind| {
+            fields
+                .iter()
+                .find(|f| f.stage == ModifierStage::Render(kind))
+        });
+        fields
+            .iter()
+            .filter(|f| !matches!(f.stage, ModifierStage::Render(_)))
+            .chain(render)
+            .flat_map(|f| (f.modifiers)(self).into_iter().map(|m| (*f, m)))
+            .collect()
+    }
+}
+
+/// Where a modifier field is applied. Render modifiers are applied in [REffect::render_order].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModifierStage {
+    Init,
+    Update,
+    Render(RenderModifierKind),
+}
+
+/// How an [REffect] field becomes hanabi modifiers.
+#[derive(Clone, Copy)]
+pub struct ModifierField {
+    /// The field, as written in files.
+    pub field: &'static str,
+    /// Label in the UI, also the key in [crate::docs].
+    pub label: &'static str,
+    pub stage: ModifierStage,
+    /// The modifiers for the field, in order. Empty if the field is unset.
+    pub modifiers: fn(&REffect) -> Vec<Box<dyn Modifier>>,
+}
+
+// Clone the modifier into a box, if set.
+fn boxed<M: Modifier>(m: Option<&M>) -> Vec<Box<dyn Modifier>> {
+    m.map(|m| m.boxed_clone()).into_iter().collect()
+}
+
+/// Every field that produces modifiers, in the order init and update modifiers are applied.
+pub fn modifier_fields() -> Vec<ModifierField> {
+    use ModifierStage::*;
+
+    #[allow(unused_mut)]
+    let mut fields = vec![
+        ModifierField {
+            field: "init_position",
+            label: "Position",
+            stage: Init,
+            modifiers: |re| {
+                vec![match re.init_position {
+                    InitPosition::Circle(m) => m.boxed_clone(),
+                    InitPosition::Sphere(m) => m.boxed_clone(),
+                    InitPosition::Cone(m) => m.boxed_clone(),
+                }]
+            },
+        },
+        ModifierField {
+            field: "init_velocity",
+            label: "Velocity",
+            stage: Init,
+            modifiers: |re| match re.init_velocity.as_ref() {
+                Some(InitVelocity::Circle(m)) => boxed(Some(m)),
+                Some(InitVelocity::Sphere(m)) => boxed(Some(m)),
+                Some(InitVelocity::Cone(m)) => boxed(Some(m)),
+                None => Vec::new(),
+            },
+        },
+        ModifierField {
+            field: "init_size",
+            label: "Size",
+            stage: Init,
+            modifiers: |re| {
+                let Some(m) = re.init_size.as_ref() else {
+                    return Vec::new();
+                };
+                let mut modifiers = Vec::new();
+                if matches!(m.size, DimValue::D2(_)) {
+                    modifiers.push(
+                        InitAttributeModifier {
+                            attribute: Attribute::SIZE2,
+                            value: ValueOrProperty::Value(Vec2::new(1.0, 1.0).into()),
+                        }
+                        .boxed_clone(),
+                    );
+                }
+                modifiers.push(m.boxed_clone());
+                modifiers
+            },
+        },
+        ModifierField {
+            field: "init_age",
+            label: "Age",
+            stage: Init,
+            modifiers: |re| boxed(re.init_age.as_ref()),
+        },
+        ModifierField {
+            field: "init_lifetime",
+            label: "Lifetime",
+            stage: Init,
+            modifiers: |re| boxed(re.init_lifetime.as_ref()),
+        },
+        ModifierField {
+            field: "init_attributes",
+            label: "Attributes",
+            stage: Init,
+            modifiers: |re| {
+                re.init_attributes
+                    .iter()
+                    .filter_map(|a| match Attribute::from_name(&a.attribute) {
+                        Some(attribute) => Some(
+                            InitAttributeModifier {
+                                attribute,
+                                value: ValueOrProperty::Value(a.value.into()),
+                            }
+                            .boxed_clone(),
+                        ),
+                        None => {
+                            error!("unknown attribute: {}", a.attribute);
+                            None
+                        }
+                    })
+                    .collect()
+            },
+        },
+        ModifierField {
+            field: "update_accels",
+            label: "Acceleration",
+            stage: Update,
+            modifiers: |re| {
+                re.update_accels
+                    .iter()
+                    .map(|m| match m {
+                        UpdateAccel::Linear(m) => m.boxed_clone(),
+                        UpdateAccel::Radial(m) => m.boxed_clone(),
+                        UpdateAccel::Tangent(m) => m.boxed_clone(),
+                    })
+                    .collect()
+            },
+        },
+    ];
+
+    #[cfg(feature = "force-field")]
+    fields.push(ModifierField {
+        field: "update_force_field",
+        label: "Force Field",
+        stage: Update,
+        modifiers: ForceField::modifiers,
+    });
+
+    fields.push(ModifierField {
+        field: "update_linear_drag",
+        label: "Linear Drag",
+        stage: Update,
+        modifiers: |re| boxed(re.update_linear_drag.as_ref()),
+    });
+
+    #[cfg(feature = "aabb-kill")]
+    fields.push(ModifierField {
+        field: "update_aabb_kill",
+        label: "AABB Kill",
+        stage: Update,
+        modifiers: AabbKill::modifiers,
+    });
+
+    fields.extend([
+        ModifierField {
+            field: "render_particle_texture",
+            label: "Particle Texture",
+            stage: Render(RenderModifierKind::ParticleTexture),
+            modifiers: |re| match re.render_particle_texture {
+                ParticleTexture::Path(ref path) => {
+                    // This should never happen since the texture is loaded when the asset is loaded.
+                    error!("particle texture not loaded: {}", path);
+                    Vec::new()
+                }
+                ParticleTexture::Texture(ref handle) => vec![ParticleTextureModifier {
+                    texture: handle.clone(),
+                }
+                .boxed_clone()],
+                ParticleTexture::Missing(_) => vec![ParticleTextureModifier {
+                    texture: PLACEHOLDER_TEXTURE.typed(),
+                }
+                .boxed_clone()],
+                ParticleTexture::Solid(color) => vec![ParticleTextureModifier {
+                    texture: solid_texture(color),
+                }
+                .boxed_clone()],
+                ParticleTexture::None => Vec::new(),
+            },
+        },
+        ModifierField {
+            field: "render_set_color",
+            label: "Set Color",
+            stage: Render(RenderModifierKind::SetColor),
+            modifiers: |re| boxed(re.render_set_color.as_ref()),
+        },
+        ModifierField {
+            field: "render_color_over_lifetime",
+            label: "Color Over Lifetime",
+            stage: Render(RenderModifierKind::ColorOverLifetime),
+            modifiers: |re| {
+                let m = re
+                    .render_color_over_lifetime
+                    .clone()
+                    .map(ColorOverLifetimeModifier::from);
+                boxed(m.as_ref())
+            },
+        },
+        ModifierField {
+            field: "render_set_size",
+            label: "Set Size",
+            stage: Render(RenderModifierKind::SetSize),
+            modifiers: |re| boxed(re.render_set_size.as_ref()),
+        },
+        ModifierField {
+            field: "render_size_over_lifetime",
+            label: "Size Over Lifetime",
+            stage: Render(RenderModifierKind::SizeOverLifetime),
+            modifiers: |re| {
+                let m = re
+                    .render_size_over_lifetime
+                    .clone()
+                    .map(SizeOverLifetimeModifier::from);
+                boxed(m.as_ref())
+            },
+        },
+        ModifierField {
+            field: "render_orientation",
+            label: "Orientation",
+            stage: Render(RenderModifierKind::Orientation),
+            modifiers: |re| re.render_orientation.modifiers(),
+        },
+    ]);
+
+    fields
+}
+
+fn scale_value(value: &mut Value<f32>, factor: f32) {
+    match value {
+        Value::Single(v) => *v *= factor,
+        Value::Uniform((a, b)) => {
+            *a *= factor;
+            *b *= factor;
+        }
+        _ => (),
+    }
+}
+
+fn scale_dim_value(value: &mut DimValue, factor: f32) {
+    match value {
+        DimValue::D1(v) => scale_value(v, factor),
+        DimValue::D2(Value::Single(v)) => *v *= factor,
+        DimValue::D2(Value::Uniform((a, b))) => {
+            *a *= factor;
+            *b *= factor;
+        }
+        _ => (),
+    }
+}
+
+// Properties are left alone.
+fn scale_value_or_property(value: &mut ValueOrProperty, factor: f32) {
+    match value {
+        ValueOrProperty::Value(graph::Value::Float(v)) => *v *= factor,
+        ValueOrProperty::Value(graph::Value::Float2(v)) => *v *= factor,
+        ValueOrProperty::Value(graph::Value::Float3(v)) => *v *= factor,
+        _ => (),
+    }
+}
+
+fn value_min(value: &Value<f32>) -> f32 {
+    match value {
+        Value::Single(v) => *v,
+        Value::Uniform((a, b)) => a.min(*b),
+        _ => 0.0,
+    }
+}
+
+fn value_max(value: &Value<f32>) -> f32 {
+    match value {
+        Value::Single(v) => *v,
+        Value::Uniform((a, b)) => a.max(*b),
+        _ => 0.0,
+    }
+}
+
+fn dim_value_max(value: &DimValue) -> f32 {
+    match value {
+        DimValue::D1(v) => value_max(v),
+        DimValue::D2(Value::Single(v)) => v.max_element(),
+        DimValue::D2(Value::Uniform((a, b))) => a.max(*b).max_element(),
+        _ => 0.0,
+    }
+}
+
+// Properties can't be known ahead of time.
+fn value_or_property_magnitude(value: &ValueOrProperty) -> f32 {
+    match value {
+        ValueOrProperty::Value(graph::Value::Float(v)) => v.abs(),
+        ValueOrProperty::Value(graph::Value::Float2(v)) => v.length(),
+        ValueOrProperty::Value(graph::Value::Float3(v)) => v.length(),
+        _ => 0.0,
+    }
+}
+
+fn value_mean(value: &Value<f32>) -> f32 {
+    match value {
+        Value::Single(v) => *v,
+        Value::Uniform((a, b)) => (a + b) / 2.0,
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn effect(spawner: Spawner, lifetime: Value<f32>) -> REffect {
+        REffect {
+            spawner,
+            init_lifetime: Some(InitLifetimeModifier { lifetime }),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn once_duration() {
+        let re = effect(
+            Spawner::new(10.0.into(), 0.5.into(), f32::INFINITY.into()),
+            2.0.into(),
+        );
+        assert!(re.is_once());
+        assert_eq!(re.duration(), Some(2.5));
+        assert_eq!(re.steady_state_count(), None);
+    }
+
+    #[test]
+    fn spawn_windows() {
+        let cycle = SpawnCycle::of(&Spawner::new(10.0.into(), 0.5.into(), 2.0.into()));
+        assert_eq!(cycle.windows(4.0), vec![(0.0, 0.5), (2.0, 2.5)]);
+        assert_eq!(cycle.count, 10.0);
+
+        // Spawning can't run past the period.
+        let cycle = SpawnCycle::of(&Spawner::new(10.0.into(), 3.0.into(), 2.0.into()));
+        assert_eq!(cycle.windows(4.0), vec![(0.0, 2.0), (2.0, 4.0)]);
+    }
+
+    // Periods too short to draw are continuous, rather than one window each.
+    #[test]
+    fn spawn_windows_tiny_period() {
+        let cycle = SpawnCycle::of(&Spawner::new(1.0.into(), 0.0.into(), 0.0001.into()));
+        assert_eq!(cycle.windows(5.0), vec![(0.0, 5.0)]);
+        assert_eq!(cycle.windows(0.0002).len(), 2);
+
+        let cycle = SpawnCycle::of(&Spawner::new(
+            1.0.into(),
+            0.0.into(),
+            f32::MIN_POSITIVE.into(),
+        ));
+        assert_eq!(cycle.windows(1.0), vec![(0.0, 1.0)]);
+    }
+
+    #[test]
+    fn spawn_windows_infinite_period() {
+        let cycle = SpawnCycle::of(&Spawner::new(10.0.into(), 0.5.into(), f32::INFINITY.into()));
+        assert_eq!(cycle.period, None);
+        assert_eq!(cycle.windows(4.0), vec![(0.0, 0.5)]);
+        assert_eq!(cycle.windows(0.25), vec![(0.0, 0.25)]);
+    }
+
+    #[test]
+    fn spawn_windows_zero_spawn_time() {
+        // Bursts at the start of each period.
+        let cycle = SpawnCycle::of(&Spawner::new(5.0.into(), 0.0.into(), 1.0.into()));
+        assert_eq!(cycle.windows(2.0), vec![(0.0, 0.0), (1.0, 1.0)]);
+
+        let cycle = SpawnCycle::of(&Spawner::once(5.0.into(), true));
+        assert_eq!(cycle.windows(1.0), vec![(0.0, 0.0)]);
+    }
+
+    #[test]
+    fn start_delay() {
+        let mut re = effect(
+            Spawner::new(10.0.into(), 0.5.into(), f32::INFINITY.into()),
+            2.0.into(),
+        );
+        re.start_delay = 1.0;
+        assert_eq!(re.duration(), Some(3.5));
+        assert_eq!(re.period(), None);
+
+        let re = effect(
+            Spawner::new(10.0.into(), 0.5.into(), 0.5.into()),
+            2.0.into(),
+        );
+        assert_eq!(re.period(), Some(0.5));
+    }
+
+    #[test]
+    fn rate_steady_state() {
+        // 10 particles every half second, living 2 seconds.
+        let re = effect(
+            Spawner::new(10.0.into(), 0.5.into(), 0.5.into()),
+            2.0.into(),
+        );
+        assert!(!re.is_once());
+        assert_eq!(re.duration(), None);
+        assert_eq!(re.steady_state_count(), Some(40.0));
+    }
+
+    #[test]
+    fn uniform_lifetime() {
+        let lifetime = Value::Uniform((1.0, 3.0));
+        let re = effect(Spawner::once(5.0.into(), true), lifetime);
+        assert_eq!(re.duration(), Some(3.0));
+
+        let re = effect(Spawner::new(4.0.into(), 1.0.into(), 1.0.into()), lifetime);
+        assert_eq!(re.steady_state_count(), Some(8.0));
+    }
+
+    #[test]
+    fn initial_age() {
+        let mut re = effect(Spawner::once(5.0.into(), true), 2.0.into());
+        re.init_age = Some(InitAgeModifier {
+            age: Value::Uniform((0.5, 1.0)),
+        });
+        // The youngest particle lives the longest.
+        assert_eq!(re.duration(), Some(1.5));
+        assert_eq!(re.mean_remaining_lifetime(), Some(1.25));
+    }
+
+    #[test]
+    #[cfg(all(feature = "force-field", feature = "aabb-kill"))]
+    fn scale_spatial_values() {
+        let mut re = REffect {
+            init_position: InitPosition::Sphere(InitPositionSphereModifier {
+                center: Vec3::new(1.0, 2.0, 3.0),
+                radius: 2.0,
+                dimension: ShapeDimension::Volume,
+            }),
+            init_velocity: Some(InitVelocity::Sphere(InitVelocitySphereModifier {
+                center: Vec3::ONE,
+                speed: Value::Uniform((1.0, 2.0)),
+            })),
+            init_size: Some(InitSizeModifier {
+                size: DimValue::D1(4.0.into()),
+            }),
+            init_lifetime: Some(InitLifetimeModifier {
+                lifetime: 3.0.into(),
+            }),
+            update_accels: vec![
+                UpdateAccel::Linear(AccelModifier::constant(Vec3::new(0.0, -10.0, 0.0))),
+                UpdateAccel::Radial(RadialAccelModifier::constant(Vec3::X, 2.0)),
+            ],
+            update_force_field: vec![ForceFieldSource {
+                position: Vec3::Y,
+                min_radius: 0.5,
+                max_radius: 5.0,
+                mass: 3.0,
+                ..default()
+            }],
+            update_linear_drag: Some(LinearDragModifier { drag: 2.0 }),
+            update_aabb_kill: Some(AabbKillModifier {
+                center: Vec3::ONE,
+                half_size: Vec3::splat(4.0),
+                kill_inside: false,
+            }),
+            ..default()
+        };
+
+        re.scale(0.5);
+
+        let InitPosition::Sphere(position) = re.init_position else {
+            panic!("position variant changed");
+        };
+        assert_eq!(position.center, Vec3::new(0.5, 1.0, 1.5));
+        assert_eq!(position.radius, 1.0);
+
+        let Some(InitVelocity::Sphere(velocity)) = re.init_velocity else {
+            panic!("velocity variant changed");
+        };
+        assert_eq!(velocity.center, Vec3::splat(0.5));
+        assert_eq!(velocity.speed, Value::Uniform((0.5, 1.0)));
+
+        assert_eq!(re.init_size.unwrap().size, DimValue::D1(2.0.into()));
+
+        assert_eq!(
+            re.update_accels[0],
+            UpdateAccel::Linear(AccelModifier::constant(Vec3::new(0.0, -5.0, 0.0)))
+        );
+        assert_eq!(
+            re.update_accels[1],
+            UpdateAccel::Radial(RadialAccelModifier::constant(Vec3::new(0.5, 0.0, 0.0), 1.0))
+        );
+
+        let source = &re.update_force_field[0];
+        assert_eq!(source.position, Vec3::new(0.0, 0.5, 0.0));
+        assert_eq!((source.min_radius, source.max_radius), (0.25, 2.5));
+        assert_eq!(source.mass, 3.0);
+
+        let aabb = re.update_aabb_kill.unwrap();
+        assert_eq!(aabb.center, Vec3::splat(0.5));
+        assert_eq!(aabb.half_size, Vec3::splat(2.0));
+
+        // Times and drag are unchanged.
+        assert_eq!(re.init_lifetime.unwrap().lifetime, 3.0.into());
+        assert_eq!(re.update_linear_drag.unwrap().drag, 2.0);
+    }
+
+    #[test]
+    fn scale_cone_and_render_sizes() {
+        let mut re = REffect {
+            init_position: InitPosition::Cone(InitPositionCone3dModifier {
+                base_radius: 1.0,
+                top_radius: 2.0,
+                height: 4.0,
+                dimension: ShapeDimension::Volume,
+            }),
+            render_set_size: Some(SetSizeModifier {
+                size: DimValue::D2(Vec2::new(1.0, 2.0).into()),
+            }),
+            render_size_over_lifetime: Some(SizeGradient::default()),
+            ..default()
+        };
+
+        re.scale(10.0);
+
+        let InitPosition::Cone(cone) = re.init_position else {
+            panic!("position variant changed");
+        };
+        assert_eq!(
+            (cone.base_radius, cone.top_radius, cone.height),
+            (10.0, 20.0, 40.0)
+        );
+        assert_eq!(
+            re.render_set_size.unwrap().size,
+            DimValue::D2(Vec2::new(10.0, 20.0).into())
+        );
+        assert_eq!(
+            re.render_size_over_lifetime.unwrap().keys(),
+            &[(0.5, Vec2::splat(10.0))]
+        );
+    }
+
+    #[test]
+    fn scale_round_trip() {
+        let mut re = REffect::default();
+        re.scale(4.0);
+        re.scale(0.25);
+        assert_eq!(re.init_position, InitPosition::default());
+    }
+
+    #[test]
+    fn tint_colors() {
+        let blue = Vec4::new(0.0, 0.5, 1.0, 1.0);
+        let mut re = REffect {
+            render_set_color: Some(SetColorModifier {
+                color: Value::Uniform((Vec4::ONE, Vec4::splat(0.5))),
+            }),
+            render_color_over_lifetime: Some(ColorGradient::default()),
+            ..default()
+        };
+        re.tint(blue);
+        assert_eq!(
+            re.render_set_color.unwrap().color,
+            Value::Uniform((blue, Vec4::new(0.0, 0.25, 0.5, 0.5)))
+        );
+        assert_eq!(re.render_color_over_lifetime.unwrap().sample(0.5), blue);
+
+        // Uncolored effects get the tint as their color.
+        let re = REffect::default().tinted(blue);
+        assert_eq!(re.render_set_color.unwrap().color, Value::Single(blue));
+        assert!(re.render_color_over_lifetime.is_none());
+
+        // White changes nothing.
+        let re = REffect {
+            render_color_over_lifetime: Some(ColorGradient::default()),
+            ..default()
+        };
+        let tinted = re.tinted(Vec4::ONE);
+        assert!(tinted.render_set_color.is_none());
+        assert_eq!(
+            tinted.render_color_over_lifetime.unwrap().sample(0.3),
+            Vec4::ONE
+        );
+    }
+
+    fn assert_near(a: Vec3, b: Vec3) {
+        assert!(a.abs_diff_eq(b, 1e-5), "{} != {}", a, b);
+    }
+
+    #[test]
+    #[cfg(feature = "force-field")]
+    fn reorient_z_to_y() {
+        let mut re = REffect {
+            init_velocity: Some(InitVelocity::Cone(InitVelocityTangentModifier {
+                origin: Vec3::new(0.0, 0.0, 2.0),
+                axis: Vec3::Z,
+                speed: 1.0.into(),
+            })),
+            update_accels: vec![
+                UpdateAccel::Linear(AccelModifier::constant(Vec3::new(0.0, 0.0, -9.8))),
+                UpdateAccel::Tangent(TangentAccelModifier::constant(Vec3::Z, Vec3::Z, 1.0)),
+            ],
+            update_force_field: vec![ForceFieldSource {
+                position: Vec3::new(1.0, 0.0, 1.0),
+                ..default()
+            }],
+            ..default()
+        };
+
+        re.reorient(Quat::from_rotation_arc(Vec3::Z, Vec3::Y));
+
+        // The default position is a circle around +Z.
+        let InitPosition::Circle(position) = re.init_position else {
+            panic!("position variant changed");
+        };
+        assert_near(position.axis, Vec3::Y);
+
+        let Some(InitVelocity::Cone(velocity)) = re.init_velocity else {
+            panic!("velocity variant changed");
+        };
+        assert_near(velocity.origin, Vec3::new(0.0, 2.0, 0.0));
+        assert_near(velocity.axis, Vec3::Y);
+        assert_eq!(velocity.speed, 1.0.into());
+
+        let UpdateAccel::Linear(AccelModifier {
+            accel: ValueOrProperty::Value(graph::Value::Float3(accel)),
+            ..
+        }) = re.update_accels[0]
+        else {
+            panic!("accel changed");
+        };
+        assert_near(accel, Vec3::new(0.0, -9.8, 0.0));
+
+        let UpdateAccel::Tangent(tangent) = &re.update_accels[1] else {
+            panic!("accel variant changed");
+        };
+        assert_near(tangent.origin, Vec3::Y);
+        assert_near(tangent.axis, Vec3::Y);
+
+        assert_near(re.update_force_field[0].position, Vec3::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    #[cfg(feature = "aabb-kill")]
+    fn space_conflicts() {
+        let mut re = REffect {
+            simulation_space: SimulationSpace::Global,
+            update_aabb_kill: Some(AabbKillModifier {
+                center: Vec3::ZERO,
+                half_size: Vec3::ONE,
+                kill_inside: false,
+            }),
+            ..default()
+        };
+        assert_eq!(re.space_conflicts(), ["AABB Kill"]);
+
+        re.simulation_space = SimulationSpace::Local;
+        assert!(re.space_conflicts().is_empty());
+
+        // Placed somewhere in the world on purpose.
+        re.simulation_space = SimulationSpace::Global;
+        re.update_aabb_kill.as_mut().unwrap().center = Vec3::Y * 10.0;
+        assert!(re.space_conflicts().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "aabb-kill")]
+    fn reorient_keeps_cone_and_aabb_extents() {
+        let cone = InitPositionCone3dModifier {
+            base_radius: 1.0,
+            top_radius: 0.0,
+            height: 2.0,
+            dimension: ShapeDimension::Volume,
+        };
+        let mut re = REffect {
+            init_position: InitPosition::Cone(cone),
+            update_aabb_kill: Some(AabbKillModifier {
+                center: Vec3::X,
+                half_size: Vec3::new(1.0, 2.0, 3.0),
+                kill_inside: false,
+            }),
+            ..default()
+        };
+
+        re.reorient(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+
+        assert_eq!(re.init_position, InitPosition::Cone(cone));
+        let aabb = re.update_aabb_kill.unwrap();
+        assert_near(aabb.center, Vec3::Y);
+        assert_eq!(aabb.half_size, Vec3::new(1.0, 2.0, 3.0));
+    }
+
+    #[test]
+    fn reorient_round_trip() {
+        let rotation = Quat::from_rotation_arc(Vec3::Z, Vec3::new(1.0, 1.0, 0.0).normalize());
+        let mut re = REffect::default();
+        re.reorient(rotation);
+        re.reorient(rotation.inverse());
+
+        let InitPosition::Circle(position) = re.init_position else {
+            panic!("position variant changed");
+        };
+        assert_near(position.axis, Vec3::Z);
+    }
+
+    #[test]
+    fn no_lifetime() {
+        let mut re = effect(Spawner::once(5.0.into(), true), 2.0.into());
+        re.init_lifetime = None;
+        assert_eq!(re.duration(), None);
+
+        re.init_attributes.push(InitAttribute {
+            attribute: Attribute::LIFETIME.name().to_string(),
+            value: AttributeValue::Float(4.0),
+        });
+        assert_eq!(re.duration(), Some(4.0));
+    }
+
+    #[test]
+    fn suggested_capacity() {
+        // 100 per second for 2 seconds, plus headroom.
+        let re = effect(Spawner::rate(100.0.into()), 2.0.into());
+        assert_eq!(re.suggested_capacity(), Some(250));
+
+        let re = effect(
+            Spawner::once(Value::Uniform((10.0, 20.0)), true),
+            2.0.into(),
+        );
+        assert_eq!(re.suggested_capacity(), Some(20));
+
+        let mut re = effect(Spawner::rate(100.0.into()), 2.0.into());
+        re.init_lifetime = None;
+        assert_eq!(re.suggested_capacity(), None);
+    }
+
+    #[test]
+    fn computed_bounds() {
+        let mut re = effect(Spawner::rate(10.0.into()), 3.0.into());
+        re.init_position = InitPosition::Sphere(InitPositionSphereModifier {
+            center: Vec3::Y,
+            radius: 1.0,
+            dimension: ShapeDimension::Volume,
+        });
+        re.init_velocity = Some(InitVelocity::Sphere(InitVelocitySphereModifier {
+            center: Vec3::ZERO,
+            speed: Value::Uniform((1.0, 2.0)),
+        }));
+        // The radius, plus 2 units/s for 3 seconds.
+        let bounds = re.computed_bounds().unwrap();
+        assert_eq!(bounds.center, Vec3::Y);
+        assert_eq!(bounds.half_extents, Vec3::splat(7.0));
+
+        // Falling at 2 units/s² for 3 seconds adds 9.
+        re.update_accels = vec![UpdateAccel::Linear(AccelModifier::constant(Vec3::new(
+            0.0, -2.0, 0.0,
+        )))];
+        assert_eq!(
+            re.computed_bounds().unwrap().half_extents,
+            Vec3::splat(16.0)
+        );
+
+        re.init_lifetime = None;
+        assert_eq!(re.computed_bounds(), None);
+    }
+
+    #[test]
+    fn bounds_corners() {
+        let bounds = EffectBounds::from_min_max(Vec3::ZERO, Vec3::new(1.0, 2.0, 3.0));
+        let corners = bounds.corners();
+        assert_eq!(corners[0], bounds.min());
+        assert_eq!(corners[7], bounds.max());
+        assert_eq!(corners[1], Vec3::X);
+        assert_eq!(corners[2], Vec3::Y * 2.0);
+    }
+
+    fn fields(re: &REffect) -> Vec<&'static str> {
+        re.modifiers().into_iter().map(|(f, _)| f.field).collect()
+    }
+
+    #[test]
+    fn modifiers_default() {
+        let re = REffect::default();
+        assert_eq!(fields(&re), ["init_position"]);
+    }
+
+    #[test]
+    fn modifiers_order() {
+        let mut re = effect(Spawner::once(5.0.into(), true), 2.0.into());
+        re.init_size = Some(InitSizeModifier {
+            size: DimValue::D2(Vec2::ONE.into()),
+        });
+        re.update_linear_drag = Some(LinearDragModifier { drag: 0.5 });
+        re.render_orientation = Orientation::Billboard;
+        re.render_set_color = Some(SetColorModifier {
+            color: Vec4::ONE.into(),
+        });
+        re.render_order = vec![RenderModifierKind::Orientation];
+        assert_eq!(
+            fields(&re),
+            [
+                "init_position",
+                // D2 sizes also init the SIZE2 attribute.
+                "init_size",
+                "init_size",
+                "init_lifetime",
+                "update_linear_drag",
+                "render_orientation",
+                "render_set_color",
+            ]
+        );
+
+        // One modifier for each orientation but none.
+        for orientation in Orientation::ALL {
+            re.render_orientation = orientation;
+            assert_eq!(
+                fields(&re).contains(&"render_orientation"),
+                orientation != Orientation::None
+            );
+        }
+    }
+
+    #[test]
+    fn migrate_orientation() {
+        use RenderModifierKind as K;
+
+        let old = |billboard, orient: bool, order| {
+            let mut re = REffect {
+                render_billboard: billboard,
+                render_orient_along_velocity: orient.then_some(OldOrientAlongVelocity),
+                render_order: order,
+                ..default()
+            };
+            re.migrate();
+            assert!(!re.render_billboard && re.render_orient_along_velocity.is_none());
+            (re.render_orientation, re.render_order)
+        };
+
+        assert_eq!(old(false, false, vec![]), (Orientation::None, vec![]));
+        assert_eq!(
+            old(true, false, vec![K::Billboard, K::SetColor]),
+            (Orientation::Billboard, vec![K::Orientation, K::SetColor])
+        );
+        // Orient along velocity won when both were set.
+        assert_eq!(
+            old(
+                true,
+                true,
+                vec![K::Billboard, K::SetSize, K::OrientAlongVelocity]
+            ),
+            (Orientation::AlongVelocity, vec![K::SetSize, K::Orientation])
+        );
+        // Unset kinds are dropped from the order.
+        assert_eq!(
+            old(false, false, vec![K::OrientAlongVelocity, K::SetColor]),
+            (Orientation::None, vec![K::SetColor])
+        );
+    }
 }
@@ -0,0 +1,83 @@
+// Update modifiers that a hanabi build may not have, e.g. a fork. Each is behind a cargo feature
+// of the same name. With the feature disabled, the field is compiled out of [REffect] and the UI,
+// and the loader keeps the field's text as-is so saving doesn't drop it. See
+// [crate::asset::split_unknown_fields].
+
+use bevy::prelude::*;
+use bevy_hanabi::prelude::*;
+
+use super::REffect;
+
+/// What [REffect] needs to know about an optional modifier. Called at the same points as the
+/// built-in modifiers.
+pub trait OptionalModifier {
+    /// Number of modifiers enabled.
+    fn count(re: &REffect) -> usize;
+
+    /// See [REffect::scale].
+    fn scale(re: &mut REffect, factor: f32);
+
+    /// See [REffect::reorient].
+    fn reorient(re: &mut REffect, rotation: Quat);
+
+    /// The modifiers for the field. See [super::ModifierField].
+    fn modifiers(re: &REffect) -> Vec<Box<dyn Modifier>>;
+}
+
+#[cfg(feature = "force-field")]
+pub struct ForceField;
+
+#[cfg(feature = "force-field")]
+impl OptionalModifier for ForceField {
+    fn count(re: &REffect) -> usize {
+        usize::from(!re.update_force_field.is_empty())
+    }
+
+    fn scale(re: &mut REffect, factor: f32) {
+        for source in re.update_force_field.iter_mut() {
+            source.position *= factor;
+            source.min_radius *= factor;
+            source.max_radius *= factor;
+        }
+    }
+
+    fn reorient(re: &mut REffect, rotation: Quat) {
+        for source in re.update_force_field.iter_mut() {
+            source.position = rotation * source.position;
+        }
+    }
+
+    fn modifiers(re: &REffect) -> Vec<Box<dyn Modifier>> {
+        if re.update_force_field.is_empty() {
+            return Vec::new();
+        }
+        vec![ForceFieldModifier::new(re.update_force_field.iter().cloned()).boxed_clone()]
+    }
+}
+
+#[cfg(feature = "aabb-kill")]
+pub struct AabbKill;
+
+#[cfg(feature = "aabb-kill")]
+impl OptionalModifier for AabbKill {
+    fn count(re: &REffect) -> usize {
+        usize::from(re.update_aabb_kill.is_some())
+    }
+
+    fn scale(re: &mut REffect, factor: f32) {
+        if let Some(m) = re.update_aabb_kill.as_mut() {
+            m.center *= factor;
+            m.half_size *= factor;
+        }
+    }
+
+    fn reorient(re: &mut REffect, rotation: Quat) {
+        if let Some(m) = re.update_aabb_kill.as_mut() {
+            m.center = rotation * m.center;
+        }
+    }
+
+    fn modifiers(re: &REffect) -> Vec<Box<dyn Modifier>> {
+        super::boxed(re.update_aabb_kill.as_ref())
+    }
+}
@@ -0,0 +1,252 @@
+// Scatter test: short-lived copies of an effect spawned at random positions in a box, a burst of
+// them every few seconds. For effects that will be attached to things that move (e.g.
+// projectiles), to check they read well wherever they appear and that global-space modifiers (the
+// kill box, force fields at fixed positions) behave. Each copy plays the effect once, as a
+// spawn-once burst, and is despawned when it has played out. The copies aren't live effects; all of
+// them go when the test is stopped.
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_egui::egui::{self, DragValue};
+use bevy_hanabi::{prelude::*, Random};
+use rand::Rng;
+
+use crate::{docs::Doc, reffect::REffect, ui::actions::set_bounds};
+
+/// Shortest time between bursts, in seconds.
+pub const MIN_INTERVAL: f32 = 0.1;
+
+/// Seconds a copy is kept if the effect has no lifetime.
+const DEFAULT_LIFETIME: f32 = 2.0;
+
+pub struct ScatterPlugin;
+
+impl Plugin for ScatterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Scatters>()
+            .add_system(scatter)
+            .add_system(despawn_scattered);
+    }
+}
+
+/// Settings of a running scatter test.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scatter {
+    /// Copies spawned each burst.
+    pub count: usize,
+    /// Seconds between bursts.
+    pub interval: f32,
+    pub center: Vec3,
+    pub half_extents: Vec3,
+    /// Seconds until the next burst.
+    next: f32,
+}
+
+impl Default for Scatter {
+    fn default() -> Self {
+        Self {
+            count: 8,
+            interval: 2.0,
+            center: Vec3::ZERO,
+            half_extents: Vec3::new(5.0, 1.0, 5.0),
+            next: 0.0,
+        }
+    }
+}
+
+/// Scatter tests running, by effect.
+#[derive(Resource, Default)]
+pub struct Scatters(pub HashMap<Handle<REffect>, Scatter>);
+
+/// A copy spawned by a scatter test, despawned when the timer finishes.
+#[derive(Component)]
+pub struct Scattered {
+    pub effect: Handle<REffect>,
+    timer: Timer,
+}
+
+/// The effect as played by each copy: a single burst, starting immediately. Repeating effects
+/// spawn one cycle.
+pub fn burst(re: &REffect) -> REffect {
+    let mut burst = re.clone();
+    burst.spawner.period = f32::INFINITY.into();
+    burst.spawner.starts_active = true;
+    burst.spawner.starts_immediately = true;
+    burst.start_delay = 0.0;
+    burst
+}
+
+/// Random positions in the box.
+pub fn scatter_positions(
+    count: usize,
+    center: Vec3,
+    half_extents: Vec3,
+    rng: &mut impl Rng,
+) -> Vec<Vec3> {
+    let half_extents = half_extents.abs();
+    (0..count)
+        .map(|_| {
+            center
+                + Vec3::new(
+                    rng.gen_range(-1.0..=1.0),
+                    rng.gen_range(-1.0..=1.0),
+                    rng.gen_range(-1.0..=1.0),
+                ) * half_extents
+        })
+        .collect()
+}
+
+// Spawn a burst of copies for each test that's due. Tests of effects that are gone are stopped.
+fn scatter(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut random: ResMut<Random>,
+    mut scatters: ResMut<Scatters>,
+    reffects: Res<Assets<REffect>>,
+    mut effects: ResMut<Assets<EffectAsset>>,
+    asset_server: Res<AssetServer>,
+) {
+    if scatters.0.is_empty() {
+        return;
+    }
+    scatters.0.retain(|handle, _| reffects.contains(handle));
+
+    let dt = time.delta_seconds();
+    for (handle, scatter) in scatters.0.iter_mut() {
+        scatter.next -= dt;
+        if scatter.next > 0.0 {
+            continue;
+        }
+        scatter.next = scatter.interval.max(MIN_INTERVAL);
+        let Some(re) = reffects.get(handle) else {
+            continue;
+        };
+
+        // One asset for the burst, so edits show up in the next.
+        let burst = burst(re);
+        let lifetime = burst.duration().unwrap_or(DEFAULT_LIFETIME);
+        let asset = effects.add(burst.to_effect_asset(&asset_server));
+        for position in scatter_positions(
+            scatter.count,
+            scatter.center,
+            scatter.half_extents,
+            &mut random.0,
+        ) {
+            let mut entity = commands.spawn((
+                ParticleEffectBundle {
+                    transform: Transform::from_translation(position),
+                    ..ParticleEffectBundle::new(asset.clone())
+                },
+                Scattered {
+                    effect: handle.clone(),
+                    timer: Timer::from_seconds(lifetime, TimerMode::Once),
+                },
+                Name::new(format!("{} (scatter)", re.name)),
+            ));
+            set_bounds(&mut entity, re);
+        }
+    }
+}
+
+// Despawn copies that have played out, or whose test was stopped.
+fn despawn_scattered(
+    mut commands: Commands,
+    time: Res<Time>,
+    scatters: Res<Scatters>,
+    mut scattered: Query<(Entity, &mut Scattered)>,
+) {
+    for (entity, mut copy) in scattered.iter_mut() {
+        if copy.timer.tick(time.delta()).finished() || !scatters.0.contains_key(&copy.effect) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// Start and stop the test for an effect, and change its settings while it runs.
+pub fn ui_scatter(handle: &Handle<REffect>, scatters: &mut Scatters, ui: &mut egui::Ui) {
+    let running = scatters.0.contains_key(handle);
+    let label = if running { "Scatter ⏹" } else { "Scatter…" };
+    ui.menu_button(label, |ui| {
+        let mut enabled = running;
+        if ui
+            .checkbox(&mut enabled, "Scatter test")
+            .doc("Scatter test")
+            .changed()
+        {
+            match enabled {
+                true => scatters.0.insert(handle.clone(), Scatter::default()),
+                false => scatters.0.remove(handle),
+            };
+        }
+
+        let Some(scatter) = scatters.0.get_mut(handle) else {
+            return;
+        };
+        egui::Grid::new("scatter").num_columns(2).show(ui, |ui| {
+            ui.label("Copies");
+            ui.add(DragValue::new(&mut scatter.count).clamp_range(1..=64));
+            ui.end_row();
+
+            ui.label("Every");
+            ui.add(
+                DragValue::new(&mut scatter.interval)
+                    .clamp_range(MIN_INTERVAL..=60.0)
+                    .speed(0.1)
+                    .suffix("s"),
+            );
+            ui.end_row();
+
+            for (label, v) in [
+                ("Center", &mut scatter.center),
+                ("Half extents", &mut scatter.half_extents),
+            ] {
+                ui.label(label);
+                ui.horizontal(|ui| {
+                    ui.add(DragValue::new(&mut v.x).speed(0.1));
+                    ui.add(DragValue::new(&mut v.y).speed(0.1));
+                    ui.add(DragValue::new(&mut v.z).speed(0.1));
+                });
+                ui.end_row();
+            }
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_hanabi::InitLifetimeModifier;
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+
+    use super::*;
+
+    #[test]
+    fn burst_spawns_once() {
+        let mut re = REffect {
+            spawner: Spawner::rate(30.0.into()),
+            start_delay: 1.0,
+            init_lifetime: Some(InitLifetimeModifier {
+                lifetime: 2.0.into(),
+            }),
+            ..default()
+        };
+        re.spawner.starts_active = false;
+        assert_eq!(re.duration(), None);
+
+        let burst = burst(&re);
+        assert!(burst.is_once());
+        assert!(burst.spawner.starts_active);
+        assert_eq!(burst.duration(), Some(2.0));
+    }
+
+    #[test]
+    fn positions_in_box() {
+        let mut rng = Pcg32::seed_from_u64(1);
+        let (center, half_extents) = (Vec3::new(10.0, 0.0, -4.0), Vec3::new(2.0, 0.0, 1.0));
+        let positions = scatter_positions(100, center, half_extents, &mut rng);
+        assert_eq!(positions.len(), 100);
+        for p in positions {
+            let d = (p - center).abs();
+            assert!(d.x <= 2.0 && d.y == 0.0 && d.z <= 1.0, "{:?}", p);
+        }
+    }
+}
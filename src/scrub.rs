@@ -0,0 +1,93 @@
+// Preview a live effect at a specific time. The effect is restarted like any other (see
+// [Action::Restart]) and the simulation advanced in fixed steps until the time is reached, then
+// held there.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    reffect::REffect,
+    ui::actions::{Action, Actions},
+};
+
+/// Fixed time step while scrubbing, in seconds.
+pub const SCRUB_STEP: f32 = 1.0 / 60.0;
+
+/// Memory id for [ScrubRequest]s from the UI. The held state is stored under `.with("held")`.
+pub const SCRUB: &str = "scrub";
+
+pub struct ScrubPlugin;
+
+impl Plugin for ScrubPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(scrub.in_base_set(CoreSet::PreUpdate));
+    }
+}
+
+#[derive(Clone)]
+pub enum ScrubRequest {
+    /// Restart the effect and advance it to the time (seconds).
+    Seek(Handle<REffect>, f32),
+    /// Go back to real time.
+    Resume,
+}
+
+/// Make a request from the UI. It's handled next frame.
+pub fn request(ctx: &egui::Context, request: ScrubRequest) {
+    ctx.memory_mut(|memory| memory.data.insert_temp(egui::Id::new(SCRUB), request));
+}
+
+/// Whether time is held at a scrubbed frame (or advancing to it).
+pub fn is_held(ctx: &egui::Context) -> bool {
+    ctx.memory_mut(|memory| {
+        memory
+            .data
+            .get_temp::<bool>(egui::Id::new(SCRUB).with("held"))
+    })
+    .unwrap_or_default()
+}
+
+// Time is global, so all live effects are advanced and held together.
+fn scrub(
+    mut contexts: EguiContexts,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+    mut remaining: Local<u32>,
+    mut actions: ResMut<Actions>,
+) {
+    let ctx = contexts.ctx_mut();
+    let id = egui::Id::new(SCRUB);
+
+    match ctx.memory_mut(|memory| {
+        let request = memory.data.get_temp::<ScrubRequest>(id);
+        memory.data.remove::<ScrubRequest>(id);
+        request
+    }) {
+        Some(ScrubRequest::Seek(handle, time)) => {
+            // Respawn from the same effect asset so existing particles are cleared.
+            actions.push(Action::Restart(handle));
+
+            *remaining = (time / SCRUB_STEP).round() as u32;
+            *strategy = TimeUpdateStrategy::ManualDuration(if *remaining > 0 {
+                Duration::from_secs_f32(SCRUB_STEP)
+            } else {
+                Duration::ZERO
+            });
+            ctx.memory_mut(|memory| memory.data.insert_temp(id.with("held"), true));
+        }
+        Some(ScrubRequest::Resume) => {
+            *remaining = 0;
+            *strategy = TimeUpdateStrategy::Automatic;
+            ctx.memory_mut(|memory| memory.data.insert_temp(id.with("held"), false));
+        }
+        None if *remaining > 0 => {
+            *remaining -= 1;
+            if *remaining == 0 {
+                // Hold the frame.
+                *strategy = TimeUpdateStrategy::ManualDuration(Duration::ZERO);
+            }
+        }
+        None => (),
+    }
+}
@@ -0,0 +1,320 @@
+// Session log of edits, for reproducing crashes. When enabled, every change the editor applies to
+// an effect (the same changes that regenerate live effects, see [ChangeFlags]) is appended to
+// `han-ed/session.log` in the platform cache directory, with the new values of the fields in the
+// changed categories. The previous session's log is kept as `session.1.log`, since that's the one
+// wanted after a crash. Writing happens on the IO task pool; if it falls behind or fails, entries
+// are dropped rather than slowing down editing. Disabled, nothing is recorded or written.
+
+use std::{
+    collections::VecDeque,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    prelude::*,
+    reflect::{serde::ReflectSerializer, Struct},
+    tasks::{futures_lite::future, IoTaskPool, Task},
+};
+use bevy_egui::egui;
+
+use crate::{
+    asset::asset_path_string,
+    browser::AssetBrowser,
+    change::ChangeFlags,
+    config::EditorConfig,
+    docs::Doc,
+    reffect::{ParticleTexture, REffect},
+    ui::{
+        actions::{process_actions, Action, Actions},
+        han_ed_ui,
+    },
+};
+
+/// Edits kept in memory for copying into a bug report.
+pub const RECENT_EDITS: usize = 50;
+
+/// Bytes waiting to be written before the rest are dropped.
+pub const MAX_PENDING: usize = 1 << 20;
+
+pub struct SessionPlugin;
+
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SessionLog>()
+            .add_system(record_edits.after(han_ed_ui).before(process_actions))
+            .add_system(write_session_log);
+    }
+}
+
+/// A change applied to an effect.
+#[derive(Clone, Debug)]
+pub struct Edit {
+    pub time: SystemTime,
+    /// Effect path, or name for effects that aren't in the browser.
+    pub effect: String,
+    pub changes: ChangeFlags,
+    /// Fields in the changed categories, with their new values as RON.
+    pub fields: Vec<(String, String)>,
+}
+
+impl Edit {
+    pub fn format(&self) -> String {
+        let millis = self
+            .time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let mut s = format!("{} {} {:?}\n", millis, self.effect, self.changes);
+        for (field, value) in self.fields.iter() {
+            s.push_str(&format!("    {}: {}\n", field, value));
+        }
+        s
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct SessionLog {
+    /// Found on the first write.
+    file: Option<PathBuf>,
+    /// Whether the previous session's log has been moved aside.
+    started: bool,
+    recent: VecDeque<Edit>,
+    pending: String,
+    dropped: usize,
+    task: Option<Task<()>>,
+}
+
+impl SessionLog {
+    pub fn file() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("han-ed").join("session.log"))
+    }
+
+    /// The most recent edits, oldest first, formatted for a bug report.
+    pub fn recent(&self) -> String {
+        let mut s = format!("Last {} edits:\n", self.recent.len());
+        for edit in self.recent.iter() {
+            s.push_str(&edit.format());
+        }
+        s
+    }
+
+    pub fn len(&self) -> usize {
+        self.recent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recent.is_empty()
+    }
+
+    fn push(&mut self, edit: Edit) {
+        if self.pending.len() < MAX_PENDING {
+            self.pending.push_str(&edit.format());
+        } else {
+            self.dropped += 1;
+        }
+        self.recent.push_back(edit);
+        while self.recent.len() > RECENT_EDITS {
+            self.recent.pop_front();
+        }
+    }
+}
+
+/// The change category of a field.
+pub fn field_changes(field: &str) -> ChangeFlags {
+    match field {
+        "name" | "target" => ChangeFlags::META,
+        "capacity" => ChangeFlags::CAPACITY,
+        "spawner" | "start_delay" => ChangeFlags::SPAWNER,
+        "bounds" => ChangeFlags::BOUNDS,
+        "properties" => ChangeFlags::PROPERTIES,
+        "render_particle_texture" => ChangeFlags::TEXTURE,
+        f if f.starts_with("init_") => ChangeFlags::INIT,
+        f if f.starts_with("update_") => ChangeFlags::UPDATE,
+        f if f.starts_with("render_") => ChangeFlags::RENDER,
+        _ => ChangeFlags::SIMULATION,
+    }
+}
+
+// Serialize the fields in the changed categories. The texture is logged by path, since the handle
+// means nothing outside the session.
+fn changed_fields(
+    re: &REffect,
+    changes: ChangeFlags,
+    asset_server: &AssetServer,
+    type_registry: &AppTypeRegistry,
+) -> Vec<(String, String)> {
+    let type_registry = type_registry.read();
+    let mut fields = Vec::new();
+    for (i, value) in re.iter_fields().enumerate() {
+        let Some(name) = re.name_at(i) else {
+            continue;
+        };
+        if !changes.intersects(field_changes(name)) {
+            continue;
+        }
+        let ron = match &re.render_particle_texture {
+            ParticleTexture::Texture(h) if name == "render_particle_texture" => asset_server
+                .get_handle_path(h.id())
+                .and_then(|p| asset_path_string(&p).ok())
+                .map(|p| format!("Path({:?})", p))
+                .unwrap_or_else(|| "Texture(?)".to_string()),
+            _ => ron::to_string(&ReflectSerializer::new(value, &type_registry))
+                .unwrap_or_else(|e| format!("<{}>", e)),
+        };
+        fields.push((name.to_string(), ron));
+    }
+    fields
+}
+
+// Record the changes about to be applied. Runs between the UI and the actions so it sees them all.
+fn record_edits(
+    config: Res<EditorConfig>,
+    actions: Res<Actions>,
+    browser: Res<AssetBrowser<REffect>>,
+    reffects: Res<Assets<REffect>>,
+    asset_server: Res<AssetServer>,
+    type_registry: Res<AppTypeRegistry>,
+    mut log: ResMut<SessionLog>,
+) {
+    if !config.session_log {
+        return;
+    }
+    for action in actions.iter() {
+        let Action::Regenerate(handle, changes) = action else {
+            continue;
+        };
+        let Some(re) = reffects.get(handle) else {
+            continue;
+        };
+        let effect = browser
+            .entries
+            .iter()
+            .find(|e| e.handle.as_ref() == Some(handle))
+            .map(|e| e.path.display().to_string())
+            .unwrap_or_else(|| re.name.clone());
+        log.push(Edit {
+            time: SystemTime::now(),
+            effect,
+            changes: *changes,
+            fields: changed_fields(re, *changes, &asset_server, &type_registry),
+        });
+    }
+}
+
+// Append what's pending. A write still in progress skips this frame rather than waiting.
+fn write_session_log(mut log: ResMut<SessionLog>) {
+    if log.pending.is_empty() {
+        return;
+    }
+    if let Some(task) = log.task.as_mut() {
+        if future::block_on(future::poll_once(task)).is_none() {
+            return;
+        }
+        log.task = None;
+    }
+
+    let Some(file) = log.file.clone().or_else(SessionLog::file) else {
+        log.pending.clear();
+        return;
+    };
+    log.file = Some(file.clone());
+    let rotate = !std::mem::replace(&mut log.started, true);
+    let mut text = std::mem::take(&mut log.pending);
+    if log.dropped > 0 {
+        text.push_str(&format!("({} edits dropped)\n", log.dropped));
+        log.dropped = 0;
+    }
+
+    log.task = Some(IoTaskPool::get().spawn(async move {
+        if let Err(e) = append(&file, &text, rotate) {
+            warn!("failed to write {}: {}", file.display(), e);
+        }
+    }));
+}
+
+/// Append to the log file. Rotating first moves the previous log aside, replacing the one before.
+pub fn append(file: &Path, text: &str, rotate: bool) -> std::io::Result<()> {
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if rotate && file.exists() {
+        std::fs::rename(file, file.with_extension("1.log"))?;
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file)?
+        .write_all(text.as_bytes())
+}
+
+// Turn the log on or off, and copy the recent edits for a bug report.
+pub fn ui_session_log(log: &SessionLog, config: &mut EditorConfig, ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut config.session_log, "Session log")
+            .doc("Session log");
+        if ui
+            .add_enabled(
+                !log.is_empty(),
+                egui::Button::new(format!("Copy last {} actions", RECENT_EDITS)),
+            )
+            .clicked()
+        {
+            let recent = log.recent();
+            ui.output_mut(|output| output.copied_text = recent);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories() {
+        assert_eq!(field_changes("init_size"), ChangeFlags::INIT);
+        assert_eq!(field_changes("update_accels"), ChangeFlags::UPDATE);
+        assert_eq!(
+            field_changes("render_particle_texture"),
+            ChangeFlags::TEXTURE
+        );
+        assert_eq!(field_changes("render_set_color"), ChangeFlags::RENDER);
+        assert_eq!(field_changes("simulation_space"), ChangeFlags::SIMULATION);
+        assert_eq!(field_changes("start_delay"), ChangeFlags::SPAWNER);
+    }
+
+    #[test]
+    fn recent_bounded() {
+        let mut log = SessionLog::default();
+        for i in 0..RECENT_EDITS + 5 {
+            log.push(Edit {
+                time: UNIX_EPOCH,
+                effect: format!("{}.han", i),
+                changes: ChangeFlags::CAPACITY,
+                fields: vec![("capacity".to_string(), i.to_string())],
+            });
+        }
+        assert_eq!(log.len(), RECENT_EDITS);
+        let recent = log.recent();
+        assert!(recent.starts_with(&format!("Last {} edits:\n0 5.han CAPACITY\n", RECENT_EDITS)));
+        assert!(recent.ends_with(&format!("    capacity: {}\n", RECENT_EDITS + 4)));
+    }
+
+    #[test]
+    fn rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("han-ed/session.log");
+        append(&file, "a\n", true).unwrap();
+        append(&file, "b\n", false).unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "a\nb\n");
+
+        // The next session starts a new log and keeps the last one.
+        append(&file, "c\n", true).unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "c\n");
+        assert_eq!(
+            std::fs::read_to_string(file.with_extension("1.log")).unwrap(),
+            "a\nb\n"
+        );
+    }
+}
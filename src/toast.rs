@@ -0,0 +1,117 @@
+// Transient notifications shown in the corner of the screen. Everything that goes to the log that
+// the user should actually see goes here too.
+
+use std::sync::{Arc, Mutex};
+
+use bevy::{prelude::*, utils::Instant};
+use bevy_egui::{egui, EguiContexts};
+
+/// Seconds before info and warnings disappear. Errors stay until dismissed.
+const TOAST_DURATION: f32 = 4.0;
+/// Seconds spent fading out at the end.
+const TOAST_FADE: f32 = 1.0;
+
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Toasts>().add_system(show_toasts);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToastLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+pub struct Toast {
+    pub level: ToastLevel,
+    pub text: String,
+    pub time: Instant,
+}
+
+/// Queue of toasts. Cloning shares the queue, so it can be moved into tasks to surface async
+/// failures.
+#[derive(Resource, Default, Clone)]
+pub struct Toasts(Arc<Mutex<Vec<Toast>>>);
+
+impl Toasts {
+    pub fn push(&self, level: ToastLevel, text: impl Into<String>) {
+        self.0.lock().unwrap().push(Toast {
+            level,
+            text: text.into(),
+            time: Instant::now(),
+        });
+    }
+
+    pub fn info(&self, text: impl Into<String>) {
+        let text = text.into();
+        info!("{}", text);
+        self.push(ToastLevel::Info, text);
+    }
+
+    pub fn warn(&self, text: impl Into<String>) {
+        let text = text.into();
+        warn!("{}", text);
+        self.push(ToastLevel::Warn, text);
+    }
+
+    pub fn error(&self, text: impl Into<String>) {
+        let text = text.into();
+        error!("{}", text);
+        self.push(ToastLevel::Error, text);
+    }
+}
+
+// Stacked in the bottom right corner, newest at the bottom.
+fn show_toasts(mut contexts: EguiContexts, toasts: Res<Toasts>) {
+    let mut toasts = toasts.0.lock().unwrap();
+
+    // Expire old toasts.
+    toasts.retain(|toast| {
+        toast.level == ToastLevel::Error || toast.time.elapsed().as_secs_f32() < TOAST_DURATION
+    });
+
+    if toasts.is_empty() {
+        return;
+    }
+
+    let mut dismissed = None;
+    egui::Area::new("toasts")
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-8.0, -8.0))
+        .order(egui::Order::Foreground)
+        .show(contexts.ctx_mut(), |ui| {
+            for (i, toast) in toasts.iter().enumerate() {
+                let visuals = ui.visuals();
+                let color = match toast.level {
+                    ToastLevel::Info => visuals.text_color(),
+                    ToastLevel::Warn => visuals.warn_fg_color,
+                    ToastLevel::Error => visuals.error_fg_color,
+                };
+                let remaining = TOAST_DURATION - toast.time.elapsed().as_secs_f32();
+                let alpha = match toast.level {
+                    ToastLevel::Error => 1.0,
+                    _ => (remaining / TOAST_FADE).clamp(0.0, 1.0),
+                };
+
+                let frame = egui::Frame::popup(ui.style());
+                frame
+                    .fill(frame.fill.linear_multiply(alpha))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.colored_label(color.linear_multiply(alpha), &toast.text);
+                            if toast.level == ToastLevel::Error && ui.small_button("🗙").clicked()
+                            {
+                                dismissed = Some(i);
+                            }
+                        });
+                    });
+            }
+        });
+
+    if let Some(i) = dismissed {
+        toasts.remove(i);
+    }
+}
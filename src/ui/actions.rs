@@ -0,0 +1,1231 @@
+// Things the UI asks for that reach outside of it: spawning and despawning live effects, and
+// saving. They're queued while the UI runs and processed afterwards by [process_actions], so the
+// UI only needs the queue rather than the world.
+
+use std::path::{Path, PathBuf};
+
+use bevy::{prelude::*, render::primitives::Aabb, window::PrimaryWindow};
+use bevy_hanabi::prelude::*;
+
+use super::{defaults::NewEffectTemplate, wizard::EffectIntent};
+use crate::{
+    alive::AliveHistory,
+    asset::{circle_effect, file_slug, save_effect, EXAMPLE_TEXTURE},
+    browser::{AssetBrowser, AssetEntry, FileStatus},
+    camera::OrbitCamera,
+    capture::LogCapture,
+    change::{ChangeFlags, ContentHashes, Regeneration},
+    config::EditorConfig,
+    preview::{ground_hit, row_position, GridCell, Placement, PreviewMutes, PreviewSettings},
+    profile::DeviceLimits,
+    reffect::{ParticleTexture, REffect},
+    toast::Toasts,
+    LiveEffect,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    /// Spawn a live effect, unless one is already live. It's placed according to the preview
+    /// settings.
+    Show(Handle<REffect>),
+    /// Despawn a live effect. See [despawn_live].
+    Despawn(Entity),
+    /// Reset a live effect's spawner.
+    Reset(Entity),
+    /// Activate a live effect's spawner, for effects that don't start active.
+    Activate(Entity),
+    /// Multiply a live effect's colors by the tint, or clear the tint. The effect itself is
+    /// unchanged. See [REffect::tint].
+    Tint(Entity, Option<Vec4>),
+    /// Update the live effect after the effect changed. How much is regenerated depends on the
+    /// changes; see [ChangeFlags::regeneration].
+    Regenerate(Handle<REffect>, ChangeFlags),
+    /// Respawn all live effects from their current effect assets.
+    RestartAll,
+    /// Respawn an effect's live effects from their current effect assets, clearing their
+    /// particles.
+    Restart(Handle<REffect>),
+    /// Save an effect and mark it clean.
+    Save(Handle<REffect>),
+    /// Save effects that need it, or only the selected ones.
+    SaveAll { selected_only: bool },
+    /// Save effects that need it in the folder (not subfolders).
+    SaveFolder(PathBuf),
+    /// Spawn live effects for the loaded effects in the folder (not subfolders) that aren't live
+    /// yet, in a row.
+    ShowFolder(PathBuf),
+    /// Spawn live effects for those that aren't live yet, in a row.
+    ShowAll(Vec<Handle<REffect>>),
+    /// Export the world as a scene. See [save_scene](crate::save_scene).
+    ExportScene,
+    /// Add a new, unsaved effect and show it: the [new effect template](NewEffectTemplate), or one
+    /// generated from the intent over it.
+    New(Option<EffectIntent>),
+    /// Add a loaded effect that isn't in the browser yet as a new, unsaved effect.
+    Keep(Handle<REffect>),
+    /// Despawn an effect's live effects and drop it. For effects that aren't in the browser.
+    Discard(Handle<REffect>),
+    /// Add the example effect as a new effect, save it and show it. See
+    /// [circle_effect](crate::asset::circle_effect).
+    Example,
+    /// Add a test effect in memory, without a path, and show it. See
+    /// [spawn_circle](crate::asset::spawn_circle).
+    #[cfg(debug_assertions)]
+    SpawnCircle,
+    /// Pick effect files to open in the browser.
+    #[cfg(target_arch = "wasm32")]
+    OpenFile,
+}
+
+/// A preview tint on a live effect, kept when it's regenerated. See [Action::Tint].
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Tint(pub Vec4);
+
+/// Actions queued by the UI this frame.
+#[derive(Resource, Default)]
+pub struct Actions(Vec<Action>);
+
+impl Actions {
+    pub fn push(&mut self, action: Action) {
+        self.0.push(action);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Action> {
+        self.0.iter()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_actions(
+    mut commands: Commands,
+    mut actions: ResMut<Actions>,
+    live_effects: Query<(
+        Entity,
+        &Name,
+        &LiveEffect,
+        &ParticleEffect,
+        &Transform,
+        Option<&GridCell>,
+    )>,
+    mut spawners: Query<&mut EffectSpawner>,
+    tints: Query<&Tint>,
+    (windows, cameras): (
+        Query<&Window, With<PrimaryWindow>>,
+        Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    ),
+    mut reffects: ResMut<Assets<REffect>>,
+    mut effects: ResMut<Assets<EffectAsset>>,
+    mut alive: ResMut<AliveHistory>,
+    asset_server: Res<AssetServer>,
+    (preview, mutes, capture, toasts, config, limits, template): (
+        Res<PreviewSettings>,
+        Res<PreviewMutes>,
+        Res<LogCapture>,
+        Res<Toasts>,
+        Res<EditorConfig>,
+        Option<Res<DeviceLimits>>,
+        Res<NewEffectTemplate>,
+    ),
+    mut browser: ResMut<AssetBrowser<REffect>>,
+    mut hashes: ResMut<ContentHashes>,
+    type_registry: Res<AppTypeRegistry>,
+    #[cfg(target_arch = "wasm32")] opened_files: Res<crate::web::OpenedFiles>,
+) {
+    if actions.0.is_empty() {
+        return;
+    }
+
+    // Effects spawned by earlier actions aren't in the query yet, and those despawned still are.
+    // Later actions on a despawned entity are skipped, and those on a respawned entity go to its
+    // replacement, so hiding an effect and changing it in the same frame leaves it hidden in
+    // either order.
+    let mut shown = Vec::new();
+    let mut despawned = Vec::new();
+    let mut replaced: Vec<(Entity, Entity)> = Vec::new();
+    let resolve = |replaced: &[(Entity, Entity)], entity| {
+        replaced
+            .iter()
+            .find(|(old, _)| *old == entity)
+            .map_or(entity, |(_, new)| *new)
+    };
+    let mut cells: Vec<_> = live_effects
+        .iter()
+        .filter_map(|(.., cell)| cell.map(|c| c.0))
+        .collect();
+    // Effects too big for the adapter aren't spawned.
+    let fits = |re: &REffect| match limits.as_ref().map(|l| l.check_capacity(re)) {
+        Some(Err(e)) => {
+            toasts.warn(e);
+            false
+        }
+        _ => true,
+    };
+
+    for action in std::mem::take(&mut actions.0) {
+        // A folder is shown like any other list of effects.
+        let action = match action {
+            Action::ShowFolder(folder) => Action::ShowAll(
+                browser
+                    .entries
+                    .iter()
+                    .filter(|e| e.folder() == folder)
+                    .filter_map(|e| e.handle.clone())
+                    .collect(),
+            ),
+            action => action,
+        };
+
+        match action {
+            Action::Show(handle) => {
+                if shown.contains(&handle) || live_effects.iter().any(|(_, _, e, ..)| e.0 == handle)
+                {
+                    continue;
+                }
+                let Some(re) = reffects.get(&handle) else {
+                    continue;
+                };
+                if !fits(re) {
+                    continue;
+                }
+
+                capture.regenerated(&re.name);
+                let (translation, cell) = match preview.placement {
+                    Placement::Origin => (Vec3::ZERO, None),
+                    Placement::Grid => {
+                        let cell = GridCell::free(&cells);
+                        (cell.position(preview.grid_spacing), Some(cell))
+                    }
+                    Placement::Cursor => {
+                        (cursor_ground(&windows, &cameras).unwrap_or_default(), None)
+                    }
+                };
+                let entity = spawn(
+                    &mut commands,
+                    &mut effects,
+                    &asset_server,
+                    re,
+                    &handle,
+                    Transform::from_translation(translation),
+                    (&preview, &mutes),
+                );
+                if let Some(cell) = cell {
+                    commands.entity(entity).insert(cell);
+                    cells.push(cell.0);
+                }
+                shown.push(handle);
+            }
+            Action::Despawn(original) => {
+                let entity = resolve(&replaced, original);
+                if despawned.contains(&entity) {
+                    continue;
+                }
+                // Replacements spawned this frame aren't in the query; their assets are dropped
+                // with the entity.
+                let effect = live_effects.get(entity).ok().map(|(.., e, _, _)| &e.handle);
+                despawn_live(entity, effect, &mut commands, &mut effects, &mut alive);
+                despawned.push(entity);
+                // Free the grid cell for effects shown later this frame. A replacement kept the
+                // original's cell.
+                if let Ok((.., Some(cell))) = live_effects.get(original) {
+                    cells.retain(|c| *c != cell.0);
+                }
+            }
+            Action::Reset(entity) => {
+                let entity = resolve(&replaced, entity);
+                if despawned.contains(&entity) {
+                    continue;
+                }
+                if let Ok(mut spawner) = spawners.get_mut(entity) {
+                    spawner.reset();
+                    preview.reseed(&mut commands);
+                }
+            }
+            Action::Activate(entity) => {
+                let entity = resolve(&replaced, entity);
+                if despawned.contains(&entity) {
+                    continue;
+                }
+                if let Ok(mut spawner) = spawners.get_mut(entity) {
+                    spawner.set_active(true);
+                }
+            }
+            Action::Tint(entity, tint) => {
+                let entity = resolve(&replaced, entity);
+                if despawned.contains(&entity) {
+                    continue;
+                }
+                let Ok((_, _, live_effect, effect, ..)) = live_effects.get(entity) else {
+                    continue;
+                };
+                let Some(re) = reffects.get(&live_effect.0) else {
+                    continue;
+                };
+
+                // Colors are render changes, applied in place.
+                let tinted = tint.map(|tint| re.tinted(tint));
+                if let Some(effect) = effects.get_mut(&effect.handle) {
+                    *effect = tinted
+                        .as_ref()
+                        .unwrap_or(re)
+                        .to_effect_asset_muted(&asset_server, mutes.muted(&live_effect.0));
+                }
+                match tint {
+                    Some(tint) => commands.entity(entity).insert(Tint(tint)),
+                    None => commands.entity(entity).remove::<Tint>(),
+                };
+            }
+            Action::Regenerate(handle, changes) => {
+                let Some(re) = reffects.get(&handle) else {
+                    continue;
+                };
+                if changes.regeneration() != Regeneration::Entity && !fits(re) {
+                    continue;
+                }
+
+                for (entity, _, _, effect, transform, cell) in live_effects
+                    .iter()
+                    .filter(|(entity, _, e, ..)| e.0 == handle && !despawned.contains(entity))
+                {
+                    if changes.contains(ChangeFlags::META) {
+                        commands.entity(entity).insert(Name::new(re.name.clone()));
+                    }
+                    if changes.contains(ChangeFlags::BOUNDS) {
+                        set_bounds(&mut commands.entity(entity), re);
+                    }
+
+                    let regeneration = changes.regeneration();
+                    if regeneration == Regeneration::Entity {
+                        // Nothing else to regenerate.
+                        continue;
+                    }
+
+                    let tint = tints.get(entity).ok().copied();
+                    let tinted = tint.map(|tint| re.tinted(tint.0));
+                    let re = tinted.as_ref().unwrap_or(re);
+
+                    capture.regenerated(&re.name);
+                    if regeneration == Regeneration::InPlace {
+                        // Replace the asset under the same handle so the entity (and spawner
+                        // state) is kept.
+                        if let Some(effect) = effects.get_mut(&effect.handle) {
+                            *effect = re.to_effect_asset_muted(&asset_server, mutes.muted(&handle));
+                        }
+                    } else {
+                        // This is just hide/show, keeping the placement.
+                        despawn_live(
+                            entity,
+                            Some(&effect.handle),
+                            &mut commands,
+                            &mut effects,
+                            &mut alive,
+                        );
+                        despawned.push(entity);
+                        let new = spawn(
+                            &mut commands,
+                            &mut effects,
+                            &asset_server,
+                            re,
+                            &handle,
+                            *transform,
+                            (&preview, &mutes),
+                        );
+                        if let Some(cell) = cell {
+                            commands.entity(new).insert(*cell);
+                        }
+                        if let Some(tint) = tint {
+                            commands.entity(new).insert(tint);
+                        }
+                        replaced.push((entity, new));
+                    }
+                }
+            }
+            Action::RestartAll | Action::Restart(_) => {
+                let only = match &action {
+                    Action::Restart(handle) => Some(handle),
+                    _ => None,
+                };
+                for (entity, name, live_effect, effect, transform, cell) in
+                    live_effects.iter().filter(|(entity, _, live_effect, ..)| {
+                        !despawned.contains(entity) && only.map_or(true, |h| live_effect.0 == *h)
+                    })
+                {
+                    // The new entity takes over the effect asset.
+                    despawn_live(entity, None, &mut commands, &mut effects, &mut alive);
+                    despawned.push(entity);
+                    let mut new = commands.spawn((
+                        ParticleEffectBundle {
+                            transform: *transform,
+                            ..ParticleEffectBundle::new(effect.handle.clone())
+                        },
+                        LiveEffect(live_effect.0.clone()),
+                        name.clone(),
+                    ));
+                    if let Some(cell) = cell {
+                        new.insert(*cell);
+                    }
+                    if let Ok(tint) = tints.get(entity) {
+                        new.insert(*tint);
+                    }
+                    if let Some(re) = reffects.get(&live_effect.0) {
+                        set_bounds(&mut new, re);
+                    }
+                    replaced.push((entity, new.id()));
+                }
+            }
+            Action::Save(handle) => {
+                let root_path = browser.root_path.clone();
+                let Some(entry) = browser
+                    .entries
+                    .iter_mut()
+                    .find(|e| e.handle.as_ref() == Some(&handle))
+                else {
+                    continue;
+                };
+                let Some(re) = reffects.get(&handle) else {
+                    continue;
+                };
+
+                save(
+                    (&handle, re),
+                    (&root_path, &entry.path),
+                    config.backups.keep,
+                    &mut entry.dirty,
+                    &mut hashes,
+                    &type_registry,
+                    &asset_server,
+                    &toasts,
+                );
+            }
+            Action::SaveAll { selected_only } => save_all(
+                &mut browser,
+                &reffects,
+                |entry| !selected_only || entry.selected,
+                &mut hashes,
+                config.backups.keep,
+                &type_registry,
+                &asset_server,
+                &toasts,
+            ),
+            Action::SaveFolder(folder) => save_all(
+                &mut browser,
+                &reffects,
+                |entry| entry.folder() == folder,
+                &mut hashes,
+                config.backups.keep,
+                &type_registry,
+                &asset_server,
+                &toasts,
+            ),
+            // Mapped to ShowAll above.
+            Action::ShowFolder(_) => (),
+            Action::ShowAll(handles) => {
+                let handles: Vec<_> = handles
+                    .into_iter()
+                    .filter(|h| reffects.contains(h))
+                    .collect();
+                let n = handles.len();
+                for (i, handle) in handles.into_iter().enumerate() {
+                    // Live already, or shown earlier this frame.
+                    if shown.contains(&handle)
+                        || live_effects.iter().any(|(_, _, e, ..)| e.0 == handle)
+                    {
+                        continue;
+                    }
+                    let Some(re) = reffects.get(&handle) else {
+                        continue;
+                    };
+                    if !fits(re) {
+                        continue;
+                    }
+
+                    capture.regenerated(&re.name);
+                    let translation = row_position(i, n, preview.grid_spacing);
+                    spawn(
+                        &mut commands,
+                        &mut effects,
+                        &asset_server,
+                        re,
+                        &handle,
+                        Transform::from_translation(translation),
+                        (&preview, &mutes),
+                    );
+                    shown.push(handle);
+                }
+            }
+            Action::ExportScene => commands.add(crate::save_scene),
+            Action::New(intent) => {
+                let re = match intent {
+                    Some(intent) => intent.effect_from(&template.0),
+                    None => template.0.clone(),
+                };
+                let handle = reffects.add(re);
+                keep(&handle, &reffects, &mut browser, &toasts);
+                // Processed next frame.
+                actions.push(Action::Show(handle));
+            }
+            Action::Keep(handle) => keep(&handle, &reffects, &mut browser, &toasts),
+            Action::Discard(handle) => {
+                for (entity, _, _, effect, ..) in
+                    live_effects.iter().filter(|(_, _, e, ..)| e.0 == handle)
+                {
+                    let entity = resolve(&replaced, entity);
+                    if !despawned.contains(&entity) {
+                        despawn_live(
+                            entity,
+                            Some(&effect.handle),
+                            &mut commands,
+                            &mut effects,
+                            &mut alive,
+                        );
+                        despawned.push(entity);
+                    }
+                }
+                reffects.remove(&handle);
+            }
+            Action::Example => {
+                // Without the texture, rather than a missing one.
+                let texture = match browser.root_path.join(EXAMPLE_TEXTURE).exists() {
+                    true => asset_server.load(EXAMPLE_TEXTURE).into(),
+                    false => ParticleTexture::None,
+                };
+                let handle = reffects.add(REffect {
+                    name: "example".to_string(),
+                    ..circle_effect(texture)
+                });
+                keep(&handle, &reffects, &mut browser, &toasts);
+                // Processed next frame.
+                actions.push(Action::Save(handle.clone()));
+                actions.push(Action::Show(handle));
+            }
+            #[cfg(debug_assertions)]
+            Action::SpawnCircle => {
+                let handle = crate::asset::spawn_circle(&asset_server, &mut reffects);
+                // Adopted by the browser and shown next frame.
+                actions.push(Action::Show(handle));
+            }
+            #[cfg(target_arch = "wasm32")]
+            Action::OpenFile => {
+                if let Err(e) = crate::web::open_file(opened_files.clone()) {
+                    toasts.error(format!("failed to open file: {:?}", e));
+                }
+            }
+        }
+    }
+}
+
+/// Tear down a live effect: despawn it with its children, drop its effect asset, and forget its
+/// particle history. Every way a live effect goes away (hiding, the Live list, discarding and
+/// respawning to regenerate) goes through here.
+pub fn despawn_live(
+    entity: Entity,
+    effect: Option<&Handle<EffectAsset>>,
+    commands: &mut Commands,
+    effects: &mut Assets<EffectAsset>,
+    alive: &mut AliveHistory,
+) {
+    if let Some(entity) = commands.get_entity(entity) {
+        entity.despawn_recursive();
+    }
+    if let Some(effect) = effect {
+        effects.remove(effect);
+    }
+    alive.remove(entity);
+}
+
+fn spawn(
+    commands: &mut Commands,
+    effects: &mut Assets<EffectAsset>,
+    asset_server: &AssetServer,
+    re: &REffect,
+    handle: &Handle<REffect>,
+    transform: Transform,
+    (preview, mutes): (&PreviewSettings, &PreviewMutes),
+) -> Entity {
+    // The spawner is activated later, see [crate::preview].
+    let mut effect = re.to_effect_asset_muted(asset_server, mutes.muted(handle));
+    if preview.starts_delayed(re) {
+        effect.spawner.starts_active = false;
+    }
+
+    let mut entity = commands.spawn((
+        ParticleEffectBundle {
+            transform,
+            ..ParticleEffectBundle::new(effects.add(effect))
+        },
+        LiveEffect(handle.clone()),
+        Name::new(re.name.clone()),
+    ));
+    set_bounds(&mut entity, re);
+    entity.id()
+}
+
+/// Cull the live effect by its bounds, if set.
+pub fn set_bounds(entity: &mut bevy::ecs::system::EntityCommands, re: &REffect) {
+    match re.bounds {
+        Some(bounds) => entity.insert(Aabb::from_min_max(bounds.min(), bounds.max())),
+        None => entity.remove::<Aabb>(),
+    };
+}
+
+// Add an effect to the browser under a new path, unsaved.
+fn keep(
+    handle: &Handle<REffect>,
+    reffects: &Assets<REffect>,
+    browser: &mut AssetBrowser<REffect>,
+    toasts: &Toasts,
+) {
+    let Some(re) = reffects.get(handle) else {
+        return;
+    };
+    let path = browser.new_path(&file_slug(&re.name));
+    browser.insert(path.clone(), handle.clone());
+    browser.mark_dirty(&path);
+    toasts.info(format!("new effect {}", path.display()));
+}
+
+// Where the cursor points at the ground, for [Placement::Cursor].
+fn cursor_ground(
+    windows: &Query<&Window, With<PrimaryWindow>>,
+    cameras: &Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+) -> Option<Vec3> {
+    let cursor = windows.get_single().ok()?.cursor_position()?;
+    let (camera, transform) = cameras.get_single().ok()?;
+    ground_hit(camera.viewport_to_world(transform, cursor)?)
+}
+
+// Save an effect and mark it clean. Writing happens in a task, which reports errors.
+#[allow(clippy::too_many_arguments)]
+fn save(
+    (handle, re): (&Handle<REffect>, &REffect),
+    (root_path, path): (&Path, &Path),
+    backups: usize,
+    dirty: &mut bool,
+    hashes: &mut ContentHashes,
+    type_registry: &AppTypeRegistry,
+    asset_server: &AssetServer,
+    toasts: &Toasts,
+) {
+    // Clone some things so they can be processed in a different thread.
+    match save_effect(
+        re.clone(),
+        (root_path, path),
+        backups,
+        type_registry.clone(),
+        asset_server,
+        toasts,
+    ) {
+        // The file state is updated on the next refresh.
+        Ok(_) => {
+            *dirty = false;
+            hashes.insert(handle, re);
+        }
+        Err(e) => e.toast("error saving", toasts),
+    }
+}
+
+// Save loaded effects that need it, of those matching the filter.
+#[allow(clippy::too_many_arguments)]
+fn save_all(
+    browser: &mut AssetBrowser<REffect>,
+    reffects: &Assets<REffect>,
+    filter: impl Fn(&AssetEntry<REffect>) -> bool,
+    hashes: &mut ContentHashes,
+    backups: usize,
+    type_registry: &AppTypeRegistry,
+    asset_server: &AssetServer,
+    toasts: &Toasts,
+) {
+    let root_path = browser.root_path.clone();
+    for entry in browser.entries.iter_mut() {
+        if entry.status() == FileStatus::Clean || !filter(entry) {
+            continue;
+        }
+        let Some(handle) = entry.handle.as_ref() else {
+            continue;
+        };
+        let Some(re) = reffects.get(handle) else {
+            continue;
+        };
+        save(
+            (handle, re),
+            (&root_path, &entry.path),
+            backups,
+            &mut entry.dirty,
+            hashes,
+            type_registry,
+            asset_server,
+            toasts,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reffect::EffectBounds;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<REffect>()
+            .add_asset::<EffectAsset>()
+            .init_resource::<Actions>()
+            .init_resource::<PreviewSettings>()
+            .init_resource::<PreviewMutes>()
+            .init_resource::<LogCapture>()
+            .init_resource::<Toasts>()
+            .init_resource::<ContentHashes>()
+            .init_resource::<EditorConfig>()
+            .init_resource::<AliveHistory>()
+            .init_resource::<NewEffectTemplate>()
+            .insert_resource(AssetBrowser::<REffect>::new("assets", "han"))
+            .add_system(process_actions);
+        app
+    }
+
+    fn add(app: &mut App, name: &str) -> Handle<REffect> {
+        app.world.resource_mut::<Assets<REffect>>().add(REffect {
+            name: name.to_string(),
+            ..default()
+        })
+    }
+
+    // Queue actions and run them.
+    fn run(app: &mut App, actions: impl IntoIterator<Item = Action>) {
+        app.world.resource_mut::<Actions>().0.extend(actions);
+        app.update();
+    }
+
+    fn live(app: &mut App) -> Vec<(Entity, String, Handle<REffect>)> {
+        app.world
+            .query::<(Entity, &Name, &LiveEffect)>()
+            .iter(&app.world)
+            .map(|(entity, name, e)| (entity, name.to_string(), e.0.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn show_spawns_live_effect() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(&mut app, [Action::Show(handle.clone())]);
+
+        let live = live(&mut app);
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].1, "spark");
+        assert_eq!(live[0].2, handle);
+        assert!(app.world.resource::<Actions>().0.is_empty());
+    }
+
+    #[test]
+    fn show_is_idempotent() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(
+            &mut app,
+            [Action::Show(handle.clone()), Action::Show(handle.clone())],
+        );
+        run(&mut app, [Action::Show(handle)]);
+
+        assert_eq!(live(&mut app).len(), 1);
+    }
+
+    #[test]
+    fn despawn_removes_live_effect() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(&mut app, [Action::Show(handle)]);
+        let entity = live(&mut app)[0].0;
+
+        // Despawning twice is harmless.
+        run(&mut app, [Action::Despawn(entity), Action::Despawn(entity)]);
+        assert!(live(&mut app).is_empty());
+    }
+
+    #[test]
+    fn regenerate_respawns() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(&mut app, [Action::Show(handle.clone())]);
+        let entity = live(&mut app)[0].0;
+
+        run(
+            &mut app,
+            [Action::Regenerate(handle.clone(), ChangeFlags::SPAWNER)],
+        );
+        let live = live(&mut app);
+        assert_eq!(live.len(), 1);
+        assert_ne!(live[0].0, entity);
+        assert_eq!(live[0].2, handle);
+    }
+
+    // Despawning drops the entity's children, its effect asset and its particle history.
+    #[test]
+    fn despawn_cleans_up() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(&mut app, [Action::Show(handle.clone())]);
+        let entity = live(&mut app)[0].0;
+        let child = app.world.spawn_empty().id();
+        app.world.entity_mut(entity).add_child(child);
+        app.world
+            .resource_mut::<AliveHistory>()
+            .0
+            .insert(entity, default());
+        assert_eq!(app.world.resource::<Assets<EffectAsset>>().len(), 1);
+
+        run(&mut app, [Action::Despawn(entity)]);
+        assert!(live(&mut app).is_empty());
+        assert!(app.world.get_entity(child).is_none());
+        assert_eq!(app.world.resource::<Assets<EffectAsset>>().len(), 0);
+        assert!(app.world.resource::<AliveHistory>().get(entity).is_none());
+    }
+
+    // Restarting drops the children and history, but the new entity keeps the effect asset.
+    #[test]
+    fn restart_cleans_up() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(&mut app, [Action::Show(handle.clone())]);
+        let entity = live(&mut app)[0].0;
+        let child = app.world.spawn_empty().id();
+        app.world.entity_mut(entity).add_child(child);
+        app.world
+            .resource_mut::<AliveHistory>()
+            .0
+            .insert(entity, default());
+
+        run(&mut app, [Action::RestartAll]);
+        let live = live(&mut app);
+        assert_eq!(live.len(), 1);
+        assert!(app.world.get_entity(child).is_none());
+        assert!(app.world.resource::<AliveHistory>().get(entity).is_none());
+        let effects = app.world.resource::<Assets<EffectAsset>>();
+        assert_eq!(effects.len(), 1);
+        let effect = app.world.get::<ParticleEffect>(live[0].0).unwrap();
+        assert!(effects.contains(&effect.handle));
+    }
+
+    #[test]
+    fn tint_kept_until_cleared() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(&mut app, [Action::Show(handle.clone())]);
+        let entity = live(&mut app)[0].0;
+        let blue = Vec4::new(0.0, 0.0, 1.0, 1.0);
+
+        let tints = |app: &mut App| {
+            app.world
+                .query::<&Tint>()
+                .iter(&app.world)
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        run(&mut app, [Action::Tint(entity, Some(blue))]);
+        assert_eq!(tints(&mut app), vec![Tint(blue)]);
+        // In place.
+        assert_eq!(live(&mut app)[0].0, entity);
+        assert_eq!(app.world.resource::<Assets<EffectAsset>>().len(), 1);
+        // The effect itself isn't tinted.
+        let reffects = app.world.resource::<Assets<REffect>>();
+        assert!(reffects.get(&handle).unwrap().render_set_color.is_none());
+
+        run(
+            &mut app,
+            [Action::Regenerate(handle.clone(), ChangeFlags::SPAWNER)],
+        );
+        assert_eq!(tints(&mut app), vec![Tint(blue)]);
+
+        let entity = live(&mut app)[0].0;
+        run(&mut app, [Action::Tint(entity, None)]);
+        assert!(tints(&mut app).is_empty());
+        assert_eq!(live(&mut app).len(), 1);
+    }
+
+    // Respawning to regenerate replaces the effect asset rather than adding another.
+    #[test]
+    fn regenerate_replaces_asset() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(&mut app, [Action::Show(handle.clone())]);
+        run(
+            &mut app,
+            [Action::Regenerate(handle.clone(), ChangeFlags::SPAWNER)],
+        );
+        assert_eq!(live(&mut app).len(), 1);
+        assert_eq!(app.world.resource::<Assets<EffectAsset>>().len(), 1);
+
+        run(&mut app, [Action::Discard(handle)]);
+        assert!(live(&mut app).is_empty());
+        assert_eq!(app.world.resource::<Assets<EffectAsset>>().len(), 0);
+    }
+
+    // Hiding an effect and changing it in the same frame leaves it hidden, in either order.
+    #[test]
+    fn despawn_and_regenerate_same_frame() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(&mut app, [Action::Show(handle.clone())]);
+        let entity = live(&mut app)[0].0;
+
+        run(
+            &mut app,
+            [
+                Action::Despawn(entity),
+                Action::Regenerate(handle.clone(), ChangeFlags::SPAWNER),
+                Action::Reset(entity),
+                Action::Activate(entity),
+                Action::Tint(entity, Some(Vec4::ONE)),
+                Action::RestartAll,
+            ],
+        );
+        assert!(live(&mut app).is_empty());
+
+        run(&mut app, [Action::Show(handle.clone())]);
+        let entity = live(&mut app)[0].0;
+        run(
+            &mut app,
+            [
+                Action::Regenerate(handle.clone(), ChangeFlags::SPAWNER),
+                Action::Despawn(entity),
+            ],
+        );
+        assert!(live(&mut app).is_empty());
+    }
+
+    #[test]
+    fn bounds_set_aabb() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(&mut app, [Action::Show(handle.clone())]);
+        let entity = live(&mut app)[0].0;
+        assert!(app.world.get::<Aabb>(entity).is_none());
+
+        let bounds = EffectBounds::default();
+        app.world
+            .resource_mut::<Assets<REffect>>()
+            .get_mut(&handle)
+            .unwrap()
+            .bounds = Some(bounds);
+        run(&mut app, [Action::Regenerate(handle, ChangeFlags::BOUNDS)]);
+        assert_eq!(live(&mut app)[0].0, entity);
+        let aabb = app.world.get::<Aabb>(entity).unwrap();
+        assert_eq!(Vec3::from(aabb.half_extents), bounds.half_extents);
+    }
+
+    #[test]
+    fn regenerate_in_place_keeps_entity() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(&mut app, [Action::Show(handle.clone())]);
+        let entity = live(&mut app)[0].0;
+
+        app.world
+            .resource_mut::<Assets<REffect>>()
+            .get_mut(&handle)
+            .unwrap()
+            .capacity = 1234;
+        run(
+            &mut app,
+            [Action::Regenerate(handle.clone(), ChangeFlags::TEXTURE)],
+        );
+        assert_eq!(live(&mut app)[0].0, entity);
+
+        let effect = app
+            .world
+            .get::<ParticleEffect>(entity)
+            .unwrap()
+            .handle
+            .clone();
+        let effects = app.world.resource::<Assets<EffectAsset>>();
+        assert_eq!(effects.get(&effect).unwrap().capacity, 1234);
+    }
+
+    // Render changes keep the spawner going; init changes start it over.
+    #[test]
+    fn regenerate_render_keeps_spawner() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(&mut app, [Action::Show(handle.clone())]);
+        let entity = live(&mut app)[0].0;
+
+        run(
+            &mut app,
+            [Action::Regenerate(handle.clone(), ChangeFlags::RENDER)],
+        );
+        assert_eq!(live(&mut app)[0].0, entity);
+        assert_eq!(app.world.resource::<Assets<EffectAsset>>().len(), 1);
+
+        run(&mut app, [Action::Regenerate(handle, ChangeFlags::INIT)]);
+        assert_ne!(live(&mut app)[0].0, entity);
+        assert_eq!(app.world.resource::<Assets<EffectAsset>>().len(), 1);
+    }
+
+    #[test]
+    fn regenerate_meta_renames() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(&mut app, [Action::Show(handle.clone())]);
+        let entity = live(&mut app)[0].0;
+
+        app.world
+            .resource_mut::<Assets<REffect>>()
+            .get_mut(&handle)
+            .unwrap()
+            .name = "fizz".to_string();
+        run(&mut app, [Action::Regenerate(handle, ChangeFlags::META)]);
+
+        let live = live(&mut app);
+        assert_eq!(live[0].0, entity);
+        assert_eq!(live[0].1, "fizz");
+    }
+
+    #[test]
+    fn regenerate_hidden_does_nothing() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(&mut app, [Action::Regenerate(handle, ChangeFlags::all())]);
+        assert!(live(&mut app).is_empty());
+    }
+
+    #[test]
+    fn restart_all_respawns_each() {
+        let mut app = app();
+        let a = add(&mut app, "a");
+        let b = add(&mut app, "b");
+        run(&mut app, [Action::Show(a), Action::Show(b)]);
+        let before: Vec<_> = live(&mut app).into_iter().map(|(e, ..)| e).collect();
+
+        run(&mut app, [Action::RestartAll]);
+        let after = live(&mut app);
+        assert_eq!(after.len(), 2);
+        assert!(after.iter().all(|(e, ..)| !before.contains(e)));
+    }
+
+    // Restarting one effect, e.g. to scrub it, keeps its placement and tint.
+    #[test]
+    fn restart_keeps_placement() {
+        let mut app = app();
+        app.world.resource_mut::<PreviewSettings>().placement = Placement::Grid;
+        let (a, b) = (add(&mut app, "a"), add(&mut app, "b"));
+        run(&mut app, [Action::Show(a.clone()), Action::Show(b.clone())]);
+        let find = |app: &mut App, handle: &Handle<REffect>| {
+            live(app).into_iter().find(|l| l.2 == *handle).unwrap().0
+        };
+        let (entity, other) = (find(&mut app, &b), find(&mut app, &a));
+        let moved = Transform::from_xyz(1.0, 2.0, 3.0);
+        *app.world.get_mut::<Transform>(entity).unwrap() = moved;
+        run(&mut app, [Action::Tint(entity, Some(Vec4::ONE))]);
+
+        run(&mut app, [Action::Restart(b.clone())]);
+        let new = find(&mut app, &b);
+        assert_ne!(new, entity);
+        assert_eq!(find(&mut app, &a), other);
+        assert_eq!(*app.world.get::<Transform>(new).unwrap(), moved);
+        assert_eq!(app.world.get::<Tint>(new), Some(&Tint(Vec4::ONE)));
+        assert_eq!(grid_cell(&mut app, &b), 1);
+    }
+
+    #[test]
+    fn new_adds_unsaved_effect() {
+        let mut app = app();
+        run(&mut app, [Action::New(None)]);
+
+        let browser = app.world.resource::<AssetBrowser<REffect>>();
+        let entry = browser.entry(Path::new("new_continuous.han")).unwrap();
+        assert_eq!(entry.status(), FileStatus::NeverSaved);
+        assert!(entry.dirty);
+        let handle = entry.handle.clone().unwrap();
+
+        app.update();
+        let live = live(&mut app);
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].2, handle);
+    }
+
+    #[test]
+    fn new_uses_template() {
+        let mut app = app();
+        app.world.resource_mut::<NewEffectTemplate>().0.capacity = 8192;
+        run(&mut app, [Action::New(None)]);
+
+        let browser = app.world.resource::<AssetBrowser<REffect>>();
+        let entry = browser.entry(Path::new("new_continuous.han")).unwrap();
+        let handle = entry.handle.clone().unwrap();
+        let reffects = app.world.resource::<Assets<REffect>>();
+        assert_eq!(reffects.get(&handle).unwrap().capacity, 8192);
+    }
+
+    // The example is added like a new effect, then saved and shown next frame.
+    #[test]
+    fn example_saved_and_shown() {
+        let mut app = app();
+        run(&mut app, [Action::Example]);
+
+        let browser = app.world.resource::<AssetBrowser<REffect>>();
+        let entry = browser.entry(Path::new("example.han")).unwrap();
+        assert_eq!(entry.status(), FileStatus::NeverSaved);
+        let handle = entry.handle.clone().unwrap();
+        assert_eq!(
+            app.world.resource::<Actions>().0,
+            vec![Action::Save(handle.clone()), Action::Show(handle)]
+        );
+    }
+
+    #[test]
+    fn show_folder_skips_live() {
+        let mut app = app();
+        let mut handles = Vec::new();
+        for path in ["fx/a.han", "fx/b.han", "other/c.han"] {
+            let handle = add(&mut app, path);
+            app.world
+                .resource_mut::<AssetBrowser<REffect>>()
+                .insert(path.into(), handle.clone());
+            handles.push(handle);
+        }
+
+        run(&mut app, [Action::Show(handles[0].clone())]);
+        run(
+            &mut app,
+            [
+                Action::ShowFolder("fx".into()),
+                Action::ShowFolder("fx".into()),
+            ],
+        );
+
+        let mut live: Vec<_> = live(&mut app).into_iter().map(|(.., h)| h).collect();
+        live.sort();
+        assert_eq!(live, handles[..2]);
+    }
+
+    #[test]
+    fn keep_and_discard() {
+        let mut app = app();
+        let (a, b) = (add(&mut app, "a ~1"), add(&mut app, "a ~2"));
+        run(&mut app, [Action::ShowAll(vec![a.clone(), b.clone()])]);
+        assert_eq!(live(&mut app).len(), 2);
+
+        run(
+            &mut app,
+            [Action::Keep(a.clone()), Action::Discard(b.clone())],
+        );
+        let live = live(&mut app);
+        assert_eq!(live.len(), 1);
+        assert_eq!(live[0].2, a);
+        assert!(!app.world.resource::<Assets<REffect>>().contains(&b));
+
+        let browser = app.world.resource::<AssetBrowser<REffect>>();
+        let entry = browser.entry(Path::new("a_1.han")).unwrap();
+        assert_eq!(entry.handle.as_ref(), Some(&a));
+        assert!(entry.dirty);
+    }
+
+    fn grid_cell(app: &mut App, handle: &Handle<REffect>) -> usize {
+        app.world
+            .query::<(&LiveEffect, &GridCell)>()
+            .iter(&app.world)
+            .find_map(|(e, cell)| (e.0 == *handle).then_some(cell.0))
+            .unwrap()
+    }
+
+    #[test]
+    fn grid_placement_frees_cells() {
+        let mut app = app();
+        app.world.resource_mut::<PreviewSettings>().placement = Placement::Grid;
+        let (a, b, c) = (add(&mut app, "a"), add(&mut app, "b"), add(&mut app, "c"));
+        run(&mut app, [Action::Show(a.clone()), Action::Show(b.clone())]);
+        assert_eq!(grid_cell(&mut app, &a), 0);
+        assert_eq!(grid_cell(&mut app, &b), 1);
+
+        // Hiding frees the cell for the next effect shown.
+        let entity = live(&mut app).into_iter().find(|l| l.2 == a).unwrap().0;
+        run(&mut app, [Action::Despawn(entity), Action::Show(c.clone())]);
+        assert_eq!(grid_cell(&mut app, &c), 0);
+    }
+
+    #[test]
+    fn regenerate_keeps_transform() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        run(&mut app, [Action::Show(handle.clone())]);
+        let entity = live(&mut app)[0].0;
+
+        let moved = Transform::from_xyz(1.0, 2.0, 3.0);
+        *app.world.get_mut::<Transform>(entity).unwrap() = moved;
+        run(&mut app, [Action::Regenerate(handle, ChangeFlags::SPAWNER)]);
+
+        let entity = live(&mut app)[0].0;
+        assert_eq!(*app.world.get::<Transform>(entity).unwrap(), moved);
+    }
+
+    #[test]
+    fn too_big_not_spawned() {
+        let mut app = app();
+        // Room for 100 particles of the default effect, which only has positions.
+        app.insert_resource(DeviceLimits {
+            max_storage_buffer: 12 * 100,
+        });
+        let handle = add(&mut app, "spark");
+        app.world
+            .resource_mut::<Assets<REffect>>()
+            .get_mut(&handle)
+            .unwrap()
+            .capacity = 100;
+        run(&mut app, [Action::Show(handle.clone())]);
+        assert_eq!(live(&mut app).len(), 1);
+
+        // Over the limit, the live effect is left as it was.
+        let entity = live(&mut app)[0].0;
+        app.world
+            .resource_mut::<Assets<REffect>>()
+            .get_mut(&handle)
+            .unwrap()
+            .capacity = 101;
+        run(
+            &mut app,
+            [Action::Regenerate(handle, ChangeFlags::CAPACITY)],
+        );
+        assert_eq!(live(&mut app)[0].0, entity);
+    }
+
+    #[test]
+    fn muted_modifiers_left_out() {
+        let mut app = app();
+        let handle = add(&mut app, "spark");
+        app.world
+            .resource_mut::<Assets<REffect>>()
+            .get_mut(&handle)
+            .unwrap()
+            .init_velocity = Some(default());
+        let modifiers = |app: &mut App| {
+            let entity = live(app)[0].0;
+            let effect = app
+                .world
+                .get::<ParticleEffect>(entity)
+                .unwrap()
+                .handle
+                .clone();
+            app.world
+                .resource::<Assets<EffectAsset>>()
+                .get(&effect)
+                .unwrap()
+                .modifiers
+                .len()
+        };
+        run(&mut app, [Action::Show(handle.clone())]);
+        assert_eq!(modifiers(&mut app), 2);
+
+        app.world
+            .resource_mut::<PreviewMutes>()
+            .toggle(&handle, "init_velocity");
+        run(
+            &mut app,
+            [Action::Regenerate(handle.clone(), ChangeFlags::INIT)],
+        );
+        assert_eq!(modifiers(&mut app), 1);
+        // The effect is unchanged.
+        let reffects = app.world.resource::<Assets<REffect>>();
+        assert!(reffects.get(&handle).unwrap().init_velocity.is_some());
+    }
+}
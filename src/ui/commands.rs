@@ -0,0 +1,181 @@
+// The editor's commands, shared by the menu bar and the command palette so anything in the menus
+// can also be run by name. Running a command through either remembers it, and the palette ranks
+// recent commands first.
+
+use std::{borrow::Cow, path::PathBuf};
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use super::{
+    actions::{Action, Actions},
+    wizard::{open_wizard, EffectIntent, Style},
+    WINDOW,
+};
+use crate::{
+    browser::AssetBrowser,
+    camera::{OrbitCamera, ViewPreset},
+    quit,
+    reffect::REffect,
+    toast::Toasts,
+};
+
+/// Memory id for the labels of recently run commands, most recent first.
+const RECENT: &str = "recent_commands";
+
+/// Recent commands remembered.
+pub const MAX_RECENT: usize = 8;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    New,
+    /// A new effect from the wizard's defaults for the style.
+    NewFrom(Style),
+    Wizard,
+    #[cfg(not(target_arch = "wasm32"))]
+    Open,
+    #[cfg(not(target_arch = "wasm32"))]
+    Library,
+    /// Save the selected effects.
+    Save,
+    SaveAll,
+    ExportScene,
+    Quit,
+    ToggleWindow,
+    View(ViewPreset),
+    ToggleOrthographic,
+    Show(PathBuf, Handle<REffect>),
+    #[cfg(debug_assertions)]
+    AddInMemory,
+}
+
+impl Command {
+    pub fn label(&self) -> Cow<'static, str> {
+        match self {
+            Self::New => "New".into(),
+            Self::NewFrom(style) => format!("New {} effect", style.label()).into(),
+            Self::Wizard => "New (wizard)…".into(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Open => "Open…".into(),
+            #[cfg(not(target_arch = "wasm32"))]
+            Self::Library => "Library…".into(),
+            Self::Save => "Save".into(),
+            Self::SaveAll => "Save All".into(),
+            Self::ExportScene => "Export Scene".into(),
+            Self::Quit => "Quit".into(),
+            Self::ToggleWindow => "Editor window".into(),
+            Self::View(preset) => format!("{} view", preset.label()).into(),
+            Self::ToggleOrthographic => "Orthographic".into(),
+            Self::Show(path, _) => format!("Show {}", path.display()).into(),
+            #[cfg(debug_assertions)]
+            Self::AddInMemory => "Add in-memory effect".into(),
+        }
+    }
+
+    pub fn shortcut(&self) -> Option<&'static str> {
+        match self {
+            Self::Quit => Some("Ctrl+Q"),
+            _ => None,
+        }
+    }
+
+    pub fn enabled(&self, browser: &AssetBrowser<REffect>) -> bool {
+        match self {
+            Self::Save => browser.selected().next().is_some(),
+            _ => true,
+        }
+    }
+}
+
+/// Every command, with one to show each loaded effect.
+pub fn commands(browser: &AssetBrowser<REffect>) -> Vec<Command> {
+    let mut commands = vec![
+        Command::New,
+        Command::NewFrom(Style::Burst),
+        Command::NewFrom(Style::Continuous),
+        Command::Wizard,
+        #[cfg(not(target_arch = "wasm32"))]
+        Command::Open,
+        #[cfg(not(target_arch = "wasm32"))]
+        Command::Library,
+        Command::Save,
+        Command::SaveAll,
+        Command::ExportScene,
+        Command::Quit,
+        Command::ToggleWindow,
+    ];
+    commands.extend(ViewPreset::ALL.map(Command::View));
+    commands.push(Command::ToggleOrthographic);
+    #[cfg(debug_assertions)]
+    commands.push(Command::AddInMemory);
+    commands.extend(
+        browser
+            .iter()
+            .map(|(path, handle)| Command::Show(path.to_path_buf(), handle.clone())),
+    );
+    commands.retain(|c| c.enabled(browser));
+    commands
+}
+
+/// Labels of recently run commands, most recent first.
+pub fn recent(ctx: &egui::Context) -> Vec<String> {
+    ctx.memory_mut(|memory| memory.data.get_temp(egui::Id::new(RECENT)))
+        .unwrap_or_default()
+}
+
+fn remember(label: String, ctx: &egui::Context) {
+    let mut recent = recent(ctx);
+    recent.retain(|l| *l != label);
+    recent.insert(0, label);
+    recent.truncate(MAX_RECENT);
+    ctx.memory_mut(|memory| memory.data.insert_temp(egui::Id::new(RECENT), recent));
+}
+
+#[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+pub fn run(
+    command: Command,
+    ctx: &egui::Context,
+    browser: &mut AssetBrowser<REffect>,
+    orbit: &mut OrbitCamera,
+    asset_server: &AssetServer,
+    actions: &mut Actions,
+    toasts: &Toasts,
+) {
+    remember(command.label().into_owned(), ctx);
+
+    match command {
+        Command::New => actions.push(Action::New(None)),
+        Command::NewFrom(style) => {
+            actions.push(Action::New(Some(EffectIntent { style, ..default() })))
+        }
+        Command::Wizard => open_wizard(ctx),
+        #[cfg(not(target_arch = "wasm32"))]
+        Command::Open => {
+            if let Err(e) = super::effects::open_effect(browser, asset_server, ctx) {
+                e.toast("failed to open effect", toasts);
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        Command::Library => super::library::open_library(ctx),
+        Command::Save => actions.push(Action::SaveAll {
+            selected_only: true,
+        }),
+        Command::SaveAll => actions.push(Action::SaveAll {
+            selected_only: false,
+        }),
+        Command::ExportScene => actions.push(Action::ExportScene),
+        Command::Quit => quit::request(ctx),
+        Command::ToggleWindow => {
+            let id = egui::Id::new(WINDOW);
+            ctx.memory_mut(|memory| {
+                let open = memory.data.get_temp::<bool>(id).unwrap_or(true);
+                memory.data.insert_temp(id, !open)
+            });
+        }
+        Command::View(preset) => orbit.preset(preset),
+        Command::ToggleOrthographic => orbit.orthographic = !orbit.orthographic,
+        Command::Show(_, handle) => actions.push(Action::Show(handle)),
+        #[cfg(debug_assertions)]
+        Command::AddInMemory => actions.push(Action::SpawnCircle),
+    }
+}
@@ -0,0 +1,342 @@
+// Defaults for modifiers when they're first enabled. They're kept as an effect with every optional
+// modifier set, serialized like an effect file into the editor config. The built-in defaults are
+// picked to show something right away, where the modifiers' own defaults are often zero. New
+// effects likewise start from a template effect kept in the config, see [NewEffectTemplate].
+
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_egui::egui::{self, CollapsingHeader, DragValue};
+use bevy_hanabi::prelude::*;
+use bevy_inspector_egui::reflect_inspector::{Context, InspectorUi};
+
+use super::{
+    actions::Actions, effects::ui_spawner, focus::FocusOrder, modifiers::ui_orientation,
+    widgets::*, wizard::EffectIntent,
+};
+use crate::{
+    asset::{deserialize_effect, serialize_effect},
+    change::*,
+    config::EditorConfig,
+    docs::Doc,
+    gradient::{ColorGradient, Gradient, SizeGradient},
+    reffect::{InitVelocity, REffect},
+};
+
+/// What New starts from, and what the wizard builds on. See
+/// [EffectIntent::effect_from](super::wizard::EffectIntent::effect_from).
+#[derive(Resource, Clone)]
+pub struct NewEffectTemplate(pub REffect);
+
+impl Default for NewEffectTemplate {
+    fn default() -> Self {
+        Self(Self::builtin())
+    }
+}
+
+impl NewEffectTemplate {
+    /// The template without one configured: the wizard's default effect.
+    pub fn builtin() -> REffect {
+        EffectIntent::default().effect()
+    }
+}
+
+/// The value each optional modifier starts with when enabled.
+#[derive(Resource, Clone)]
+pub struct ModifierDefaults(pub REffect);
+
+impl Default for ModifierDefaults {
+    fn default() -> Self {
+        Self(Self::builtin())
+    }
+}
+
+impl ModifierDefaults {
+    /// The defaults without any configured.
+    pub fn builtin() -> REffect {
+        let mut color = ColorGradient::default();
+        *color.keys_mut() = vec![(0.0, Vec4::ONE), (1.0, Vec4::new(1.0, 1.0, 1.0, 0.0))];
+        let mut size = SizeGradient::default();
+        *size.keys_mut() = vec![(0.0, Vec2::splat(0.1)), (1.0, Vec2::ZERO)];
+
+        REffect {
+            name: "defaults".to_string(),
+            init_velocity: Some(InitVelocity::default()),
+            init_size: Some(InitSizeModifier {
+                size: DimValue::D1(0.1.into()),
+            }),
+            init_age: Some(InitAgeModifier { age: 0.0.into() }),
+            init_lifetime: Some(InitLifetimeModifier {
+                lifetime: 1.0.into(),
+            }),
+            update_linear_drag: Some(LinearDragModifier { drag: 1.0 }),
+            // A 1m cube around the emitter.
+            #[cfg(feature = "aabb-kill")]
+            update_aabb_kill: Some(AabbKillModifier {
+                center: Vec3::ZERO,
+                half_size: Vec3::splat(0.5),
+                kill_inside: false,
+            }),
+            render_set_color: Some(SetColorModifier {
+                color: Vec4::ONE.into(),
+            }),
+            render_color_over_lifetime: Some(color),
+            render_set_size: Some(SetSizeModifier {
+                size: DimValue::D1(0.1.into()),
+            }),
+            render_size_over_lifetime: Some(size),
+            ..default()
+        }
+    }
+
+    /// Set any modifiers missing from configured defaults to the built-in ones.
+    fn fill_missing(mut re: REffect) -> REffect {
+        let builtin = Self::builtin();
+        re.init_velocity = re.init_velocity.or(builtin.init_velocity);
+        re.init_size = re.init_size.or(builtin.init_size);
+        re.init_age = re.init_age.or(builtin.init_age);
+        re.init_lifetime = re.init_lifetime.or(builtin.init_lifetime);
+        re.update_linear_drag = re.update_linear_drag.or(builtin.update_linear_drag);
+        #[cfg(feature = "aabb-kill")]
+        {
+            re.update_aabb_kill = re.update_aabb_kill.or(builtin.update_aabb_kill);
+        }
+        re.render_set_color = re.render_set_color.or(builtin.render_set_color);
+        re.render_color_over_lifetime = re
+            .render_color_over_lifetime
+            .or(builtin.render_color_over_lifetime);
+        re.render_set_size = re.render_set_size.or(builtin.render_set_size);
+        re.render_size_over_lifetime = re
+            .render_size_over_lifetime
+            .or(builtin.render_size_over_lifetime);
+        re
+    }
+}
+
+/// Read the configured defaults, if any.
+pub fn load_modifier_defaults(
+    config: Res<EditorConfig>,
+    type_registry: Res<AppTypeRegistry>,
+    mut defaults: ResMut<ModifierDefaults>,
+) {
+    let Some(ron) = &config.modifier_defaults else {
+        return;
+    };
+    match deserialize_effect(
+        ron.as_bytes(),
+        &type_registry.read(),
+        Path::new("modifier defaults"),
+    ) {
+        Ok(re) => defaults.0 = ModifierDefaults::fill_missing(re),
+        Err(e) => warn!("failed to read modifier defaults: {}", e),
+    }
+}
+
+/// Write the defaults back to the config when they're edited.
+pub fn store_modifier_defaults(
+    defaults: Res<ModifierDefaults>,
+    type_registry: Res<AppTypeRegistry>,
+    mut config: ResMut<EditorConfig>,
+) {
+    if !defaults.is_changed() || defaults.is_added() {
+        return;
+    }
+    match serialize_effect(&defaults.0, &type_registry.read()) {
+        Ok(ron) => config.modifier_defaults = Some(ron),
+        Err(e) => warn!("failed to write modifier defaults: {}", e),
+    }
+}
+
+/// Read the configured new effect template, if any. The built-in one is kept if it fails.
+pub fn load_new_effect_template(
+    config: Res<EditorConfig>,
+    type_registry: Res<AppTypeRegistry>,
+    mut template: ResMut<NewEffectTemplate>,
+) {
+    let Some(ron) = &config.new_effect else {
+        return;
+    };
+    match deserialize_effect(
+        ron.as_bytes(),
+        &type_registry.read(),
+        Path::new("new effect defaults"),
+    ) {
+        Ok(re) => template.0 = re,
+        Err(e) => warn!("failed to read new effect defaults: {}", e),
+    }
+}
+
+/// Write the template back to the config when it's edited.
+pub fn store_new_effect_template(
+    template: Res<NewEffectTemplate>,
+    type_registry: Res<AppTypeRegistry>,
+    mut config: ResMut<EditorConfig>,
+) {
+    if !template.is_changed() || template.is_added() {
+        return;
+    }
+    match serialize_effect(&template.0, &type_registry.read()) {
+        Ok(ron) => config.new_effect = Some(ron),
+        Err(e) => warn!("failed to write new effect defaults: {}", e),
+    }
+}
+
+// The template's capacity, lifetime, orientation and spawner, with the effect editor's widgets, and
+// a button to go back to the built-in template.
+pub fn ui_new_effect_defaults(
+    template: &mut NewEffectTemplate,
+    type_registry: &AppTypeRegistry,
+    actions: &mut Actions,
+    ui: &mut egui::Ui,
+) -> Change {
+    let mut cx = Context::default();
+    let tr = type_registry.read();
+    let mut env = InspectorUi::new(&tr, &mut cx, Some(short_circuit), None, None);
+
+    CollapsingHeader::new("New Effect Defaults")
+        .show(ui, |ui| {
+            let re = &mut template.0;
+            let mut changes = grid!(ui, "new_effect_defaults", |ui| {
+                row!("Capacity", ui, |ui| ui
+                    .add(DragValue::new(&mut re.capacity)))
+                    | ui_option_reflect(
+                        "Lifetime",
+                        &mut re.init_lifetime,
+                        || InitLifetimeModifier {
+                            lifetime: 1.0.into(),
+                        },
+                        &mut env,
+                        ui,
+                    )
+                    | ui_orientation(&mut re.render_orientation, ui)
+            });
+
+            let lifecycle = (re.duration(), re.steady_state_count());
+            changes = changes
+                | ui_spawner(
+                    (&mut re.spawner, &mut re.start_delay),
+                    lifecycle,
+                    "new_effect_defaults",
+                    &mut FocusOrder::default(),
+                    (None, actions),
+                    ui,
+                );
+
+            if ui
+                .button("Built-in")
+                .on_hover_text("Go back to the built-in template.")
+                .clicked()
+            {
+                *re = NewEffectTemplate::builtin();
+                changes = changes | Change::from(true);
+            }
+            changes
+        })
+        .merge()
+}
+
+// Each optional modifier with its default, and a button to go back to the built-in ones.
+pub fn ui_modifier_defaults(
+    defaults: &mut ModifierDefaults,
+    type_registry: &AppTypeRegistry,
+    ui: &mut egui::Ui,
+) -> Change {
+    let mut cx = Context::default();
+    let tr = type_registry.read();
+    let mut env = InspectorUi::new(&tr, &mut cx, Some(short_circuit), None, None);
+
+    CollapsingHeader::new("Modifier Defaults")
+        .show(ui, |ui| {
+            let re = &mut defaults.0;
+            let env = &mut env;
+            let mut changes = grid!(ui, "modifier_defaults", |ui| {
+                let changes = ui_default("Velocity", &mut re.init_velocity, env, ui)
+                    | ui_default("Size", &mut re.init_size, env, ui)
+                    | ui_default("Age", &mut re.init_age, env, ui)
+                    | ui_default("Lifetime", &mut re.init_lifetime, env, ui)
+                    | row!("Linear Drag", ui, |ui| ui.add(drag_value(
+                        &mut re.update_linear_drag.get_or_insert_with(default).drag,
+                        "/s"
+                    )));
+                #[cfg(feature = "aabb-kill")]
+                let changes = changes | ui_default("AABB Kill", &mut re.update_aabb_kill, env, ui);
+                changes
+                    | ui_default("Set Color", &mut re.render_set_color, env, ui)
+                    | row!("Color Over Lifetime", ui, |ui| re
+                        .render_color_over_lifetime
+                        .get_or_insert_with(default)
+                        .show(ui))
+                    | ui_default("Set Size", &mut re.render_set_size, env, ui)
+                    | row!("Size Over Lifetime", ui, |ui| re
+                        .render_size_over_lifetime
+                        .get_or_insert_with(default)
+                        .show(ui))
+            });
+            if ui
+                .button("Built-in")
+                .on_hover_text("Go back to the built-in defaults.")
+                .clicked()
+            {
+                *re = ModifierDefaults::builtin();
+                changes = changes | Change::from(true);
+            }
+            changes
+        })
+        .merge()
+}
+
+fn ui_default<T: Reflect + Default>(
+    label: &str,
+    value: &mut Option<T>,
+    env: &mut InspectorUi,
+    ui: &mut egui::Ui,
+) -> Change {
+    ui_reflect(label, value.get_or_insert_with(T::default), env, ui)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_not_zero() {
+        let re = ModifierDefaults::builtin();
+        assert_eq!(re.update_linear_drag.unwrap().drag, 1.0);
+        assert!(matches!(
+            re.init_lifetime.unwrap().lifetime,
+            Value::Single(lifetime) if lifetime > 0.0
+        ));
+        assert!(matches!(
+            re.render_set_size.unwrap().size,
+            DimValue::D1(Value::Single(size)) if size == 0.1
+        ));
+    }
+
+    // A template that fails to load leaves the built-in one.
+    #[test]
+    fn template_fallback() {
+        let mut app = App::new();
+        app.init_resource::<AppTypeRegistry>()
+            .insert_resource(EditorConfig {
+                new_effect: Some("(not an effect".to_string()),
+                ..default()
+            })
+            .init_resource::<NewEffectTemplate>()
+            .add_system(load_new_effect_template);
+        app.update();
+        let re = &app.world.resource::<NewEffectTemplate>().0;
+        assert_eq!(re.capacity, NewEffectTemplate::builtin().capacity);
+    }
+
+    #[test]
+    fn missing_filled() {
+        let re = ModifierDefaults::fill_missing(REffect {
+            update_linear_drag: Some(LinearDragModifier { drag: 3.0 }),
+            ..default()
+        });
+        // Configured ones are kept.
+        assert_eq!(re.update_linear_drag.unwrap().drag, 3.0);
+        assert!(re.init_velocity.is_some());
+        assert!(re.render_color_over_lifetime.is_some());
+    }
+}
@@ -0,0 +1,67 @@
+// Which effects use which textures, with links to jump to each effect.
+
+use bevy::prelude::*;
+use bevy_egui::egui::{self, CollapsingHeader};
+
+use super::{replace::open_replace, JUMP_TO};
+use crate::{asset::Dependencies, browser::AssetBrowser, reffect::REffect};
+
+pub fn ui_dependencies(
+    dependencies: &mut Option<Dependencies>,
+    reffect_paths: &mut AssetBrowser<REffect>,
+    image_paths: &AssetBrowser<Image>,
+    reffects: &Assets<REffect>,
+    asset_server: &AssetServer,
+    ui: &mut egui::Ui,
+) {
+    CollapsingHeader::new("Dependencies")
+        .default_open(false)
+        .show(ui, |ui| {
+            let mut refresh = dependencies.is_none();
+            let mut replace = false;
+            ui.horizontal(|ui| {
+                refresh |= ui.button("Refresh").clicked();
+                replace = ui
+                    .button("Replace texture references…")
+                    .on_hover_text("Point every effect using one texture at another.")
+                    .clicked();
+            });
+            // Replacing picks from the textures in use now.
+            if refresh || replace {
+                *dependencies = Some(Dependencies::new(
+                    reffect_paths,
+                    image_paths,
+                    reffects,
+                    asset_server,
+                ));
+            }
+
+            let Some(deps) = dependencies.as_ref() else {
+                return;
+            };
+            if replace {
+                open_replace(deps.textures.keys().cloned().collect(), ui.ctx());
+            }
+
+            for (texture, effect_paths) in deps.textures.iter() {
+                ui.collapsing(format!("{} ({})", texture, effect_paths.len()), |ui| {
+                    for path in effect_paths {
+                        if ui.link(path.display().to_string()).clicked() {
+                            reffect_paths.load(path, asset_server);
+                            ui.memory_mut(|memory| {
+                                memory
+                                    .data
+                                    .insert_temp(egui::Id::new(JUMP_TO), path.clone())
+                            });
+                        }
+                    }
+                });
+            }
+
+            ui.collapsing(format!("Unused ({})", deps.unused.len()), |ui| {
+                for path in deps.unused.iter() {
+                    ui.label(path.display().to_string());
+                }
+            });
+        });
+}
@@ -0,0 +1,1038 @@
+// The effects list and the editor for each effect.
+
+use std::path::{Path, PathBuf};
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_egui::egui::{
+    self, collapsing_header::CollapsingState, widgets::DragValue, CollapsingHeader,
+};
+use bevy_hanabi::prelude::*;
+use bevy_inspector_egui::reflect_inspector::{Context, InspectorUi};
+
+use super::{
+    actions::{Action, Actions},
+    defaults::ModifierDefaults,
+    focus::{field_id, FocusOrder},
+    ids::effect_scope,
+    modifiers::{self, Textures},
+    paths::ui_copy_path,
+    timeline::{describe, ui_spawn_timeline},
+    tools::*,
+    variations::open_variations,
+    widgets::*,
+    wizard::open_wizard,
+    JUMP_TO, LABEL_WIDTH,
+};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::asset::{import_file, AssetOpError};
+use crate::{
+    asset::{file_slug, rename_file, validate_path},
+    browser::*,
+    change::*,
+    complete::PathCompletions,
+    docs::{self, Doc},
+    preview::PreviewMutes,
+    profile::{self, DeviceLimits, TargetProfile, TextureFacts},
+    reffect::{AttributeValue, EffectProperty, REffect, SpawnCycle},
+    scatter::{ui_scatter, Scatters},
+    toast::Toasts,
+};
+
+/// Memory id for the effect last edited with the keyboard, for switching with PageUp/PageDown.
+const CURRENT_EFFECT: &str = "current_effect";
+
+#[allow(clippy::too_many_arguments)]
+pub fn ui_effects(
+    reffect_paths: &mut AssetBrowser<REffect>,
+    reffects: &mut Assets<REffect>,
+    live: &HashMap<Handle<REffect>, Entity>,
+    textures: &mut Textures,
+    defaults: &ModifierDefaults,
+    scatters: &mut Scatters,
+    limits: Option<&DeviceLimits>,
+    mutes: &mut PreviewMutes,
+    type_registry: &AppTypeRegistry,
+    toasts: &Toasts,
+    actions: &mut Actions,
+    hashes: &mut ContentHashes,
+    ui: &mut egui::Ui,
+) {
+    let asset_server = textures.asset_server;
+    let one_open_id = ui.id().with("one_open");
+    let mut one_open = ui
+        .memory_mut(|memory| memory.data.get_temp::<bool>(one_open_id))
+        .unwrap_or_default();
+    // Header ids, and the one just opened.
+    let mut ids = Vec::new();
+    let mut opened = None;
+    // Effects in the order shown, with their header ids.
+    let mut loaded = Vec::new();
+
+    CollapsingHeader::new("Effects")
+        .default_open(true)
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .button("New")
+                    .on_hover_text("An effect from the new effect defaults.")
+                    .clicked()
+                {
+                    actions.push(Action::New(None));
+                }
+                if ui.button("New (wizard)…").clicked() {
+                    open_wizard(ui.ctx());
+                }
+
+                #[cfg(not(target_arch = "wasm32"))]
+                if ui.button("Open…").clicked() {
+                    if let Err(e) = open_effect(reffect_paths, asset_server, ui.ctx()) {
+                        e.toast("failed to open effect", toasts);
+                    }
+                }
+
+                #[cfg(target_arch = "wasm32")]
+                if ui.button("Open file…").clicked() {
+                    actions.push(Action::OpenFile);
+                }
+
+                ui.add_enabled_ui(false, |ui| {
+                    if ui.button("Random").clicked() {
+                        // TODO spawn random
+                    }
+                });
+
+                ui.checkbox(&mut one_open, "One at a time").on_hover_text(
+                    "Opening an effect closes the others, so only one editor is built each frame.",
+                );
+            });
+            ui.separator();
+
+            let count = reffect_paths.selected().count();
+            if count > 0 {
+                if let Some(batch) =
+                    ui_batch_edit(count, reffect_paths, asset_server, textures.image_paths, ui)
+                {
+                    let mut edited = 0;
+                    let mut locked = 0;
+                    for entry in reffect_paths.entries.iter_mut().filter(|e| e.selected) {
+                        if textures.config.is_locked(&entry.path) {
+                            locked += 1;
+                            continue;
+                        }
+                        let Some(handle) = entry.handle.as_ref() else {
+                            continue;
+                        };
+                        let Some(re) = reffects.get_mut(handle) else {
+                            continue;
+                        };
+
+                        batch.apply(re);
+                        entry.dirty = true;
+                        edited += 1;
+                        actions.push(Action::Regenerate(handle.clone(), ChangeFlags::all()));
+                    }
+                    match locked {
+                        0 => toasts.info(format!("batch edited {} effects", edited)),
+                        _ => toasts.info(format!(
+                            "batch edited {} effects, skipped {} locked",
+                            edited, locked
+                        )),
+                    }
+                }
+                ui.separator();
+            }
+
+            // Folder buttons can't borrow the actions while the rows do.
+            let mut folder_actions = Vec::new();
+            let folder = |folder: &Path, entries: &[&AssetEntry<REffect>], ui: &mut egui::Ui| {
+                ui_folder(folder, entries, live, &mut folder_actions, ui)
+            };
+
+            let jump_id = egui::Id::new(JUMP_TO);
+            // The rows can't borrow the browser either.
+            let completions = reffect_paths.completions.clone();
+            reffect_paths.show(asset_server, ui, folder, |root_path, entry, ui| {
+                let status = entry.status();
+                let AssetEntry {
+                    path,
+                    handle,
+                    dirty,
+                    file,
+                    selected,
+                    ..
+                } = entry;
+
+                // Scroll to the effect selected in dependencies.
+                let jump_to = ui.memory_mut(|memory| memory.data.get_temp::<PathBuf>(jump_id));
+                if jump_to.as_deref() == Some(path.as_path()) {
+                    ui.scroll_to_cursor(Some(egui::Align::TOP));
+                    ui.memory_mut(|memory| memory.data.remove::<PathBuf>(jump_id));
+                }
+
+                let Some(handle) = handle.as_ref() else {
+                    return None;
+                };
+
+                let Some(re) = reffects.get_mut(handle) else {
+                    ui.spinner(); // loading still
+                    return None;
+                };
+
+                // Effects scrolled out of view take up the space they did when last shown, without
+                // building their editors.
+                let id = ui.make_persistent_id(handle);
+                ids.push(id);
+                loaded.push((handle.clone(), path.clone(), id));
+                let height_id = id.with("height");
+                if let Some(height) = ui.memory_mut(|memory| memory.data.get_temp::<f32>(height_id))
+                {
+                    let size = egui::vec2(ui.available_width(), height);
+                    if !ui.is_rect_visible(egui::Rect::from_min_size(ui.cursor().min, size)) {
+                        ui.allocate_space(size);
+                        return None;
+                    }
+                }
+                let top = ui.cursor().top();
+                let locked = textures.config.is_locked(path);
+
+                let mut effect_header = match path.file_name() {
+                    Some(_) => format!(
+                        "{} {}: ({})",
+                        status.glyph(),
+                        re.name,
+                        textures.config.path_display.format(path)
+                    ),
+                    None => format!("{} {}", status.glyph(), re.name),
+                };
+                if let FileState::Exists(meta) = file {
+                    effect_header.push_str(&format!(" {}", file_size(meta.size)));
+                    if let Some(mtime) = meta.mtime {
+                        effect_header.push_str(&format!(", {}", modified_ago(mtime)));
+                    }
+                }
+                if locked {
+                    effect_header.push_str(" 🔒");
+                }
+
+                let response = CollapsingHeader::new(effect_header)
+                    .default_open(!one_open)
+                    // If we don't set the source, it uses the header text, which potentially changes.
+                    .id_source(&handle)
+                    .selectable(true)
+                    .selected(*selected)
+                    .show(ui, |ui| {
+                        effect_scope(ui, handle, |ui| {
+                            ui_effect(
+                                re,
+                                handle,
+                                (root_path, path, &completions),
+                                status,
+                                live.get(handle).copied(),
+                                textures,
+                                defaults,
+                                scatters,
+                                limits,
+                                mutes,
+                                type_registry,
+                                toasts,
+                                actions,
+                                ui,
+                            )
+                        })
+                    });
+                let changes = response.body_returned.unwrap_or_default();
+                let mut header = response.header_response.on_hover_text(status.description());
+                if path.file_name().is_some() {
+                    header = header.context_menu(|ui| {
+                        ui_copy_path(root_path, path, ui);
+                        let label = if locked { "Unlock" } else { "Lock" };
+                        if ui.button(label).clicked() {
+                            textures.config.set_locked(path, !locked);
+                            ui.close_menu();
+                        }
+                    });
+                }
+
+                // Ctrl/shift-click selects (see AssetBrowser::show) rather than opening or closing,
+                // so undo the toggle.
+                let modifiers = ui.input(|input| input.modifiers);
+                if header.clicked() && (modifiers.command || modifiers.shift) {
+                    if let Some(mut state) = CollapsingState::load(ui.ctx(), id) {
+                        state.toggle(ui);
+                        state.store(ui.ctx());
+                    }
+                } else if header.clicked()
+                    && one_open
+                    && CollapsingState::load(ui.ctx(), id).map_or(false, |s| s.is_open())
+                {
+                    opened = Some(id);
+                }
+
+                // Less the spacing added after the space is allocated.
+                let height = ui.cursor().top() - top - ui.spacing().item_spacing.y;
+                ui.memory_mut(|memory| memory.data.insert_temp(height_id, height));
+
+                if !changes.is_empty() && hashes.changed(handle, re) {
+                    *dirty = true;
+                    clear_generated(handle, ui.ctx());
+                    actions.push(Action::Regenerate(handle.clone(), changes));
+                }
+
+                Some(header)
+            });
+            for action in folder_actions {
+                actions.push(action);
+            }
+        });
+
+    let (up, down) = ui.input(|input| {
+        (
+            input.key_pressed(egui::Key::PageUp),
+            input.key_pressed(egui::Key::PageDown),
+        )
+    });
+    if up || down {
+        if let Some(id) = switch_effect(&loaded, up, ui.ctx()) {
+            if one_open {
+                opened = Some(id);
+            }
+        }
+    }
+
+    // Close the others.
+    if let Some(opened) = opened {
+        for id in ids.into_iter().filter(|id| *id != opened) {
+            if let Some(mut state) = CollapsingState::load(ui.ctx(), id) {
+                state.set_open(false);
+                state.store(ui.ctx());
+            }
+        }
+    }
+
+    ui.memory_mut(|memory| memory.data.insert_temp(one_open_id, one_open));
+}
+
+// Open the effect before or after the current one (the last with focus) and focus its name.
+// Returns the header id.
+fn switch_effect(
+    loaded: &[(Handle<REffect>, PathBuf, egui::Id)],
+    backward: bool,
+    ctx: &egui::Context,
+) -> Option<egui::Id> {
+    let current_id = egui::Id::new(CURRENT_EFFECT);
+    let current = ctx.memory_mut(|memory| memory.data.get_temp::<Handle<REffect>>(current_id));
+    let i = match current.and_then(|c| loaded.iter().position(|(h, ..)| *h == c)) {
+        None => 0,
+        Some(i) if backward => i.saturating_sub(1),
+        Some(i) => (i + 1).min(loaded.len().saturating_sub(1)),
+    };
+    let (handle, path, id) = loaded.get(i)?;
+
+    let mut state = CollapsingState::load_with_default_open(ctx, *id, true);
+    state.set_open(true);
+    state.store(ctx);
+    ctx.memory_mut(|memory| {
+        // Build the editor even if it's scrolled out of view, so the name can take focus.
+        memory.data.remove::<f32>(id.with("height"));
+        memory
+            .data
+            .insert_temp(egui::Id::new(JUMP_TO), path.clone());
+        memory.data.insert_temp(current_id, handle.clone());
+        memory.request_focus(field_id(handle, "name"));
+    });
+    Some(*id)
+}
+
+// Show, hide and save every effect in a folder. Effects that are already live are left alone.
+fn ui_folder(
+    folder: &Path,
+    entries: &[&AssetEntry<REffect>],
+    live: &HashMap<Handle<REffect>, Entity>,
+    actions: &mut Vec<Action>,
+    ui: &mut egui::Ui,
+) {
+    let live: Vec<Entity> = entries
+        .iter()
+        .filter_map(|e| live.get(e.handle.as_ref()?).copied())
+        .collect();
+    let loaded = entries.iter().filter(|e| e.handle.is_some()).count();
+    let unsaved = entries
+        .iter()
+        .filter(|e| e.handle.is_some() && e.status() != FileStatus::Clean)
+        .count();
+
+    if ui
+        .add_enabled(live.len() < loaded, egui::Button::new("Show all"))
+        .on_hover_text("Show the loaded effects in this folder in a row.")
+        .clicked()
+    {
+        actions.push(Action::ShowFolder(folder.to_path_buf()));
+    }
+    if ui
+        .add_enabled(!live.is_empty(), egui::Button::new("Hide all"))
+        .clicked()
+    {
+        actions.extend(live.into_iter().map(Action::Despawn));
+    }
+    if ui
+        .add_enabled(unsaved > 0, egui::Button::new("Save all"))
+        .on_hover_text(format!("{} to save", unsaved))
+        .clicked()
+    {
+        actions.push(Action::SaveFolder(folder.to_path_buf()));
+    }
+}
+
+// The editor for one effect. Returns what changed.
+#[allow(clippy::too_many_arguments)]
+fn ui_effect(
+    re: &mut REffect,
+    handle: &Handle<REffect>,
+    (root_path, path, completions): (&Path, &mut PathBuf, &PathCompletions),
+    status: FileStatus,
+    live: Option<Entity>,
+    textures: &mut Textures,
+    defaults: &ModifierDefaults,
+    scatters: &mut Scatters,
+    limits: Option<&DeviceLimits>,
+    mutes: &mut PreviewMutes,
+    type_registry: &AppTypeRegistry,
+    toasts: &Toasts,
+    actions: &mut Actions,
+    ui: &mut egui::Ui,
+) -> ChangeFlags {
+    let mut changes = ChangeFlags::empty();
+    let mut name_committed = false;
+    // Before egui moves it for Tab.
+    let focused = ui.memory(|memory| memory.focus());
+    let mut focus = FocusOrder::default();
+    let locked = textures.config.is_locked(path);
+
+    ui.horizontal(|ui| {
+        ui.label("Name");
+        let response = ui.add_enabled(
+            !locked,
+            egui::TextEdit::singleline(&mut re.name)
+                .desired_width(140.0)
+                .id(field_id(handle, "name")),
+        );
+        focus.add("name", [response.id]);
+        if response.changed() {
+            changes |= ChangeFlags::META;
+        }
+        name_committed = response.lost_focus();
+
+        match live {
+            Some(entity) => {
+                if ui.button("Hide").clicked() {
+                    actions.push(Action::Despawn(entity));
+                }
+            }
+            None => {
+                if ui.button("Show").clicked() {
+                    actions.push(Action::Show(handle.clone()));
+                }
+            }
+        }
+
+        if path.file_name().is_some() {
+            let lock = if locked { "🔒" } else { "🔓" };
+            if ui.selectable_label(locked, lock).doc("Lock").clicked() {
+                textures.config.set_locked(path, !locked);
+            }
+        }
+
+        // Move to AssetBrowser?
+        // TODO confirm overwrite if the name has changed
+        let save = match status {
+            FileStatus::Missing => "Save (recreate)",
+            _ => "Save",
+        };
+        if ui
+            .add_enabled(
+                status != FileStatus::Clean && !locked,
+                egui::Button::new(save),
+            )
+            .clicked()
+        {
+            actions.push(Action::Save(handle.clone()));
+        }
+
+        if ui
+            .add_enabled_ui(!locked, |ui| ui_scale(re, ui) | ui_reorient(re, ui))
+            .inner
+        {
+            changes |= ChangeFlags::INIT | ChangeFlags::UPDATE | ChangeFlags::RENDER;
+        }
+
+        if ui
+            .button("Variations…")
+            .on_hover_text("Copies with some values randomly jittered, to pick from.")
+            .clicked()
+        {
+            open_variations(handle, ui.ctx());
+        }
+
+        ui_scatter(handle, scatters, ui);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if ui
+            .add_enabled(!locked, egui::Button::new("History…"))
+            .on_hover_text("Backups kept from earlier saves, to restore from.")
+            .clicked()
+        {
+            crate::backup::open_history(handle, root_path, path, ui.ctx());
+        }
+
+        // TODO
+        _ = ui.add_enabled(false, egui::Button::new("Clone"));
+        _ = ui.add_enabled(false, egui::Button::new("🗙"));
+    });
+
+    // Locked effects can still be shown and reset, but nothing below can change.
+    ui.add_enabled_ui(!locked, |ui| {
+        ui_rename_file(
+            &re.name,
+            name_committed,
+            handle,
+            (root_path, path),
+            toasts,
+            ui,
+        );
+
+        _ = hl!("Path", ui, |ui| {
+            let change = focus.field("path", ui, |ui| {
+                edit_path(path, Some(completions), toasts, ui, |path| {
+                    validate_path(path, "han", root_path)
+                })
+            });
+            #[cfg(not(target_arch = "wasm32"))]
+            let change = {
+                let change = change | browse_path(path, root_path, "han", toasts, ui);
+                let (file, exists) = (root_path.join(&*path), status.exists());
+                reveal_button(&file, exists, toasts, ui);
+                open_button(&file, exists, toasts, ui);
+                change
+            };
+            change
+        });
+
+        // Set up context for reflect values.
+        let mut cx = Context::default();
+        let tr = type_registry.read();
+        let mut env = InspectorUi::new(&tr, &mut cx, Some(short_circuit), None, None);
+
+        grid!(ui, ui.id().with("effect"), |ui| {
+            changes |= row!("Capacity", ui, |ui| {
+                let max = limits.map_or(u32::MAX, |l| l.max_capacity(re));
+                let response = ui.add(DragValue::new(&mut re.capacity).clamp_range(0..=max));
+                focus.add("capacity", [response.id]);
+                ui.weak(format!(
+                    "{} particles ≈ {}",
+                    re.capacity,
+                    file_size(re.capacity as u64 * profile::particle_bytes(re))
+                ))
+                .on_hover_text(match limits {
+                    Some(_) => format!("Up to {} particles fit on this adapter.", max),
+                    None => "The adapter's limit is unknown.".to_string(),
+                });
+                response
+            })
+            .tag(ChangeFlags::CAPACITY);
+
+            changes |= (ui_simulation_space(&mut re.simulation_space, ui)
+                | ui_reflect(
+                    "Simulation Condition",
+                    &mut re.simulation_condition,
+                    &mut env,
+                    ui,
+                ))
+            .tag(ChangeFlags::SIMULATION);
+
+            let conflicts = re.space_conflicts();
+            if !conflicts.is_empty() {
+                ui.label("");
+                ui.label(format!("⚠ {} in global space", conflicts.join(", ")))
+                    .on_hover_text_at_pointer(docs::get("Space conflict").unwrap_or_default());
+                ui.end_row();
+            }
+
+            let computed = re.computed_bounds();
+            let had_bounds = re.bounds.is_some();
+            changes |= ui_option("Bounds", &mut re.bounds, ui, |bounds, ui| {
+                let mut response = value_vec3_single(&mut bounds.center, "", ui)
+                    | ui.label("±")
+                    | value_vec3_single(&mut bounds.half_extents, "", ui);
+                if ui
+                    .add_enabled(computed.is_some(), egui::Button::new("From modifiers"))
+                    .on_hover_text(
+                        "Fit the bounds to the emitter, speed, acceleration and lifetime.",
+                    )
+                    .clicked()
+                {
+                    *bounds = computed.unwrap_or_default();
+                    response.mark_changed();
+                }
+                response.into()
+            })
+            .tag(ChangeFlags::BOUNDS);
+            // Start from the computed bounds rather than a unit box.
+            if !had_bounds {
+                if let (Some(bounds), Some(computed)) = (re.bounds.as_mut(), computed) {
+                    *bounds = computed;
+                }
+            }
+
+            changes |= row!("Target", ui, |ui| {
+                TargetProfile::ALL
+                    .iter()
+                    .map(|profile| ui.selectable_value(&mut re.target, *profile, profile.label()))
+                    .reduce(|a, b| a | b)
+                    .expect("at least one profile")
+            })
+            .tag(ChangeFlags::META);
+
+            let texture = re
+                .render_particle_texture
+                .handle()
+                .and_then(|handle| textures.images.get(handle))
+                .map(|image| {
+                    let size = image.texture_descriptor.size;
+                    TextureFacts {
+                        size: UVec2::new(size.width, size.height),
+                        format: image.texture_descriptor.format,
+                    }
+                });
+            for warning in profile::check(re, texture, re.target) {
+                ui.label("");
+                ui.label(format!("⚠ {}", warning));
+                ui.end_row();
+            }
+
+            Change::from(())
+        });
+
+        let lifecycle = (re.duration(), re.steady_state_count());
+        changes |= ui_spawner(
+            (&mut re.spawner, &mut re.start_delay),
+            lifecycle,
+            handle,
+            &mut focus,
+            (live, actions),
+            ui,
+        )
+        .tag(ChangeFlags::SPAWNER);
+
+        let count = Some(re.properties.len());
+        changes |= header!(ui, "Properties", handle, count, |ui| {
+            ui_properties(&mut re.properties, ui)
+        })
+        .tag(ChangeFlags::PROPERTIES);
+
+        changes |= modifiers::ui_init(
+            re,
+            handle,
+            defaults,
+            (&mut *mutes, &mut *actions),
+            &mut env,
+            ui,
+        )
+        .tag(ChangeFlags::INIT);
+        changes |= modifiers::ui_update(
+            re,
+            handle,
+            defaults,
+            (&mut *mutes, &mut *actions),
+            &mut env,
+            ui,
+        )
+        .tag(ChangeFlags::UPDATE);
+        changes |= modifiers::ui_render(
+            re,
+            handle,
+            defaults,
+            (&mut *mutes, &mut *actions),
+            &mut env,
+            textures,
+            toasts,
+            ui,
+        );
+    });
+
+    ui_generated(re, textures.asset_server, handle, ui);
+
+    focus.update(focused, ui);
+    if focused.map_or(false, |id| focus.contains(id)) {
+        ui.memory_mut(|memory| {
+            memory
+                .data
+                .insert_temp(egui::Id::new(CURRENT_EFFECT), handle.clone())
+        });
+    }
+
+    changes
+}
+
+// Declared properties: name, type and default value.
+// Global or local, with a sketch of what happens to particles when the emitter moves.
+fn ui_simulation_space(space: &mut SimulationSpace, ui: &mut egui::Ui) -> Change {
+    row!("Simulation Space", ui, |ui| {
+        let response = ui
+            .selectable_value(space, SimulationSpace::Global, "Global")
+            .doc("Global")
+            | ui.selectable_value(space, SimulationSpace::Local, "Local")
+                .doc("Local");
+        space_sketch(*space, ui);
+        response
+    })
+}
+
+// The emitter has moved from left to right. In global space the particles trail behind it; in
+// local space they went along.
+fn space_sketch(space: SimulationSpace, ui: &mut egui::Ui) {
+    let size = egui::vec2(48.0, ui.spacing().interact_size.y);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let color = ui.visuals().text_color();
+    let emitter = rect.right_center() - egui::vec2(6.0, 0.0);
+
+    painter.line_segment(
+        [rect.left_center() + egui::vec2(2.0, 0.0), emitter],
+        egui::Stroke::new(1.0, ui.visuals().weak_text_color()),
+    );
+    for i in 0..5 {
+        let offset = match space {
+            SimulationSpace::Global => egui::vec2(-8.0 * i as f32, (i % 2) as f32 * 4.0 - 2.0),
+            _ => egui::vec2((i % 3) as f32 * 2.0 - 2.0, (i % 2) as f32 * 6.0 - 3.0),
+        };
+        painter.circle_filled(emitter + offset, 1.5, ui.visuals().weak_text_color());
+    }
+    painter.circle_stroke(emitter, 4.0, egui::Stroke::new(1.5, color));
+}
+
+fn ui_properties(properties: &mut Vec<EffectProperty>, ui: &mut egui::Ui) -> Change {
+    let mut change = Change::from(false);
+    let mut remove = None;
+
+    for (i, p) in properties.iter_mut().enumerate() {
+        change = change
+            | ui.push_id(i, |ui| {
+                let name =
+                    ui.add(egui::TextEdit::singleline(&mut p.name).desired_width(LABEL_WIDTH));
+                let value = ui
+                    .horizontal(|ui| {
+                        if ui.small_button("🗙").clicked() {
+                            remove = Some(i);
+                        }
+                        ui.label(p.default_value.type_name());
+                        ui_attribute_value(&mut p.default_value, ui)
+                    })
+                    .inner;
+                name | value
+            })
+            .inner;
+        ui.end_row();
+    }
+
+    if let Some(i) = remove {
+        properties.remove(i);
+        change = change | Change::from(true);
+    }
+
+    ui.label("");
+    ui.menu_button("Add property", |ui| {
+        for value in AttributeValue::TYPES {
+            if ui.button(value.type_name()).clicked() {
+                properties.push(EffectProperty {
+                    name: format!("property{}", properties.len()),
+                    default_value: value,
+                });
+                change = Change::from(true);
+                ui.close_menu();
+            }
+        }
+    });
+    ui.end_row();
+
+    change
+}
+
+// Maybe infinite period should be a separate checkbox. The duration (spawn once) and steady state
+// particle count (repeating) are computed from the effect and shown read-only. Spawners that don't
+// start active can be activated on the live effect.
+pub fn ui_spawner(
+    (spawner, start_delay): (&mut Spawner, &mut f32),
+    (duration, steady_state): (Option<f32>, Option<f32>),
+    id: impl std::hash::Hash,
+    focus: &mut FocusOrder,
+    (live, actions): (Option<Entity>, &mut Actions),
+    ui: &mut egui::Ui,
+) -> Change {
+    header!(ui, "Spawner", id, |ui| {
+        _ = row!("Behavior", ui, |ui| {
+            ui.vertical(|ui| {
+                ui.add(egui::Label::new(egui::RichText::new(describe(spawner)).weak()).wrap(true));
+                if let (false, Some(entity)) = (spawner.starts_active, live) {
+                    if ui.button("Activate now").clicked() {
+                        actions.push(Action::Activate(entity));
+                    }
+                }
+            })
+            .response
+        });
+
+        let change = focus.field("num_particles", ui, |ui| {
+            value!("Particles", ui, spawner.num_particles, "#")
+        }) | focus.field("spawn_time", ui, |ui| {
+            value!("Spawn Time", ui, spawner.spawn_time, "s")
+        }) | focus.field("period", ui, |ui| {
+            value!("Period", ui, spawner.period, "period")
+        }) | ui_checkbox("Starts Active", &mut spawner.starts_active, ui)
+            | ui_checkbox("Starts Immediately", &mut spawner.starts_immediately, ui)
+            | row!("Start Delay", ui, |ui| {
+                let response = ui.add(drag_value(start_delay, "s"));
+                focus.add("start_delay", [response.id]);
+                response
+            });
+
+        let cycle = SpawnCycle::of(spawner);
+        _ = row!("Timeline", ui, |ui| ui_spawn_timeline(&cycle, ui));
+
+        if let Some(duration) = duration {
+            _ = row!("Duration", ui, |ui| ui.label(format!("{:.2}s", duration)));
+        }
+        if let Some(count) = steady_state {
+            _ = row!("Steady State", ui, |ui| ui
+                .label(format!("~{:.0} particles", count)));
+        }
+
+        change
+    })
+}
+
+// Read-only view of what hanabi actually receives. It's generated when the section is opened and
+// cached until the effect changes.
+fn ui_generated(
+    re: &REffect,
+    asset_server: &AssetServer,
+    handle: &Handle<REffect>,
+    ui: &mut egui::Ui,
+) {
+    let id = egui::Id::new(("generated", handle));
+    CollapsingHeader::new("Generated EffectAsset")
+        .id_source(id)
+        .default_open(false)
+        .show(ui, |ui| {
+            let lines = ui
+                .memory_mut(|memory| memory.data.get_temp::<Vec<(String, String)>>(id))
+                .unwrap_or_else(|| {
+                    let lines = generated_lines(re, &re.to_effect_asset(asset_server));
+                    ui.memory_mut(|memory| memory.data.insert_temp(id, lines.clone()));
+                    lines
+                });
+
+            egui::Grid::new(id.with("grid"))
+                .num_columns(2)
+                .min_col_width(LABEL_WIDTH)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (label, value) in lines {
+                        ui.label(label);
+                        ui.monospace(value);
+                        ui.end_row();
+                    }
+                });
+        })
+        .header_response
+        .on_hover_text("The effect asset generated from this effect, for diagnostics.");
+}
+
+fn clear_generated(handle: &Handle<REffect>, ctx: &egui::Context) {
+    let id = egui::Id::new(("generated", handle));
+    ctx.memory_mut(|memory| memory.data.remove::<Vec<(String, String)>>(id));
+}
+
+// Modifiers are listed in the order they're applied within each context (init, update, render),
+// with the field they came from.
+fn generated_lines(re: &REffect, effect: &EffectAsset) -> Vec<(String, String)> {
+    let mut lines = vec![
+        ("Capacity".to_string(), effect.capacity.to_string()),
+        ("Spawner".to_string(), format!("{:?}", effect.spawner)),
+        (
+            "Simulation".to_string(),
+            format!(
+                "{:?}, {:?}",
+                effect.simulation_space, effect.simulation_condition
+            ),
+        ),
+    ];
+
+    for property in effect.properties() {
+        lines.push((
+            format!("Property {}", property.name()),
+            format!("{:?}", property.default_value()),
+        ));
+    }
+
+    let fields = re.modifiers();
+    for (i, modifier) in effect.modifiers.iter().enumerate() {
+        let field = fields
+            .get(i)
+            .map(|(f, _)| format!(" {} ({})", f.label, f.field))
+            .unwrap_or_default();
+        lines.push((
+            format!("{}. {:?}{}", i + 1, modifier.context(), field),
+            format!("{:?}", modifier.as_reflect()),
+        ));
+    }
+
+    let attributes: Vec<_> = effect
+        .particle_layout()
+        .attributes()
+        .iter()
+        .map(|layout| layout.attribute.name())
+        .collect();
+    lines.push(("Attributes".to_string(), attributes.join(", ")));
+
+    lines
+}
+
+const IMPORT_EFFECT: &str = "import_effect";
+
+// After the name is edited, offer to rename the file to match. Declining is remembered for the
+// effect, so it isn't offered again.
+fn ui_rename_file(
+    name: &str,
+    name_committed: bool,
+    handle: &Handle<REffect>,
+    (root_path, path): (&Path, &mut PathBuf),
+    toasts: &Toasts,
+    ui: &mut egui::Ui,
+) {
+    let id = egui::Id::new(("rename_file", handle.id()));
+    let declined_id = id.with("declined");
+    if ui.memory_mut(|memory| memory.data.get_temp::<bool>(declined_id)) == Some(true) {
+        return;
+    }
+
+    if name_committed {
+        let slug = file_slug(name);
+        if slug.is_empty() || path.file_stem().map_or(false, |stem| *stem == *slug) {
+            ui.memory_mut(|memory| memory.data.remove::<PathBuf>(id));
+        } else {
+            let suggestion = path.with_file_name(format!("{}.han", slug));
+            ui.memory_mut(|memory| memory.data.insert_temp(id, suggestion));
+        }
+    }
+
+    let Some(suggestion) = ui.memory_mut(|memory| memory.data.get_temp::<PathBuf>(id)) else {
+        return;
+    };
+
+    ui.horizontal(|ui| {
+        ui.label(format!(
+            "Rename file to {}?",
+            suggestion.file_name().unwrap_or_default().to_string_lossy()
+        ));
+        if ui
+            .button("Apply")
+            .on_hover_text("Rename the file on disk if it has been saved.")
+            .clicked()
+        {
+            match validate_path(&suggestion.to_string_lossy(), "han", root_path)
+                .and_then(|to| rename_file(root_path, path, &to))
+            {
+                Ok(to) => {
+                    toasts.info(format!("renamed {} to {}", path.display(), to.display()));
+                    *path = to;
+                }
+                Err(e) => e.toast(format!("failed to rename {}", path.display()), toasts),
+            }
+            ui.memory_mut(|memory| memory.data.remove::<PathBuf>(id));
+        }
+        if ui.button("Keep").clicked() {
+            ui.memory_mut(|memory| {
+                memory.data.remove::<PathBuf>(id);
+                memory.data.insert_temp(declined_id, true);
+            });
+        }
+    });
+}
+
+// Open an effect from anywhere. Effects outside the assets root need to be copied in first, since
+// textures are resolved relative to it.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open_effect(
+    browser: &mut AssetBrowser<REffect>,
+    asset_server: &AssetServer,
+    ctx: &egui::Context,
+) -> Result<(), AssetOpError> {
+    let file = rfd::FileDialog::new()
+        .add_filter("han", &["han"])
+        .set_directory(&browser.root_path)
+        .pick_file()
+        .ok_or(AssetOpError::Cancelled)?;
+    let file = file.canonicalize()?;
+
+    match file.strip_prefix(&browser.root_path) {
+        Ok(path) => {
+            let path = path.to_path_buf();
+            if browser.entry(&path).is_none() {
+                browser.entries.push(AssetEntry::new(path.clone()));
+            }
+            browser.load(&path, asset_server);
+        }
+        // Confirm first.
+        Err(_) => {
+            ctx.memory_mut(|memory| memory.data.insert_temp(egui::Id::new(IMPORT_EFFECT), file))
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn confirm_import(
+    browser: &mut AssetBrowser<REffect>,
+    asset_server: &AssetServer,
+    toasts: &Toasts,
+    ctx: &egui::Context,
+) {
+    let id = egui::Id::new(IMPORT_EFFECT);
+    let Some(file) = ctx.memory_mut(|memory| memory.data.get_temp::<PathBuf>(id)) else {
+        return;
+    };
+
+    let mut close = false;
+    egui::Window::new("Import effect")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "{} is outside the assets root. Copy it to {}?",
+                file.display(),
+                browser.root_path.display()
+            ));
+            ui.label("Textures used by the effect must also be under the assets root.");
+            ui.horizontal(|ui| {
+                if ui.button("Copy").clicked() {
+                    match import_file(&file, &browser.root_path, Path::new("")) {
+                        Ok(path) => {
+                            toasts.info(format!("imported {}", path.display()));
+                            let handle = asset_server.load(path.as_path());
+                            browser.insert(path, handle);
+                        }
+                        Err(e) => e.toast(format!("failed to import {}", file.display()), toasts),
+                    }
+                    close = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    if close {
+        ctx.memory_mut(|memory| memory.data.remove::<PathBuf>(id));
+    }
+}
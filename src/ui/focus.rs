@@ -0,0 +1,178 @@
+// Keyboard focus order in the effect editor. egui moves focus in the order widgets are created,
+// which visits every button and combo box on the way. Instead, fields register their widgets as
+// they're drawn (see [FocusOrder::field] and [register]), and after the editor is drawn Tab,
+// Shift+Tab and Enter move focus between them in [FIELDS] order. PageUp and PageDown switch
+// effects, see [super::effects].
+
+use std::hash::Hash;
+
+use bevy_egui::egui;
+
+/// Fields of an effect's editor, in focus order.
+pub const FIELDS: &[&str] = &[
+    "name",
+    "path",
+    "capacity",
+    "num_particles",
+    "spawn_time",
+    "period",
+    "start_delay",
+];
+
+/// Memory id for the widgets registered by the field being drawn.
+const RECORDING: &str = "focus_recording";
+
+/// Id for a field's widget in an effect's editor, for widgets that take an id. Others are
+/// registered by the id egui assigns them.
+pub fn field_id(effect: impl Hash, field: &str) -> egui::Id {
+    egui::Id::new(("field", effect, field))
+}
+
+/// Register a widget for the field being drawn, if any. Widgets outside [FocusOrder::field] are
+/// ignored, so shared widgets can always call this.
+pub fn register(response: &egui::Response, ui: &egui::Ui) {
+    let key = egui::Id::new(RECORDING);
+    ui.memory_mut(|memory| {
+        if let Some(mut ids) = memory.data.get_temp::<Vec<egui::Id>>(key) {
+            ids.push(response.id);
+            memory.data.insert_temp(key, ids);
+        }
+    });
+}
+
+/// Widgets registered for each field of one effect's editor this frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FocusOrder(Vec<(&'static str, egui::Id)>);
+
+impl FocusOrder {
+    /// Draw a field, keeping the widgets that [register] themselves.
+    pub fn field<R>(
+        &mut self,
+        field: &'static str,
+        ui: &mut egui::Ui,
+        add: impl FnOnce(&mut egui::Ui) -> R,
+    ) -> R {
+        let key = egui::Id::new(RECORDING);
+        ui.memory_mut(|memory| memory.data.insert_temp(key, Vec::<egui::Id>::new()));
+        let inner = add(ui);
+        let ids = ui.memory_mut(|memory| {
+            let ids = memory.data.get_temp::<Vec<egui::Id>>(key);
+            memory.data.remove::<Vec<egui::Id>>(key);
+            ids.unwrap_or_default()
+        });
+        self.add(field, ids);
+        inner
+    }
+
+    /// Add widgets for a field, in the order they're drawn.
+    pub fn add(&mut self, field: &'static str, ids: impl IntoIterator<Item = egui::Id>) {
+        self.0.extend(ids.into_iter().map(|id| (field, id)));
+    }
+
+    pub fn contains(&self, id: egui::Id) -> bool {
+        self.0.iter().any(|(_, i)| *i == id)
+    }
+
+    /// All widgets in focus order: by field in [FIELDS], then in the order drawn.
+    pub fn ids(&self) -> Vec<egui::Id> {
+        let mut ids = self.0.clone();
+        ids.sort_by_key(|(field, _)| FIELDS.iter().position(|f| f == field));
+        ids.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// The widget after (or before) `focused`. None if it isn't registered or is the last (or
+    /// first).
+    pub fn next(&self, focused: egui::Id, backward: bool) -> Option<egui::Id> {
+        let ids = self.ids();
+        let i = ids.iter().position(|id| *id == focused)?;
+        match backward {
+            true => i.checked_sub(1).map(|i| ids[i]),
+            false => ids.get(i + 1).copied(),
+        }
+    }
+
+    /// Move focus after the editor is drawn, if Tab, Shift+Tab or Enter was pressed while one of
+    /// the registered widgets had focus. `focused` is the focus before the editor was drawn, since
+    /// egui has moved on by now. Past the last field egui's order takes over.
+    pub fn update(&self, focused: Option<egui::Id>, ui: &egui::Ui) {
+        let Some(focused) = focused else {
+            return;
+        };
+        let (tab, enter, shift) = ui.input(|input| {
+            (
+                input.key_pressed(egui::Key::Tab),
+                input.key_pressed(egui::Key::Enter),
+                input.modifiers.shift,
+            )
+        });
+        if !(tab || enter) {
+            return;
+        }
+        if let Some(next) = self.next(focused, tab && shift) {
+            ui.memory_mut(|memory| memory.request_focus(next));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Registered out of order, as the editor might draw them, with a uniform value's two widgets.
+    fn order(effect: u32) -> FocusOrder {
+        let id = |field| field_id(effect, field);
+        let mut order = FocusOrder::default();
+        order.add("capacity", [id("capacity")]);
+        order.add("name", [id("name")]);
+        order.add("start_delay", [id("start_delay")]);
+        order.add("spawn_time", [id("spawn_time_min"), id("spawn_time_max")]);
+        order.add("path", [id("path")]);
+        order
+    }
+
+    #[test]
+    fn field_ids_unique() {
+        let ids: std::collections::HashSet<_> = [1, 2]
+            .iter()
+            .flat_map(|effect| FIELDS.iter().map(move |f| field_id(effect, f)))
+            .collect();
+        assert_eq!(ids.len(), FIELDS.len() * 2);
+    }
+
+    // The checklist: Tab from the name visits each shown field once, in order, and Shift+Tab comes
+    // back the same way.
+    #[test]
+    fn tab_order() {
+        let order = order(1);
+        let id = |field| field_id(1, field);
+        let expected = [
+            id("name"),
+            id("path"),
+            id("capacity"),
+            id("spawn_time_min"),
+            id("spawn_time_max"),
+            id("start_delay"),
+        ];
+        assert_eq!(order.ids(), expected);
+
+        let mut forward = vec![id("name")];
+        while let Some(next) = order.next(*forward.last().unwrap(), false) {
+            forward.push(next);
+        }
+        assert_eq!(forward, expected);
+
+        let mut backward = vec![id("start_delay")];
+        while let Some(next) = order.next(*backward.last().unwrap(), true) {
+            backward.push(next);
+        }
+        backward.reverse();
+        assert_eq!(backward, expected);
+    }
+
+    #[test]
+    fn other_effects_ignored() {
+        let order = order(1);
+        assert_eq!(order.next(field_id(2, "name"), false), None);
+        assert!(!order.contains(field_id(2, "capacity")));
+    }
+}
@@ -0,0 +1,309 @@
+// Editor-wide settings: the camera, preview and the assets root.
+
+use std::path::Path;
+
+use bevy::{
+    core_pipeline::bloom::BloomSettings,
+    prelude::*,
+    render::{
+        render_resource::WgpuFeatures,
+        renderer::{RenderAdapterInfo, RenderDevice},
+    },
+};
+use bevy_egui::egui::{self, widgets::DragValue, CollapsingHeader};
+
+use super::{
+    actions::{Action, Actions},
+    paths::PathDisplay,
+};
+use crate::{
+    camera::{OrbitCamera, ViewPreset},
+    config::{EditorConfig, Palette},
+    docs::Doc,
+    preview::{Placement, PreviewSettings},
+};
+
+// show/hide, pause, slow time? reset
+// move entity w/ mouse?
+pub fn ui_global(
+    (camera, bloom, orbit): (&mut Camera, &mut BloomSettings, &mut OrbitCamera),
+    preview: &mut PreviewSettings,
+    config: &mut EditorConfig,
+    render: (Option<&RenderDevice>, Option<&RenderAdapterInfo>),
+    root_path: &Path,
+    actions: &mut Actions,
+    ui: &mut egui::Ui,
+) {
+    CollapsingHeader::new("Global")
+        .default_open(true)
+        .show(ui, |ui| {
+            ui.checkbox(&mut camera.hdr, "HDR");
+            ui.horizontal(|ui| {
+                ui.label("Bloom:");
+                ui.add(
+                    DragValue::new(&mut bloom.intensity)
+                        .clamp_range(0.0..=1.0)
+                        .speed(0.01),
+                );
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("View:");
+                for preset in ViewPreset::ALL {
+                    if ui.button(preset.label()).clicked() {
+                        orbit.preset(preset);
+                    }
+                }
+                ui.checkbox(&mut orbit.orthographic, "Orthographic");
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("FOV:");
+                let mut fov = orbit.fov.to_degrees();
+                if ui
+                    .add_enabled(
+                        !orbit.orthographic,
+                        egui::Slider::new(&mut fov, 20.0..=100.0).suffix("°"),
+                    )
+                    .changed()
+                {
+                    orbit.fov = fov.to_radians();
+                }
+            });
+
+            let mut show_tooltips = ui.ctx().style().explanation_tooltips;
+            if ui.checkbox(&mut show_tooltips, "Show tooltips").changed() {
+                let mut style = (*ui.ctx().style()).clone();
+                style.explanation_tooltips = show_tooltips;
+                ui.ctx().set_style(style);
+            }
+
+            let mut debug = ui.ctx().debug_on_hover();
+            if ui
+                .checkbox(&mut debug, "Debug")
+                .on_hover_text("Outline widgets on hover, and list ids used by more than one widget.")
+                .changed()
+            {
+                ui.ctx().set_debug_on_hover(debug);
+            }
+
+            ui.horizontal(|ui| {
+                let toggled = ui
+                    .checkbox(&mut preview.deterministic, "Deterministic preview")
+                    .on_hover_text(
+                        "Seed the spawner's random number generator when effects are shown or reset, so the same particles are spawned each time.",
+                    )
+                    .changed();
+                ui.add_enabled(
+                    preview.deterministic,
+                    DragValue::new(&mut preview.seed).prefix("seed: "),
+                );
+
+                // Restart live effects from their current assets.
+                if toggled {
+                    actions.push(Action::RestartAll);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Spawn at:");
+                for placement in Placement::ALL {
+                    ui.radio_value(&mut preview.placement, placement, placement.label());
+                }
+                ui.add_enabled(
+                    preview.placement == Placement::Grid,
+                    DragValue::new(&mut preview.grid_spacing)
+                        .clamp_range(0.1..=100.0)
+                        .speed(0.1)
+                        .prefix("spacing: "),
+                );
+            })
+            .response
+            .on_hover_text(
+                "Where newly shown effects are placed. Grid uses the next free cell on the ground, Cursor where the cursor points at the ground.",
+            );
+
+            ui.checkbox(&mut preview.phase_offset, "Random phase").on_hover_text(
+                "Delay each shown effect by a random fraction of its period, so copies of a repeating effect don't pulse in sync.",
+            );
+
+            ui.checkbox(&mut preview.show_gizmos, "Show gizmos")
+                .on_hover_text("Outline the emitter shape, kill box and culling bounds of live effects.");
+
+            ui.checkbox(&mut preview.show_axes, "Show axes").on_hover_text(
+                "Draw the X (red), Y (green) and Z (blue) axes at the origin of each live effect. Effects selected in the browser get larger ones.",
+            );
+
+            ui.horizontal(|ui| {
+                ui.label("Paths:");
+                for display in PathDisplay::ALL {
+                    ui.radio_value(&mut config.path_display, display, display.label());
+                }
+            })
+            .response
+            .doc("Paths");
+
+            CollapsingHeader::new("Colors").show(ui, |ui| {
+                let palette = &mut config.palette;
+                egui::Grid::new("palette").show(ui, |ui| {
+                    for (label, color) in [
+                        ("Size curve X", &mut palette.curve_x),
+                        ("Size curve Y", &mut palette.curve_y),
+                        ("Emitter", &mut palette.emitter),
+                        ("Kill box", &mut palette.kill_box),
+                        ("Bounds", &mut palette.bounds),
+                        ("Selection", &mut palette.selection),
+                    ] {
+                        ui.label(label);
+                        ui.color_edit_button_srgba_unmultiplied(color);
+                        ui.end_row();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Default").clicked() {
+                        *palette = Palette::default();
+                    }
+                    if ui
+                        .button("Colorblind safe")
+                        .on_hover_text("Colors told apart with the common forms of color blindness.")
+                        .clicked()
+                    {
+                        *palette = Palette::colorblind();
+                    }
+                });
+            });
+
+            CollapsingHeader::new("Capabilities").show(ui, |ui| ui_capabilities(render, ui));
+
+            ui.horizontal(|ui| {
+                let background = &mut config.background;
+                ui.checkbox(&mut background.simulate, "Simulate in background")
+                    .doc("Simulate in background");
+                ui.add_enabled(
+                    background.simulate,
+                    DragValue::new(&mut background.rate)
+                        .clamp_range(1.0..=60.0)
+                        .suffix(" fps"),
+                );
+            });
+
+            #[cfg(not(target_arch = "wasm32"))]
+            ui.horizontal(|ui| {
+                ui.label("Backups kept:");
+                ui.add(DragValue::new(&mut config.backups.keep).clamp_range(0..=50))
+                    .on_hover_text(
+                        "Timestamped copies kept of each effect on save, under .han-ed/backups in the assets root. Zero disables backups.",
+                    );
+            });
+
+            #[cfg(not(target_arch = "wasm32"))]
+            ui_ignore(&mut config.ignore, ui);
+
+            ui.horizontal(|ui| {
+                ui.label(format!("Assets: {}", root_path.display()));
+
+                #[cfg(not(target_arch = "wasm32"))]
+                if ui
+                    .button("Change…")
+                    .on_hover_text(
+                        "Restarts han-ed with the new assets root. Unsaved changes are lost.",
+                    )
+                    .clicked()
+                {
+                    if let Some(folder) = rfd::FileDialog::new()
+                        .set_directory(root_path)
+                        .pick_folder()
+                    {
+                        restart(&folder);
+                    }
+                }
+            });
+        });
+}
+
+// Comma separated ignore patterns, applied when the field loses focus so the browsers aren't
+// rescanned on every key.
+#[cfg(not(target_arch = "wasm32"))]
+fn ui_ignore(ignore: &mut Vec<String>, ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+        ui.label("Ignore:").doc("Ignore");
+        let id = ui.id().with("ignore");
+        let mut text = ui
+            .memory_mut(|memory| memory.data.get_temp::<String>(id))
+            .unwrap_or_else(|| ignore.join(", "));
+        let response = ui.text_edit_singleline(&mut text).doc("Ignore");
+        if response.lost_focus() {
+            *ignore = text
+                .split(',')
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .map(String::from)
+                .collect();
+            ui.memory_mut(|memory| memory.data.remove::<String>(id));
+        } else if response.has_focus() {
+            ui.memory_mut(|memory| memory.data.insert_temp(id, text));
+        }
+    });
+}
+
+// Relaunch with a different assets root.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn restart(assets_root: &Path) {
+    match std::env::current_exe()
+        .and_then(|exe| std::process::Command::new(exe).arg(assets_root).spawn())
+    {
+        Ok(_) => std::process::exit(0),
+        Err(e) => error!("failed to restart: {:?}", e),
+    }
+}
+
+// What the adapter provides, of what effects depend on. Effects are checked against their target
+// rather than this; see [crate::profile].
+fn ui_capabilities(
+    (device, adapter): (Option<&RenderDevice>, Option<&RenderAdapterInfo>),
+    ui: &mut egui::Ui,
+) {
+    let Some(device) = device else {
+        ui.label("No render device.");
+        return;
+    };
+    if let Some(adapter) = adapter {
+        ui.label(format!("{} ({:?})", adapter.name, adapter.backend));
+    }
+
+    let (features, limits) = (device.features(), device.limits());
+    egui::Grid::new("capabilities").show(ui, |ui| {
+        for (label, feature) in [
+            (
+                "Vertex writable storage",
+                WgpuFeatures::VERTEX_WRITABLE_STORAGE,
+            ),
+            ("BC textures", WgpuFeatures::TEXTURE_COMPRESSION_BC),
+            ("ETC2 textures", WgpuFeatures::TEXTURE_COMPRESSION_ETC2),
+        ] {
+            ui.label(label);
+            ui.label(if features.contains(feature) {
+                "✔"
+            } else {
+                "✘"
+            });
+            ui.end_row();
+        }
+        for (label, limit) in [
+            ("Max texture size", limits.max_texture_dimension_2d),
+            ("Max storage buffer", limits.max_storage_buffer_binding_size),
+            (
+                "Storage buffers per stage",
+                limits.max_storage_buffers_per_shader_stage,
+            ),
+            (
+                "Compute workgroups",
+                limits.max_compute_workgroups_per_dimension,
+            ),
+        ] {
+            ui.label(label);
+            ui.label(limit.to_string());
+            ui.end_row();
+        }
+    });
+}
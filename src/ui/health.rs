@@ -0,0 +1,240 @@
+// Startup health check. Once the first scans are done, the assets root is checked and what was
+// found is logged. If the root is missing, a panel offers to create it or pick another (which
+// restarts han-ed). If there are no effects or images, a first-run panel offers an example effect
+// and opens the folder. Failures are shown in the panel or as toasts; none stop the editor.
+
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use super::{
+    actions::{process_actions, Action, Actions},
+    global::restart,
+    han_ed_ui,
+    timeline::capitalize,
+    widgets::open_path,
+};
+use crate::{browser::AssetBrowser, reffect::REffect, toast::Toasts};
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Health>()
+            .add_system(check_health)
+            .add_system(ui_health.after(han_ed_ui).before(process_actions));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RootStatus {
+    Ok,
+    Missing,
+    NotAFolder,
+}
+
+impl RootStatus {
+    pub fn of(root: &Path) -> Self {
+        match std::fs::metadata(root) {
+            Ok(meta) if meta.is_dir() => Self::Ok,
+            Ok(_) => Self::NotAFolder,
+            Err(_) => Self::Missing,
+        }
+    }
+}
+
+/// What the check found.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    pub root: RootStatus,
+    pub effects: usize,
+    pub images: usize,
+}
+
+impl Report {
+    pub fn is_empty(&self) -> bool {
+        self.effects == 0 && self.images == 0
+    }
+
+    pub fn summary(&self, root: &Path) -> String {
+        let count = |n: usize, what: &str| match n {
+            1 => format!("1 {}", what),
+            n => format!("{} {}s", n, what),
+        };
+        match self.root {
+            RootStatus::Ok => format!(
+                "found {} and {} in {}",
+                count(self.effects, "effect"),
+                count(self.images, "image"),
+                root.display()
+            ),
+            RootStatus::Missing => format!("assets root {} doesn't exist", root.display()),
+            RootStatus::NotAFolder => format!("assets root {} isn't a folder", root.display()),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Health {
+    /// None until the scans are done.
+    pub report: Option<Report>,
+    /// The panel was closed.
+    pub dismissed: bool,
+}
+
+// Check the root once the scans are done. Cleared to check again.
+fn check_health(
+    mut health: ResMut<Health>,
+    reffect_paths: Res<AssetBrowser<REffect>>,
+    image_paths: Res<AssetBrowser<Image>>,
+) {
+    if health.report.is_some() || reffect_paths.is_scanning() || image_paths.is_scanning() {
+        return;
+    }
+
+    let report = Report {
+        root: RootStatus::of(&reffect_paths.root_path),
+        // Not those in memory.
+        effects: reffect_paths
+            .entries
+            .iter()
+            .filter(|e| !e.path.as_os_str().is_empty())
+            .count(),
+        images: image_paths.entries.len(),
+    };
+    let summary = report.summary(&reffect_paths.root_path);
+    match report.root {
+        RootStatus::Ok => info!("{}", summary),
+        _ => warn!("{}", summary),
+    }
+    health.report = Some(report);
+}
+
+// The panel for a missing root or an empty project.
+fn ui_health(
+    mut contexts: EguiContexts,
+    mut health: ResMut<Health>,
+    mut reffect_paths: ResMut<AssetBrowser<REffect>>,
+    mut image_paths: ResMut<AssetBrowser<Image>>,
+    mut actions: ResMut<Actions>,
+    toasts: Res<Toasts>,
+) {
+    let Some(report) = health.report.clone() else {
+        return;
+    };
+    if health.dismissed || (report.root == RootStatus::Ok && !report.is_empty()) {
+        return;
+    }
+
+    let root = reffect_paths.root_path.clone();
+    let mut open = true;
+    let title = match report.root {
+        RootStatus::Ok => "Welcome",
+        _ => "Assets folder",
+    };
+    egui::Window::new(title)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .open(&mut open)
+        .show(contexts.ctx_mut(), |ui| {
+            if report.root != RootStatus::Ok {
+                ui.label(format!("{}.", capitalize(&report.summary(&root))));
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            report.root == RootStatus::Missing,
+                            egui::Button::new("Create it"),
+                        )
+                        .clicked()
+                    {
+                        match std::fs::create_dir_all(&root) {
+                            Ok(()) => {
+                                reffect_paths.resolve_root();
+                                image_paths.resolve_root();
+                                // Check again once scanned.
+                                health.report = None;
+                            }
+                            Err(e) => {
+                                toasts.error(format!("failed to create {}: {}", root.display(), e))
+                            }
+                        }
+                    }
+                    if ui
+                        .button("Pick another…")
+                        .on_hover_text("Restarts han-ed with the picked folder as the assets root.")
+                        .clicked()
+                    {
+                        if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                            restart(&folder);
+                        }
+                    }
+                });
+                return;
+            }
+
+            ui.label(format!(
+                "There are no effects or images in {} yet.",
+                root.display()
+            ));
+            ui.label("Start from an example, or add some files to the folder.");
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Create example effect")
+                    .on_hover_text("A burst of particles, saved as example.han.")
+                    .clicked()
+                {
+                    actions.push(Action::Example);
+                    health.dismissed = true;
+                }
+                if ui.button("Open assets folder…").clicked() {
+                    open_path(&root, &toasts);
+                }
+            });
+        });
+
+    if !open {
+        health.dismissed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_status() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(RootStatus::of(dir.path()), RootStatus::Ok);
+        assert_eq!(
+            RootStatus::of(&dir.path().join("assets")),
+            RootStatus::Missing
+        );
+        let file = dir.path().join("assets.txt");
+        std::fs::write(&file, "").unwrap();
+        assert_eq!(RootStatus::of(&file), RootStatus::NotAFolder);
+    }
+
+    #[test]
+    fn summary() {
+        let root = Path::new("assets");
+        let report = Report {
+            root: RootStatus::Ok,
+            effects: 1,
+            images: 0,
+        };
+        assert!(!report.is_empty());
+        assert_eq!(
+            report.summary(root),
+            "found 1 effect and 0 images in assets"
+        );
+        let report = Report {
+            root: RootStatus::Missing,
+            effects: 0,
+            images: 0,
+        };
+        assert!(report.is_empty());
+        assert_eq!(report.summary(root), "assets root assets doesn't exist");
+    }
+}
@@ -0,0 +1,148 @@
+// Ids for the state egui keeps between frames. Each effect's editor is drawn under its own id scope
+// (see [effect_scope]), so widgets inside can use plain salts like "path" without sharing state with
+// the same widget in another effect. With Debug on, the ids of section headers and of the widgets
+// that keep state are recorded as they're drawn, and any seen twice in a frame are listed.
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::reffect::REffect;
+
+/// Memory id for the ids recorded this frame.
+const SEEN: &str = "ids_seen";
+
+/// Draw an effect's editor under an id scope of its own.
+pub fn effect_scope<R>(
+    ui: &mut egui::Ui,
+    handle: &Handle<REffect>,
+    add_contents: impl FnOnce(&mut egui::Ui) -> R,
+) -> R {
+    ui.push_id(("effect", handle.id()), add_contents).inner
+}
+
+/// Ids recorded in a frame, with what used them.
+#[derive(Clone, Debug, Default)]
+pub struct IdsSeen(Vec<(egui::Id, String)>);
+
+impl IdsSeen {
+    pub fn push(&mut self, id: egui::Id, what: &str) {
+        self.0.push((id, what.to_string()));
+    }
+
+    /// Ids seen more than once, with each use, in the order first seen.
+    pub fn duplicates(&self) -> Vec<(egui::Id, Vec<&str>)> {
+        let mut ids: Vec<(egui::Id, Vec<&str>)> = Vec::new();
+        for (id, what) in self.0.iter() {
+            match ids.iter_mut().find(|(i, _)| i == id) {
+                Some((_, uses)) => uses.push(what),
+                None => ids.push((*id, vec![what])),
+            }
+        }
+        ids.retain(|(_, uses)| uses.len() > 1);
+        ids
+    }
+}
+
+/// Record an id used this frame for the duplicate check. Does nothing unless debugging.
+pub fn record(ui: &egui::Ui, id: egui::Id, what: &str) {
+    if !ui.ctx().debug_on_hover() {
+        return;
+    }
+    ui.memory_mut(|memory| {
+        memory
+            .data
+            .get_temp_mut_or_default::<IdsSeen>(egui::Id::new(SEEN))
+            .push(id, what)
+    });
+}
+
+// List the ids recorded more than once this frame, and start over for the next.
+pub fn ui_duplicate_ids(ctx: &egui::Context) {
+    let id = egui::Id::new(SEEN);
+    let Some(seen) = ctx.memory_mut(|memory| {
+        let seen = memory.data.get_temp::<IdsSeen>(id);
+        memory.data.remove::<IdsSeen>(id);
+        seen
+    }) else {
+        return;
+    };
+
+    let duplicates = seen.duplicates();
+    if duplicates.is_empty() {
+        return;
+    }
+    egui::Window::new("Duplicate ids").show(ctx, |ui| {
+        ui.label("Widgets sharing these ids share their state.");
+        egui::Grid::new("duplicate_ids")
+            .striped(true)
+            .show(ui, |ui| {
+                for (id, uses) in duplicates {
+                    ui.monospace(format!("{:?}", id));
+                    ui.label(uses.join(", "));
+                    ui.end_row();
+                }
+            });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        borrow::Cow,
+        path::{Path, PathBuf},
+    };
+
+    use bevy::asset::HandleId;
+
+    use super::*;
+    use crate::{asset::AssetOpError, toast::Toasts, ui::widgets::edit_path};
+
+    fn valid(path: &str) -> Result<Cow<Path>, AssetOpError> {
+        Ok(Cow::Borrowed(Path::new(path)))
+    }
+
+    // Draw the path field of two effects with the same path, scoped or not, and return the ids
+    // recorded.
+    fn two_effects(scoped: bool) -> IdsSeen {
+        let ctx = egui::Context::default();
+        ctx.set_debug_on_hover(true);
+        let toasts = Toasts::default();
+        let handles = [(); 2].map(|_| Handle::<REffect>::weak(HandleId::random::<REffect>()));
+        let mut paths = [(); 2].map(|_| PathBuf::from("fx/ember.han"));
+
+        _ = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                for (handle, path) in handles.iter().zip(paths.iter_mut()) {
+                    _ = match scoped {
+                        true => {
+                            effect_scope(ui, handle, |ui| edit_path(path, None, &toasts, ui, valid))
+                        }
+                        false => edit_path(path, None, &toasts, ui, valid),
+                    };
+                }
+            });
+        });
+        ctx.memory_mut(|memory| memory.data.get_temp::<IdsSeen>(egui::Id::new(SEEN)))
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn scoped_paths_separate() {
+        let seen = two_effects(true);
+        assert_eq!(seen.0.len(), 2);
+        assert!(seen.duplicates().is_empty());
+
+        let seen = two_effects(false);
+        assert_eq!(seen.duplicates().len(), 1);
+    }
+
+    #[test]
+    fn duplicates() {
+        let (a, b) = (egui::Id::new("a"), egui::Id::new("b"));
+        let mut seen = IdsSeen::default();
+        seen.push(a, "header");
+        seen.push(b, "grid");
+        seen.push(a, "combo");
+        assert_eq!(seen.duplicates(), vec![(a, vec!["header", "combo"])]);
+    }
+}
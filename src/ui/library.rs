@@ -0,0 +1,239 @@
+// The library: a folder of effects outside the assets root, e.g. shared between projects, listed
+// like the effects but read-only. Effects can be previewed live, as effects in memory that aren't
+// added to the browser, and imported: copied to the same path under the assets root along with
+// their textures (see [import_from_library]). The folder is kept in the config.
+
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use super::{
+    actions::{process_actions, Action, Actions},
+    han_ed_ui,
+};
+use crate::{
+    asset::{import_from_library, load_library_effect, TextureImport},
+    browser::AssetBrowser,
+    config::EditorConfig,
+    reffect::REffect,
+    toast::Toasts,
+};
+
+/// Memory id for whether the library window is open.
+const LIBRARY: &str = "library";
+
+pub struct LibraryPlugin;
+
+impl Plugin for LibraryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Library>()
+            .add_system(ui_library.after(han_ed_ui).before(process_actions));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Library {
+    /// The library's effects, once the window has been opened with a folder set.
+    pub browser: Option<AssetBrowser<REffect>>,
+    /// Effects being previewed, by path in the library.
+    pub previews: Vec<(PathBuf, Handle<REffect>)>,
+}
+
+pub fn open_library(ctx: &egui::Context) {
+    ctx.memory_mut(|memory| memory.data.insert_temp(egui::Id::new(LIBRARY), true));
+}
+
+fn library_browser(folder: &Path) -> AssetBrowser<REffect> {
+    let mut browser = AssetBrowser::new(folder, "han");
+    browser.external = true;
+    browser
+}
+
+fn import_message(path: &Path, texture: Option<&TextureImport>) -> String {
+    match texture {
+        Some(TextureImport::Copied(texture)) => {
+            format!("imported {} with {}", path.display(), texture)
+        }
+        Some(TextureImport::Renamed { from, to }) => format!(
+            "imported {}, with {} copied as {}",
+            path.display(),
+            from,
+            to
+        ),
+        _ => format!("imported {}", path.display()),
+    }
+}
+
+// The folder, then its effects, each with buttons to preview and import it. Previews are dropped
+// when the window is closed.
+#[allow(clippy::too_many_arguments)]
+fn ui_library(
+    mut contexts: EguiContexts,
+    mut library: ResMut<Library>,
+    mut config: ResMut<EditorConfig>,
+    mut reffect_paths: ResMut<AssetBrowser<REffect>>,
+    mut image_paths: ResMut<AssetBrowser<Image>>,
+    mut reffects: ResMut<Assets<REffect>>,
+    asset_server: Res<AssetServer>,
+    type_registry: Res<AppTypeRegistry>,
+    mut actions: ResMut<Actions>,
+    toasts: Res<Toasts>,
+) {
+    let ctx = contexts.ctx_mut();
+    let id = egui::Id::new(LIBRARY);
+    let mut open = ctx
+        .memory_mut(|memory| memory.data.get_temp::<bool>(id))
+        .unwrap_or_default();
+    if !open {
+        for (_, handle) in library.previews.drain(..) {
+            actions.push(Action::Discard(handle));
+        }
+        return;
+    }
+
+    let Library { browser, previews } = &mut *library;
+    if browser.is_none() {
+        *browser = config.library.as_deref().map(library_browser);
+    }
+
+    egui::Window::new("Library")
+        .open(&mut open)
+        .vscroll(true)
+        .default_width(360.0)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Folder");
+                match config.library.as_ref() {
+                    Some(folder) => ui.monospace(folder.display().to_string()),
+                    None => ui.weak("none"),
+                };
+                if ui.button("Choose…").clicked() {
+                    if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                        *browser = Some(library_browser(&folder));
+                        config.library = Some(folder);
+                    }
+                }
+            });
+
+            let Some(browser) = browser.as_mut() else {
+                ui.label("Choose a folder of effects, e.g. a shared library, to preview and import them from.");
+                return;
+            };
+            browser.poll_scan();
+            let library_root = browser.root_path.clone();
+            let same_root = library_root == reffect_paths.root_path;
+            if same_root {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    "⚠ This is the assets root; there's nothing to import.",
+                );
+            }
+            ui.separator();
+
+            browser.show(
+                &asset_server,
+                ui,
+                |_, _, _| (),
+                |_, entry, ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(entry.path.to_string_lossy());
+
+                        match previews.iter().position(|(path, _)| *path == entry.path) {
+                            Some(i) => {
+                                if ui.button("Hide").clicked() {
+                                    let (_, handle) = previews.remove(i);
+                                    actions.push(Action::Discard(handle));
+                                }
+                            }
+                            None => {
+                                if ui.button("Preview").clicked() {
+                                    match load_library_effect(
+                                        &library_root,
+                                        &entry.path,
+                                        &type_registry.read(),
+                                        &asset_server,
+                                    ) {
+                                        Ok(re) => {
+                                            let handle = reffects.add(re);
+                                            actions.push(Action::Show(handle.clone()));
+                                            previews.push((entry.path.clone(), handle));
+                                        }
+                                        Err(e) => e.toast(
+                                            format!("failed to load {}", entry.path.display()),
+                                            &toasts,
+                                        ),
+                                    }
+                                }
+                            }
+                        }
+
+                        if ui
+                            .add_enabled(!same_root, egui::Button::new("Import"))
+                            .on_hover_text(
+                                "Copy the effect and its texture to the same path under the \
+                                 assets root.",
+                            )
+                            .clicked()
+                        {
+                            match import_from_library(
+                                &library_root,
+                                &entry.path,
+                                &reffect_paths.root_path,
+                            ) {
+                                Ok((path, texture)) => {
+                                    toasts.info(import_message(&path, texture.as_ref()));
+                                    match texture {
+                                        Some(TextureImport::Missing(texture)) => toasts.warn(
+                                            format!("texture {} isn't in the library", texture),
+                                        ),
+                                        Some(TextureImport::Copied(_))
+                                        | Some(TextureImport::Renamed { .. }) => {
+                                            image_paths.rescan()
+                                        }
+                                        _ => (),
+                                    }
+                                    let handle = asset_server.load(path.as_path());
+                                    reffect_paths.insert(path, handle);
+                                }
+                                Err(e) => e.toast(
+                                    format!("failed to import {}", entry.path.display()),
+                                    &toasts,
+                                ),
+                            }
+                        }
+                    });
+                    None
+                },
+            );
+        });
+
+    if !open {
+        ctx.memory_mut(|memory| memory.data.insert_temp(id, false));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages() {
+        let path = Path::new("fx/spark.han");
+        assert_eq!(import_message(path, None), "imported fx/spark.han");
+        assert_eq!(
+            import_message(path, Some(&TextureImport::Copied("dot.png".to_string()))),
+            "imported fx/spark.han with dot.png"
+        );
+        assert_eq!(
+            import_message(
+                path,
+                Some(&TextureImport::Renamed {
+                    from: "dot.png".to_string(),
+                    to: "dot1.png".to_string()
+                })
+            ),
+            "imported fx/spark.han, with dot.png copied as dot1.png"
+        );
+    }
+}
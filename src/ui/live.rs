@@ -0,0 +1,418 @@
+// Live (spawned) effects: spawner state, particle counts, scrubbing, preview tints and property
+// previews.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_egui::egui::{self, CollapsingHeader};
+use bevy_hanabi::prelude::*;
+
+use super::{
+    actions::{Action, Actions, Tint},
+    widgets::{color_edit_button, ui_attribute_value, value_vec3_single},
+};
+use crate::{
+    alive::{AliveHistory, AliveSample, HISTORY_SECONDS},
+    asset::asset_path_string,
+    browser::AssetBrowser,
+    change::*,
+    config::{EditorConfig, Note},
+    reffect::{AttributeValue, REffect},
+    scrub, LiveEffect,
+};
+
+pub type LiveEffects<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Name,
+        &'static LiveEffect,
+        Option<&'static EffectSpawner>,
+        Option<&'static mut CompiledParticleEffect>,
+        Option<&'static Tint>,
+    ),
+>;
+
+/// Height of the list of live effects before it scrolls.
+const LIST_HEIGHT: f32 = 400.0;
+
+/// Filter and compact mode for the Live section, kept in memory.
+#[derive(Clone, Default)]
+struct LiveView {
+    filter: String,
+    compact: bool,
+}
+
+// We want to keep this around so that we can package these live effects into a scene later?
+pub fn ui_live(
+    live_effects: &mut LiveEffects,
+    reffects: &mut Assets<REffect>,
+    reffect_paths: &mut AssetBrowser<REffect>,
+    actions: &mut Actions,
+    alive: &AliveHistory,
+    config: &mut EditorConfig,
+    ui: &mut egui::Ui,
+) {
+    CollapsingHeader::new("Live")
+        .default_open(true)
+        .show(ui, |ui| {
+            if scrub::is_held(ui.ctx()) {
+                ui.horizontal(|ui| {
+                    ui.label("⏸ time held at the scrubbed frame");
+                    if ui.button("Resume").clicked() {
+                        scrub::request(ui.ctx(), scrub::ScrubRequest::Resume);
+                    }
+                });
+            }
+
+            let id = ui.id().with("view");
+            let mut view = ui
+                .memory_mut(|memory| memory.data.get_temp::<LiveView>(id))
+                .unwrap_or_default();
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut view.filter)
+                        .hint_text("Filter by name")
+                        .desired_width(140.0),
+                );
+                ui.checkbox(&mut view.compact, "Compact")
+                    .on_hover_text("One line per effect.");
+            });
+            ui.memory_mut(|memory| memory.data.insert_temp(id, view.clone()));
+
+            let filter = view.filter.to_lowercase();
+            let rows: Vec<Entity> = live_effects
+                .iter()
+                .filter(|(_, name, ..)| name.as_str().to_lowercase().contains(&filter))
+                .map(|(entity, ..)| entity)
+                .collect();
+            let total = live_effects.iter().len();
+            if rows.len() < total {
+                ui.weak(format!("{} of {} shown", rows.len(), total));
+            }
+
+            // Rows are keyed by entity so their ids don't change as others are filtered out or
+            // scrolled past.
+            let scroll = egui::ScrollArea::vertical()
+                .id_source("live_rows")
+                .max_height(LIST_HEIGHT);
+            if view.compact {
+                // Only the visible rows are built.
+                let row_height = ui.spacing().interact_size.y;
+                scroll.show_rows(ui, row_height, rows.len(), |ui, range| {
+                    for &entity in &rows[range] {
+                        if let Ok((entity, name, _, spawner, _, tint)) = live_effects.get(entity) {
+                            ui.push_id(entity, |ui| {
+                                ui_live_row(entity, name, spawner, tint, actions, ui)
+                            });
+                        }
+                    }
+                });
+                return;
+            }
+
+            scroll.show(ui, |ui| {
+                for &entity in rows.iter() {
+                    let Ok((entity, name, live_effect, spawner, compiled, tint)) =
+                        live_effects.get_mut(entity)
+                    else {
+                        continue;
+                    };
+                    ui.push_id(entity, |ui| {
+                        ui_live_row(entity, name, spawner, tint, actions, ui);
+
+                        if let (Some(tint), Some(re)) = (tint, reffects.get_mut(&live_effect.0)) {
+                            let entry = reffect_paths
+                                .entries
+                                .iter_mut()
+                                .find(|e| e.handle.as_ref() == Some(&live_effect.0));
+                            let locked =
+                                entry.as_ref().map_or(false, |e| config.is_locked(&e.path));
+                            if ui_bake_tint(entity, &live_effect.0, re, tint.0, locked, actions, ui)
+                            {
+                                if let Some(entry) = entry {
+                                    entry.dirty = true;
+                                }
+                            }
+                        }
+
+                        if let Some(re) = reffects.get(&live_effect.0) {
+                            ui_scrub(&live_effect.0, re, ui);
+
+                            if let Some(samples) = alive.get(entity) {
+                                CollapsingHeader::new("Particles")
+                                    .id_source(("alive", entity))
+                                    .show(ui, |ui| ui_alive_graph(samples, re.capacity, ui));
+                            }
+                        }
+
+                        // Notes are kept by path, so unsaved effects can't have any.
+                        let key = reffect_paths
+                            .entries
+                            .iter()
+                            .find(|e| e.handle.as_ref() == Some(&live_effect.0))
+                            .and_then(|e| asset_path_string(&e.path.as_path().into()).ok());
+                        if let Some(key) = key {
+                            ui_notes(entity, key, config, ui);
+                        }
+
+                        if let (Some(re), Some(compiled)) =
+                            (reffects.get_mut(&live_effect.0), compiled)
+                        {
+                            if ui_live_properties(entity, re, compiled, ui) {
+                                // Written back to the defaults.
+                                if let Some(entry) = reffect_paths
+                                    .entries
+                                    .iter_mut()
+                                    .find(|e| e.handle.as_ref() == Some(&live_effect.0))
+                                {
+                                    entry.dirty = true;
+                                }
+                            }
+                        }
+                    });
+                }
+            });
+        });
+}
+
+// Name, spawner state and the per-effect buttons, on one line.
+fn ui_live_row(
+    entity: Entity,
+    name: &Name,
+    spawner: Option<&EffectSpawner>,
+    tint: Option<&Tint>,
+    actions: &mut Actions,
+    ui: &mut egui::Ui,
+) {
+    ui.horizontal(|ui| {
+        ui.label(format!(
+            "{} ({:?}): active: {} particles: {}",
+            name,
+            entity,
+            spawner.map_or(false, |s| s.is_active()),
+            spawner.map_or(0, |s| s.spawn_count()),
+        ));
+        if ui.button("Reset").clicked() {
+            actions.push(Action::Reset(entity));
+        }
+
+        // Preview only; see ui_bake_tint.
+        let mut color = tint.map_or(Vec4::ONE, |t| t.0);
+        if color_edit_button(&mut color, ui) {
+            actions.push(Action::Tint(entity, Some(color)));
+        }
+        if tint.is_some()
+            && ui
+                .small_button("⟲")
+                .on_hover_text("Clear the tint.")
+                .clicked()
+        {
+            actions.push(Action::Tint(entity, None));
+        }
+        // Confirmed, since the spawner state and placement are lost.
+        ui.menu_button("🗙", |ui| {
+            ui.label(format!("Despawn {}?", name));
+            ui.horizontal(|ui| {
+                if ui.button("Despawn").clicked() {
+                    actions.push(Action::Despawn(entity));
+                    ui.close_menu();
+                }
+                if ui.button("Cancel").clicked() {
+                    ui.close_menu();
+                }
+            });
+        });
+    });
+}
+
+// Multiply the effect's colors by the live effect's tint for good, and clear the tint. Returns true
+// if the effect changed.
+fn ui_bake_tint(
+    entity: Entity,
+    handle: &Handle<REffect>,
+    re: &mut REffect,
+    tint: Vec4,
+    locked: bool,
+    actions: &mut Actions,
+    ui: &mut egui::Ui,
+) -> bool {
+    let baked = ui
+        .add_enabled(!locked, egui::Button::new("Bake tint into effect"))
+        .on_hover_text("Multiply the set color and color over lifetime by the tint.")
+        .clicked();
+    if baked {
+        re.tint(tint);
+        actions.push(Action::Tint(entity, None));
+        actions.push(Action::Regenerate(handle.clone(), ChangeFlags::RENDER));
+    }
+    baked
+}
+
+// Add, edit and delete the notes shown next to an effect in the viewport.
+fn ui_notes(entity: Entity, key: String, config: &mut EditorConfig, ui: &mut egui::Ui) {
+    let count = config.notes.get(&key).map_or(0, Vec::len);
+    CollapsingHeader::new(format!("Notes ({})", count))
+        .id_source(("notes", entity))
+        .show(ui, |ui| {
+            let notes = config.notes.entry(key).or_default();
+            let mut delete = None;
+            for (i, note) in notes.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut note.text).desired_width(160.0));
+                    _ = value_vec3_single(&mut note.offset, "", ui)
+                        .on_hover_text("Offset from the effect.");
+                    if ui.small_button("🗙").clicked() {
+                        delete = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = delete {
+                notes.remove(i);
+            }
+            if ui.button("Add note").clicked() {
+                notes.push(Note {
+                    offset: Vec3::Y,
+                    ..default()
+                });
+            }
+        });
+
+    // Don't keep empty entries around.
+    config.notes.retain(|_, notes| !notes.is_empty());
+}
+
+// Estimated particles alive (bright) and spawned per frame (dim) over the last few seconds, scaled
+// to the capacity. A graph that's flat along the top means the capacity is being hit.
+fn ui_alive_graph(samples: &VecDeque<AliveSample>, capacity: u32, ui: &mut egui::Ui) {
+    let Some(last) = samples.back() else {
+        return;
+    };
+
+    let size = egui::vec2(ui.available_width().min(240.0), 48.0);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, ui.visuals().extreme_bg_color);
+
+    let max = (capacity as f32).max(1.0);
+    let point = |time: f32, count: f32| {
+        egui::pos2(
+            rect.max.x - (last.time - time) / HISTORY_SECONDS * rect.width(),
+            rect.max.y - (count / max).min(1.0) * rect.height(),
+        )
+    };
+    let recent = samples
+        .iter()
+        .filter(|s| last.time - s.time <= HISTORY_SECONDS);
+    let spawned: Vec<_> = recent
+        .clone()
+        .map(|s| point(s.time, s.spawned as f32))
+        .collect();
+    let alive: Vec<_> = recent
+        .filter_map(|s| s.alive.map(|alive| point(s.time, alive)))
+        .collect();
+
+    let visuals = ui.visuals();
+    painter.add(egui::Shape::line(
+        spawned,
+        egui::Stroke::new(1.0, visuals.weak_text_color()),
+    ));
+    painter.add(egui::Shape::line(
+        alive,
+        egui::Stroke::new(1.5, visuals.strong_text_color()),
+    ));
+    painter.rect_stroke(rect, 0.0, visuals.widgets.noninteractive.bg_stroke);
+
+    _ = response.on_hover_text(format!(
+        "alive: ~{} of {}\nspawned this frame: {}\nlast {} seconds",
+        last.alive
+            .map_or("?".to_string(), |alive| format!("{:.0}", alive)),
+        capacity,
+        last.spawned,
+        HISTORY_SECONDS,
+    ));
+}
+
+// Scrub from zero to the effect's duration (or a few seconds for repeating effects). Seeking
+// restarts the effect and advances it in fixed steps. See [scrub].
+fn ui_scrub(handle: &Handle<REffect>, re: &REffect, ui: &mut egui::Ui) {
+    let max = re.duration().unwrap_or(5.0).max(scrub::SCRUB_STEP);
+    let id = ui.id().with(("scrub", handle));
+    let mut time = ui
+        .memory_mut(|memory| memory.data.get_temp::<f32>(id))
+        .unwrap_or_default();
+
+    ui.horizontal(|ui| {
+        ui.label("Time");
+        if ui
+            .add(egui::Slider::new(&mut time, 0.0..=max).suffix("s"))
+            .on_hover_text(
+                "Restart the effect and show it at this time. All live effects are held.",
+            )
+            .changed()
+        {
+            scrub::request(ui.ctx(), scrub::ScrubRequest::Seek(handle.clone(), time));
+        }
+    });
+
+    ui.memory_mut(|memory| memory.data.insert_temp(id, time));
+}
+
+// Preview values for a live effect's properties, set on the compiled effect without regenerating.
+// They're kept in memory per entity, so a regenerated (respawned) effect starts over from the
+// defaults. Returns true if the values were written back to the defaults.
+fn ui_live_properties(
+    entity: Entity,
+    re: &mut REffect,
+    mut compiled: Mut<CompiledParticleEffect>,
+    ui: &mut egui::Ui,
+) -> bool {
+    if re.properties.is_empty() {
+        return false;
+    }
+
+    let id = egui::Id::new(("live_properties", entity));
+    let mut values = ui
+        .memory_mut(|memory| memory.data.get_temp::<Vec<AttributeValue>>(id))
+        .filter(|values| {
+            values.len() == re.properties.len()
+                && values
+                    .iter()
+                    .zip(re.properties.iter())
+                    .all(|(v, p)| v.type_name() == p.default_value.type_name())
+        })
+        .unwrap_or_else(|| re.properties.iter().map(|p| p.default_value).collect());
+
+    let mut write_back = false;
+    ui.indent(id, |ui| {
+        grid!(ui, id.with("grid"), |ui| {
+            for (p, value) in re.properties.iter().zip(values.iter_mut()) {
+                ui.label(&p.name);
+                if ui
+                    .horizontal(|ui| ui_attribute_value(value, ui))
+                    .inner
+                    .changed()
+                {
+                    compiled.set_property(&p.name, (*value).into());
+                }
+                ui.end_row();
+            }
+            Change::from(())
+        });
+
+        if ui
+            .button("Write back to defaults")
+            .on_hover_text("Make the preview values the effect's property defaults.")
+            .clicked()
+        {
+            for (p, value) in re.properties.iter_mut().zip(values.iter()) {
+                p.default_value = *value;
+            }
+            write_back = true;
+        }
+    });
+
+    ui.memory_mut(|memory| memory.data.insert_temp(id, values));
+    write_back
+}
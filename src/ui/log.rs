@@ -0,0 +1,35 @@
+use bevy_egui::egui::{self, CollapsingHeader};
+
+use crate::capture::LogCapture;
+
+// Records captured from hanabi and the renderer, newest at the bottom.
+pub fn ui_log(capture: &LogCapture, ui: &mut egui::Ui) {
+    let records = capture.history();
+    CollapsingHeader::new(format!("Log ({})", records.len()))
+        .id_source("log")
+        .default_open(false)
+        .show(ui, |ui| {
+            if ui.button("Clear").clicked() {
+                capture.clear();
+            }
+
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for record in records.iter() {
+                        let color = match record.level {
+                            bevy::log::Level::ERROR => ui.visuals().error_fg_color,
+                            bevy::log::Level::WARN => ui.visuals().warn_fg_color,
+                            _ => ui.visuals().text_color(),
+                        };
+                        let text = match &record.effect {
+                            Some(effect) => format!("[{}] {}", effect, record.message),
+                            None => record.message.clone(),
+                        };
+                        ui.colored_label(color, text)
+                            .on_hover_text(format!("{} {}", record.level, record.target));
+                    }
+                });
+        });
+}
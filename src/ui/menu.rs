@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use super::{
+    actions::Actions,
+    commands::{self, Command},
+    palette::open_palette,
+    wizard::Style,
+    WINDOW,
+};
+use crate::{
+    browser::AssetBrowser,
+    camera::{OrbitCamera, ViewPreset},
+    reffect::REffect,
+    toast::Toasts,
+};
+
+const SHORTCUTS: &[(&str, &str)] = &[
+    ("Esc", "Cancel edit, close popup, or quit"),
+    ("Ctrl+Q", "Quit"),
+    ("Ctrl+P", "Command palette"),
+    (
+        "Tab, Shift+Tab",
+        "Next or previous field in the effect editor",
+    ),
+    ("Enter", "Commit the field and go to the next"),
+    ("PageUp, PageDown", "Open the previous or next effect"),
+    ("Ctrl+click", "Select effect"),
+    ("Shift+click", "Select range of effects"),
+    ("Right drag", "Orbit camera"),
+    ("Middle drag", "Pan camera"),
+    ("Scroll", "Zoom camera"),
+];
+
+// A button for the command that closes the menu when clicked.
+fn button(
+    c: Command,
+    command: &mut Option<Command>,
+    browser: &AssetBrowser<REffect>,
+    ui: &mut egui::Ui,
+) -> egui::Response {
+    let mut response = ui.add_enabled(c.enabled(browser), egui::Button::new(c.label().as_ref()));
+    if let Some(shortcut) = c.shortcut() {
+        response = response.on_hover_text(shortcut);
+    }
+    if response.clicked() {
+        *command = Some(c);
+        ui.close_menu();
+    }
+    response
+}
+
+// File, Edit, View and Help menus. Entries run the same [commands](super::commands) as the
+// buttons in the editor window and the command palette. Things that don't exist yet are disabled.
+pub fn ui_menu_bar(
+    ctx: &egui::Context,
+    reffect_paths: &mut AssetBrowser<REffect>,
+    orbit: &mut OrbitCamera,
+    asset_server: &AssetServer,
+    actions: &mut Actions,
+    toasts: &Toasts,
+) {
+    let mut command = None;
+    egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
+        egui::menu::bar(ui, |ui| {
+            ui.menu_button("File", |ui| {
+                button(Command::New, &mut command, reffect_paths, ui);
+                ui.menu_button("New from template", |ui| {
+                    button(
+                        Command::NewFrom(Style::Burst),
+                        &mut command,
+                        reffect_paths,
+                        ui,
+                    );
+                    button(
+                        Command::NewFrom(Style::Continuous),
+                        &mut command,
+                        reffect_paths,
+                        ui,
+                    );
+                });
+                button(Command::Wizard, &mut command, reffect_paths, ui);
+
+                #[cfg(not(target_arch = "wasm32"))]
+                button(Command::Open, &mut command, reffect_paths, ui);
+                #[cfg(not(target_arch = "wasm32"))]
+                button(Command::Library, &mut command, reffect_paths, ui)
+                    .on_hover_text("Preview and import effects from another folder.");
+
+                button(Command::Save, &mut command, reffect_paths, ui)
+                    .on_hover_text("Save the selected effects.");
+                button(Command::SaveAll, &mut command, reffect_paths, ui);
+                button(Command::ExportScene, &mut command, reffect_paths, ui);
+
+                ui.separator();
+                button(Command::Quit, &mut command, reffect_paths, ui);
+            });
+
+            ui.menu_button("Edit", |ui| {
+                for label in ["Undo", "Redo", "Copy Effect", "Paste Effect"] {
+                    _ = ui.add_enabled(false, egui::Button::new(label));
+                }
+            });
+
+            ui.menu_button("View", |ui| {
+                let mut open = ui
+                    .memory_mut(|memory| memory.data.get_temp::<bool>(egui::Id::new(WINDOW)))
+                    .unwrap_or(true);
+                if ui
+                    .checkbox(&mut open, Command::ToggleWindow.label().as_ref())
+                    .changed()
+                {
+                    command = Some(Command::ToggleWindow);
+                }
+                _ = ui.add_enabled(false, egui::Button::new("Stats overlay"));
+                _ = ui.add_enabled(false, egui::Button::new("Gizmos"));
+
+                ui.separator();
+                for preset in ViewPreset::ALL {
+                    button(Command::View(preset), &mut command, reffect_paths, ui);
+                }
+                let mut orthographic = orbit.orthographic;
+                if ui
+                    .checkbox(
+                        &mut orthographic,
+                        Command::ToggleOrthographic.label().as_ref(),
+                    )
+                    .changed()
+                {
+                    command = Some(Command::ToggleOrthographic);
+                }
+
+                ui.separator();
+                if ui
+                    .button("Command palette…")
+                    .on_hover_text("Ctrl+P")
+                    .clicked()
+                {
+                    open_palette(ui.ctx());
+                    ui.close_menu();
+                }
+            });
+
+            #[cfg(debug_assertions)]
+            ui.menu_button("Debug", |ui| {
+                button(Command::AddInMemory, &mut command, reffect_paths, ui)
+                    .on_hover_text("An effect without a path, listed under Unsaved / in-memory.");
+            });
+
+            ui.menu_button("Help", |ui| {
+                ui.menu_button("Shortcuts", |ui| {
+                    egui::Grid::new("shortcuts").show(ui, |ui| {
+                        for (keys, action) in SHORTCUTS {
+                            ui.monospace(*keys);
+                            ui.label(*action);
+                            ui.end_row();
+                        }
+                    });
+                });
+                ui.menu_button("About", |ui| {
+                    ui.label(format!("han-ed {}", env!("CARGO_PKG_VERSION")));
+                    ui.label("An editor for bevy_hanabi particle effects.");
+                });
+            });
+        });
+    });
+
+    if let Some(command) = command {
+        commands::run(
+            command,
+            ctx,
+            reffect_paths,
+            orbit,
+            asset_server,
+            actions,
+            toasts,
+        );
+    }
+}
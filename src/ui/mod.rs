@@ -0,0 +1,426 @@
+// The editor UI. Each section of the window is drawn by its own module with only what it needs.
+// Anything that reaches outside the UI (spawning and despawning live effects, saving) is pushed to
+// [Actions] and handled after the UI runs. See [actions].
+
+/// Collapsing header and body. The id salt (e.g. the effect handle) keeps the open state separate
+/// for each effect. The optional count is shown in the header, e.g. "Update Modifiers (2)".
+macro_rules! header {
+    ($ui:ident, $label:literal, $id:expr, $body:expr) => {
+        header!($ui, $label, $id, None::<usize>, $body)
+    };
+    ($ui:ident, $label:literal, $id:expr, $count:expr, $body:expr) => {{
+        let label = match $count {
+            Some(n) => format!("{} ({})", $label, n),
+            None => $label.to_string(),
+        };
+        let id = $ui.make_persistent_id(($label, $id));
+        $crate::ui::ids::record($ui, id, $label);
+        let mut response = bevy_egui::egui::CollapsingHeader::new(label)
+            .id_source(id)
+            .default_open(true)
+            .show($ui, |ui| grid!(ui, id.with("grid"), $body));
+        response.header_response = response.header_response.doc($label);
+        response.merge()
+    }};
+}
+
+/// Two-column grid (label, widget) for a section of fields. Use [row!] for each field.
+macro_rules! grid {
+    ($ui:ident, $id:expr, $body:expr) => {
+        bevy_egui::egui::Grid::new($id)
+            .num_columns(2)
+            .min_col_width($crate::ui::LABEL_WIDTH)
+            .show($ui, $body)
+            .merge()
+    };
+}
+
+/// Label and value.
+macro_rules! value {
+    ($label:literal, $ui:ident, $value:expr, $suffix:literal) => {{
+        let id = $ui.id().with($label);
+        row!($label, $ui, |ui| $crate::ui::widgets::ui_value(
+            id,
+            &mut $value,
+            $suffix,
+            ui,
+            $crate::ui::widgets::value_f32
+        ))
+    }};
+}
+
+/// Horizontal, with label.
+macro_rules! hl {
+    ($label:expr, $ui:ident, $body:expr) => {
+        $ui.horizontal(|ui| {
+            ui.label($label);
+            $crate::ui::__contents(ui, $body)
+        })
+        .inner
+    };
+}
+
+/// Grid row, with label.
+macro_rules! row {
+    ($label:expr, $ui:ident, $body:expr) => {{
+        $ui.label($label).doc($label);
+        let change = $ui.horizontal(|ui| $crate::ui::__contents(ui, $body)).inner;
+        $ui.end_row();
+        change
+    }};
+}
+
+pub mod actions;
+mod commands;
+pub mod defaults;
+mod dependencies;
+mod effects;
+mod focus;
+mod global;
+#[cfg(not(target_arch = "wasm32"))]
+mod health;
+pub mod ids;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod library;
+mod live;
+mod log;
+mod menu;
+mod modifiers;
+mod overlay;
+mod palette;
+pub mod paths;
+pub mod replace;
+mod timeline;
+mod tools;
+pub mod variations;
+mod widgets;
+pub mod wizard;
+
+use bevy::{
+    asset::HandleId,
+    core_pipeline::bloom::BloomSettings,
+    ecs::system::SystemParam,
+    prelude::*,
+    render::renderer::{RenderAdapterInfo, RenderDevice},
+    utils::HashMap,
+};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    alive::AliveHistory,
+    asset::Dependencies,
+    browser::AssetBrowser,
+    camera::OrbitCamera,
+    capture::LogCapture,
+    change::{record_loaded, Change, ContentHashes},
+    config::{EditorConfig, Palette},
+    preview::{PreviewMutes, PreviewSettings},
+    profile::DeviceLimits,
+    reffect::REffect,
+    scatter::Scatters,
+    toast::Toasts,
+};
+use actions::Actions;
+use defaults::{ModifierDefaults, NewEffectTemplate};
+use modifiers::{TextureInfo, Textures};
+
+/// Minimum width of the label column in grids, so sections line up.
+pub const LABEL_WIDTH: f32 = 120.0;
+
+/// Memory id for whether the editor window is open.
+pub const WINDOW: &str = "window";
+
+/// Memory id for the effect path to scroll to.
+pub const JUMP_TO: &str = "jump_to";
+
+/// Memory id for the [Palette], copied from the config each frame for widgets that can't reach it.
+pub const PALETTE: &str = "palette";
+
+/// The configured palette. See [PALETTE].
+pub fn palette(ctx: &egui::Context) -> Palette {
+    ctx.memory_mut(|memory| memory.data.get_temp(egui::Id::new(PALETTE)))
+        .unwrap_or_default()
+}
+
+/// A palette color for egui.
+pub fn color32([r, g, b, a]: [u8; 4]) -> egui::Color32 {
+    egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+// So we don't have to explicitly set the type for body in hl!
+#[doc(hidden)]
+#[inline]
+pub fn __contents<R: Into<Change>>(
+    ui: &mut egui::Ui,
+    f: impl FnOnce(&mut egui::Ui) -> R,
+) -> Change {
+    f(ui).into()
+}
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Actions>()
+            .init_resource::<ContentHashes>()
+            .init_resource::<ModifierDefaults>()
+            .init_resource::<NewEffectTemplate>()
+            .add_startup_system(defaults::load_modifier_defaults)
+            .add_startup_system(defaults::load_new_effect_template)
+            .add_system(record_loaded.before(han_ed_ui))
+            .add_system(adopt_unsaved.before(han_ed_ui))
+            .add_system(han_ed_ui)
+            .add_system(actions::process_actions.after(han_ed_ui))
+            .add_system(defaults::store_modifier_defaults.after(han_ed_ui))
+            .add_system(defaults::store_new_effect_template.after(han_ed_ui))
+            .add_system(overlay::draw_gizmos.after(han_ed_ui))
+            .add_system(overlay::draw_axes.after(han_ed_ui))
+            .add_system(overlay::draw_notes.after(han_ed_ui));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_plugin(health::HealthPlugin)
+            .add_plugin(library::LibraryPlugin);
+    }
+}
+
+// Effects added in memory by other systems (not loaded from a file, nor added by the editor) get an
+// entry without a path, so they can be edited and saved. Variations are left out until kept, and
+// library previews altogether.
+fn adopt_unsaved(
+    mut contexts: EguiContexts,
+    reffects: Res<Assets<REffect>>,
+    mut reffect_paths: ResMut<AssetBrowser<REffect>>,
+    #[cfg(not(target_arch = "wasm32"))] library: Res<library::Library>,
+) {
+    if !reffects.is_changed() {
+        return;
+    }
+    #[allow(unused_mut)]
+    let mut skip = variations::variation_handles(contexts.ctx_mut());
+    #[cfg(not(target_arch = "wasm32"))]
+    skip.extend(library.previews.iter().map(|(_, handle)| handle.clone()));
+    for id in reffects.ids().filter(|id| matches!(id, HandleId::Id(..))) {
+        let handle = reffects.get_handle(id);
+        if !skip.contains(&handle) {
+            reffect_paths.adopt(handle);
+        }
+    }
+}
+
+/// Editor-wide resources, bundled to keep [han_ed_ui] under the system parameter limit.
+#[derive(SystemParam)]
+pub struct EditorResources<'w> {
+    toasts: Res<'w, Toasts>,
+    preview: ResMut<'w, PreviewSettings>,
+    capture: Res<'w, LogCapture>,
+    actions: ResMut<'w, Actions>,
+    alive: Res<'w, AliveHistory>,
+    hashes: ResMut<'w, ContentHashes>,
+    config: ResMut<'w, EditorConfig>,
+    defaults: ResMut<'w, ModifierDefaults>,
+    template: ResMut<'w, NewEffectTemplate>,
+    scatters: ResMut<'w, Scatters>,
+    device: Option<Res<'w, RenderDevice>>,
+    adapter: Option<Res<'w, RenderAdapterInfo>>,
+    limits: Option<Res<'w, DeviceLimits>>,
+    mutes: ResMut<'w, PreviewMutes>,
+}
+
+/// Image assets and their egui textures, for texture previews.
+#[derive(SystemParam)]
+pub struct ImageResources<'w, 's> {
+    contexts: EguiContexts<'w, 's>,
+    images: Res<'w, Assets<Image>>,
+    image_events: EventReader<'w, 's, AssetEvent<Image>>,
+    image_paths: ResMut<'w, AssetBrowser<Image>>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn han_ed_ui(
+    mut cameras: Query<(&mut Camera, &mut BloomSettings, &mut OrbitCamera)>,
+    asset_server: Res<AssetServer>,
+    image_resources: ImageResources,
+    mut texture_infos: Local<HashMap<HandleId, TextureInfo>>,
+    mut dependencies: Local<Option<Dependencies>>,
+    editor: EditorResources,
+    mut reffect_paths: ResMut<AssetBrowser<REffect>>,
+    mut reffects: ResMut<Assets<REffect>>,
+    mut live_effects: live::LiveEffects,
+    type_registry: Res<AppTypeRegistry>,
+    #[cfg(not(target_arch = "wasm32"))] mut imports: ResMut<crate::import::TextureImports>,
+    #[cfg(not(target_arch = "wasm32"))] session: Res<crate::session::SessionLog>,
+) {
+    let EditorResources {
+        toasts,
+        mut preview,
+        capture,
+        mut actions,
+        alive,
+        mut hashes,
+        mut config,
+        mut defaults,
+        mut template,
+        mut scatters,
+        device,
+        adapter,
+        limits,
+        mut mutes,
+    } = editor;
+    let ImageResources {
+        mut contexts,
+        images,
+        mut image_events,
+        mut image_paths,
+    } = image_resources;
+
+    // Drop cached texture info for changed images.
+    for event in image_events.iter() {
+        match event {
+            AssetEvent::Modified { handle } | AssetEvent::Removed { handle } => {
+                texture_infos.remove(&handle.id());
+            }
+            _ => (),
+        }
+    }
+
+    // Clone the context so we can register textures with egui inside the window.
+    let ctx = contexts.ctx_mut().clone();
+    ctx.memory_mut(|memory| {
+        memory
+            .data
+            .insert_temp(egui::Id::new(PALETTE), config.palette.clone())
+    });
+
+    let (_, _, mut orbit) = cameras.single_mut();
+    menu::ui_menu_bar(
+        &ctx,
+        &mut reffect_paths,
+        &mut orbit,
+        &asset_server,
+        &mut actions,
+        &toasts,
+    );
+    palette::ui_palette(
+        &ctx,
+        &mut reffect_paths,
+        &mut orbit,
+        &asset_server,
+        &mut actions,
+        &toasts,
+    );
+
+    // The live entity for each effect, collected up front so the sections below can borrow the
+    // query mutably.
+    let live: HashMap<Handle<REffect>, Entity> = live_effects
+        .iter()
+        .map(|(entity, _, live_effect, ..)| (live_effect.0.clone(), entity))
+        .collect();
+
+    let window_id = egui::Id::new(WINDOW);
+    let mut open = ctx
+        .memory_mut(|memory| memory.data.get_temp::<bool>(window_id))
+        .unwrap_or(true);
+    let window = egui::Window::new("han-ed").vscroll(true).open(&mut open);
+    // Edit a copy so the config is only marked changed (and saved) when it is.
+    let mut editor_config = config.clone();
+    window.show(&ctx, |ui| {
+        let (mut camera, mut bloom, mut orbit) = cameras.single_mut();
+        global::ui_global(
+            (&mut *camera, &mut *bloom, &mut *orbit),
+            &mut preview,
+            &mut editor_config,
+            (device.as_deref(), adapter.as_deref()),
+            &reffect_paths.root_path,
+            &mut actions,
+            ui,
+        );
+
+        // Only marked changed when edited, so it's only written back to the config then.
+        if defaults::ui_modifier_defaults(defaults.bypass_change_detection(), &type_registry, ui)
+            .changed()
+        {
+            defaults.set_changed();
+        }
+        if defaults::ui_new_effect_defaults(
+            template.bypass_change_detection(),
+            &type_registry,
+            &mut actions,
+            ui,
+        )
+        .changed()
+        {
+            template.set_changed();
+        }
+
+        live::ui_live(
+            &mut live_effects,
+            &mut reffects,
+            &mut reffect_paths,
+            &mut actions,
+            &alive,
+            &mut editor_config,
+            ui,
+        );
+
+        let mut textures = Textures {
+            asset_server: &asset_server,
+            images: &images,
+            image_paths: &mut image_paths,
+            contexts: &mut contexts,
+            infos: &mut texture_infos,
+            config: &mut editor_config,
+            #[cfg(not(target_arch = "wasm32"))]
+            imports: &mut imports,
+        };
+        effects::ui_effects(
+            &mut reffect_paths,
+            &mut reffects,
+            &live,
+            &mut textures,
+            &defaults,
+            &mut scatters,
+            limits.as_deref(),
+            &mut mutes,
+            &type_registry,
+            &toasts,
+            &mut actions,
+            &mut hashes,
+            ui,
+        );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        effects::confirm_import(&mut reffect_paths, &asset_server, &toasts, ui.ctx());
+
+        dependencies::ui_dependencies(
+            &mut dependencies,
+            &mut reffect_paths,
+            &image_paths,
+            &reffects,
+            &asset_server,
+            ui,
+        );
+
+        log::ui_log(&capture, ui);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        crate::session::ui_session_log(&session, &mut editor_config, ui);
+    });
+    ctx.memory_mut(|memory| memory.data.insert_temp(window_id, open));
+    if editor_config != *config {
+        *config = editor_config;
+    }
+
+    wizard::ui_wizard(&template.0, &mut actions, &ctx);
+    variations::ui_variations(&mut reffects, &mut actions, &ctx);
+    ids::ui_duplicate_ids(&ctx);
+    replace::ui_replace(
+        &mut reffect_paths,
+        &mut reffects,
+        &image_paths,
+        &asset_server,
+        &config,
+        &toasts,
+        &mut actions,
+        &ctx,
+    );
+}
@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use bevy_hanabi::prelude::*;
+use bevy_inspector_egui::reflect_inspector::InspectorUi;
+
+use crate::{
+    change::*,
+    docs::{self, Doc},
+    preview::PreviewMutes,
+    reffect::{InitAttribute, ModifierStage, REffect},
+    ui::{actions::Actions, defaults::ModifierDefaults, widgets::*},
+};
+
+pub fn ui_init(
+    re: &mut REffect,
+    handle: &Handle<REffect>,
+    defaults: &ModifierDefaults,
+    mutes: (&mut PreviewMutes, &mut Actions),
+    env: &mut InspectorUi,
+    ui: &mut egui::Ui,
+) -> Change {
+    let defaults = &defaults.0;
+    let count = Some(re.init_count());
+    let lifetime_attribute = re.has_lifetime() && re.init_lifetime.is_none();
+    header!(ui, "Initial Modifiers", handle, count, |ui| {
+        super::ui_mutes(ModifierStage::Init, re, handle, mutes, ui);
+        ui_reflect("Position", &mut re.init_position, env, ui)
+            | ui_option_reflect(
+                "Velocity",
+                &mut re.init_velocity,
+                || defaults.init_velocity.unwrap_or_default(),
+                env,
+                ui,
+            )
+            | ui_option_reflect(
+                "Size",
+                &mut re.init_size,
+                || defaults.init_size.clone().unwrap_or_default(),
+                env,
+                ui,
+            )
+            | ui_option_reflect(
+                "Age",
+                &mut re.init_age,
+                || defaults.init_age.clone().unwrap_or_default(),
+                env,
+                ui,
+            )
+            | ui_init_lifetime(
+                &mut re.init_lifetime,
+                || defaults.init_lifetime.clone().unwrap_or_default(),
+                lifetime_attribute,
+                env,
+                ui,
+            )
+            | ui_init_attributes(&mut re.init_attributes, ui)
+    })
+}
+
+// The lifetime can also be set with an attribute, in which case we don't warn.
+fn ui_init_lifetime(
+    v: &mut Option<InitLifetimeModifier>,
+    default: impl FnOnce() -> InitLifetimeModifier,
+    lifetime_attribute: bool,
+    env: &mut InspectorUi,
+    ui: &mut egui::Ui,
+) -> Change {
+    let change = ui_option_reflect("Lifetime", v, default, env, ui);
+
+    // Warn in the widget column of the following row.
+    if v.is_none() && !lifetime_attribute {
+        ui.label("");
+        ui.label("⚠ no lifetime")
+            .on_hover_text_at_pointer(docs::get("Lifetime warning").unwrap_or_default());
+        ui.end_row();
+    }
+
+    change
+}
+
+// One row per attribute: name combo and value, with a button to add more.
+fn ui_init_attributes(attributes: &mut Vec<InitAttribute>, ui: &mut egui::Ui) -> Change {
+    let mut change = Change::from(false);
+    let mut remove = None;
+
+    for (i, a) in attributes.iter_mut().enumerate() {
+        let label = if i == 0 { "Attributes" } else { "" };
+        ui.label(label).doc(label);
+        change = change
+            | ui.push_id(i, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.small_button("🗙").clicked() {
+                        remove = Some(i);
+                    }
+                    ui_init_attribute(a, ui)
+                })
+                .inner
+            })
+            .inner;
+        ui.end_row();
+    }
+
+    if let Some(i) = remove {
+        attributes.remove(i);
+        change = change | Change::from(true);
+    }
+
+    let label = if attributes.is_empty() {
+        "Attributes"
+    } else {
+        ""
+    };
+    ui.label(label).doc(label);
+    if ui.button("Add attribute").clicked() {
+        attributes.push(InitAttribute::default());
+        change = change | Change::from(true);
+    }
+    ui.end_row();
+
+    change
+}
+
+fn ui_init_attribute(a: &mut InitAttribute, ui: &mut egui::Ui) -> Change {
+    let combo = egui::ComboBox::from_id_source(ui.id().with("attribute"))
+        .selected_text(a.attribute.as_str())
+        .show_ui(ui, |ui| {
+            for attribute in Attribute::ALL {
+                let mut response =
+                    ui.selectable_label(a.attribute == attribute.name(), attribute.name());
+                if response.clicked() && a.attribute != attribute.name() {
+                    // The value type depends on the attribute.
+                    *a = InitAttribute::new(attribute);
+                    response.mark_changed();
+                    return Some(response);
+                }
+            }
+            None
+        })
+        .merge();
+
+    combo | ui_attribute_value(&mut a.value, ui)
+}
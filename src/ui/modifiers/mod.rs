@@ -0,0 +1,91 @@
+// The initial, update and render modifier sections of the effect editor.
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::{
+    change::ChangeFlags,
+    docs::Doc,
+    preview::PreviewMutes,
+    reffect::{modifier_fields, ModifierField, ModifierStage, REffect},
+    ui::actions::{Action, Actions},
+};
+
+mod init;
+mod render;
+mod update;
+
+pub use init::ui_init;
+pub use render::{ui_image_list, ui_orientation, ui_render, TextureInfo, Textures};
+pub use update::ui_update;
+
+/// What regenerates live effects after a mute in the section changes.
+fn section_flags(section: ModifierStage) -> ChangeFlags {
+    match section {
+        ModifierStage::Init => ChangeFlags::INIT,
+        ModifierStage::Update => ChangeFlags::UPDATE,
+        ModifierStage::Render(_) => ChangeFlags::RENDER,
+    }
+}
+
+// Mute toggles for the section's modifiers that are set, as the section's first row. They're
+// drawn as speakers rather than checkboxes since they only change the preview: live effects are
+// regenerated without the muted modifiers and the effect is left alone. Right-click solos.
+fn ui_mutes(
+    section: ModifierStage,
+    re: &REffect,
+    handle: &Handle<REffect>,
+    (mutes, actions): (&mut PreviewMutes, &mut Actions),
+    ui: &mut egui::Ui,
+) {
+    let fields: Vec<ModifierField> = modifier_fields()
+        .into_iter()
+        .filter(|f| section_flags(f.stage) == section_flags(section))
+        .filter(|f| !(f.modifiers)(re).is_empty())
+        .collect();
+    if fields.is_empty() {
+        return;
+    }
+
+    let mut changes = ChangeFlags::empty();
+    ui.label("Mute").doc("Mute");
+    ui.horizontal_wrapped(|ui| {
+        for field in fields.iter() {
+            let muted = mutes.is_muted(handle, field.field);
+            let text = egui::RichText::new(match muted {
+                true => format!("🔇 {}", field.label),
+                false => format!("🔈 {}", field.label),
+            })
+            .small();
+            let response = ui.selectable_label(
+                muted,
+                match muted {
+                    true => text.color(ui.visuals().warn_fg_color),
+                    false => text.weak(),
+                },
+            );
+            if response.clicked() {
+                mutes.toggle(handle, field.field);
+                changes |= section_flags(section);
+            }
+            if response.secondary_clicked() {
+                mutes.solo(handle, field.field, &fields);
+                changes |= section_flags(section);
+            }
+        }
+        if !mutes.muted(handle).is_empty()
+            && ui
+                .small_button("Clear all")
+                .on_hover_text("Unmute every modifier of the effect.")
+                .clicked()
+        {
+            mutes.clear(handle);
+            changes |= ChangeFlags::INIT | ChangeFlags::UPDATE | ChangeFlags::RENDER;
+        }
+    });
+    ui.end_row();
+
+    if !changes.is_empty() {
+        actions.push(Action::Regenerate(handle.clone(), changes));
+    }
+}
@@ -0,0 +1,793 @@
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::{HandleId, LoadState},
+    prelude::*,
+    render::render_resource::TextureFormat,
+    utils::{HashMap, Instant},
+};
+use bevy_egui::{egui, EguiContexts};
+use bevy_hanabi::prelude::*;
+use bevy_inspector_egui::reflect_inspector::InspectorUi;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::asset::{write_file, AssetOpError};
+use crate::{
+    asset::{asset_path_string, split_label, texture_asset_path, validate_path},
+    browser::{AssetBrowser, AssetEntry},
+    change::*,
+    config::EditorConfig,
+    docs::{self, Doc},
+    gradient::{self, ColorGradient, Gradient},
+    preview::PreviewMutes,
+    reffect::{ModifierStage, Orientation, ParticleTexture, REffect, RenderModifierKind},
+    toast::Toasts,
+    ui::{
+        actions::Actions,
+        defaults::ModifierDefaults,
+        ids,
+        paths::{ui_copy_path, PathDisplay},
+        widgets::*,
+    },
+};
+
+/// Texture assets and their previews, for the render section.
+pub struct Textures<'a, 'w, 's> {
+    pub asset_server: &'a AssetServer,
+    pub images: &'a Assets<Image>,
+    pub image_paths: &'a mut AssetBrowser<Image>,
+    pub contexts: &'a mut EguiContexts<'w, 's>,
+    pub infos: &'a mut HashMap<HandleId, TextureInfo>,
+    /// For the recently used textures.
+    pub config: &'a mut EditorConfig,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub imports: &'a mut crate::import::TextureImports,
+}
+
+// The texture is tagged separately since it can be swapped without respawning.
+#[allow(clippy::too_many_arguments)]
+pub fn ui_render(
+    re: &mut REffect,
+    handle: &Handle<REffect>,
+    defaults: &ModifierDefaults,
+    mutes: (&mut PreviewMutes, &mut Actions),
+    env: &mut InspectorUi,
+    textures: &mut Textures,
+    toasts: &Toasts,
+    ui: &mut egui::Ui,
+) -> ChangeFlags {
+    let defaults = &defaults.0;
+    let mut changes = ChangeFlags::empty();
+    let count = Some(re.render_count());
+    let render = header!(ui, "Render Modifiers", handle, count, |ui| {
+        let section = ModifierStage::Render(RenderModifierKind::ParticleTexture);
+        super::ui_mutes(section, re, handle, mutes, ui);
+        let start = Instant::now();
+        let texture = ui_particle_texture(
+            "Particle Texture",
+            &mut re.render_particle_texture,
+            textures,
+            ui,
+        );
+
+        // Timing for the texture list, which can be long.
+        if ui.ctx().debug_on_hover() {
+            let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+            ui.label("");
+            ui.weak(format!(
+                "{:.3} ms ({} textures)",
+                elapsed,
+                textures.image_paths.entries.len()
+            ));
+            ui.end_row();
+        }
+
+        let texture = texture
+            | ui_texture_path(
+                &mut re.render_particle_texture,
+                textures.asset_server,
+                textures.image_paths,
+                toasts,
+                ui,
+            );
+        if texture.changed() {
+            if let Some(path) = texture_path(&re.render_particle_texture, textures.asset_server) {
+                textures.config.use_texture(path);
+            }
+        }
+        changes |= texture.tag(ChangeFlags::TEXTURE);
+
+        // The texture is set when the copy finishes.
+        #[cfg(not(target_arch = "wasm32"))]
+        ui_import_texture(handle, textures, ui);
+
+        if let Some(info) = re.render_particle_texture.handle().and_then(|handle| {
+            texture_info(
+                handle,
+                textures.images,
+                textures.asset_server,
+                &textures.image_paths.root_path,
+                textures.contexts,
+                textures.infos,
+            )
+        }) {
+            ui.label("");
+            ui_texture_preview(info, toasts, ui);
+            ui.end_row();
+        }
+
+        ui_option_with(
+            "Set Color",
+            &mut re.render_set_color,
+            || defaults.render_set_color.clone().unwrap_or_default(),
+            ui,
+            ui_set_color,
+        ) | ui_option_with(
+            "Color Over Lifetime",
+            &mut re.render_color_over_lifetime,
+            || {
+                defaults
+                    .render_color_over_lifetime
+                    .clone()
+                    .unwrap_or_default()
+            },
+            ui,
+            |g, ui| {
+                #[allow(unused_mut)]
+                let mut changes = g.show(ui) | ui_gradient_from_image(g, textures, toasts, ui);
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    changes |= ui_gradient_file(g, &textures.image_paths.root_path, toasts, ui);
+                }
+                changes
+            },
+        ) | ui_option_reflect(
+            "Set Size",
+            &mut re.render_set_size,
+            || defaults.render_set_size.clone().unwrap_or_default(),
+            env,
+            ui,
+        ) | ui_option_with(
+            "Size Over Lifetime",
+            &mut re.render_size_over_lifetime,
+            || {
+                defaults
+                    .render_size_over_lifetime
+                    .clone()
+                    .unwrap_or_default()
+            },
+            ui,
+            |g, ui| {
+                #[allow(unused_mut)]
+                let mut changes = g.show(ui);
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    changes |= ui_gradient_file(g, &textures.image_paths.root_path, toasts, ui);
+                }
+                changes
+            },
+        ) | ui_orientation(&mut re.render_orientation, ui)
+            | ui_render_order(re, ui)
+    });
+    changes | render.tag(ChangeFlags::RENDER)
+}
+
+pub fn ui_orientation(orientation: &mut Orientation, ui: &mut egui::Ui) -> Change {
+    let change = row!("Orientation", ui, |ui| {
+        egui::ComboBox::from_id_source(ui.id().with("orientation"))
+            .selected_text(orientation.label())
+            .show_ui(ui, |ui| {
+                Orientation::ALL
+                    .iter()
+                    .map(|o| ui.selectable_value(orientation, *o, o.label()))
+                    .reduce(|a, b| a | b)
+            })
+            .merge()
+    });
+
+    // Hanabi 0.6 has no face camera modifier.
+    #[cfg(not(feature = "orient-modifier"))]
+    if *orientation == Orientation::FaceCamera {
+        ui.label("");
+        ui.label("⚠ as billboard")
+            .on_hover_text_at_pointer(docs::get("Face Camera fallback").unwrap_or_default());
+        ui.end_row();
+    }
+
+    change
+}
+
+// Reorder the enabled render modifiers. Disabled ones keep their place in the full order.
+fn ui_render_order(re: &mut REffect, ui: &mut egui::Ui) -> Change {
+    let mut order = re.render_order();
+    let enabled: Vec<usize> = (0..order.len())
+        .filter(|i| re.has_render(order[*i]))
+        .collect();
+    if enabled.len() < 2 {
+        return false.into();
+    }
+
+    let mut swap = None;
+    _ = row!("Order", ui, |ui| {
+        ui.vertical(|ui| {
+            for (n, i) in enabled.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(n > 0, egui::Button::new("⏶").small())
+                        .clicked()
+                    {
+                        swap = Some((*i, enabled[n - 1]));
+                    }
+                    if ui
+                        .add_enabled(n + 1 < enabled.len(), egui::Button::new("⏷").small())
+                        .clicked()
+                    {
+                        swap = Some((*i, enabled[n + 1]));
+                    }
+                    ui.label(order[*i].label());
+                });
+            }
+        });
+    });
+
+    match swap {
+        Some((a, b)) => {
+            order.swap(a, b);
+            re.render_order = order;
+            true.into()
+        }
+        None => false.into(),
+    }
+}
+
+fn ui_particle_texture(
+    label: &str,
+    data: &mut ParticleTexture,
+    textures: &mut Textures,
+    ui: &mut egui::Ui,
+) -> Change {
+    let asset_server = textures.asset_server;
+    let display = textures.config.path_display;
+    let change = row!(label, ui, |ui| {
+        let id = ui.id().with(label);
+
+        // In the loop below we already have the path, but here we have to fetch it from assets for
+        // the selected texture (if any). It only changes with the handle (or the display), so keep
+        // it in memory.
+        let selected = match data {
+            ParticleTexture::Missing(path) => format!("⚠ {}", display.format(Path::new(path))),
+            ParticleTexture::Solid(_) => "Solid color".into(),
+            _ => {
+                let key = (data.handle().map(|handle| handle.id()), display);
+                let cached = ui.memory_mut(|memory| {
+                    memory
+                        .data
+                        .get_temp::<((Option<HandleId>, PathDisplay), String)>(id.with("selected"))
+                });
+                match cached {
+                    Some((cached_key, selected)) if cached_key == key => selected,
+                    _ => match data.handle() {
+                        Some(handle) => match texture_label(handle, display, asset_server) {
+                            Some(selected) => {
+                                ui.memory_mut(|memory| {
+                                    memory
+                                        .data
+                                        .insert_temp(id.with("selected"), (key, selected.clone()))
+                                });
+                                selected
+                            }
+                            // Not cached, the path may show up later.
+                            None => "??? (no path for asset handle)".to_string(),
+                        },
+                        None => "None".into(),
+                    },
+                }
+            }
+        };
+
+        let copy_path = match data {
+            ParticleTexture::Missing(path) => Some(path.clone()),
+            _ => texture_path(data, asset_server),
+        };
+        ids::record(ui, id, label);
+        let mut combo = egui::ComboBox::from_id_source(id)
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                // None is the first option.
+                let none = ui.selectable_value(data, ParticleTexture::None, "None");
+                if none.changed {
+                    return Some(none);
+                }
+
+                // Untextured particles of one color, without an image file.
+                let solid = matches!(data, ParticleTexture::Solid(_));
+                let mut response = ui.selectable_label(solid, "Solid color");
+                if response.clicked() && !solid {
+                    *data = ParticleTexture::Solid(Vec4::ONE);
+                    response.mark_changed();
+                    return Some(response);
+                }
+
+                ui_image_list(data, asset_server, textures.image_paths, ui)
+            });
+        if let Some(path) = copy_path {
+            let root_path = &textures.image_paths.root_path;
+            combo.response = combo
+                .response
+                .context_menu(|ui| ui_copy_path(root_path, Path::new(&path), ui));
+        }
+        let combo = combo.merge();
+
+        match data {
+            ParticleTexture::Solid(color) => combo | Change::from(color_edit_button(color, ui)),
+            _ => combo,
+        }
+    });
+    let change = change | ui_recent_textures(data, textures, ui);
+
+    // Warn about missing textures and offer to pick a replacement.
+    if let ParticleTexture::Missing(path) = data {
+        let missing = format!("missing: {}", path);
+        // Paths outside the assets root aren't loaded at all.
+        let hover = match texture_asset_path(path) {
+            Ok(_) => "The texture failed to load. A placeholder is shown instead.".to_string(),
+            Err(e) => format!("{}. A placeholder is shown instead.", e),
+        };
+        ui.label("");
+        let locate = ui
+            .horizontal(|ui| {
+                ui_error(ui, &missing).on_hover_text(hover);
+                ui.menu_button("Locate…", |ui| {
+                    let response = ui_image_list(data, asset_server, textures.image_paths, ui);
+                    if response.is_some() {
+                        ui.close_menu();
+                    }
+                    response
+                })
+                .inner
+                .flatten()
+            })
+            .inner;
+        ui.end_row();
+
+        if let Some(response) = locate {
+            return change | response;
+        }
+    }
+
+    change
+}
+
+// One-click buttons for the recently used textures, with thumbnails once they're loaded.
+fn ui_recent_textures(
+    data: &mut ParticleTexture,
+    textures: &mut Textures,
+    ui: &mut egui::Ui,
+) -> Change {
+    const THUMBNAIL_SIZE: f32 = 18.0;
+
+    if textures.config.recent_textures.is_empty() {
+        return false.into();
+    }
+
+    let selected = texture_path(data, textures.asset_server);
+    let mut changed = false;
+    ui.label("");
+    ui.horizontal_wrapped(|ui| {
+        for path in textures.config.recent_textures.iter() {
+            let handle: Handle<Image> = textures.asset_server.get_handle(path.as_str());
+            let checked = selected.as_ref() == Some(path);
+            let response = match texture_info(
+                &handle,
+                textures.images,
+                textures.asset_server,
+                &textures.image_paths.root_path,
+                textures.contexts,
+                textures.infos,
+            ) {
+                Some(info) => ui.add(
+                    egui::ImageButton::new(info.texture_id, egui::Vec2::splat(THUMBNAIL_SIZE))
+                        .selected(checked),
+                ),
+                None => {
+                    let name = Path::new(path).file_stem().unwrap_or_default();
+                    ui.selectable_label(checked, name.to_string_lossy())
+                }
+            };
+
+            if response.on_hover_text(path).clicked() && !checked {
+                *data = ParticleTexture::Texture(textures.asset_server.load(path.as_str()));
+                changed = true;
+            }
+        }
+    });
+    ui.end_row();
+
+    changed.into()
+}
+
+// The asset path of an effect's texture, as kept in the recently used textures.
+fn texture_path(data: &ParticleTexture, asset_server: &AssetServer) -> Option<String> {
+    let asset_path = asset_server.get_handle_path(data.handle()?.id())?;
+    asset_path_string(&asset_path).ok()
+}
+
+// The asset path of a texture, with the label for sub-assets.
+fn texture_label(
+    handle: &Handle<Image>,
+    display: PathDisplay,
+    asset_server: &AssetServer,
+) -> Option<String> {
+    asset_server.get_handle_path(handle.id()).map(|asset_path| {
+        let path = display.format(asset_path.path());
+        match asset_path.label() {
+            // Sub-assets, e.g. textures from a GLTF.
+            Some(label) => format!("{} ({})", path, label),
+            None => format!("{}", path),
+        }
+    })
+}
+
+// Selectable list of image paths. Returns the response for the selected image, if changed.
+pub fn ui_image_list(
+    data: &mut ParticleTexture,
+    asset_server: &AssetServer,
+    image_paths: &AssetBrowser<Image>,
+    ui: &mut egui::Ui,
+) -> Option<egui::Response> {
+    // We need to filter out textures that don't work for effects like D3 textures.
+    //for (id, _image) in (*images).iter() {
+    for AssetEntry {
+        path,
+        handle,
+        label,
+        ..
+    } in image_paths.entries.iter()
+    {
+        // Can an effect point to an unloaded image?
+        let checked = handle
+            .as_ref()
+            .zip(data.handle())
+            .map(|(a, b)| a == b)
+            .unwrap_or_default();
+
+        // Show thumbnails?
+        let mut resp = ui.selectable_label(checked, label.as_str());
+
+        if resp.clicked() && !checked {
+            // Is this really be the only way to make a strong handle from an id?
+            // let mut texture = Handle::weak(id);
+            // texture.make_strong(&*images);
+            let texture = match handle {
+                Some(h) => h.clone(),
+                None => asset_server.load(path.as_path()),
+            };
+
+            *data = ParticleTexture::Texture(texture);
+            resp.mark_changed();
+            return Some(resp);
+        }
+    }
+
+    None
+}
+
+// Type a texture path that isn't in the combo. Valid paths are loaded and added to the image paths.
+// Copy textures from outside the assets root. The first one picked is set on the effect.
+#[cfg(not(target_arch = "wasm32"))]
+fn ui_import_texture(handle: &Handle<REffect>, textures: &mut Textures, ui: &mut egui::Ui) {
+    _ = row!("Import Texture", ui, |ui| {
+        if ui.button("Import…").clicked() {
+            textures
+                .imports
+                .pick(textures.image_paths, Some(handle.clone()));
+        }
+
+        let mut folder = textures.imports.folder.to_string_lossy().to_string();
+        if ui
+            .add(egui::TextEdit::singleline(&mut folder).desired_width(80.0))
+            .on_hover_text("Folder under the assets root that textures are copied to.")
+            .changed()
+        {
+            textures.imports.folder = folder.into();
+        }
+
+        if textures.imports.is_importing() {
+            ui.spinner();
+        }
+    });
+}
+
+// Replace a color gradient with samples from a loaded image, e.g. a ramp supplied as a 256×1 PNG.
+fn ui_gradient_from_image(
+    gradient: &mut ColorGradient,
+    textures: &Textures,
+    toasts: &Toasts,
+    ui: &mut egui::Ui,
+) -> Change {
+    let menu = ui.menu_button("From image…", |ui| {
+        let mut changed = false;
+        let mut any = false;
+        for entry in textures.image_paths.entries.iter() {
+            let Some(image) = entry.handle.as_ref().and_then(|h| textures.images.get(h)) else {
+                continue;
+            };
+            any = true;
+
+            if ui.button(&entry.label).clicked() {
+                match ColorGradient::from_image(
+                    image,
+                    gradient::IMAGE_SAMPLES,
+                    gradient::IMAGE_MAX_KEYS,
+                ) {
+                    Ok(g) => {
+                        *gradient = g;
+                        changed = true;
+                    }
+                    Err(e) => toasts.error(format!("can't sample {}: {}", entry.label, e)),
+                }
+                ui.close_menu();
+            }
+        }
+        if !any {
+            ui.weak("No images loaded");
+        }
+        changed
+    });
+    _ = menu
+        .response
+        .on_hover_text("Replace the gradient with colors sampled from the middle row of an image.");
+    menu.inner.unwrap_or_default().into()
+}
+
+// Export the gradient to, or import it from, a gradient file under the assets root. See
+// [gradient::GradientFile] for the format.
+#[cfg(not(target_arch = "wasm32"))]
+fn ui_gradient_file<G: Gradient>(
+    gradient: &mut G,
+    root_path: &Path,
+    toasts: &Toasts,
+    ui: &mut egui::Ui,
+) -> Change {
+    let dialog = || {
+        rfd::FileDialog::new()
+            .add_filter("gradient", &["ron"])
+            .set_directory(root_path)
+    };
+
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        if ui
+            .button("Export…")
+            .on_hover_text("Save the gradient to a file under the assets root.")
+            .clicked()
+        {
+            let export = || -> Result<(), AssetOpError> {
+                let file = dialog().save_file().ok_or(AssetOpError::Cancelled)?;
+                let path = validate_path(&file.to_string_lossy(), "ron", root_path)?;
+                write_file(root_path.join(path), gradient.to_ron()?, toasts);
+                Ok(())
+            };
+            if let Err(e) = export() {
+                e.toast("failed to export gradient", toasts);
+            }
+        }
+
+        if ui
+            .button("Import…")
+            .on_hover_text("Replace the gradient with one from a file.")
+            .clicked()
+        {
+            let import = || -> Result<G, AssetOpError> {
+                let file = dialog().pick_file().ok_or(AssetOpError::Cancelled)?;
+                G::from_ron(&std::fs::read_to_string(file)?)
+            };
+            match import() {
+                Ok(g) => {
+                    *gradient = g;
+                    changed = true;
+                }
+                Err(e) => e.toast("failed to import gradient", toasts),
+            }
+        }
+    });
+    changed.into()
+}
+
+fn ui_texture_path(
+    data: &mut ParticleTexture,
+    asset_server: &AssetServer,
+    image_paths: &mut AssetBrowser<Image>,
+    toasts: &Toasts,
+    ui: &mut egui::Ui,
+) -> Change {
+    row!("Texture Path", ui, |ui| {
+        let id = ui.id().with("texture_path");
+
+        let current = data
+            .handle()
+            .and_then(|handle| asset_server.get_handle_path(handle.id()))
+            .map(|asset_path| match asset_path.label() {
+                Some(label) => PathBuf::from(format!("{}#{}", asset_path.path().display(), label)),
+                None => asset_path.path().to_path_buf(),
+            })
+            .unwrap_or_default();
+
+        // Keep the path being edited in memory along with the texture path it started from. If the
+        // texture changes elsewhere (e.g. the combo), start over.
+        let (mut last, mut path) = ui
+            .memory_mut(|memory| memory.data.get_temp::<(PathBuf, PathBuf)>(id))
+            .unwrap_or_default();
+        if last != current {
+            last = current.clone();
+            path = current;
+        }
+
+        let (ext, root_path) = (image_paths.extension, image_paths.root_path.clone());
+        let completions = Some(&image_paths.completions);
+        let change = edit_path(
+            &mut path,
+            completions,
+            toasts,
+            ui,
+            |path| match split_label(path) {
+                // Labeled sub-assets keep their own extension.
+                (path, Some(label)) => {
+                    let ext = Path::new(path)
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .unwrap_or(ext);
+                    let path = validate_path(path, ext, &root_path)?;
+                    Ok(Cow::Owned(format!("{}#{}", path.display(), label).into()))
+                }
+                (path, None) => validate_path(path, ext, &root_path),
+            },
+        );
+
+        if change.changed() && path != last {
+            // Load from a string so the label is parsed.
+            let handle: Handle<Image> = asset_server.load(&*path.to_string_lossy());
+            image_paths.insert(path.clone(), handle.clone());
+            *data = ParticleTexture::Texture(handle);
+            last = path.clone();
+        }
+
+        ui.memory_mut(|memory| memory.data.insert_temp(id, (last, path)));
+
+        if let Some(handle) = data.handle() {
+            if asset_server.get_load_state(handle.id()) == LoadState::Failed {
+                ui_error(ui, "failed to load");
+            }
+        }
+
+        change
+    })
+}
+
+/// Cached information about a texture for the preview.
+pub struct TextureInfo {
+    pub texture_id: egui::TextureId,
+    pub size: UVec2,
+    pub format: TextureFormat,
+    pub file_size: Option<u64>,
+    /// The image file, if it exists.
+    pub file: Option<PathBuf>,
+}
+
+impl TextureInfo {
+    pub fn is_power_of_two(&self) -> bool {
+        self.size.x.is_power_of_two() && self.size.y.is_power_of_two()
+    }
+
+    pub fn has_alpha(&self) -> bool {
+        self.format.describe().components == 4
+    }
+}
+
+// Returns None if the image is not loaded yet.
+fn texture_info<'a>(
+    handle: &Handle<Image>,
+    images: &Assets<Image>,
+    asset_server: &AssetServer,
+    root_path: &Path,
+    contexts: &mut EguiContexts,
+    cache: &'a mut HashMap<HandleId, TextureInfo>,
+) -> Option<&'a TextureInfo> {
+    use bevy::utils::hashbrown::hash_map::Entry;
+
+    match cache.entry(handle.id()) {
+        Entry::Occupied(e) => Some(e.into_mut()),
+        Entry::Vacant(e) => {
+            let image = images.get(handle)?;
+            let size = image.texture_descriptor.size;
+            let file = asset_server
+                .get_handle_path(handle.id())
+                .map(|path| root_path.join(path.path()));
+            let file_size = file
+                .as_ref()
+                .and_then(|file| std::fs::metadata(file).ok())
+                .map(|m| m.len());
+
+            Some(e.insert(TextureInfo {
+                texture_id: contexts.add_image(handle.clone_weak()),
+                size: UVec2::new(size.width, size.height),
+                format: image.texture_descriptor.format,
+                file: file.filter(|_| file_size.is_some()),
+                file_size,
+            }))
+        }
+    }
+}
+
+fn ui_texture_preview(info: &TextureInfo, toasts: &Toasts, ui: &mut egui::Ui) {
+    const PREVIEW_SIZE: f32 = 64.0;
+    #[cfg(target_arch = "wasm32")]
+    let _ = toasts;
+
+    ui.horizontal(|ui| {
+        // Fit the longest side to the preview size.
+        let scale = PREVIEW_SIZE / info.size.x.max(info.size.y).max(1) as f32;
+        let size = egui::vec2(info.size.x as f32, info.size.y as f32) * scale;
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        if ui.is_rect_visible(rect) {
+            checkerboard(rect, 8.0, ui.painter());
+            ui.painter().image(
+                info.texture_id,
+                rect,
+                egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                egui::Color32::WHITE,
+            );
+        }
+
+        ui.vertical(|ui| {
+            ui.label(format!("{}×{}", info.size.x, info.size.y));
+            ui.label(format!("{:?}", info.format));
+            ui.horizontal(|ui| {
+                if let Some(bytes) = info.file_size {
+                    ui.label(file_size(bytes));
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                if let Some(file) = &info.file {
+                    reveal_button(file, true, toasts, ui);
+                }
+            });
+            if !info.is_power_of_two() {
+                ui.label("⚠ not power-of-two")
+                    .on_hover_text("Dimensions that aren't a power of two may not mip or wrap well.");
+            }
+            if !info.has_alpha() {
+                ui.label("⚠ no alpha").on_hover_text(
+                    "Without an alpha channel the whole quad is drawn, so particles look like squares.",
+                );
+            }
+        });
+    });
+}
+
+fn checkerboard(rect: egui::Rect, size: f32, painter: &egui::Painter) {
+    painter.rect_filled(rect, 0.0, egui::Color32::from_gray(96));
+    let nx = (rect.width() / size).ceil() as usize;
+    let ny = (rect.height() / size).ceil() as usize;
+    for y in 0..ny {
+        for x in 0..nx {
+            if (x + y) % 2 == 0 {
+                let min = rect.min + egui::vec2(x as f32, y as f32) * size;
+                let square = egui::Rect::from_min_size(min, egui::Vec2::splat(size));
+                painter.rect_filled(square.intersect(rect), 0.0, egui::Color32::from_gray(160));
+            }
+        }
+    }
+}
+
+fn ui_set_color(color: &mut SetColorModifier, ui: &mut egui::Ui) -> Change {
+    ui_value(
+        ui.id().with("set_color"),
+        &mut color.color,
+        "",
+        ui,
+        value_color,
+    )
+}
@@ -0,0 +1,199 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use bevy_hanabi::prelude::*;
+use bevy_inspector_egui::reflect_inspector::InspectorUi;
+
+use crate::{
+    change::*,
+    docs::Doc,
+    preview::PreviewMutes,
+    reffect::{ModifierStage, REffect, UpdateAccel},
+    ui::{actions::Actions, defaults::ModifierDefaults, widgets::*},
+};
+
+pub fn ui_update(
+    re: &mut REffect,
+    handle: &Handle<REffect>,
+    defaults: &ModifierDefaults,
+    mutes: (&mut PreviewMutes, &mut Actions),
+    env: &mut InspectorUi,
+    ui: &mut egui::Ui,
+) -> Change {
+    let defaults = &defaults.0;
+    let count = Some(re.update_count());
+    #[cfg(not(any(feature = "force-field", feature = "aabb-kill")))]
+    let _ = env;
+    header!(ui, "Update Modifiers", handle, count, |ui| {
+        super::ui_mutes(ModifierStage::Update, re, handle, mutes, ui);
+        let mut changes = ui_update_accels(&mut re.update_accels, ui);
+        #[cfg(feature = "force-field")]
+        {
+            changes = changes | ui_reflect("Force Field", &mut re.update_force_field, env, ui);
+        }
+        changes = changes
+            | ui_option_with(
+                "Linear Drag",
+                &mut re.update_linear_drag,
+                || defaults.update_linear_drag.clone().unwrap_or_default(),
+                ui,
+                ui_linear_drag,
+            );
+        #[cfg(feature = "aabb-kill")]
+        {
+            changes = changes
+                | ui_option_reflect(
+                    "AABB Kill",
+                    &mut re.update_aabb_kill,
+                    || defaults.update_aabb_kill.clone().unwrap_or_default(),
+                    env,
+                    ui,
+                );
+        }
+        changes
+    })
+}
+
+macro_rules! variant_label {
+    ($ui:expr, $value:expr, $label:literal, $variant:pat, $default:expr) => {{
+        let selected = matches!($value, $variant);
+        let mut label = $ui.selectable_label(selected, $label);
+        if label.clicked() && !selected {
+            *$value = $default;
+            label.mark_changed();
+        }
+        label
+    }};
+}
+
+// One row per acceleration, with a button to add more.
+fn ui_update_accels(accels: &mut Vec<UpdateAccel>, ui: &mut egui::Ui) -> Change {
+    let mut change = Change::from(false);
+    let mut remove = None;
+
+    for (i, accel) in accels.iter_mut().enumerate() {
+        let label = if i == 0 { "Acceleration" } else { "" };
+        ui.label(label).doc(label);
+        change = change
+            | ui.push_id(i, |ui| {
+                ui.horizontal(|ui| {
+                    if ui.small_button("🗙").clicked() {
+                        remove = Some(i);
+                    }
+                    ui_update_accel(accel, ui)
+                })
+                .inner
+            })
+            .inner;
+        ui.end_row();
+    }
+
+    if let Some(i) = remove {
+        accels.remove(i);
+        change = change | Change::from(true);
+    }
+
+    let label = if accels.is_empty() {
+        "Acceleration"
+    } else {
+        ""
+    };
+    ui.label(label).doc(label);
+    if ui.button("Add acceleration").clicked() {
+        accels.push(UpdateAccel::default());
+        change = change | Change::from(true);
+    }
+    ui.end_row();
+
+    change
+}
+
+// Not recreating a reflective wheel...
+fn ui_update_accel(accel: &mut UpdateAccel, ui: &mut egui::Ui) -> Change {
+    egui::ComboBox::from_id_source(ui.id().with("update_accel"))
+        .selected_text(match accel {
+            UpdateAccel::Linear(_) => "Linear",
+            UpdateAccel::Radial(_) => "Radial",
+            UpdateAccel::Tangent(_) => "Tangent",
+        })
+        .show_ui(ui, |ui| {
+            (variant_label!(
+                ui,
+                accel,
+                "Linear",
+                UpdateAccel::Linear(_),
+                UpdateAccel::Linear(AccelModifier::constant(Vec3::ZERO))
+            ) | variant_label!(
+                ui,
+                accel,
+                "Radial",
+                UpdateAccel::Radial(_),
+                UpdateAccel::Radial(RadialAccelModifier::constant(Vec3::ZERO, 1.0))
+            ) | variant_label!(
+                ui,
+                accel,
+                "Tangent",
+                UpdateAccel::Tangent(_),
+                UpdateAccel::Tangent(TangentAccelModifier::constant(Vec3::ZERO, Vec3::Y, 1.0))
+            ))
+            .into()
+        })
+        .merge()
+        | match accel {
+            UpdateAccel::Linear(linear) => ui_linear_accel(linear, ui),
+            UpdateAccel::Radial(radial) => ui_radial_accel(radial, ui),
+            UpdateAccel::Tangent(tangent) => ui_tangent_accel(tangent, ui),
+        }
+}
+
+fn ui_linear_accel(linear: &mut AccelModifier, ui: &mut egui::Ui) -> Change {
+    match &mut linear.accel {
+        ValueOrProperty::Value(graph::Value::Float3(v)) => value_vec3_single(v, "", ui),
+        // ValueOrProperty::Property(_) => todo!(),
+        // ValueOrProperty::ResolvedProperty(_) => todo!(),
+        _ => ui_error(ui, "unhandled"),
+    }
+    .into()
+}
+
+fn ui_radial_accel(radial: &mut RadialAccelModifier, ui: &mut egui::Ui) -> Change {
+    match &mut radial.accel {
+        ValueOrProperty::Value(graph::Value::Float(v)) => {
+            ui.add(drag_value(v, ""))
+                | ui.label("Origin").doc("Origin")
+                | value_vec3_single(&mut radial.origin, "", ui)
+        }
+        _ => ui_error(ui, "unhandled"),
+    }
+    .into()
+}
+
+fn ui_tangent_accel(tangent: &mut TangentAccelModifier, ui: &mut egui::Ui) -> Change {
+    match &mut tangent.accel {
+        ValueOrProperty::Value(graph::Value::Float(v)) => {
+            egui::Grid::new("tangent_accel")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Accel.").doc("Accel.");
+                    let accel = ui.add(drag_value(v, ""));
+                    ui.end_row();
+
+                    ui.label("Origin").doc("Origin");
+                    let origin = value_vec3_single(&mut tangent.origin, "", ui);
+                    ui.end_row();
+
+                    ui.label("Axis").doc("Axis");
+                    let axis = value_vec3_single(&mut tangent.axis, "", ui);
+
+                    accel | origin | axis
+                })
+                .inner
+        }
+
+        _ => ui_error(ui, "unhandled"),
+    }
+    .into()
+}
+
+fn ui_linear_drag(drag: &mut LinearDragModifier, ui: &mut egui::Ui) -> Change {
+    ui.add(drag_value(&mut drag.drag, "/s")).into()
+}
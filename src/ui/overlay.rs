@@ -0,0 +1,308 @@
+// Gizmos, axes and notes drawn over the 3d view. There are no gizmos yet, so these are egui shapes on
+// the background layer, projected through the orbit camera.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use super::color32;
+use crate::{
+    asset::asset_path_string,
+    browser::AssetBrowser,
+    camera::OrbitCamera,
+    config::EditorConfig,
+    preview::PreviewSettings,
+    reffect::{EffectBounds, InitPosition, REffect},
+    LiveEffect,
+};
+
+/// Projects world positions to egui screen positions.
+struct Projection<'a> {
+    camera: &'a Camera,
+    transform: &'a GlobalTransform,
+    viewport: Vec2,
+}
+
+impl<'a> Projection<'a> {
+    fn new(camera: &'a Camera, transform: &'a GlobalTransform) -> Option<Self> {
+        Some(Self {
+            camera,
+            transform,
+            viewport: camera.logical_viewport_size()?,
+        })
+    }
+
+    /// None if the position is behind the camera or off the viewport.
+    fn project(&self, p: Vec3) -> Option<egui::Pos2> {
+        if !in_front(self.transform, p) {
+            return None;
+        }
+        // world_to_viewport has the origin at the bottom left; egui's is at the top left.
+        self.camera
+            .world_to_viewport(self.transform, p)
+            .map(|p| egui::pos2(p.x, self.viewport.y - p.y))
+    }
+}
+
+// The camera looks down -Z. Points behind it can still project into the viewport, mirrored.
+fn in_front(camera_transform: &GlobalTransform, p: Vec3) -> bool {
+    camera_transform
+        .compute_matrix()
+        .inverse()
+        .transform_point3(p)
+        .z
+        < 0.0
+}
+
+/// Outline the emitter shape, kill box and bounds of each live effect, in the palette colors.
+pub fn draw_gizmos(
+    mut contexts: EguiContexts,
+    cameras: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    live_effects: Query<(&GlobalTransform, &LiveEffect, &ComputedVisibility)>,
+    reffects: Res<Assets<REffect>>,
+    preview: Res<PreviewSettings>,
+    config: Res<EditorConfig>,
+) {
+    if !preview.show_gizmos {
+        return;
+    }
+    let Some(projection) = cameras
+        .get_single()
+        .ok()
+        .and_then(|(camera, transform)| Projection::new(camera, transform))
+    else {
+        return;
+    };
+
+    let painter = contexts
+        .ctx_mut()
+        .layer_painter(egui::LayerId::background());
+    let palette = &config.palette;
+    let stroke = |color| egui::Stroke::new(1.0, color32(color));
+
+    for (transform, LiveEffect(handle), visibility) in live_effects.iter() {
+        if !visibility.is_visible_in_hierarchy() {
+            continue;
+        }
+        let Some(re) = reffects.get(handle) else {
+            continue;
+        };
+        // Segments between points in the effect's space.
+        let mut line = |a: Vec3, b: Vec3, stroke: egui::Stroke| {
+            let a = projection.project(transform.transform_point(a));
+            let b = projection.project(transform.transform_point(b));
+            if let (Some(a), Some(b)) = (a, b) {
+                painter.line_segment([a, b], stroke);
+            }
+        };
+
+        let emitter = stroke(palette.emitter);
+        match &re.init_position {
+            InitPosition::Circle(m) => ring(m.center, m.axis, m.radius, emitter, &mut line),
+            InitPosition::Sphere(m) => {
+                for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+                    ring(m.center, axis, m.radius, emitter, &mut line);
+                }
+            }
+            // The base is at the origin and the cone extends up the Y axis.
+            InitPosition::Cone(m) => {
+                ring(Vec3::ZERO, Vec3::Y, m.base_radius, emitter, &mut line);
+                ring(
+                    Vec3::Y * m.height,
+                    Vec3::Y,
+                    m.top_radius,
+                    emitter,
+                    &mut line,
+                );
+            }
+        }
+
+        #[cfg(feature = "aabb-kill")]
+        if let Some(m) = &re.update_aabb_kill {
+            let kill_box = EffectBounds {
+                center: m.center,
+                half_extents: m.half_size,
+            };
+            cuboid(&kill_box, stroke(palette.kill_box), &mut line);
+        }
+
+        if let Some(bounds) = &re.bounds {
+            cuboid(bounds, stroke(palette.bounds), &mut line);
+        }
+    }
+}
+
+// The edges of a box, which join corners that differ along one axis.
+fn cuboid(
+    bounds: &EffectBounds,
+    stroke: egui::Stroke,
+    line: &mut impl FnMut(Vec3, Vec3, egui::Stroke),
+) {
+    let corners = bounds.corners();
+    for (i, a) in corners.iter().enumerate() {
+        for axis in [1, 2, 4] {
+            if i & axis == 0 {
+                line(*a, corners[i | axis], stroke);
+            }
+        }
+    }
+}
+
+fn ring(
+    center: Vec3,
+    axis: Vec3,
+    radius: f32,
+    stroke: egui::Stroke,
+    line: &mut impl FnMut(Vec3, Vec3, egui::Stroke),
+) {
+    const SEGMENTS: usize = 32;
+    let (u, v) = axis.normalize_or_zero().any_orthonormal_pair();
+    let point = |i: usize| {
+        let angle = i as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+        center + (u * angle.cos() + v * angle.sin()) * radius
+    };
+    for i in 0..SEGMENTS {
+        line(point(i), point(i + 1), stroke);
+    }
+}
+
+/// Length of the axes at or closer than [AXES_DISTANCE] from the camera, in meters.
+const AXES_LENGTH: f32 = 0.25;
+/// Camera distance beyond which the axes grow, so they stay about the same size on screen.
+const AXES_DISTANCE: f32 = 5.0;
+/// Scale of the axes of selected effects.
+const SELECTED_AXES_SCALE: f32 = 1.6;
+
+/// Length of the axes at a distance from the camera.
+fn axes_length(distance: f32) -> f32 {
+    AXES_LENGTH * (distance / AXES_DISTANCE).max(1.0)
+}
+
+/// Draw the axes of each live effect's transform, X red, Y green and Z blue. Effects selected in
+/// the browser get larger, brighter axes.
+pub fn draw_axes(
+    mut contexts: EguiContexts,
+    cameras: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    live_effects: Query<(&GlobalTransform, &LiveEffect, &ComputedVisibility)>,
+    reffect_paths: Res<AssetBrowser<REffect>>,
+    preview: Res<PreviewSettings>,
+) {
+    if !preview.show_axes {
+        return;
+    }
+    let Some(projection) = cameras
+        .get_single()
+        .ok()
+        .and_then(|(camera, transform)| Projection::new(camera, transform))
+    else {
+        return;
+    };
+
+    let painter = contexts
+        .ctx_mut()
+        .layer_painter(egui::LayerId::background());
+    let selected: Vec<&Handle<REffect>> = reffect_paths
+        .selected()
+        .filter_map(|e| e.handle.as_ref())
+        .collect();
+
+    for (transform, LiveEffect(handle), visibility) in live_effects.iter() {
+        if !visibility.is_visible_in_hierarchy() {
+            continue;
+        }
+        let origin = transform.translation();
+        let Some(o) = projection.project(origin) else {
+            continue;
+        };
+
+        let (scale, width, dim) = match selected.contains(&handle) {
+            true => (SELECTED_AXES_SCALE, 2.0, 1.0),
+            false => (1.0, 1.0, 0.6),
+        };
+        let length = axes_length(projection.transform.translation().distance(origin)) * scale;
+        // Along the effect's axes, unscaled.
+        let (_, rotation, _) = transform.to_scale_rotation_translation();
+        for (axis, color) in [
+            (Vec3::X, egui::Color32::RED),
+            (Vec3::Y, egui::Color32::GREEN),
+            (Vec3::Z, egui::Color32::BLUE),
+        ] {
+            if let Some(p) = projection.project(origin + rotation * axis * length) {
+                painter.line_segment([o, p], egui::Stroke::new(width, color.linear_multiply(dim)));
+            }
+        }
+    }
+}
+
+/// Show the notes of each live effect next to the effect.
+pub fn draw_notes(
+    mut contexts: EguiContexts,
+    cameras: Query<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    live_effects: Query<(Entity, &GlobalTransform, &LiveEffect, &ComputedVisibility)>,
+    reffect_paths: Res<AssetBrowser<REffect>>,
+    config: Res<EditorConfig>,
+) {
+    if config.notes.is_empty() {
+        return;
+    }
+    let Some(projection) = cameras
+        .get_single()
+        .ok()
+        .and_then(|(camera, transform)| Projection::new(camera, transform))
+    else {
+        return;
+    };
+
+    let ctx = contexts.ctx_mut();
+    let painter = ctx.layer_painter(egui::LayerId::background());
+    for (entity, transform, LiveEffect(handle), visibility) in live_effects.iter() {
+        if !visibility.is_visible_in_hierarchy() {
+            continue;
+        }
+        let Some(notes) = reffect_paths
+            .entries
+            .iter()
+            .find(|e| e.handle.as_ref() == Some(handle))
+            .and_then(|e| asset_path_string(&e.path.as_path().into()).ok())
+            .and_then(|key| config.notes.get(&key))
+        else {
+            continue;
+        };
+
+        for (i, note) in notes.iter().enumerate() {
+            let Some(pos) = projection.project(transform.transform_point(note.offset)) else {
+                continue;
+            };
+            painter.circle_filled(pos, 3.0, color32(config.palette.selection));
+            egui::Area::new(egui::Id::new(("note", entity, i)))
+                .order(egui::Order::Background)
+                .fixed_pos(pos + egui::vec2(6.0, -6.0))
+                .interactable(false)
+                .show(ctx, |ui| {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| ui.label(&note.text));
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn behind_camera() {
+        let camera = GlobalTransform::from(
+            Transform::from_xyz(0.0, 0.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
+        );
+        assert!(in_front(&camera, Vec3::ZERO));
+        assert!(in_front(&camera, Vec3::new(5.0, 5.0, 0.0)));
+        assert!(!in_front(&camera, Vec3::new(0.0, 0.0, 20.0)));
+    }
+
+    #[test]
+    fn axes_readable() {
+        assert_eq!(axes_length(0.5), AXES_LENGTH);
+        assert_eq!(axes_length(AXES_DISTANCE), AXES_LENGTH);
+        // Twice as far, twice as long.
+        assert_eq!(axes_length(AXES_DISTANCE * 2.0), AXES_LENGTH * 2.0);
+    }
+}
@@ -0,0 +1,174 @@
+// A command palette, opened with Ctrl+P: type part of a command's name and Enter runs the best
+// match (see [fuzzy](crate::fuzzy)). Up and Down pick another and Esc closes. Commands run recently,
+// from here or the menus, rank higher, and with nothing typed they're listed first.
+
+use bevy::prelude::*;
+use bevy_egui::egui::{self, Align2, Key, Modifiers};
+
+use super::{
+    actions::Actions,
+    commands::{self, Command},
+};
+use crate::{browser::AssetBrowser, camera::OrbitCamera, fuzzy, reffect::REffect, toast::Toasts};
+
+/// Memory id for the query and the picked row while the palette is open.
+const COMMAND_PALETTE: &str = "command_palette";
+
+/// Rows listed at most.
+const MAX_ROWS: usize = 12;
+
+/// Score added for the most recent command, less for each older one.
+const RECENT_BONUS: i32 = 6;
+
+pub fn open_palette(ctx: &egui::Context) {
+    ctx.memory_mut(|memory| {
+        memory
+            .data
+            .insert_temp(egui::Id::new(COMMAND_PALETTE), (String::new(), 0usize))
+    });
+}
+
+/// Commands matching the query, best first. Ties keep the order given.
+pub fn rank(query: &str, commands: Vec<Command>, recent: &[String]) -> Vec<Command> {
+    let mut ranked: Vec<(i32, Command)> = commands
+        .into_iter()
+        .filter_map(|c| {
+            let label = c.label();
+            let score = fuzzy::score(query, &label)?;
+            let bonus = recent
+                .iter()
+                .position(|r| *r == label)
+                .map_or(0, |i| (recent.len() - i) as i32 * RECENT_BONUS);
+            Some((score + bonus, c))
+        })
+        .collect();
+    ranked.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    ranked.into_iter().map(|(_, c)| c).collect()
+}
+
+pub fn ui_palette(
+    ctx: &egui::Context,
+    browser: &mut AssetBrowser<REffect>,
+    orbit: &mut OrbitCamera,
+    asset_server: &AssetServer,
+    actions: &mut Actions,
+    toasts: &Toasts,
+) {
+    if ctx.input_mut(|input| input.consume_key(Modifiers::COMMAND, Key::P)) {
+        open_palette(ctx);
+    }
+
+    let id = egui::Id::new(COMMAND_PALETTE);
+    let Some((mut query, mut picked)) =
+        ctx.memory_mut(|memory| memory.data.get_temp::<(String, usize)>(id))
+    else {
+        return;
+    };
+
+    let ranked = rank(&query, commands::commands(browser), &commands::recent(ctx));
+    let rows = ranked.len().min(MAX_ROWS);
+
+    let (up, down, enter, escape) = ctx.input(|input| {
+        (
+            input.key_pressed(Key::ArrowUp),
+            input.key_pressed(Key::ArrowDown),
+            input.key_pressed(Key::Enter),
+            input.key_pressed(Key::Escape),
+        )
+    });
+    if up {
+        picked = picked.saturating_sub(1);
+    }
+    if down {
+        picked += 1;
+    }
+    picked = picked.min(rows.saturating_sub(1));
+
+    let mut close = escape;
+    let mut chosen = None;
+    egui::Window::new("Commands")
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+        .show(ctx, |ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut query)
+                    .hint_text("Type a command…")
+                    .desired_width(320.0),
+            );
+            if response.changed() {
+                picked = 0;
+            }
+            if !close {
+                response.request_focus();
+            }
+
+            if ranked.is_empty() {
+                ui.weak("No matching commands");
+            }
+            for (i, command) in ranked.iter().take(MAX_ROWS).enumerate() {
+                ui.horizontal(|ui| {
+                    if ui
+                        .selectable_label(i == picked, command.label().as_ref())
+                        .clicked()
+                    {
+                        chosen = Some(command.clone());
+                    }
+                    if let Some(shortcut) = command.shortcut() {
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            ui.weak(shortcut)
+                        });
+                    }
+                });
+            }
+        });
+
+    if enter && chosen.is_none() {
+        chosen = ranked.get(picked).cloned();
+    }
+    if let Some(command) = chosen {
+        commands::run(command, ctx, browser, orbit, asset_server, actions, toasts);
+        close = true;
+    }
+
+    ctx.memory_mut(|memory| match close {
+        true => memory.data.remove::<(String, usize)>(id),
+        false => memory.data.insert_temp(id, (query, picked)),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::ViewPreset;
+
+    #[test]
+    fn recent_first() {
+        let commands = vec![
+            Command::SaveAll,
+            Command::View(ViewPreset::Side),
+            Command::ExportScene,
+        ];
+        let ranked = rank("", commands.clone(), &[]);
+        assert_eq!(ranked, commands);
+
+        // Only matches, best first.
+        let ranked = rank("sav", commands.clone(), &[]);
+        assert_eq!(ranked, vec![Command::SaveAll]);
+        let ranked = rank("s", commands.clone(), &[]);
+        assert_eq!(ranked[0], Command::SaveAll);
+
+        // A recent command outranks a better match.
+        let recent = ["Export Scene".to_string(), "Side view".to_string()];
+        let ranked = rank("s", commands.clone(), &recent);
+        assert_eq!(
+            ranked,
+            vec![
+                Command::ExportScene,
+                Command::View(ViewPreset::Side),
+                Command::SaveAll
+            ]
+        );
+    }
+}
@@ -0,0 +1,136 @@
+// How asset paths are shown in headers and combo labels, which truncate awkwardly when paths are
+// deeply nested. Truncation counts graphemes so it never splits a character, and is also used for
+// other long labels.
+
+use std::{borrow::Cow, path::Path};
+
+use bevy_egui::egui;
+use path_absolutize::Absolutize;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Graphemes shown of abbreviated paths.
+pub const ABBREVIATED_WIDTH: usize = 32;
+
+const ELLIPSIS: &str = "…";
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PathDisplay {
+    /// Relative to the assets root.
+    #[default]
+    Full,
+    FileName,
+    /// The first folder and the file name, e.g. `fx/…/ember.han`.
+    Abbreviated,
+}
+
+impl PathDisplay {
+    pub const ALL: [Self; 3] = [Self::Full, Self::FileName, Self::Abbreviated];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Full => "Full",
+            Self::FileName => "File name",
+            Self::Abbreviated => "Abbreviated",
+        }
+    }
+
+    pub fn format(&self, path: &Path) -> String {
+        match self {
+            Self::Full => path.display().to_string(),
+            Self::FileName => match path.file_name() {
+                Some(name) => name.to_string_lossy().into_owned(),
+                None => path.display().to_string(),
+            },
+            Self::Abbreviated => abbreviate_path(path, ABBREVIATED_WIDTH),
+        }
+    }
+}
+
+/// Shorten to `width` graphemes by replacing the middle with an ellipsis.
+pub fn truncate_middle(s: &str, width: usize) -> Cow<str> {
+    let graphemes: Vec<&str> = s.graphemes(true).collect();
+    if graphemes.len() <= width {
+        return Cow::Borrowed(s);
+    }
+    // The ellipsis takes one.
+    let keep = width.saturating_sub(1);
+    let tail = keep / 2;
+    let head = keep - tail;
+    let mut short = graphemes[..head].concat();
+    short.push_str(ELLIPSIS);
+    short.push_str(&graphemes[graphemes.len() - tail..].concat());
+    Cow::Owned(short)
+}
+
+/// Drop the folders between the first and the file name if the path is longer than `width`, then
+/// truncate the middle if it still is.
+pub fn abbreviate_path(path: &Path, width: usize) -> String {
+    let full = path.display().to_string();
+    if full.graphemes(true).count() <= width {
+        return full;
+    }
+    let components: Vec<_> = path.components().collect();
+    let short = match components.as_slice() {
+        [first, .., last] if components.len() > 2 => Path::new(first.as_os_str())
+            .join(ELLIPSIS)
+            .join(last.as_os_str())
+            .display()
+            .to_string(),
+        _ => full,
+    };
+    truncate_middle(&short, width).into_owned()
+}
+
+/// Context menu buttons to copy the path, relative to the assets root or absolute.
+pub fn ui_copy_path(root_path: &Path, path: &Path, ui: &mut egui::Ui) {
+    if ui.button("Copy path").clicked() {
+        let path = path.display().to_string();
+        ui.output_mut(|output| output.copied_text = path);
+        ui.close_menu();
+    }
+    if ui.button("Copy absolute path").clicked() {
+        let path = root_path.join(path);
+        let path = path
+            .absolutize()
+            .map_or_else(|_| path.display().to_string(), |p| p.display().to_string());
+        ui.output_mut(|output| output.copied_text = path);
+        ui.close_menu();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate() {
+        assert_eq!(truncate_middle("ember.han", 12), "ember.han");
+        assert_eq!(truncate_middle("abcdefghij", 5), "ab…ij");
+        assert_eq!(truncate_middle("abcdefghij", 6), "abc…ij");
+        assert_eq!(truncate_middle("abcdefghij", 1), "…");
+        // Combining marks stay with their letter.
+        assert_eq!(
+            truncate_middle("e\u{301}e\u{301}e\u{301}e\u{301}", 3),
+            "e\u{301}…e\u{301}"
+        );
+        assert_eq!(truncate_middle("🔥🔥🔥🔥🔥", 3), "🔥…🔥");
+    }
+
+    #[test]
+    fn abbreviate() {
+        let path = Path::new("fx/fire/small/ember.han");
+        assert_eq!(abbreviate_path(path, 32), "fx/fire/small/ember.han");
+        assert_eq!(abbreviate_path(path, 16), "fx/…/ember.han");
+        assert_eq!(abbreviate_path(path, 10), "fx/…/….han");
+        assert_eq!(abbreviate_path(Path::new("ember.han"), 5), "em…an");
+    }
+
+    #[test]
+    fn display() {
+        let path = Path::new("fx/fire/ember.han");
+        assert_eq!(PathDisplay::Full.format(path), "fx/fire/ember.han");
+        assert_eq!(PathDisplay::FileName.format(path), "ember.han");
+        assert_eq!(PathDisplay::Abbreviated.format(path), "fx/fire/ember.han");
+    }
+}
@@ -0,0 +1,335 @@
+// Replace texture references across all effects, e.g. after a texture is renamed. Effects that
+// aren't loaded are matched by peeking at their files, and loaded when the replacement is applied.
+// The matching effects are listed before anything changes.
+
+use std::path::{Path, PathBuf};
+
+use bevy::{asset::LoadState, prelude::*};
+use bevy_egui::egui;
+
+use super::{
+    actions::{Action, Actions},
+    modifiers::ui_image_list,
+};
+use crate::{
+    asset::{asset_path_string, peek_texture_path},
+    browser::AssetBrowser,
+    change::ChangeFlags,
+    config::EditorConfig,
+    reffect::{ParticleTexture, REffect},
+    toast::Toasts,
+};
+
+/// Memory id for the dialog state while it's open.
+pub const REPLACE: &str = "replace_textures";
+
+/// Which texture references to replace.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TextureMatch {
+    Path(String),
+    /// Any texture that doesn't exist.
+    Missing,
+}
+
+impl TextureMatch {
+    /// Whether an effect's texture (asset path, and whether it's missing) matches.
+    pub fn matches(&self, texture: Option<&str>, missing: bool) -> bool {
+        match self {
+            Self::Path(path) => texture == Some(path.as_str()),
+            Self::Missing => texture.is_some() && missing,
+        }
+    }
+}
+
+/// The texture of an effect, as an asset path, and whether it's missing. Uses the loaded effect if
+/// there is one, otherwise peeks at the file.
+fn effect_texture(
+    root_path: &Path,
+    path: &Path,
+    loaded: Option<&REffect>,
+    image_paths: &AssetBrowser<Image>,
+    asset_server: &AssetServer,
+) -> (Option<String>, bool) {
+    let exists = |p: &str| image_paths.entry(Path::new(p)).is_some();
+    match loaded.map(|re| &re.render_particle_texture) {
+        Some(ParticleTexture::None | ParticleTexture::Solid(_)) => (None, false),
+        Some(ParticleTexture::Missing(p)) => (Some(p.clone()), true),
+        Some(ParticleTexture::Path(p)) => (Some(p.clone()), !exists(p)),
+        Some(ParticleTexture::Texture(h)) => (
+            asset_server
+                .get_handle_path(h.id())
+                .and_then(|p| asset_path_string(&p).ok()),
+            false,
+        ),
+        None => match peek_texture_path(&root_path.join(path)) {
+            Some(p) => {
+                let missing = !exists(&p);
+                (Some(p), missing)
+            }
+            None => (None, false),
+        },
+    }
+}
+
+// Marks the effect dirty and swaps the texture of its live effect.
+fn replace(
+    re: &mut REffect,
+    target: &ParticleTexture,
+    path: &Path,
+    reffect_paths: &mut AssetBrowser<REffect>,
+    actions: &mut Actions,
+) {
+    re.render_particle_texture = target.clone();
+    reffect_paths.mark_dirty(path);
+    if let Some(handle) = reffect_paths.entry(path).and_then(|e| e.handle.clone()) {
+        actions.push(Action::Regenerate(handle, ChangeFlags::TEXTURE));
+    }
+}
+
+#[derive(Clone)]
+struct ReplaceState {
+    /// Textures referenced by effects, to pick from.
+    sources: Vec<String>,
+    source: TextureMatch,
+    target: ParticleTexture,
+    /// Effects found by the dry run.
+    found: Option<Vec<PathBuf>>,
+    /// Matching effects left out since they're locked.
+    locked: usize,
+    /// Effects being loaded to apply the replacement to.
+    pending: Vec<PathBuf>,
+    replaced: usize,
+}
+
+/// Open the dialog, with the textures currently referenced to pick from.
+pub fn open_replace(sources: Vec<String>, ctx: &egui::Context) {
+    let state = ReplaceState {
+        source: sources
+            .first()
+            .cloned()
+            .map_or(TextureMatch::Missing, TextureMatch::Path),
+        sources,
+        target: ParticleTexture::None,
+        found: None,
+        locked: 0,
+        pending: Vec::new(),
+        replaced: 0,
+    };
+    ctx.memory_mut(|memory| memory.data.insert_temp(egui::Id::new(REPLACE), state));
+}
+
+// Pick the source and target, list the effects that match, then replace. Effects that aren't loaded
+// are loaded and replaced as they finish. Live effects are regenerated with the new texture. Locked
+// effects are left alone.
+#[allow(clippy::too_many_arguments)]
+pub fn ui_replace(
+    reffect_paths: &mut AssetBrowser<REffect>,
+    reffects: &mut Assets<REffect>,
+    image_paths: &AssetBrowser<Image>,
+    asset_server: &AssetServer,
+    config: &EditorConfig,
+    toasts: &Toasts,
+    actions: &mut Actions,
+    ctx: &egui::Context,
+) {
+    let id = egui::Id::new(REPLACE);
+    let Some(mut state) = ctx.memory_mut(|memory| memory.data.get_temp::<ReplaceState>(id)) else {
+        return;
+    };
+
+    // Finish effects loaded since the last frame.
+    let mut replaced = 0;
+    state.pending.retain(|path| {
+        let Some(handle) = reffect_paths.entry(path).and_then(|e| e.handle.clone()) else {
+            return false;
+        };
+        if let Some(re) = reffects.get_mut(&handle) {
+            replace(re, &state.target, path, reffect_paths, actions);
+            replaced += 1;
+            return false;
+        }
+        if asset_server.get_load_state(&handle) == LoadState::Failed {
+            toasts.error(format!("failed to load {}", path.display()));
+            return false;
+        }
+        true
+    });
+    state.replaced += replaced;
+
+    let mut open = true;
+    let mut close = false;
+    let working = !state.pending.is_empty();
+    egui::Window::new("Replace texture references")
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            if working {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("Loading {} effects…", state.pending.len()));
+                });
+                return;
+            }
+
+            let before = (state.source.clone(), state.target.clone());
+            egui::Grid::new("replace").num_columns(2).show(ui, |ui| {
+                ui.label("Replace");
+                let selected = match &state.source {
+                    TextureMatch::Path(p) => p.clone(),
+                    TextureMatch::Missing => "Any missing texture".to_string(),
+                };
+                egui::ComboBox::from_id_source("replace_source")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut state.source,
+                            TextureMatch::Missing,
+                            "Any missing texture",
+                        );
+                        for source in state.sources.iter() {
+                            ui.selectable_value(
+                                &mut state.source,
+                                TextureMatch::Path(source.clone()),
+                                source,
+                            );
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("With");
+                let selected = state
+                    .target
+                    .handle()
+                    .and_then(|h| asset_server.get_handle_path(h.id()))
+                    .map(|p| p.path().display().to_string())
+                    .unwrap_or_else(|| "None".into());
+                egui::ComboBox::from_id_source("replace_target")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        ui_image_list(&mut state.target, asset_server, image_paths, ui)
+                    });
+                ui.end_row();
+            });
+            // The results are stale once either changes.
+            if (state.source.clone(), state.target.clone()) != before {
+                state.found = None;
+            }
+
+            let Some(found) = &state.found else {
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            state.target.handle().is_some(),
+                            egui::Button::new("Find effects"),
+                        )
+                        .clicked()
+                    {
+                        let (locked, found) = reffect_paths
+                            .entries
+                            .iter()
+                            .filter(|entry| {
+                                let loaded = entry.handle.as_ref().and_then(|h| reffects.get(h));
+                                let (texture, missing) = effect_texture(
+                                    &reffect_paths.root_path,
+                                    &entry.path,
+                                    loaded,
+                                    image_paths,
+                                    asset_server,
+                                );
+                                state.source.matches(texture.as_deref(), missing)
+                            })
+                            .map(|entry| entry.path.clone())
+                            .partition::<Vec<_>, _>(|path| config.is_locked(path));
+                        state.locked = locked.len();
+                        state.found = Some(found);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+                return;
+            };
+
+            if found.is_empty() {
+                ui.label("No effects use that texture.");
+            } else {
+                ui.label(format!("{} effects will change:", found.len()));
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for path in found {
+                            ui.label(path.display().to_string());
+                        }
+                    });
+            }
+            if state.locked > 0 {
+                ui.label(format!("{} locked effects are skipped.", state.locked));
+            }
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(!found.is_empty(), egui::Button::new("Replace"))
+                    .clicked()
+                {
+                    // Locked since the dry run.
+                    for path in found.iter().filter(|path| !config.is_locked(path)) {
+                        let handle = reffect_paths.entry(path).and_then(|e| e.handle.clone());
+                        match handle.as_ref().and_then(|h| reffects.get_mut(h)) {
+                            Some(re) => {
+                                replace(re, &state.target, path, reffect_paths, actions);
+                                state.replaced += 1;
+                            }
+                            None => {
+                                reffect_paths.load(path, asset_server);
+                                state.pending.push(path.clone());
+                            }
+                        }
+                    }
+                    close = state.pending.is_empty();
+                }
+                if ui.button("Cancel").clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    // Done once the last pending effect is replaced.
+    if working && state.pending.is_empty() {
+        close = true;
+    }
+    if close && state.replaced > 0 {
+        toasts.info(format!(
+            "replaced the texture in {} effects",
+            state.replaced
+        ));
+    }
+
+    ctx.memory_mut(|memory| {
+        if close || !open {
+            memory.data.remove::<ReplaceState>(id);
+        } else {
+            memory.data.insert_temp(id, state);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn texture_match() {
+        let path = TextureMatch::Path("spark_old.png".to_string());
+        assert!(path.matches(Some("spark_old.png"), true));
+        assert!(path.matches(Some("spark_old.png"), false));
+        assert!(!path.matches(Some("spark.png"), false));
+        assert!(!path.matches(None, false));
+
+        // Effects without a texture aren't missing one.
+        let missing = TextureMatch::Missing;
+        assert!(missing.matches(Some("spark_old.png"), true));
+        assert!(!missing.matches(Some("spark.png"), false));
+        assert!(!missing.matches(None, true));
+    }
+}
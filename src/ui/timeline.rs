@@ -0,0 +1,204 @@
+// A small timeline of the spawner: two periods, with the spawn window of each shaded and the
+// particles per cycle written over it. Spawn-once effects show a single burst or window. Also a
+// sentence describing what the spawner does, since the start flags are easy to misread.
+
+use bevy_egui::egui::{self, Align2, FontId, Rect, Sense, Stroke};
+use bevy_hanabi::prelude::*;
+
+use crate::reffect::SpawnCycle;
+
+const HEIGHT: f32 = 20.0;
+
+/// Seconds shown for spawn-once effects with no spawn time.
+const ONCE_SPAN: f32 = 1.0;
+
+pub fn ui_spawn_timeline(cycle: &SpawnCycle, ui: &mut egui::Ui) -> egui::Response {
+    let span = match cycle.period {
+        Some(period) if period > 0.0 => period * 2.0,
+        _ if cycle.spawn_time > 0.0 => cycle.spawn_time * 2.0,
+        _ => ONCE_SPAN,
+    };
+    let width = ui.available_width().clamp(120.0, 240.0);
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(width, HEIGHT), Sense::hover());
+    if !ui.is_rect_visible(rect) {
+        return response;
+    }
+
+    let visuals = ui.visuals();
+    let painter = ui.painter_at(rect);
+    let x = |t: f32| rect.left() + t / span * rect.width();
+    let fill = visuals.selection.bg_fill;
+    let text_color = visuals.strong_text_color();
+    let font = FontId::proportional(10.0);
+
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+    for (start, end) in cycle.windows(span) {
+        let count = format!("{:.0}", cycle.count);
+        if end > start {
+            let window = Rect::from_x_y_ranges(x(start)..=x(end), rect.y_range());
+            painter.rect_filled(window, 0.0, fill);
+            painter.text(
+                window.center(),
+                Align2::CENTER_CENTER,
+                count,
+                font.clone(),
+                text_color,
+            );
+        } else {
+            // Bursts all spawn at once.
+            painter.line_segment(
+                [
+                    egui::pos2(x(start), rect.top()),
+                    egui::pos2(x(start), rect.bottom()),
+                ],
+                Stroke::new(2.0, fill),
+            );
+            painter.text(
+                egui::pos2(x(start) + 3.0, rect.center().y),
+                Align2::LEFT_CENTER,
+                format!("×{}", count),
+                font.clone(),
+                text_color,
+            );
+        }
+    }
+
+    // Between the two periods.
+    if let Some(period) = cycle.period.filter(|p| *p > 0.0) {
+        painter.line_segment(
+            [
+                egui::pos2(x(period), rect.top()),
+                egui::pos2(x(period), rect.bottom()),
+            ],
+            visuals.widgets.noninteractive.fg_stroke,
+        );
+    }
+
+    painter.rect_stroke(rect, 2.0, visuals.widgets.noninteractive.bg_stroke);
+
+    response.on_hover_text(match cycle.period {
+        None => format!(
+            "{:.0} particles once, over {:.2}s",
+            cycle.count, cycle.spawn_time
+        ),
+        Some(period) if period <= 0.0 => format!("{:.0} particles continuously", cycle.count),
+        Some(period) => format!(
+            "{:.0} particles over {:.2}s, every {:.2}s (two periods shown)",
+            cycle.count,
+            cycle.spawn_time.min(period),
+            period
+        ),
+    })
+}
+
+// Seconds with at least one decimal and at most two, e.g. "2.0s" or "0.05s".
+fn seconds(t: f32) -> String {
+    let mut s = format!("{:.2}", t);
+    while s.ends_with('0') && !s.ends_with(".0") {
+        s.pop();
+    }
+    s + "s"
+}
+
+/// What the spawner does, in a sentence.
+pub fn describe(spawner: &Spawner) -> String {
+    let cycle = SpawnCycle::of(spawner);
+    let particles = match cycle.spawn_time > 0.0 {
+        true => format!(
+            "{:.0} particles over {}",
+            cycle.count,
+            seconds(cycle.spawn_time)
+        ),
+        false => format!("{:.0} particles", cycle.count),
+    };
+    let spawns = match (cycle.period, spawner.starts_immediately) {
+        (None, true) => format!("spawns {} immediately, once", particles),
+        // The period never elapses, so only a reset spawns.
+        (None, false) => format!("spawns nothing until reset, then {} once", particles),
+        (Some(period), _) if period <= 0.0 => format!("spawns {} every frame", particles),
+        (Some(period), true) => format!(
+            "spawns {} immediately, then every {}",
+            particles,
+            seconds(period)
+        ),
+        (Some(period), false) => format!(
+            "spawns {} every {}, the first after {}",
+            particles,
+            seconds(period),
+            seconds(period)
+        ),
+    };
+
+    match spawner.starts_active {
+        true => capitalize(&spawns),
+        false => format!("Does not spawn until activated via code, then {}", spawns),
+    }
+}
+
+pub fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawner(period: f32, immediately: bool, active: bool) -> Spawner {
+        let mut spawner = Spawner::new(32.0.into(), 0.0.into(), period.into());
+        spawner.starts_immediately = immediately;
+        spawner.starts_active = active;
+        spawner
+    }
+
+    #[test]
+    fn repeating() {
+        assert_eq!(
+            describe(&spawner(2.0, true, true)),
+            "Spawns 32 particles immediately, then every 2.0s"
+        );
+        assert_eq!(
+            describe(&spawner(2.0, false, true)),
+            "Spawns 32 particles every 2.0s, the first after 2.0s"
+        );
+        assert_eq!(
+            describe(&spawner(0.0, true, true)),
+            "Spawns 32 particles every frame"
+        );
+    }
+
+    #[test]
+    fn once() {
+        assert_eq!(
+            describe(&spawner(f32::INFINITY, true, true)),
+            "Spawns 32 particles immediately, once"
+        );
+        assert_eq!(
+            describe(&spawner(f32::INFINITY, false, true)),
+            "Spawns nothing until reset, then 32 particles once"
+        );
+    }
+
+    #[test]
+    fn inactive() {
+        assert_eq!(
+            describe(&spawner(0.5, true, false)),
+            "Does not spawn until activated via code, then spawns 32 particles immediately, then every 0.5s"
+        );
+    }
+
+    #[test]
+    fn spawn_time() {
+        let spawner = Spawner::new(10.0.into(), 0.25.into(), 1.0.into());
+        assert_eq!(
+            describe(&spawner),
+            "Spawns 10 particles over 0.25s immediately, then every 1.0s"
+        );
+        assert_eq!(seconds(0.05), "0.05s");
+        assert_eq!(seconds(3.0), "3.0s");
+    }
+}
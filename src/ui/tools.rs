@@ -0,0 +1,240 @@
+// Tools that change a whole effect, or several: scaling, reorienting and batch editing.
+
+use bevy::prelude::*;
+use bevy_egui::egui::{self, widgets::DragValue, CollapsingHeader};
+use bevy_hanabi::prelude::*;
+
+use super::{ids, modifiers::ui_image_list, widgets::*};
+use crate::{
+    browser::AssetBrowser,
+    change::*,
+    reffect::{Orientation, ParticleTexture, REffect},
+};
+
+// Multiply all spatial values by a factor. The factor is kept in memory until applied. Returns
+// true if the effect was scaled.
+pub fn ui_scale(re: &mut REffect, ui: &mut egui::Ui) -> bool {
+    let mut scaled = false;
+    ui.menu_button("Scale…", |ui| {
+        let id = ui.id().with("scale");
+        ids::record(ui, id, "scale");
+        let mut factor = ui
+            .memory_mut(|memory| memory.data.get_temp::<f32>(id))
+            .unwrap_or(1.0);
+
+        ui.horizontal(|ui| {
+            ui.label("Factor");
+            ui.add(
+                egui::DragValue::new(&mut factor)
+                    .speed(0.01)
+                    .clamp_range(0.001..=1000.0),
+            );
+        });
+
+        ui.label("Changes:");
+        for field in REffect::SCALED_FIELDS {
+            ui.label(format!("• {}", field));
+        }
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(factor != 1.0, egui::Button::new("Apply"))
+                .clicked()
+            {
+                re.scale(factor);
+                scaled = true;
+                factor = 1.0;
+                ui.close_menu();
+            }
+            if ui.button("Cancel").clicked() {
+                factor = 1.0;
+                ui.close_menu();
+            }
+        });
+
+        ui.memory_mut(|memory| memory.data.insert_temp(id, factor));
+    });
+    scaled
+}
+
+const AXES: [(&str, Vec3); 6] = [
+    ("+X", Vec3::X),
+    ("-X", Vec3::NEG_X),
+    ("+Y", Vec3::Y),
+    ("-Y", Vec3::NEG_Y),
+    ("+Z", Vec3::Z),
+    ("-Z", Vec3::NEG_Z),
+];
+
+// Rotate all directional values from one axis to another. The axes are kept in memory until
+// applied. Returns true if the effect was reoriented.
+pub fn ui_reorient(re: &mut REffect, ui: &mut egui::Ui) -> bool {
+    let mut reoriented = false;
+    ui.menu_button("Reorient…", |ui| {
+        let id = ui.id().with("reorient");
+        ids::record(ui, id, "reorient");
+        // Indices into AXES, +Z to +Y by default.
+        let (mut from, mut to) = ui
+            .memory_mut(|memory| memory.data.get_temp::<(usize, usize)>(id))
+            .unwrap_or((4, 2));
+
+        ui.horizontal(|ui| {
+            for (label, axis) in [("From", &mut from), ("To", &mut to)] {
+                ui.label(label);
+                egui::ComboBox::from_id_source(id.with(label))
+                    .width(40.0)
+                    .selected_text(AXES[*axis].0)
+                    .show_ui(ui, |ui| {
+                        for (i, (name, _)) in AXES.iter().enumerate() {
+                            ui.selectable_value(axis, i, *name);
+                        }
+                    });
+            }
+        });
+
+        ui.label("Changes:");
+        for field in REffect::REORIENTED_FIELDS {
+            ui.label(format!("• {}", field));
+        }
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(from != to, egui::Button::new("Apply"))
+                .clicked()
+            {
+                re.reorient(Quat::from_rotation_arc(AXES[from].1, AXES[to].1));
+                reoriented = true;
+                ui.close_menu();
+            }
+            if ui.button("Cancel").clicked() {
+                ui.close_menu();
+            }
+        });
+
+        ui.memory_mut(|memory| memory.data.insert_temp(id, (from, to)));
+    });
+    reoriented
+}
+
+/// Values to write into all selected effects. Fields that are `None` are left alone.
+#[derive(Clone, Default)]
+pub struct BatchEdit {
+    capacity: Option<u32>,
+    num_particles: Option<f32>,
+    period: Option<f32>,
+    texture: Option<ParticleTexture>,
+    simulation_space: Option<SimulationSpace>,
+    orientation: Option<Orientation>,
+}
+
+impl BatchEdit {
+    pub fn apply(&self, re: &mut REffect) {
+        if let Some(capacity) = self.capacity {
+            re.capacity = capacity;
+        }
+        if let Some(num_particles) = self.num_particles {
+            re.spawner.num_particles = num_particles.into();
+        }
+        if let Some(period) = self.period {
+            re.spawner.period = period.into();
+        }
+        if let Some(texture) = &self.texture {
+            re.render_particle_texture = texture.clone();
+        }
+        if let Some(simulation_space) = self.simulation_space {
+            re.simulation_space = simulation_space;
+        }
+        if let Some(orientation) = self.orientation {
+            re.render_orientation = orientation;
+        }
+    }
+}
+
+// Edit a few fields across the selected effects. Each field is only written if checked. The edit
+// is kept in memory until applied.
+pub fn ui_batch_edit(
+    count: usize,
+    reffect_paths: &mut AssetBrowser<REffect>,
+    asset_server: &AssetServer,
+    image_paths: &AssetBrowser<Image>,
+    ui: &mut egui::Ui,
+) -> Option<BatchEdit> {
+    let id = ui.id().with("batch_edit");
+    let mut batch = ui
+        .memory_mut(|memory| memory.data.get_temp::<BatchEdit>(id))
+        .unwrap_or_default();
+    let mut apply = false;
+
+    CollapsingHeader::new(format!("Batch edit ({} selected)", count))
+        .default_open(true)
+        .show(ui, |ui| {
+            _ = grid!(ui, id, |ui| {
+                ui_option("Capacity", &mut batch.capacity, ui, |v, ui| {
+                    ui.add(DragValue::new(v)).into()
+                }) | ui_option("Particles", &mut batch.num_particles, ui, |v, ui| {
+                    ui.add(drag_value(v, "#")).into()
+                }) | ui_option("Period", &mut batch.period, ui, |v, ui| {
+                    ui.add(drag_value(v, "period")).into()
+                }) | ui_option("Particle Texture", &mut batch.texture, ui, |v, ui| {
+                    let selected = v
+                        .handle()
+                        .and_then(|handle| asset_server.get_handle_path(handle.id()))
+                        .map(|asset_path| asset_path.path().display().to_string())
+                        .unwrap_or_else(|| "None".into());
+                    egui::ComboBox::from_id_source(id.with("texture"))
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            let none = ui.selectable_value(v, ParticleTexture::None, "None");
+                            if none.changed {
+                                return Some(none);
+                            }
+                            ui_image_list(v, asset_server, image_paths, ui)
+                        })
+                        .merge()
+                }) | ui_option(
+                    "Simulation Space",
+                    &mut batch.simulation_space,
+                    ui,
+                    |v, ui| {
+                        (ui.selectable_value(v, SimulationSpace::Global, "Global")
+                            | ui.selectable_value(v, SimulationSpace::Local, "Local"))
+                        .into()
+                    },
+                ) | ui_option("Orientation", &mut batch.orientation, ui, |v, ui| {
+                    egui::ComboBox::from_id_source(id.with("orientation"))
+                        .selected_text(v.label())
+                        .show_ui(ui, |ui| {
+                            Orientation::ALL
+                                .iter()
+                                .map(|o| ui.selectable_value(v, *o, o.label()))
+                                .reduce(|a, b| a | b)
+                        })
+                        .merge()
+                })
+            });
+
+            ui.horizontal(|ui| {
+                let fields = [
+                    batch.capacity.is_some(),
+                    batch.num_particles.is_some(),
+                    batch.period.is_some(),
+                    batch.texture.is_some(),
+                    batch.simulation_space.is_some(),
+                    batch.orientation.is_some(),
+                ];
+                let any = fields.contains(&true);
+                apply = ui
+                    .add_enabled(
+                        any,
+                        egui::Button::new(format!("Apply to {} effects", count)),
+                    )
+                    .clicked();
+                if ui.button("Clear selection").clicked() {
+                    reffect_paths.clear_selection();
+                }
+            });
+        });
+
+    ui.memory_mut(|memory| memory.data.insert_temp(id, batch.clone()));
+    apply.then_some(batch)
+}
@@ -0,0 +1,351 @@
+// Variations of an effect, for exploring: copies with some numbers randomly jittered, shown side by
+// side. Those worth keeping are added as new unsaved effects and the rest are dropped. The varied
+// fields are reflection paths (see [VARIED_FIELDS]) so more are easy to add.
+
+use bevy::{prelude::*, reflect::ReflectMut};
+use bevy_egui::egui::{self, DragValue};
+use bevy_hanabi::prelude::*;
+use rand::{Rng, RngCore};
+
+use super::actions::{Action, Actions};
+use crate::reffect::REffect;
+
+/// Memory id for the dialog state while it's open.
+pub const VARIATIONS: &str = "variations";
+
+/// Fields varied, as paths into [REffect]. Segments are field names, or indices into tuples, enum
+/// variants and lists; `*` is every element of a list. Every number under a path is varied.
+pub const VARIED_FIELDS: &[&str] = &[
+    // The option, then the velocity variant.
+    "init_velocity.0.0.speed",
+    "init_lifetime.0.lifetime",
+    "init_size.0.size",
+    "update_accels.*.0.accel",
+    "render_color_over_lifetime.0.keys.*.0",
+    "render_size_over_lifetime.0.keys.*.0",
+];
+
+/// A copy of the effect with each number under [VARIED_FIELDS] scaled by a random factor within
+/// ±`jitter` (0.1 for ±10%). Vectors are scaled as a whole, so they keep their direction. Gradient
+/// keys are kept in range and in order.
+pub fn vary(re: &REffect, jitter: f32, rng: &mut dyn RngCore) -> REffect {
+    let mut re = re.clone();
+    for path in VARIED_FIELDS {
+        let path: Vec<_> = path.split('.').collect();
+        visit_path(re.as_reflect_mut(), &path, &mut |value| {
+            jitter_numbers(value, jitter, rng)
+        });
+    }
+    _ = re.fix_up();
+    re
+}
+
+fn visit_path(value: &mut dyn Reflect, path: &[&str], f: &mut dyn FnMut(&mut dyn Reflect)) {
+    let Some((segment, rest)) = path.split_first() else {
+        return f(value);
+    };
+
+    if *segment == "*" {
+        if let ReflectMut::List(list) = value.reflect_mut() {
+            for i in 0..list.len() {
+                if let Some(item) = list.get_mut(i) {
+                    visit_path(item, rest, f);
+                }
+            }
+        }
+        return;
+    }
+
+    let index = segment.parse::<usize>().ok();
+    let field = match (value.reflect_mut(), index) {
+        (ReflectMut::Struct(s), None) => s.field_mut(segment),
+        (ReflectMut::TupleStruct(s), Some(i)) => s.field_mut(i),
+        (ReflectMut::Tuple(t), Some(i)) => t.field_mut(i),
+        (ReflectMut::List(l), Some(i)) => l.get_mut(i),
+        // A None option has no fields, so it's skipped.
+        (ReflectMut::Enum(e), Some(i)) => e.field_at_mut(i),
+        (ReflectMut::Enum(e), None) => e.field_mut(segment),
+        _ => None,
+    };
+    if let Some(field) = field {
+        visit_path(field, rest, f);
+    }
+}
+
+fn jitter_numbers(value: &mut dyn Reflect, jitter: f32, rng: &mut dyn RngCore) {
+    let factor = |rng: &mut dyn RngCore| 1.0 + rng.gen_range(-jitter..=jitter);
+
+    if let Some(v) = value.downcast_mut::<f32>() {
+        *v *= factor(rng);
+        return;
+    }
+    if let Some(v) = value.downcast_mut::<Vec2>() {
+        *v *= factor(rng);
+        return;
+    }
+    if let Some(v) = value.downcast_mut::<Vec3>() {
+        *v *= factor(rng);
+        return;
+    }
+    // Accelerations. Properties are left alone.
+    if let Some(v) = value.downcast_mut::<graph::Value>() {
+        match v {
+            graph::Value::Float(v) => *v *= factor(rng),
+            graph::Value::Float2(v) => *v *= factor(rng),
+            graph::Value::Float3(v) => *v *= factor(rng),
+            _ => (),
+        }
+        return;
+    }
+
+    match value.reflect_mut() {
+        ReflectMut::Struct(s) => {
+            for i in 0..s.field_len() {
+                if let Some(field) = s.field_at_mut(i) {
+                    jitter_numbers(field, jitter, rng);
+                }
+            }
+        }
+        ReflectMut::TupleStruct(s) => {
+            for i in 0..s.field_len() {
+                if let Some(field) = s.field_mut(i) {
+                    jitter_numbers(field, jitter, rng);
+                }
+            }
+        }
+        ReflectMut::Tuple(t) => {
+            for i in 0..t.field_len() {
+                if let Some(field) = t.field_mut(i) {
+                    jitter_numbers(field, jitter, rng);
+                }
+            }
+        }
+        ReflectMut::List(l) => {
+            for i in 0..l.len() {
+                if let Some(item) = l.get_mut(i) {
+                    jitter_numbers(item, jitter, rng);
+                }
+            }
+        }
+        ReflectMut::Enum(e) => {
+            for i in 0..e.field_len() {
+                if let Some(field) = e.field_at_mut(i) {
+                    jitter_numbers(field, jitter, rng);
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+#[derive(Clone)]
+struct VariationsState {
+    source: Handle<REffect>,
+    count: usize,
+    /// Percent.
+    jitter: f32,
+    /// Generated variations, and whether to keep each.
+    variations: Vec<(Handle<REffect>, bool)>,
+}
+
+/// Open the dialog for an effect.
+pub fn open_variations(handle: &Handle<REffect>, ctx: &egui::Context) {
+    let state = VariationsState {
+        source: handle.clone(),
+        count: 4,
+        jitter: 20.0,
+        variations: Vec::new(),
+    };
+    ctx.memory_mut(|memory| memory.data.insert_temp(egui::Id::new(VARIATIONS), state));
+}
+
+/// The variations generated and not yet kept or discarded.
+pub fn variation_handles(ctx: &egui::Context) -> Vec<Handle<REffect>> {
+    ctx.memory_mut(|memory| {
+        memory
+            .data
+            .get_temp::<VariationsState>(egui::Id::new(VARIATIONS))
+    })
+    .map(|state| {
+        state
+            .variations
+            .into_iter()
+            .map(|(handle, _)| handle)
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+// Ask for a count and jitter, then generate the variations and show them in a row. Each can be
+// kept; the rest are discarded when the dialog closes.
+pub fn ui_variations(reffects: &mut Assets<REffect>, actions: &mut Actions, ctx: &egui::Context) {
+    let id = egui::Id::new(VARIATIONS);
+    let Some(mut state) = ctx.memory_mut(|memory| memory.data.get_temp::<VariationsState>(id))
+    else {
+        return;
+    };
+    let Some(name) = reffects.get(&state.source).map(|re| re.name.clone()) else {
+        ctx.memory_mut(|memory| memory.data.remove::<VariationsState>(id));
+        return;
+    };
+
+    let mut open = true;
+    let mut close = false;
+    egui::Window::new(format!("Variations of {}", name))
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            if state.variations.is_empty() {
+                hl!("Count", ui, |ui| ui
+                    .add(DragValue::new(&mut state.count).clamp_range(1..=16)));
+                hl!("Jitter", ui, |ui| ui
+                    .add(
+                        DragValue::new(&mut state.jitter)
+                            .clamp_range(1.0..=100.0)
+                            .prefix("±")
+                            .suffix("%")
+                    )
+                    .on_hover_text(VARIED_FIELDS.join("\n")));
+
+                ui.horizontal(|ui| {
+                    if ui.button("Generate").clicked() {
+                        if let Some(re) = reffects.get(&state.source).cloned() {
+                            let mut rng = rand::thread_rng();
+                            state.variations = (0..state.count)
+                                .map(|i| {
+                                    let mut v = vary(&re, state.jitter / 100.0, &mut rng);
+                                    v.name = format!("{} ~{}", re.name, i + 1);
+                                    (reffects.add(v), false)
+                                })
+                                .collect();
+                            actions.push(Action::ShowAll(
+                                state.variations.iter().map(|(h, _)| h.clone()).collect(),
+                            ));
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            } else {
+                ui.label("Check the ones to keep, left to right.");
+                for (handle, keep) in state.variations.iter_mut() {
+                    if let Some(re) = reffects.get(handle) {
+                        ui.checkbox(keep, &re.name);
+                    }
+                }
+
+                ui.horizontal(|ui| {
+                    let kept = state.variations.iter().filter(|(_, keep)| *keep).count();
+                    if ui
+                        .add_enabled(kept > 0, egui::Button::new(format!("Keep {}", kept)))
+                        .clicked()
+                    {
+                        close = true;
+                    }
+                    if ui.button("Discard all").clicked() {
+                        state
+                            .variations
+                            .iter_mut()
+                            .for_each(|(_, keep)| *keep = false);
+                        close = true;
+                    }
+                });
+            }
+        });
+
+    // Closing the window discards what isn't checked.
+    if close || !open {
+        for (handle, keep) in state.variations.drain(..) {
+            actions.push(match keep {
+                true => Action::Keep(handle),
+                false => Action::Discard(handle),
+            });
+        }
+    }
+
+    ctx.memory_mut(|memory| {
+        if close || !open {
+            memory.data.remove::<VariationsState>(id);
+        } else {
+            memory.data.insert_temp(id, state);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::reflect::Struct;
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+
+    use super::*;
+    use crate::{
+        gradient::{ColorGradient, Gradient},
+        reffect::{InitVelocity, UpdateAccel},
+    };
+
+    fn effect() -> REffect {
+        let mut color = ColorGradient::default();
+        *color.keys_mut() = vec![(0.0, Vec4::ONE), (0.5, Vec4::X), (1.0, Vec4::ZERO)];
+        REffect {
+            name: "spark".to_string(),
+            capacity: 64,
+            init_velocity: Some(InitVelocity::default()),
+            init_lifetime: Some(InitLifetimeModifier {
+                lifetime: 2.0.into(),
+            }),
+            update_accels: vec![UpdateAccel::Linear(AccelModifier::constant(Vec3::new(
+                0.0, -9.8, 0.0,
+            )))],
+            render_set_color: Some(SetColorModifier {
+                color: Vec4::ONE.into(),
+            }),
+            render_color_over_lifetime: Some(color),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn varied_within_jitter() {
+        let re = effect();
+        let mut rng = Pcg32::seed_from_u64(1);
+        for _ in 0..20 {
+            let v = vary(&re, 0.1, &mut rng);
+            let Value::Single(lifetime) = v.init_lifetime.unwrap().lifetime else {
+                panic!("lifetime variant changed");
+            };
+            assert!((1.8..=2.2).contains(&lifetime), "{}", lifetime);
+
+            // The endpoints can't move past the ends, and the middle key stays between them.
+            let keys = v.render_color_over_lifetime.unwrap().keys_mut().clone();
+            assert_eq!(keys[0].0, 0.0);
+            assert!((0.45..=0.55).contains(&keys[1].0));
+            assert_eq!(keys[2].0, 1.0);
+        }
+    }
+
+    #[test]
+    fn other_fields_untouched() {
+        let re = effect();
+        let v = vary(&re, 0.5, &mut Pcg32::seed_from_u64(2));
+        let varied: Vec<_> = VARIED_FIELDS
+            .iter()
+            .map(|path| path.split('.').next().unwrap())
+            .collect();
+
+        for i in 0..re.field_len() {
+            let name = re.name_at(i).unwrap();
+            if varied.contains(&name) {
+                continue;
+            }
+            let (a, b) = (re.field_at(i).unwrap(), v.field_at(i).unwrap());
+            let eq = a
+                .reflect_partial_eq(b)
+                .unwrap_or_else(|| format!("{:?}", a) == format!("{:?}", b));
+            assert!(eq, "{}: {:?} != {:?}", name, a, b);
+        }
+        assert_ne!(v.update_accels, re.update_accels);
+    }
+}
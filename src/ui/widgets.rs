@@ -0,0 +1,583 @@
+// Widgets shared by the sections: values, options, reflected values and paths.
+
+use std::{
+    any::Any,
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use bevy_egui::egui::{self, widgets::DragValue};
+use bevy_hanabi::prelude::*;
+use bevy_inspector_egui::reflect_inspector::InspectorUi;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::asset::validate_path;
+use crate::{
+    asset::AssetOpError, change::*, complete::PathCompletions, docs::Doc, gradient,
+    reffect::AttributeValue, toast::Toasts,
+};
+
+pub fn ui_attribute_value(value: &mut AttributeValue, ui: &mut egui::Ui) -> egui::Response {
+    match value {
+        AttributeValue::Float(v) => ui.add(drag_value(v, "")),
+        AttributeValue::Float2(v) => {
+            ui.add(drag_value(&mut v.x, "")) | ui.add(drag_value(&mut v.y, ""))
+        }
+        AttributeValue::Float3(v) => value_vec3_single(v, "", ui),
+        AttributeValue::Float4(v) => {
+            ui.add(drag_value(&mut v.x, ""))
+                | ui.add(drag_value(&mut v.y, ""))
+                | ui.add(drag_value(&mut v.z, ""))
+                | ui.add(drag_value(&mut v.w, ""))
+        }
+        AttributeValue::Uint(v) => ui.add(DragValue::new(v)),
+    }
+}
+
+// Probably way easier to validate on save. This is only marked changed when a valid path is
+// committed (on losing focus). Validation errors are shown inline. With completions, a popup
+// offers paths while the field has focus: Up and Down pick one and Tab takes it.
+pub fn edit_path(
+    path: &mut PathBuf,
+    completions: Option<&PathCompletions>,
+    toasts: &Toasts,
+    ui: &mut egui::Ui,
+    validate: impl Fn(&str) -> Result<Cow<Path>, AssetOpError>,
+) -> Change {
+    // We have to edit as a string since PathBuf doesn't impl TextBuffer.
+    let mut path_str = path.to_string_lossy().to_string();
+
+    // Unique within the effect's scope, see [effect_scope](super::ids::effect_scope).
+    let id = ui.make_persistent_id("path");
+    let completion_id = id.with("completion");
+    // The candidates shown last frame and the one picked.
+    let mut shown =
+        ui.memory_mut(|memory| memory.data.get_temp::<(usize, Vec<String>)>(completion_id));
+    let mut accepted = false;
+    if let Some((picked, candidates)) = shown
+        .as_mut()
+        .filter(|_| ui.memory(|memory| memory.has_focus(id)))
+    {
+        let (up, down, tab) = ui.input_mut(|input| {
+            (
+                input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                input.consume_key(egui::Modifiers::NONE, egui::Key::Tab),
+            )
+        });
+        if up {
+            *picked = picked.saturating_sub(1);
+        }
+        if down {
+            *picked = (*picked + 1).min(candidates.len().saturating_sub(1));
+        }
+        if let Some(candidate) = candidates.get(*picked).filter(|_| tab) {
+            path_str = candidate.clone();
+            accepted = true;
+            // Continue typing after it.
+            let mut state = egui::TextEdit::load_state(ui.ctx(), id).unwrap_or_default();
+            state.set_ccursor_range(Some(egui::text::CCursorRange::one(
+                egui::text::CCursor::new(path_str.chars().count()),
+            )));
+            state.store(ui.ctx(), id);
+        }
+    }
+
+    // Locked so Tab completes rather than moving on.
+    let response = ui.add(
+        egui::TextEdit::singleline(&mut path_str)
+            .id(id)
+            .lock_focus(shown.is_some()),
+    );
+    super::focus::register(&response, ui);
+    super::ids::record(ui, response.id, "path");
+    let error_id = response.id.with("error");
+
+    if response.gained_focus() {
+        // Save a backup of the path in case validation fails.
+        ui.memory_mut(|memory| {
+            memory
+                .data
+                .insert_temp::<PathBuf>(response.id, path.clone());
+            memory.data.remove::<String>(error_id);
+        });
+    }
+
+    // Require enter to validate and update path?
+    //ui.input(|i| i.key_pressed(egui::Key::Enter))
+    let mut committed = false;
+    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+        // Cancelled, restore the prior path.
+        if let Some(p) = ui.memory_mut(|memory| memory.data.get_temp::<PathBuf>(response.id)) {
+            *path = p;
+        }
+    } else if response.lost_focus() {
+        match validate(&path_str) {
+            Ok(p) => {
+                match p {
+                    Cow::Borrowed(_) => info!("path valid: {}", p.display()), // It's good as is.
+                    Cow::Owned(p) => {
+                        info!("path revised: {}", p.display());
+                        *path = p;
+                    }
+                }
+                committed = true;
+            }
+            Err(e) => {
+                e.toast("not a valid path", toasts);
+                ui.memory_mut(|memory| memory.data.insert_temp(error_id, e.to_string()));
+
+                // Restore prior path.
+                if let Some(p) =
+                    ui.memory_mut(|memory| memory.data.get_temp::<PathBuf>(response.id))
+                {
+                    *path = p;
+                }
+            }
+        }
+    } else if response.changed() || accepted {
+        *path = path_str.clone().into();
+    }
+
+    match completions.filter(|_| response.has_focus()) {
+        Some(completions) => {
+            let candidates = completions.complete(&path_str);
+            match candidates.is_empty() {
+                true => ui
+                    .memory_mut(|memory| memory.data.remove::<(usize, Vec<String>)>(completion_id)),
+                false => {
+                    let picked = match response.changed() || accepted {
+                        true => 0,
+                        false => shown.map_or(0, |(picked, _)| picked),
+                    }
+                    .min(candidates.len() - 1);
+                    ui_completions(&candidates, picked, response.rect.left_bottom(), id, ui);
+                    ui.memory_mut(|memory| {
+                        memory.data.insert_temp(completion_id, (picked, candidates))
+                    });
+                }
+            }
+        }
+        None => ui.memory_mut(|memory| memory.data.remove::<(usize, Vec<String>)>(completion_id)),
+    }
+
+    if let Some(e) = ui.memory_mut(|memory| memory.data.get_temp::<String>(error_id)) {
+        ui_error(ui, &e);
+    }
+
+    committed.into()
+}
+
+// The candidates below the path field. Keyboard only, since clicking would take focus from the
+// field and commit what's typed.
+fn ui_completions(
+    candidates: &[String],
+    picked: usize,
+    pos: egui::Pos2,
+    id: egui::Id,
+    ui: &egui::Ui,
+) {
+    egui::Area::new(id.with("completions"))
+        .order(egui::Order::Foreground)
+        .fixed_pos(pos)
+        .interactable(false)
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                for (i, candidate) in candidates.iter().enumerate() {
+                    _ = ui.selectable_label(i == picked, candidate);
+                }
+                ui.weak("↑↓ pick, Tab complete");
+            });
+        });
+}
+
+// Pick a path with a save dialog rooted at the assets directory. The path still goes through
+// validation, so paths outside the root are rejected.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn browse_path(
+    path: &mut PathBuf,
+    root_path: &Path,
+    ext: &str,
+    toasts: &Toasts,
+    ui: &mut egui::Ui,
+) -> Change {
+    let error_id = ui.id().with("browse_error");
+    let mut changed = false;
+
+    if ui.button("Browse…").clicked() {
+        let abs_path = root_path.join(&path);
+        let mut dialog = rfd::FileDialog::new()
+            .add_filter(ext, &[ext])
+            .set_directory(abs_path.parent().unwrap_or(root_path));
+        if let Some(file_name) = path.file_name() {
+            dialog = dialog.set_file_name(&file_name.to_string_lossy());
+        }
+
+        if let Some(picked) = dialog.save_file() {
+            match validate_path(&picked.to_string_lossy(), ext, root_path) {
+                Ok(p) => {
+                    *path = p.into_owned();
+                    changed = true;
+                    ui.memory_mut(|memory| memory.data.remove::<String>(error_id));
+                }
+                Err(e) => {
+                    e.toast("not a valid path", toasts);
+                    ui.memory_mut(|memory| memory.data.insert_temp(error_id, e.to_string()));
+                }
+            }
+        }
+    }
+
+    if let Some(e) = ui.memory_mut(|memory| memory.data.get_temp::<String>(error_id)) {
+        ui_error(ui, &e);
+    }
+
+    changed.into()
+}
+
+// Show a file in the file manager, selected where the platform supports it. Disabled if the file
+// doesn't exist yet.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn reveal_button(file: &Path, exists: bool, toasts: &Toasts, ui: &mut egui::Ui) {
+    if ui
+        .add_enabled(exists, egui::Button::new("📂"))
+        .on_hover_text("Reveal in file manager")
+        .on_disabled_hover_text("Not saved yet")
+        .clicked()
+    {
+        #[cfg(target_os = "windows")]
+        let command = (
+            "explorer",
+            vec!["/select,".into(), file.display().to_string()],
+        );
+        #[cfg(target_os = "macos")]
+        let command = ("open", vec!["-R".to_string(), file.display().to_string()]);
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        let command = (
+            "xdg-open",
+            vec![file.parent().unwrap_or(file).display().to_string()],
+        );
+        run_detached(command, toasts);
+    }
+}
+
+// Open a file with the system's default application. Disabled if the file doesn't exist yet.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open_button(file: &Path, exists: bool, toasts: &Toasts, ui: &mut egui::Ui) {
+    if ui
+        .add_enabled(exists, egui::Button::new("✏"))
+        .on_hover_text("Open in external editor")
+        .on_disabled_hover_text("Not saved yet")
+        .clicked()
+    {
+        open_path(file, toasts);
+    }
+}
+
+// Open a file with the system's default application, or a folder in the file manager.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open_path(path: &Path, toasts: &Toasts) {
+    let path = path.display().to_string();
+    #[cfg(target_os = "windows")]
+    let command = ("cmd", vec!["/C".into(), "start".into(), "".into(), path]);
+    #[cfg(target_os = "macos")]
+    let command = ("open", vec![path]);
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let command = ("xdg-open", vec![path]);
+    run_detached(command, toasts);
+}
+
+// Run a command in a task and report failures. Explorer exits with an error even when it works, so
+// only failing to run is reported on Windows.
+#[cfg(not(target_arch = "wasm32"))]
+fn run_detached((program, args): (&'static str, Vec<String>), toasts: &Toasts) {
+    let toasts = toasts.clone();
+    bevy::tasks::IoTaskPool::get()
+        .spawn(async move {
+            match std::process::Command::new(program).args(&args).status() {
+                Ok(status) if status.success() || cfg!(target_os = "windows") => (),
+                Ok(status) => toasts.error(format!("{} failed: {}", program, status)),
+                Err(e) => toasts.error(format!("failed to run {}: {}", program, e)),
+            }
+        })
+        .detach();
+}
+
+pub fn short_circuit(
+    _env: &mut InspectorUi,
+    value: &mut dyn Reflect,
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    _options: &dyn Any,
+) -> Option<bool> {
+    if let Some(mut v) = value.downcast_mut::<Value<f32>>() {
+        // Is this id unique enough?
+        return Some(ui_value(id.with("valuef32"), &mut v, "", ui, value_f32).changed());
+    }
+
+    None
+}
+
+// Human readable time since modification.
+pub fn modified_ago(mtime: std::time::SystemTime) -> String {
+    let secs = mtime.elapsed().map(|d| d.as_secs()).unwrap_or_default();
+    match secs {
+        s if s < 60 => "modified just now".to_string(),
+        s if s < 60 * 60 => format!("modified {}m ago", s / 60),
+        s if s < 60 * 60 * 24 => format!("modified {}h ago", s / (60 * 60)),
+        s => format!("modified {}d ago", s / (60 * 60 * 24)),
+    }
+}
+
+// Human readable file size.
+pub fn file_size(bytes: u64) -> String {
+    match bytes {
+        b if b < 1024 => format!("{} B", b),
+        b if b < 1024 * 1024 => format!("{:.1} KiB", b as f32 / 1024.0),
+        b => format!("{:.1} MiB", b as f32 / (1024.0 * 1024.0)),
+    }
+}
+
+// The checkbox takes the label column.
+pub fn ui_option<T: Default>(
+    label: &str,
+    data: &mut Option<T>,
+    ui: &mut egui::Ui,
+    f: impl FnOnce(&mut T, &mut egui::Ui) -> Change,
+) -> Change {
+    ui_option_with(label, data, T::default, ui, f)
+}
+
+/// [ui_option] with the value set when enabled.
+pub fn ui_option_with<T>(
+    label: &str,
+    data: &mut Option<T>,
+    default: impl FnOnce() -> T,
+    ui: &mut egui::Ui,
+    f: impl FnOnce(&mut T, &mut egui::Ui) -> Change,
+) -> Change {
+    let mut opt = data.is_some();
+    let mut response = ui.checkbox(&mut opt, label).doc(label);
+    if response.clicked() {
+        *data = if opt { Some(default()) } else { None };
+        response.mark_changed();
+    };
+
+    let change = ui
+        .horizontal(|ui| match data {
+            Some(v) => f(v, ui) | response,
+            None => response.into(),
+        })
+        .inner;
+    ui.end_row();
+    change
+}
+
+pub fn ui_checkbox(label: &str, value: &mut bool, ui: &mut egui::Ui) -> Change {
+    let response = ui.checkbox(value, label).doc(label);
+    ui.end_row();
+    response.into()
+}
+
+pub fn ui_reflect<T: Reflect>(
+    label: &str,
+    value: &mut T,
+    env: &mut InspectorUi,
+    ui: &mut egui::Ui,
+    //options: &dyn Any
+) -> Change {
+    row!(label, ui, |ui| env.ui_for_reflect_with_options(
+        value,
+        ui,
+        ui.id().with(label),
+        &()
+    ))
+}
+
+pub fn ui_option_reflect<T: Reflect>(
+    label: &str,
+    value: &mut Option<T>,
+    default: impl FnOnce() -> T,
+    env: &mut InspectorUi,
+    ui: &mut egui::Ui,
+    //options: &dyn Any
+) -> Change {
+    ui_option_with(label, value, default, ui, |value, ui| {
+        env.ui_for_reflect_with_options(value, ui, ui.id().with(label), &())
+            .into()
+    })
+    .into()
+}
+
+// Configure DragValue based on suffix for now.
+pub fn drag_value<'a>(v: &'a mut f32, suffix: &str) -> DragValue<'a> {
+    let fin = if v.is_finite() { "s" } else { "" };
+    let dv = DragValue::new(v);
+    match suffix {
+        // Count.
+        "#" => dv.clamp_range(0..=u32::MAX),
+        // Seconds.
+        "s" => dv.speed(0.01).clamp_range(0.0..=f32::MAX).suffix(suffix),
+        // Rate (per second), e.g. drag.
+        "/s" => dv.speed(0.01).clamp_range(0.0..=50.0).suffix(suffix),
+        // Period (seconds).
+        "period" => dv.speed(0.01).clamp_range(0.0..=f32::INFINITY).suffix(fin),
+        // ?
+        _ => dv.speed(0.1).suffix(suffix),
+    }
+}
+
+// Values are all different units (time, distance, velocity, acceleration). It would be nice if we
+// could tune the DragValues for each case (and suffix). Also, hover information from the doc
+// strings would be nice. Maybe this information could be encoded statically in the modifiers.
+pub fn ui_value<T: FromReflect + Copy + Default, F>(
+    id: egui::Id,
+    value: &mut Value<T>,
+    suffix: &str,
+    ui: &mut egui::Ui,
+    mut value_fn: F,
+) -> Change
+where
+    F: FnMut(&mut Value<T>, &str, &mut egui::Ui) -> Change,
+{
+    // The horizontal is needed for when this is used within a reflect value. The reflect ui adds
+    // some odd spacing.
+    ui.horizontal(|ui| {
+        // The combo box label is on the right so we never use it, but we need the label for the
+        // unique id. (We could also use a label for units.)
+        super::ids::record(ui, id, "value");
+        egui::ComboBox::from_id_source(id)
+            .selected_text(match value {
+                Value::Single(_) => "Single",
+                Value::Uniform(_) => "Uniform",
+                _ => "Unhandled",
+            })
+            .show_ui(ui, |ui| {
+                let mut single = ui.selectable_label(matches!(value, Value::Single(_)), "Single");
+
+                if single.clicked() {
+                    match value {
+                        Value::Uniform((v, _)) => {
+                            *value = Value::Single(*v);
+                            single.mark_changed();
+                            return Some(single);
+                        }
+                        _ => (),
+                    }
+                }
+
+                let mut uniform =
+                    ui.selectable_label(matches!(value, Value::Uniform(_)), "Uniform");
+
+                if uniform.clicked() {
+                    match value {
+                        Value::Single(v) => {
+                            // An infinite uniform doensn't make much sense, nor an infinite
+                            // color. Revisit this later.
+                            *value = Value::Uniform((*v, *v));
+
+                            // *value = if v.is_finite() {
+                            //     Value::Uniform((*v, *v))
+                            // } else {
+                            //     // FIX this crashes w/o error if the effect is visible
+                            //     Value::Uniform(Default::default())
+                            // };
+                            uniform.mark_changed();
+                            return Some(uniform.into());
+                        }
+                        _ => (),
+                    }
+                }
+
+                None
+            })
+            .merge()
+            | value_fn(value, suffix, ui)
+    })
+    .inner
+}
+
+#[inline]
+pub fn ui_error(ui: &mut egui::Ui, str: &str) -> egui::Response {
+    ui.colored_label(ui.visuals().error_fg_color, str)
+}
+
+pub fn value_f32<'a>(value: &'a mut Value<f32>, suffix: &str, ui: &mut egui::Ui) -> Change {
+    match value {
+        Value::Single(v) => {
+            let mut response = ui.add(drag_value(v, suffix));
+            super::focus::register(&response, ui);
+            if suffix == "period" && response.clicked_by(egui::PointerButton::Secondary) {
+                response.mark_changed();
+                *v = f32::INFINITY;
+            }
+            response
+        }
+        Value::Uniform(v) => {
+            ui.spacing_mut().item_spacing.x = 4.0; // default is 8.0?
+            let min = ui.add(drag_value(&mut v.0, suffix).clamp_range(0.0..=v.1));
+            super::focus::register(&min, ui);
+            let dash = ui.label("-");
+            let max = ui.add(drag_value(&mut v.1, suffix).clamp_range(v.0..=f32::MAX));
+            super::focus::register(&max, ui);
+            min | dash | max
+        }
+        _ => ui_error(ui, "unhandled value type"),
+    }
+    .into()
+}
+
+pub fn value_vec3_single(v: &mut Vec3, suffix: &str, ui: &mut egui::Ui) -> egui::Response {
+    ui.add(drag_value(&mut v.x, suffix))
+        | ui.add(drag_value(&mut v.y, suffix))
+        | ui.add(drag_value(&mut v.z, suffix))
+}
+
+#[allow(unused)]
+pub fn value_vec3<'a>(value: &'a mut Value<Vec3>, suffix: &str, ui: &mut egui::Ui) -> Change {
+    match value {
+        Value::Single(v) => value_vec3_single(v, suffix, ui),
+        Value::Uniform((v0, v1)) => {
+            ui.spacing_mut().item_spacing.x = 4.0; // default is 8.0?
+
+            ui.add(drag_value(&mut v0.x, suffix).clamp_range(0.0..=v1.x))
+                | ui.add(drag_value(&mut v0.y, suffix).clamp_range(0.0..=v1.y))
+                | ui.add(drag_value(&mut v0.z, suffix).clamp_range(0.0..=v1.z))
+                | ui.label("-")
+                | ui.add(drag_value(&mut v1.x, suffix).clamp_range(v0.x..=f32::MAX))
+                | ui.add(drag_value(&mut v1.y, suffix).clamp_range(v0.y..=f32::MAX))
+                | ui.add(drag_value(&mut v1.z, suffix).clamp_range(v0.z..=f32::MAX))
+        }
+        _ => ui_error(ui, "unhandled value type"),
+    }
+    .into()
+}
+
+pub fn color_edit_button(color: &mut Vec4, ui: &mut egui::Ui) -> bool {
+    use egui::color_picker::*;
+
+    let mut hsva = gradient::hsva(color);
+    if color_edit_button_hsva(ui, &mut hsva, Alpha::OnlyBlend).changed() {
+        *color = gradient::from_hsva(hsva);
+        true
+    } else {
+        false
+    }
+}
+
+pub fn value_color<'a>(value: &'a mut Value<Vec4>, _suffix: &str, ui: &mut egui::Ui) -> Change {
+    match value {
+        Value::Single(v) => color_edit_button(v, ui).into(),
+        Value::Uniform(v) => {
+            ui.spacing_mut().item_spacing.x = 4.0; // default is 8.0?
+            let c1 = color_edit_button(&mut v.0, ui);
+            ui.label("-");
+            let c2 = color_edit_button(&mut v.1, ui);
+            (c1 || c2).into()
+        }
+        _ => ui
+            .colored_label(ui.visuals().error_fg_color, "unhandled value type")
+            .into(),
+    }
+}
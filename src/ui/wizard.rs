@@ -0,0 +1,262 @@
+// A dialog for new effects that asks what the effect is for (bursts or a steady stream, how many
+// particles, how long they live and how big they are) and picks the spawner and capacity to match.
+
+use bevy::prelude::*;
+use bevy_egui::egui::{self, DragValue};
+use bevy_hanabi::prelude::*;
+
+use super::actions::{Action, Actions};
+use crate::{
+    gradient::{ColorGradient, Gradient},
+    reffect::{InitVelocity, Orientation, REffect},
+};
+
+/// Memory id for the wizard state while it's open.
+pub const NEW_EFFECT: &str = "new_effect";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Style {
+    /// Spawn everything at once.
+    Burst,
+    /// Spawn at a steady rate.
+    #[default]
+    Continuous,
+}
+
+impl Style {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Style::Burst => "burst",
+            Style::Continuous => "continuous",
+        }
+    }
+}
+
+/// What a new effect is for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EffectIntent {
+    pub style: Style,
+    /// Particles per burst, or per second.
+    pub count: f32,
+    /// Typical particle lifetime in seconds.
+    pub lifetime: f32,
+    /// Rough particle size.
+    pub size: f32,
+}
+
+impl Default for EffectIntent {
+    fn default() -> Self {
+        Self {
+            style: Style::default(),
+            count: 100.0,
+            lifetime: 2.0,
+            size: 0.1,
+        }
+    }
+}
+
+impl EffectIntent {
+    /// A new effect with a matching spawner and [suggested
+    /// capacity](REffect::suggested_capacity), fading out over its lifetime.
+    pub fn effect(&self) -> REffect {
+        let mut color = ColorGradient::default();
+        *color.keys_mut() = vec![(0.0, Vec4::ONE), (1.0, Vec4::new(1.0, 1.0, 1.0, 0.0))];
+
+        self.effect_from(&REffect {
+            init_velocity: Some(InitVelocity::default()),
+            render_color_over_lifetime: Some(color),
+            render_orientation: Orientation::Billboard,
+            ..default()
+        })
+    }
+
+    /// [Self::effect] starting from a template, e.g. the [new effect
+    /// defaults](super::defaults::NewEffectTemplate). The spawner, lifetime, size and capacity are
+    /// the intent's; everything else is the template's.
+    pub fn effect_from(&self, template: &REffect) -> REffect {
+        let spawner = match self.style {
+            Style::Burst => Spawner::once(self.count.into(), true),
+            Style::Continuous => Spawner::rate(self.count.into()),
+        };
+
+        let mut re = REffect {
+            name: format!("new {}", self.style.label()),
+            spawner,
+            init_size: Some(InitSizeModifier {
+                size: DimValue::D1(self.size.into()),
+            }),
+            init_lifetime: Some(InitLifetimeModifier {
+                lifetime: self.lifetime.into(),
+            }),
+            ..template.clone()
+        };
+        re.capacity = re.suggested_capacity().unwrap_or(1);
+        re
+    }
+}
+
+/// Open the wizard at the first step.
+pub fn open_wizard(ctx: &egui::Context) {
+    ctx.memory_mut(|memory| {
+        memory
+            .data
+            .insert_temp(egui::Id::new(NEW_EFFECT), (0usize, EffectIntent::default()))
+    });
+}
+
+// One step at a time: style, count, lifetime and size, then a summary of the generated effect.
+// Create adds it as a new unsaved effect.
+pub fn ui_wizard(template: &REffect, actions: &mut Actions, ctx: &egui::Context) {
+    let id = egui::Id::new(NEW_EFFECT);
+    let Some((mut step, mut intent)) =
+        ctx.memory_mut(|memory| memory.data.get_temp::<(usize, EffectIntent)>(id))
+    else {
+        return;
+    };
+
+    let mut open = true;
+    let mut close = false;
+    egui::Window::new("New effect")
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut open)
+        .show(ctx, |ui| {
+            ui.label(format!("Step {} of 4", step + 1));
+            ui.separator();
+
+            match step {
+                0 => {
+                    ui.label("What kind of effect is it?");
+                    ui.radio_value(&mut intent.style, Style::Burst, "Burst")
+                        .on_hover_text("All particles at once, e.g. an explosion.");
+                    ui.radio_value(&mut intent.style, Style::Continuous, "Continuous")
+                        .on_hover_text("A steady stream, e.g. smoke or sparks.");
+                }
+                1 => {
+                    let (label, suffix) = match intent.style {
+                        Style::Burst => ("Particles per burst", ""),
+                        Style::Continuous => ("Particles per second", "/s"),
+                    };
+                    hl!(label, ui, |ui| ui.add(
+                        DragValue::new(&mut intent.count)
+                            .clamp_range(1.0..=100_000.0)
+                            .suffix(suffix)
+                    ));
+                }
+                2 => {
+                    hl!("Lifetime", ui, |ui| ui.add(
+                        DragValue::new(&mut intent.lifetime)
+                            .speed(0.01)
+                            .clamp_range(0.01..=60.0)
+                            .suffix("s")
+                    ));
+                    hl!("Particle size", ui, |ui| ui.add(
+                        DragValue::new(&mut intent.size)
+                            .speed(0.001)
+                            .clamp_range(0.001..=100.0)
+                    ));
+                }
+                _ => {
+                    let re = intent.effect_from(template);
+                    ui.label(format!("Name: {}", re.name));
+                    ui.label(format!("Capacity: {}", re.capacity));
+                    match re.steady_state_count() {
+                        Some(n) => ui.label(format!("About {:.0} particles alive", n)),
+                        None => ui.label(format!("{} particles per burst", intent.count)),
+                    };
+                    ui.label(
+                        "Lifetime and size are set. The rest is from the new effect defaults.",
+                    );
+                }
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(step > 0, egui::Button::new("Back"))
+                    .clicked()
+                {
+                    step -= 1;
+                }
+                if step < 3 {
+                    if ui.button("Next").clicked() {
+                        step += 1;
+                    }
+                } else if ui.button("Create").clicked() {
+                    actions.push(Action::New(Some(intent.clone())));
+                    close = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    ctx.memory_mut(|memory| {
+        if close || !open {
+            memory.data.remove::<(usize, EffectIntent)>(id);
+        } else {
+            memory.data.insert_temp(id, (step, intent));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn continuous_capacity() {
+        let re = EffectIntent::default().effect();
+        assert!(!re.is_once());
+        // 100 per second living 2 seconds, plus headroom.
+        assert_eq!(re.steady_state_count(), Some(200.0));
+        assert_eq!(re.capacity, 250);
+    }
+
+    #[test]
+    fn burst_capacity() {
+        let re = EffectIntent {
+            style: Style::Burst,
+            count: 64.0,
+            lifetime: 1.5,
+            ..default()
+        }
+        .effect();
+        assert!(re.is_once());
+        assert_eq!(re.capacity, 64);
+        assert_eq!(re.duration(), Some(1.5));
+    }
+
+    #[test]
+    fn size_and_gradient() {
+        let re = EffectIntent {
+            size: 0.5,
+            ..default()
+        }
+        .effect();
+        assert_eq!(re.init_size.unwrap().size, DimValue::D1(0.5.into()));
+        let color = re.render_color_over_lifetime.unwrap();
+        assert_eq!(color.sample(0.0).w, 1.0);
+        assert_eq!(color.sample(1.0).w, 0.0);
+    }
+
+    #[test]
+    fn template_kept() {
+        let template = REffect {
+            capacity: 8192,
+            update_linear_drag: Some(LinearDragModifier { drag: 2.0 }),
+            render_orientation: Orientation::AlongVelocity,
+            ..default()
+        };
+        let re = EffectIntent::default().effect_from(&template);
+        // The intent's.
+        assert_eq!(re.name, "new continuous");
+        assert_eq!(re.capacity, 250);
+        assert!(re.init_lifetime.is_some());
+        // The template's.
+        assert_eq!(re.update_linear_drag.unwrap().drag, 2.0);
+        assert_eq!(re.render_orientation, Orientation::AlongVelocity);
+        assert!(re.render_color_over_lifetime.is_none());
+    }
+}
@@ -0,0 +1,100 @@
+// Browser replacements for file system access on wasm.
+
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use bevy::prelude::*;
+use wasm_bindgen::{prelude::*, JsCast};
+
+use crate::{asset::deserialize_effect, browser::AssetBrowser, reffect::*, toast::Toasts};
+
+pub struct WebPlugin;
+
+impl Plugin for WebPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OpenedFiles>()
+            .add_system(load_opened_files);
+    }
+}
+
+/// Files (name and contents) opened with the file picker, waiting to be loaded.
+#[derive(Resource, Default, Clone)]
+pub struct OpenedFiles(Arc<Mutex<Vec<(String, Vec<u8>)>>>);
+
+/// Trigger a download of the contents as a file.
+pub fn download(file_name: &str, contents: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    let blob = web_sys::Blob::new_with_str_sequence(&parts)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)?;
+
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("no document")?;
+    let a: web_sys::HtmlAnchorElement = document.create_element("a")?.dyn_into()?;
+    a.set_href(&url);
+    a.set_download(file_name);
+    a.click();
+
+    web_sys::Url::revoke_object_url(&url)
+}
+
+/// Show a file picker. The selected file is read asynchronously and added to `opened`.
+pub fn open_file(opened: OpenedFiles) -> Result<(), JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("no document")?;
+    let input: web_sys::HtmlInputElement = document.create_element("input")?.dyn_into()?;
+    input.set_type("file");
+    input.set_accept(".han,.ron");
+
+    let input_ = input.clone();
+    let onchange = Closure::once(move || {
+        if let Some(file) = input_.files().and_then(|files| files.get(0)) {
+            let name = file.name();
+            wasm_bindgen_futures::spawn_local(async move {
+                match wasm_bindgen_futures::JsFuture::from(file.text()).await {
+                    Ok(text) => match text.as_string() {
+                        Some(text) => opened.0.lock().unwrap().push((name, text.into_bytes())),
+                        None => error!("failed to read file: {}", name),
+                    },
+                    Err(e) => error!("failed to read file: {}: {:?}", name, e),
+                }
+            });
+        }
+    });
+    input.set_onchange(Some(onchange.as_ref().unchecked_ref()));
+    // The closure has to outlive this function.
+    onchange.forget();
+
+    input.click();
+    Ok(())
+}
+
+// Deserialize opened files the same way as the loader and add them to the browser.
+fn load_opened_files(
+    opened: Res<OpenedFiles>,
+    type_registry: Res<AppTypeRegistry>,
+    asset_server: Res<AssetServer>,
+    toasts: Res<Toasts>,
+    mut reffects: ResMut<Assets<REffect>>,
+    mut browser: ResMut<AssetBrowser<REffect>>,
+) {
+    let files = std::mem::take(&mut *opened.0.lock().unwrap());
+
+    for (name, bytes) in files {
+        let path = PathBuf::from(name);
+        match deserialize_effect(&bytes, &type_registry.read(), &path) {
+            Ok(mut re) => {
+                // Textures are loaded from the hosted assets.
+                if let ParticleTexture::Path(texture) = &re.render_particle_texture {
+                    re.render_particle_texture =
+                        ParticleTexture::Texture(asset_server.load(texture.as_str()));
+                }
+                browser.insert(path, reffects.add(re));
+            }
+            Err(e) => e.toast(format!("failed to open {}", path.display()), &toasts),
+        }
+    }
+}